@@ -2,9 +2,10 @@
 // (or `npm test`, which the package.json "test" script wires to
 // `node --test js/`).
 //
-// The URL_MATCH_CASES array below is a MANUAL DUPLICATE of
-// src-tauri/src/url_fixtures.rs::URL_MATCH_CASES. The Rust file is the
-// source of truth — when you change a case there, change it here too.
+// The URL_MATCH_CASES, STRIP_CASES and URL_MATCH_IGNORING_CASES arrays
+// below are MANUAL DUPLICATES of the same-named tables in
+// src-tauri/src/url_fixtures.rs, and DEFAULT_TRACKING_PARAMS copies the
+// defaults in urls.rs. The Rust files are the source of truth — when you change a case there, change it here too.
 // A shared JSON file would avoid the duplication but would add a build
 // step; a duplicated array with this cross-reference is the pragmatic
 // zero-dependency choice. The Rust side independently asserts its own
@@ -12,7 +13,7 @@
 
 import { test } from 'node:test';
 import assert from 'node:assert/strict';
-import { urlsMatch, normalizeUrlInput } from './urltest.mjs';
+import { urlsMatch, normalizeUrlInput, stripTrackingParams } from './urltest.mjs';
 
 const URL_MATCH_CASES = [
     ['https://example.com/', 'https://example.com/', true],
@@ -42,6 +43,76 @@ test('urlsMatch agrees with the shared Rust/JS truth table', () => {
     });
 });
 
+// `TrackingParams::default()` as serialized into the page's config.
+const DEFAULT_TRACKING_PARAMS = {
+    strip: true,
+    params: [
+        'utm_*', 'fbclid', 'gclid', 'dclid', 'gbraid', 'wbraid', 'msclkid', 'mc_cid', 'mc_eid',
+        'igshid', 'yclid', '_hsenc', '_hsmi', 'mkt_tok',
+    ],
+    hosts: {
+        '*.youtube.com': ['si', 'pp', 'feature'],
+        'open.spotify.com': ['si'],
+        'youtu.be': ['si', 'feature'],
+    },
+    ignore_when_matching: false,
+};
+
+const STRIP_CASES = [
+    ['https://example.com/', 'https://example.com/'],
+    ['https://example.com/?q=1', 'https://example.com/?q=1'],
+    ['not a url', 'not a url'],
+    ['https://example.com/a?utm_source=x&utm_medium=y', 'https://example.com/a'],
+    ['https://example.com/a?id=7&fbclid=abc&page=2', 'https://example.com/a?id=7&page=2'],
+    ['https://example.com/?UTM_Source=x&q=1', 'https://example.com/?q=1'],
+    ['https://example.com/?q=1&%67clid=abc', 'https://example.com/?q=1'],
+    ['https://example.com/?b=%20&gclid=1&a=x+y#top', 'https://example.com/?b=%20&a=x+y#top'],
+    ['https://example.com/?utm=1', 'https://example.com/?utm=1'],
+    ['https://www.youtube.com/watch?v=abc&si=xyz&pp=1', 'https://www.youtube.com/watch?v=abc'],
+    ['https://youtube.com/watch?v=abc&si=xyz', 'https://youtube.com/watch?v=abc'],
+    ['https://youtu.be/abc?si=xyz', 'https://youtu.be/abc'],
+    ['https://open.spotify.com/track/1?si=abc', 'https://open.spotify.com/track/1'],
+    ['https://example.com/?si=1', 'https://example.com/?si=1'],
+    ['https://notyoutube.com/watch?v=abc&si=xyz', 'https://notyoutube.com/watch?v=abc&si=xyz'],
+];
+
+const URL_MATCH_IGNORING_CASES = [
+    ['https://example.com/a?utm_source=x', 'https://example.com/a', true],
+    ['https://www.youtube.com/watch?v=abc&si=1', 'https://www.youtube.com/watch?v=abc&si=2', true],
+    ['https://www.youtube.com/watch?v=abc&si=1', 'https://www.youtube.com/watch?v=def', false],
+    ['https://example.com/?si=1', 'https://example.com/', false],
+    ['https://example.com/?q=1&fbclid=a', 'https://example.com/?q=2', false],
+    ['https://example.com/', 'http://example.com/?gclid=1', false],
+];
+
+test('stripTrackingParams agrees with the shared Rust/JS truth table', () => {
+    STRIP_CASES.forEach(([url, expected], i) => {
+        assert.equal(
+            stripTrackingParams(url, DEFAULT_TRACKING_PARAMS),
+            expected,
+            `case #${i}: stripTrackingParams(${JSON.stringify(url)})`,
+        );
+    });
+});
+
+test('urlsMatch ignores tracking params only when asked', () => {
+    const rules = { ...DEFAULT_TRACKING_PARAMS, ignore_when_matching: true };
+    URL_MATCH_IGNORING_CASES.forEach(([a, b, expected], i) => {
+        assert.equal(
+            urlsMatch(a, b, rules),
+            expected,
+            `case #${i}: urlsMatch(${JSON.stringify(a)}, ${JSON.stringify(b)}, rules) expected ${expected}`,
+        );
+    });
+    URL_MATCH_CASES.forEach(([a, b, expected], i) => {
+        assert.equal(urlsMatch(a, b, rules), expected, `URL_MATCH_CASES #${i} with rules`);
+    });
+    assert.equal(
+        urlsMatch('https://example.com/?utm_source=x', 'https://example.com/', DEFAULT_TRACKING_PARAMS),
+        false,
+    );
+});
+
 test('normalizeUrlInput adds https scheme to bare hosts', () => {
     assert.equal(normalizeUrlInput('example.com'), 'https://example.com/');
 });
@@ -11,7 +11,7 @@ use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_updater::UpdaterExt;
 use tracing::{error, info, warn};
 
-use crate::ops;
+use crate::{deep_link, ops};
 
 pub fn do_navigate_home(app: &AppHandle) {
     if let Err(e) = ops::navigate_home(app) {
@@ -38,6 +38,52 @@ pub fn do_toggle_locked(app: &AppHandle) {
     }
 }
 
+pub fn do_switch_profile(app: &AppHandle, name: &str) {
+    if let Err(e) = ops::switch_profile(app, name) {
+        warn!(error = %e, profile = %name, "do_switch_profile failed");
+    }
+}
+
+/// A preset from the tray or its hotkey. The strip gets every value the
+/// preset set, the crop included, so it matches the window.
+pub fn do_apply_window_preset(app: &AppHandle, name: &str) {
+    match ops::apply_window_preset(app, name) {
+        Ok(preset) => {
+            ops::eval_ui_update(app, "always_on_top", preset.always_on_top);
+            ops::eval_ui_update(app, "locked", preset.locked);
+            ops::eval_ui_update(app, "opacity", preset.opacity);
+            ops::eval_ui_update(app, "crop", &preset.crop);
+        }
+        Err(e) => warn!(error = %e, preset = %name, "do_apply_window_preset failed"),
+    }
+}
+
+/// The window-moving hotkeys. The strip shows no geometry, so there is
+/// nothing to mirror.
+pub fn do_nudge_window(app: &AppHandle, direction: (i32, i32)) {
+    if let Err(e) = ops::nudge_window(app, direction) {
+        warn!(error = %e, "do_nudge_window failed");
+    }
+}
+
+pub fn do_scale_window(app: &AppHandle, factor: f64) {
+    if let Err(e) = ops::scale_window(app, factor) {
+        warn!(error = %e, "do_scale_window failed");
+    }
+}
+
+pub fn do_cycle_corner(app: &AppHandle) {
+    if let Err(e) = ops::cycle_corner(app) {
+        warn!(error = %e, "do_cycle_corner failed");
+    }
+}
+
+pub fn do_move_to_next_monitor(app: &AppHandle) {
+    if let Err(e) = ops::move_to_next_monitor(app) {
+        warn!(error = %e, "do_move_to_next_monitor failed");
+    }
+}
+
 pub fn do_opacity_change(app: &AppHandle, delta: f64) {
     match ops::adjust_opacity(app, delta) {
         Ok(new_opacity) => ops::eval_ui_update(app, "opacity", new_opacity),
@@ -45,6 +91,39 @@ pub fn do_opacity_change(app: &AppHandle, delta: f64) {
     }
 }
 
+/// The `exit_kiosk` hotkey: arm the exit and bring up the PIN prompt.
+pub fn do_arm_kiosk_exit(app: &AppHandle) {
+    match ops::arm_kiosk_exit(app) {
+        Ok(status) => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            ops::eval_ui_update(app, "kiosk", status);
+        }
+        Err(e) => warn!(error = %e, "do_arm_kiosk_exit failed"),
+    }
+}
+
+/// A `floatview://` link handed over by the OS (see [`crate::deep_link`]).
+pub fn do_open_deep_link(app: &AppHandle, raw: &str) {
+    let link = match deep_link::parse_deep_link(raw) {
+        Ok(link) => link,
+        Err(e) => {
+            warn!(error = %e, link = %raw, "Ignoring invalid deep link");
+            return;
+        }
+    };
+    match ops::open_deep_link(app, &link) {
+        Ok(()) => {
+            if let Some(opacity) = link.opacity {
+                ops::eval_ui_update(app, "opacity", opacity);
+            }
+        }
+        Err(e) => warn!(error = %e, link = %raw, "do_open_deep_link failed"),
+    }
+}
+
 pub fn do_media_action(app: &AppHandle, script: &'static str) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.eval(script);
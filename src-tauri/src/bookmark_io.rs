@@ -0,0 +1,646 @@
+//! Bookmark files from other browsers: parsing the Netscape bookmark HTML
+//! every browser exports, Chromium's `Bookmarks` JSON, and Firefox's
+//! bookmark backup JSON, and writing our own list back out as Netscape
+//! HTML.
+//!
+//! Everything here is pure. `import_bookmarks` / `export_bookmarks` in
+//! [`crate::commands`] handle the file and the config lock.
+//!
+//! Browser folders nest; ours are one level deep. An imported bookmark
+//! lands in the folder that directly contains it, and bookmarks sitting
+//! in a browser's root containers (the bookmarks bar, "Other bookmarks",
+//! the Firefox menu) land at the top level.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bookmarks::{
+    clean_title, find_folder, normalize_folder_name, normalize_tag, MAX_BOOKMARK_FOLDERS,
+    MAX_BOOKMARK_TAGS, MAX_FOLDER_NAME_LEN,
+};
+use crate::config::{AppConfig, Bookmark};
+use crate::config_io::MAX_BOOKMARKS;
+use crate::urls::{normalize_url, url_to_store, urls_match_with};
+
+/// Seconds between the Windows epoch (1601-01-01), which Chromium's
+/// `date_added` counts microseconds from, and the Unix epoch.
+const WINDOWS_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+
+/// One entry read from a bookmark file, before it is checked against the
+/// current list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedBookmark {
+    pub url: String,
+    pub title: String,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    /// Unix seconds; 0 when the file didn't say.
+    pub created: u64,
+}
+
+/// What an import did, for the settings UI.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BookmarkImport {
+    pub added: usize,
+    /// Entries that `urls_match` a bookmark already in the list (or an
+    /// earlier entry in the same file).
+    pub duplicates: usize,
+    /// Entries whose URL isn't http(s): `javascript:` bookmarklets,
+    /// `place:` queries, `file://` links.
+    pub rejected: usize,
+    /// Entries left out because the list reached `MAX_BOOKMARKS`.
+    pub over_limit: usize,
+}
+
+/// Read a bookmark file in any supported format, detected from its
+/// content rather than its name.
+pub fn parse_bookmark_file(text: &str) -> Result<Vec<ImportedBookmark>, String> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with('{') {
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| format!("Not a valid bookmark file: {e}"))?;
+        if value.get("roots").is_some() {
+            return Ok(parse_chromium(&value));
+        }
+        if value.get("children").is_some() {
+            return Ok(parse_firefox(&value));
+        }
+        return Err("Not a Chromium or Firefox bookmark file".to_string());
+    }
+    let upper = text.to_ascii_uppercase();
+    if upper.contains("NETSCAPE-BOOKMARK-FILE") || upper.contains("<DL") {
+        return Ok(parse_netscape(text));
+    }
+    Err("Not a bookmark file (expected bookmark HTML or browser bookmark JSON)".to_string())
+}
+
+/// Add `imported` to the active profile's bookmarks after the existing
+/// ones. URLs go through `normalize_url` and lose their tracking
+/// parameters like [`crate::bookmarks::add_bookmark`]'s, and are deduped
+/// with `urls_match_with`; folders are matched by name or created; titles and tags
+/// get the same cleanup as everything else in the config.
+pub fn merge_imported(config: &mut AppConfig, imported: Vec<ImportedBookmark>) -> BookmarkImport {
+    let mut summary = BookmarkImport::default();
+    for entry in imported {
+        let Ok(url) = normalize_url(&entry.url) else {
+            summary.rejected += 1;
+            continue;
+        };
+        let url = url_to_store(&url, &config.tracking_params);
+        let rules = &config.tracking_params;
+        if config
+            .bookmarks
+            .iter()
+            .any(|b| urls_match_with(&b.url, &url, rules))
+        {
+            summary.duplicates += 1;
+            continue;
+        }
+        if config.bookmarks.len() >= MAX_BOOKMARKS {
+            summary.over_limit += 1;
+            continue;
+        }
+        let mut bookmark = Bookmark::new(url, clean_title(&entry.title));
+        bookmark.folder = entry.folder.and_then(|name| import_folder(config, &name));
+        for tag in &entry.tags {
+            if bookmark.tags.len() >= MAX_BOOKMARK_TAGS {
+                break;
+            }
+            if let Ok(tag) = normalize_tag(tag) {
+                if !bookmark
+                    .tags
+                    .iter()
+                    .any(|t| t.to_lowercase() == tag.to_lowercase())
+                {
+                    bookmark.tags.push(tag);
+                }
+            }
+        }
+        if entry.created > 0 {
+            bookmark.created = entry.created;
+        }
+        config.bookmarks.push(bookmark);
+        summary.added += 1;
+    }
+    summary
+}
+
+/// The stored name for an imported folder, creating it if there's room.
+/// Names past the length limit are cut rather than dropped.
+fn import_folder(config: &mut AppConfig, name: &str) -> Option<String> {
+    let cut: String = name.trim().chars().take(MAX_FOLDER_NAME_LEN).collect();
+    let name = normalize_folder_name(&cut).ok()?;
+    if let Some(existing) = find_folder(&config.bookmark_folders, &name) {
+        return Some(existing.clone());
+    }
+    if config.bookmark_folders.len() >= MAX_BOOKMARK_FOLDERS {
+        return None;
+    }
+    config.bookmark_folders.push(name.clone());
+    Some(name)
+}
+
+/// Write `config`'s bookmarks as Netscape bookmark HTML: top-level
+/// entries first, then one `<H3>` section per folder (empty ones
+/// included), in stored order.
+pub fn export_netscape(config: &AppConfig) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <!-- This is an automatically generated file.\n     \
+         It will be read and overwritten.\n     DO NOT EDIT! -->\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n<H1>Bookmarks</H1>\n<DL><p>\n",
+    );
+    let write_entries = |out: &mut String, folder: Option<&str>, indent: &str| {
+        for b in config
+            .bookmarks
+            .iter()
+            .filter(|b| b.folder.as_deref() == folder)
+        {
+            let title = if b.title.is_empty() { &b.url } else { &b.title };
+            out.push_str(&format!(
+                "{indent}<DT><A HREF=\"{}\" ADD_DATE=\"{}\"",
+                escape_html(&b.url),
+                b.created
+            ));
+            if !b.tags.is_empty() {
+                out.push_str(&format!(" TAGS=\"{}\"", escape_html(&b.tags.join(","))));
+            }
+            out.push_str(&format!(">{}</A>\n", escape_html(title)));
+        }
+    };
+    write_entries(&mut out, None, "    ");
+    for folder in &config.bookmark_folders {
+        out.push_str(&format!(
+            "    <DT><H3>{}</H3>\n    <DL><p>\n",
+            escape_html(folder)
+        ));
+        write_entries(&mut out, Some(folder), "        ");
+        out.push_str("    </DL><p>\n");
+    }
+    out.push_str("</DL><p>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Tolerant scanner for Netscape bookmark HTML. Browsers don't close
+/// `<DT>` or `<p>`, so this tracks only `<DL>` nesting, `<H3>` folder
+/// headings and `<A>` links, and ignores everything else.
+fn parse_netscape(text: &str) -> Vec<ImportedBookmark> {
+    let mut out = Vec::new();
+    // One entry per open <DL>: the folder it belongs to, or None for the
+    // document root and the browser's toolbar folder.
+    let mut stack: Vec<Option<String>> = Vec::new();
+    let mut pending: Option<Option<String>> = None;
+    let mut rest = text;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_uppercase();
+        match name.as_str() {
+            "DL" => stack.push(pending.take().unwrap_or(None)),
+            "/DL" => {
+                stack.pop();
+            }
+            "H3" => {
+                let (inner, after) = inner_text(rest, "</H3");
+                rest = after;
+                let attrs = parse_attrs(&tag[name_end..]);
+                let toolbar = attrs.iter().any(|(k, _)| k == "PERSONAL_TOOLBAR_FOLDER");
+                pending = Some((!toolbar).then_some(inner));
+            }
+            "A" => {
+                let (inner, after) = inner_text(rest, "</A");
+                rest = after;
+                let attrs = parse_attrs(&tag[name_end..]);
+                let attr = |key: &str| {
+                    attrs
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, v)| v.as_str())
+                };
+                let Some(url) = attr("HREF") else {
+                    continue;
+                };
+                out.push(ImportedBookmark {
+                    url: url.to_string(),
+                    title: inner,
+                    folder: stack.last().cloned().flatten(),
+                    tags: attr("TAGS")
+                        .map(|t| t.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                    created: attr("ADD_DATE")
+                        .and_then(|d| d.trim().parse().ok())
+                        .unwrap_or(0),
+                });
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Decoded text up to the closing tag `end` (matched case-insensitively),
+/// and the input after it.
+fn inner_text<'a>(rest: &'a str, end: &str) -> (String, &'a str) {
+    let Some(at) = find_ignore_case(rest, end) else {
+        return (String::new(), rest);
+    };
+    let after = rest[at..].find('>').map_or("", |gt| &rest[at + gt + 1..]);
+    (unescape_html(rest[..at].trim()), after)
+}
+
+/// Byte offset of the first ASCII-case-insensitive match of `needle`.
+/// Searches in place: upper-casing the rest of the file for every `<A>`
+/// made large imports quadratic. `needle` starts with `<`, so a match
+/// always lands on a char boundary.
+fn find_ignore_case(hay: &str, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    hay.as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+/// `KEY="value"` pairs from the inside of a tag, keys upper-cased and
+/// values decoded. Accepts double, single, or no quotes.
+fn parse_attrs(mut s: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start();
+        let key_end = s
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(s.len());
+        if key_end == 0 {
+            break;
+        }
+        let key = s[..key_end].to_ascii_uppercase();
+        s = s[key_end..].trim_start();
+        let Some(after_eq) = s.strip_prefix('=') else {
+            attrs.push((key, String::new()));
+            continue;
+        };
+        s = after_eq.trim_start();
+        let (value, after) = match s.chars().next() {
+            Some(q @ ('"' | '\'')) => {
+                let body = &s[1..];
+                let end = body.find(q).unwrap_or(body.len());
+                (&body[..end], body.get(end + 1..).unwrap_or(""))
+            }
+            _ => {
+                let end = s.find(|c: char| c.is_ascii_whitespace()).unwrap_or(s.len());
+                (&s[..end], &s[end..])
+            }
+        };
+        attrs.push((key, unescape_html(value)));
+        s = after;
+    }
+    attrs
+}
+
+/// Chromium's profile `Bookmarks` file: `roots.{bookmark_bar, other,
+/// synced}`, each a tree of `{ "type": "folder" | "url", "name", "url",
+/// "date_added", "children" }`. `date_added` is a string of microseconds
+/// since 1601.
+fn parse_chromium(value: &Value) -> Vec<ImportedBookmark> {
+    fn walk(node: &Value, folder: Option<&str>, out: &mut Vec<ImportedBookmark>) {
+        let name = node.get("name").and_then(Value::as_str).unwrap_or("");
+        match node.get("type").and_then(Value::as_str) {
+            Some("url") => {
+                let Some(url) = node.get("url").and_then(Value::as_str) else {
+                    return;
+                };
+                let created = node
+                    .get("date_added")
+                    .and_then(Value::as_str)
+                    .and_then(|d| d.parse::<u64>().ok())
+                    .map(|micros| (micros / 1_000_000).saturating_sub(WINDOWS_EPOCH_OFFSET_SECS))
+                    .unwrap_or(0);
+                out.push(ImportedBookmark {
+                    url: url.to_string(),
+                    title: name.to_string(),
+                    folder: folder.map(str::to_string),
+                    tags: Vec::new(),
+                    created,
+                });
+            }
+            Some("folder") => {
+                for child in node
+                    .get("children")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    walk(child, Some(name), out);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    let roots = value.get("roots").and_then(Value::as_object);
+    for root in roots.into_iter().flat_map(|r| r.values()) {
+        // The root containers themselves are the top level.
+        for child in root
+            .get("children")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            walk(child, None, &mut out);
+        }
+    }
+    out
+}
+
+/// Firefox's `bookmarks-<date>.json` backup: a tree of
+/// `text/x-moz-place-container` nodes (the ones with a `root` key are the
+/// menu, toolbar and other built-in containers) holding
+/// `text/x-moz-place` entries with `uri`, `title`, comma-separated `tags`
+/// and `dateAdded` in microseconds since the Unix epoch.
+fn parse_firefox(value: &Value) -> Vec<ImportedBookmark> {
+    fn walk(node: &Value, folder: Option<&str>, out: &mut Vec<ImportedBookmark>) {
+        let title = node.get("title").and_then(Value::as_str).unwrap_or("");
+        match node.get("type").and_then(Value::as_str) {
+            Some("text/x-moz-place") => {
+                let Some(url) = node.get("uri").and_then(Value::as_str) else {
+                    return;
+                };
+                out.push(ImportedBookmark {
+                    url: url.to_string(),
+                    title: title.to_string(),
+                    folder: folder.map(str::to_string),
+                    tags: node
+                        .get("tags")
+                        .and_then(Value::as_str)
+                        .map(|t| t.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                    created: node
+                        .get("dateAdded")
+                        .and_then(Value::as_u64)
+                        .map(|micros| micros / 1_000_000)
+                        .unwrap_or(0),
+                });
+            }
+            Some("text/x-moz-place-container") => {
+                let folder = if node.get("root").is_some() {
+                    None
+                } else {
+                    Some(title)
+                };
+                for child in node
+                    .get("children")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    walk(child, folder, out);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(value, None, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDGE_HTML: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- This is an automatically generated file.
+     It will be read and overwritten.
+     DO NOT EDIT! -->
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000" LAST_MODIFIED="0" PERSONAL_TOOLBAR_FOLDER="true">Favorites bar</H3>
+    <DL><p>
+        <DT><A HREF="http://192.168.1.20:8096/web/index.html" ADD_DATE="1700000001">Jellyfin</A>
+        <DT><H3 ADD_DATE="1700000002">Media &amp; TV</H3>
+        <DL><p>
+            <DT><A HREF="https://www.twitch.tv/" ADD_DATE="1700000003" TAGS="live,tv">Twitch</A>
+            <DT><A href='javascript:void(0)'>Bookmarklet</A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://example.com/?a=1&amp;b=2">Example &#8211; home</A>
+</DL><p>
+"#;
+
+    fn titled(config: &AppConfig) -> Vec<(&str, &str, Option<&str>)> {
+        config
+            .bookmarks
+            .iter()
+            .map(|b| (b.url.as_str(), b.title.as_str(), b.folder.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn netscape_html_keeps_folders_titles_tags_and_dates() {
+        let parsed = parse_bookmark_file(EDGE_HTML).expect("parse");
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed[0].folder, None, "toolbar entries are top level");
+        assert_eq!(parsed[0].created, 1_700_000_001);
+        assert_eq!(parsed[1].folder.as_deref(), Some("Media & TV"));
+        assert_eq!(parsed[1].tags, ["live", "tv"]);
+        assert_eq!(parsed[2].url, "javascript:void(0)");
+        assert_eq!(parsed[3].url, "https://example.com/?a=1&b=2");
+        assert_eq!(parsed[3].title, "Example \u{2013} home");
+        assert_eq!(parsed[3].folder, None, "back at the root after </DL>");
+    }
+
+    #[test]
+    fn netscape_closing_tags_match_in_any_case() {
+        let mut text = String::from("<dl><dt><h3>Café</H3><dl>");
+        for i in 0..2000 {
+            text.push_str(&format!(
+                "<dt><a href=\"https://{i}.test/\">Ünïcode {i}</a>"
+            ));
+        }
+        text.push_str("</dl></dl>");
+        let parsed = parse_bookmark_file(&text).expect("parse");
+        assert_eq!(parsed.len(), 2000);
+        assert_eq!(parsed[0].title, "Ünïcode 0");
+        assert_eq!(parsed[1999].url, "https://1999.test/");
+        assert_eq!(parsed[1999].folder.as_deref(), Some("Café"));
+    }
+
+    #[test]
+    fn chromium_json_flattens_roots_and_converts_dates() {
+        let text = r#"{ "checksum": "x", "version": 1, "roots": {
+            "bookmark_bar": { "type": "folder", "name": "Bookmarks bar", "children": [
+                { "type": "url", "name": "Grafana", "url": "http://grafana.lan:3000/",
+                  "date_added": "13345000000000000" },
+                { "type": "folder", "name": "Media", "children": [
+                    { "type": "url", "name": "Plex", "url": "http://plex.lan:32400/web" }
+                ] }
+            ] },
+            "other": { "type": "folder", "name": "Other bookmarks", "children": [] },
+            "synced": { "type": "folder", "name": "Mobile bookmarks", "children": [] }
+        } }"#;
+        let parsed = parse_bookmark_file(text).expect("parse");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].folder, None);
+        assert_eq!(
+            parsed[0].created,
+            13_345_000_000 - WINDOWS_EPOCH_OFFSET_SECS
+        );
+        assert_eq!(parsed[1].folder.as_deref(), Some("Media"));
+        assert_eq!(parsed[1].title, "Plex");
+    }
+
+    #[test]
+    fn firefox_backup_json_reads_uris_tags_and_nested_folders() {
+        let text = r#"{ "guid": "root________", "title": "", "root": "placesRoot",
+            "type": "text/x-moz-place-container", "children": [
+            { "guid": "menu________", "title": "menu", "root": "bookmarksMenuFolder",
+              "type": "text/x-moz-place-container", "children": [
+                { "title": "Jellyfin", "uri": "http://jellyfin.lan:8096/",
+                  "type": "text/x-moz-place", "dateAdded": 1700000000000000, "tags": "media" },
+                { "title": "Streams", "type": "text/x-moz-place-container", "children": [
+                    { "title": "Recent", "uri": "place:sort=8&maxResults=10",
+                      "type": "text/x-moz-place" },
+                    { "title": "YT", "uri": "https://www.youtube.com/",
+                      "type": "text/x-moz-place" }
+                ] }
+            ] }
+        ] }"#;
+        let parsed = parse_bookmark_file(text).expect("parse");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].created, 1_700_000_000);
+        assert_eq!(parsed[0].tags, ["media"]);
+        assert_eq!(parsed[0].folder, None);
+        assert_eq!(parsed[2].folder.as_deref(), Some("Streams"));
+    }
+
+    #[test]
+    fn unknown_files_are_rejected() {
+        assert!(parse_bookmark_file("just some text").is_err());
+        assert!(parse_bookmark_file(r#"{ "format": "floatview-settings" }"#).is_err());
+        assert!(parse_bookmark_file("{ not json").is_err());
+    }
+
+    #[test]
+    fn merge_normalizes_dedups_and_counts() {
+        let mut config = AppConfig::default();
+        crate::bookmarks::add_bookmark(&mut config, "https://www.twitch.tv", "Mine").unwrap();
+        let parsed = parse_bookmark_file(EDGE_HTML).expect("parse");
+
+        let summary = merge_imported(&mut config, parsed.clone());
+        assert_eq!(
+            summary,
+            BookmarkImport {
+                added: 2,
+                duplicates: 1,
+                rejected: 1,
+                over_limit: 0
+            }
+        );
+        assert_eq!(
+            titled(&config),
+            [
+                ("https://www.twitch.tv/", "Mine", None),
+                ("http://192.168.1.20:8096/web/index.html", "Jellyfin", None),
+                (
+                    "https://example.com/?a=1&b=2",
+                    "Example \u{2013} home",
+                    None
+                ),
+            ]
+        );
+        // Twitch was the folder's only new-to-us entry, and it was a
+        // duplicate, so no empty folder is left behind.
+        assert!(config.bookmark_folders.is_empty());
+
+        let again = merge_imported(&mut config, parsed);
+        assert_eq!(again.added, 0);
+        assert_eq!(again.duplicates, 3);
+    }
+
+    #[test]
+    fn export_round_trips_through_import() {
+        let mut config = AppConfig::default();
+        crate::bookmarks::create_folder(&mut config, "A <b> & \"c\"").unwrap();
+        crate::bookmarks::create_folder(&mut config, "Empty").unwrap();
+        let id =
+            crate::bookmarks::add_bookmark(&mut config, "https://a.test/?x=1&y=2", "").unwrap();
+        crate::bookmarks::move_bookmark(&mut config, &id, Some("A <b> & \"c\"")).unwrap();
+        crate::bookmarks::set_bookmark_tags(&mut config, &id, &["t1".into(), "t2".into()]).unwrap();
+        crate::bookmarks::add_bookmark(&mut config, "https://b.test/", "B").unwrap();
+
+        let html = export_netscape(&config);
+        assert!(html.contains("<H3>Empty</H3>"));
+        let parsed = parse_bookmark_file(&html).expect("parse");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].url, "https://b.test/");
+        assert_eq!(parsed[1].url, "https://a.test/?x=1&y=2");
+        assert_eq!(
+            parsed[1].title, "https://a.test/?x=1&y=2",
+            "untitled exports the URL"
+        );
+        assert_eq!(parsed[1].folder.as_deref(), Some("A <b> & \"c\""));
+        assert_eq!(parsed[1].tags, ["t1", "t2"]);
+        assert_eq!(parsed[1].created, config.bookmarks[0].created);
+    }
+}
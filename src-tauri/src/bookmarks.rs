@@ -0,0 +1,372 @@
+//! Bookmarks: pure edits over `AppConfig::bookmarks` and
+//! `AppConfig::bookmark_folders`.
+//!
+//! Display order is list order, so reordering is a move within the
+//! `Vec`. Folders are one level deep and only group entries for display:
+//! a bookmark names its folder, and `reorder_bookmark` positions are
+//! counted among the bookmarks sharing that folder. The commands in
+//! [`crate::commands`] lock the config, call one of these, and save.
+
+use crate::config::{AppConfig, Bookmark};
+use crate::config_io::MAX_BOOKMARKS;
+use crate::urls::{normalize_url, url_to_store, urls_match_with};
+
+/// Upper bound on bookmark folders. Each is a heading in the dropdown.
+pub const MAX_BOOKMARK_FOLDERS: usize = 50;
+
+/// Longest folder name accepted, in characters.
+pub const MAX_FOLDER_NAME_LEN: usize = 40;
+
+/// Longest stored bookmark title, in characters. Page titles past this
+/// are cut rather than rejected.
+pub const MAX_BOOKMARK_TITLE_LEN: usize = 200;
+
+/// Tags per bookmark, and the longest tag accepted, in characters.
+pub const MAX_BOOKMARK_TAGS: usize = 20;
+pub const MAX_TAG_LEN: usize = 32;
+
+/// Trim and validate a user-supplied folder name.
+pub fn normalize_folder_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Folder name cannot be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_FOLDER_NAME_LEN {
+        return Err(format!(
+            "Folder name is too long (max {MAX_FOLDER_NAME_LEN} characters)"
+        ));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Folder name cannot contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Trim and validate one tag.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let trimmed = tag.trim();
+    if trimmed.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tag is too long (max {MAX_TAG_LEN} characters)"));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Tag cannot contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Strip control characters and surrounding whitespace from a title and
+/// cut it to [`MAX_BOOKMARK_TITLE_LEN`] characters. Titles come from
+/// arbitrary pages, so this never fails.
+pub fn clean_title(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    title.trim().chars().take(MAX_BOOKMARK_TITLE_LEN).collect()
+}
+
+/// The stored spelling of folder `name`. Compared case-insensitively, the
+/// same way profile names are, so "News" and "news" can't both exist.
+pub fn find_folder<'a>(folders: &'a [String], name: &str) -> Option<&'a String> {
+    let name = name.trim().to_lowercase();
+    folders.iter().find(|f| f.to_lowercase() == name)
+}
+
+fn find_bookmark(config: &AppConfig, id: &str) -> Result<usize, String> {
+    config
+        .bookmarks
+        .iter()
+        .position(|b| b.id == id)
+        .ok_or_else(|| "No such bookmark".to_string())
+}
+
+/// Resolve an optional folder argument to its stored spelling. `None`
+/// (or a blank name) means the top level.
+fn resolve_folder(config: &AppConfig, folder: Option<&str>) -> Result<Option<String>, String> {
+    match folder.map(str::trim).filter(|f| !f.is_empty()) {
+        None => Ok(None),
+        Some(name) => find_folder(&config.bookmark_folders, name)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("No folder named \"{name}\"")),
+    }
+}
+
+/// Bookmark `url` at the end of the list and return the entry's id. The
+/// URL is stored without tracking parameters (see [`url_to_store`]); one
+/// that `urls_match_with`es an existing bookmark returns that bookmark's
+/// id instead of adding a duplicate.
+pub fn add_bookmark(config: &mut AppConfig, url: &str, title: &str) -> Result<String, String> {
+    let url = url_to_store(&normalize_url(url)?, &config.tracking_params);
+    let rules = &config.tracking_params;
+    if let Some(existing) = config
+        .bookmarks
+        .iter()
+        .find(|b| urls_match_with(&b.url, &url, rules))
+    {
+        return Ok(existing.id.clone());
+    }
+    if config.bookmarks.len() >= MAX_BOOKMARKS {
+        return Err(format!("Bookmark limit reached (max {MAX_BOOKMARKS})"));
+    }
+    let bookmark = Bookmark::new(url, clean_title(title));
+    let id = bookmark.id.clone();
+    config.bookmarks.push(bookmark);
+    Ok(id)
+}
+
+/// Remove every bookmark that `urls_match_with`es `url` in the form
+/// [`add_bookmark`] would have stored it.
+pub fn remove_bookmark(config: &mut AppConfig, url: &str) -> Result<(), String> {
+    let url = url_to_store(&normalize_url(url)?, &config.tracking_params);
+    let rules = &config.tracking_params;
+    config
+        .bookmarks
+        .retain(|b| !urls_match_with(&b.url, &url, rules));
+    Ok(())
+}
+
+/// Set a bookmark's title. An empty title falls back to the URL in the
+/// UI and lets the next visit capture the page title again.
+pub fn rename_bookmark(config: &mut AppConfig, id: &str, title: &str) -> Result<(), String> {
+    let index = find_bookmark(config, id)?;
+    config.bookmarks[index].title = clean_title(title);
+    Ok(())
+}
+
+/// Move a bookmark into `folder` (`None` for the top level). It lands
+/// last among that folder's bookmarks.
+pub fn move_bookmark(config: &mut AppConfig, id: &str, folder: Option<&str>) -> Result<(), String> {
+    let folder = resolve_folder(config, folder)?;
+    let index = find_bookmark(config, id)?;
+    let mut bookmark = config.bookmarks.remove(index);
+    bookmark.folder = folder;
+    config.bookmarks.push(bookmark);
+    Ok(())
+}
+
+/// Move a bookmark to `position` among the bookmarks in its own folder.
+/// Positions past the end place it last.
+pub fn reorder_bookmark(config: &mut AppConfig, id: &str, position: usize) -> Result<(), String> {
+    let index = find_bookmark(config, id)?;
+    let bookmark = config.bookmarks.remove(index);
+    let siblings: Vec<usize> = config
+        .bookmarks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.folder == bookmark.folder)
+        .map(|(i, _)| i)
+        .collect();
+    let at = match siblings.get(position) {
+        Some(&i) => i,
+        None => siblings
+            .last()
+            .map_or(config.bookmarks.len(), |&last| last + 1),
+    };
+    config.bookmarks.insert(at, bookmark);
+    Ok(())
+}
+
+/// Replace a bookmark's tags. Blank and repeated tags (compared
+/// case-insensitively) are dropped rather than rejected.
+pub fn set_bookmark_tags(config: &mut AppConfig, id: &str, tags: &[String]) -> Result<(), String> {
+    let index = find_bookmark(config, id)?;
+    let mut clean: Vec<String> = Vec::new();
+    for tag in tags {
+        if tag.trim().is_empty() {
+            continue;
+        }
+        let tag = normalize_tag(tag)?;
+        if !clean.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            clean.push(tag);
+        }
+    }
+    if clean.len() > MAX_BOOKMARK_TAGS {
+        return Err(format!(
+            "Too many tags (max {MAX_BOOKMARK_TAGS} per bookmark)"
+        ));
+    }
+    config.bookmarks[index].tags = clean;
+    Ok(())
+}
+
+/// Add an empty folder called `name` after the existing ones and return
+/// the stored name.
+pub fn create_folder(config: &mut AppConfig, name: &str) -> Result<String, String> {
+    let name = normalize_folder_name(name)?;
+    if find_folder(&config.bookmark_folders, &name).is_some() {
+        return Err(format!("A folder named \"{name}\" already exists"));
+    }
+    if config.bookmark_folders.len() >= MAX_BOOKMARK_FOLDERS {
+        return Err(format!("Folder limit reached (max {MAX_BOOKMARK_FOLDERS})"));
+    }
+    config.bookmark_folders.push(name.clone());
+    Ok(name)
+}
+
+/// Delete folder `name`. Its bookmarks are kept and move to the top
+/// level, in their current order.
+pub fn delete_folder(config: &mut AppConfig, name: &str) -> Result<(), String> {
+    let Some(folder) = resolve_folder(config, Some(name))? else {
+        return Err("Folder name cannot be empty".to_string());
+    };
+    config.bookmark_folders.retain(|f| *f != folder);
+    for bookmark in &mut config.bookmarks {
+        if bookmark.folder.as_deref() == Some(folder.as_str()) {
+            bookmark.folder = None;
+        }
+    }
+    Ok(())
+}
+
+/// Record `title` on every untitled bookmark for `url`. Titles the user
+/// set (or an earlier visit captured) are left alone. Returns whether
+/// anything changed, so the caller only saves when it has to.
+pub fn capture_title(config: &mut AppConfig, url: &str, title: &str) -> bool {
+    let title = clean_title(title);
+    if title.is_empty() {
+        return false;
+    }
+    let rules = &config.tracking_params;
+    let url = url_to_store(url, rules);
+    let mut changed = false;
+    for bookmark in &mut config.bookmarks {
+        if bookmark.title.is_empty() && urls_match_with(&bookmark.url, &url, rules) {
+            bookmark.title = title.clone();
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(config: &AppConfig) -> Vec<&str> {
+        config.bookmarks.iter().map(|b| b.url.as_str()).collect()
+    }
+
+    fn config_with(list: &[&str]) -> AppConfig {
+        let mut config = AppConfig::default();
+        for url in list {
+            add_bookmark(&mut config, url, "").expect("add");
+        }
+        config
+    }
+
+    #[test]
+    fn add_dedups_with_urls_match_and_returns_the_existing_id() {
+        let mut config = AppConfig::default();
+        let id = add_bookmark(&mut config, "https://a.test/x", " A \n").expect("add");
+        assert_eq!(config.bookmarks[0].title, "A");
+        assert!(config.bookmarks[0].created > 0);
+        let again = add_bookmark(&mut config, "https://a.test/x/", "").expect("re-add");
+        assert_eq!(again, id);
+        assert_eq!(config.bookmarks.len(), 1);
+        assert!(add_bookmark(&mut config, "javascript:alert(1)", "").is_err());
+    }
+
+    #[test]
+    fn add_and_remove_use_the_url_without_tracking_params() {
+        let mut config = AppConfig::default();
+        let id = add_bookmark(&mut config, "https://youtu.be/abc?si=1", "").expect("add");
+        assert_eq!(config.bookmarks[0].url, "https://youtu.be/abc");
+        let again = add_bookmark(&mut config, "https://youtu.be/abc?si=2", "").expect("re-add");
+        assert_eq!(again, id);
+        assert!(capture_title(
+            &mut config,
+            "https://youtu.be/abc?si=3",
+            "Clip"
+        ));
+        remove_bookmark(&mut config, "https://youtu.be/abc?si=4").expect("remove");
+        assert!(config.bookmarks.is_empty());
+
+        config.tracking_params.strip = false;
+        add_bookmark(&mut config, "https://youtu.be/abc?si=1", "").expect("add");
+        assert_eq!(config.bookmarks[0].url, "https://youtu.be/abc?si=1");
+        add_bookmark(&mut config, "https://youtu.be/abc", "").expect("add");
+        assert_eq!(config.bookmarks.len(), 2);
+        config.tracking_params.ignore_when_matching = true;
+        remove_bookmark(&mut config, "https://youtu.be/abc").expect("remove");
+        assert!(config.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn add_enforces_the_cap() {
+        let mut config = AppConfig::default();
+        for i in 0..MAX_BOOKMARKS {
+            add_bookmark(&mut config, &format!("https://a.test/{i}"), "").expect("add");
+        }
+        assert!(add_bookmark(&mut config, "https://b.test/", "").is_err());
+        // An existing URL still resolves at the cap.
+        assert!(add_bookmark(&mut config, "https://a.test/0", "").is_ok());
+    }
+
+    #[test]
+    fn capture_title_fills_only_untitled_entries() {
+        let mut config = config_with(&["https://a.test/", "https://b.test/"]);
+        let b = config.bookmarks[1].id.clone();
+        rename_bookmark(&mut config, &b, "Mine").expect("rename");
+
+        assert!(capture_title(&mut config, "https://a.test", "Page A"));
+        assert!(!capture_title(&mut config, "https://a.test/", "Later"));
+        assert!(!capture_title(&mut config, "https://b.test/", "Page B"));
+        assert!(!capture_title(&mut config, "https://c.test/", "Page C"));
+        assert_eq!(config.bookmarks[0].title, "Page A");
+        assert_eq!(config.bookmarks[1].title, "Mine");
+    }
+
+    #[test]
+    fn folders_group_moves_and_reorder_within_the_folder() {
+        let mut config = config_with(&["https://a.test/", "https://b.test/", "https://c.test/"]);
+        let ids: Vec<String> = config.bookmarks.iter().map(|b| b.id.clone()).collect();
+        assert_eq!(create_folder(&mut config, " News ").unwrap(), "News");
+        assert!(create_folder(&mut config, "news").is_err());
+        assert!(move_bookmark(&mut config, &ids[0], Some("Nope")).is_err());
+
+        move_bookmark(&mut config, &ids[0], Some("news")).expect("move a");
+        move_bookmark(&mut config, &ids[2], Some("NEWS")).expect("move c");
+        assert_eq!(config.bookmarks[1].folder.as_deref(), Some("News"));
+        assert_eq!(
+            urls(&config),
+            ["https://b.test/", "https://a.test/", "https://c.test/"]
+        );
+
+        // Position 0 within "News" is before a, not before b.
+        reorder_bookmark(&mut config, &ids[2], 0).expect("reorder");
+        assert_eq!(
+            urls(&config),
+            ["https://b.test/", "https://c.test/", "https://a.test/"]
+        );
+        reorder_bookmark(&mut config, &ids[2], 99).expect("reorder past end");
+        assert_eq!(
+            urls(&config),
+            ["https://b.test/", "https://a.test/", "https://c.test/"]
+        );
+        assert!(reorder_bookmark(&mut config, "missing", 0).is_err());
+    }
+
+    #[test]
+    fn delete_folder_keeps_its_bookmarks_at_the_top_level() {
+        let mut config = config_with(&["https://a.test/"]);
+        let id = config.bookmarks[0].id.clone();
+        create_folder(&mut config, "Work").expect("create");
+        move_bookmark(&mut config, &id, Some("Work")).expect("move");
+        delete_folder(&mut config, "work").expect("delete");
+        assert!(config.bookmark_folders.is_empty());
+        assert_eq!(config.bookmarks[0].folder, None);
+        assert!(delete_folder(&mut config, "Work").is_err());
+    }
+
+    #[test]
+    fn set_tags_trims_and_dedups() {
+        let mut config = config_with(&["https://a.test/"]);
+        let id = config.bookmarks[0].id.clone();
+        let tags = ["tv ", "TV", "", "live"].map(String::from);
+        set_bookmark_tags(&mut config, &id, &tags).expect("tags");
+        assert_eq!(config.bookmarks[0].tags, ["tv", "live"]);
+        let bad = ["a\u{7}b".to_string()];
+        assert!(set_bookmark_tags(&mut config, &id, &bad).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Command-line modes that run instead of the app, for provisioning
+//! scripts: no window, no tray, no single-instance handoff.
+//!
+//! - `--validate-config <path>` checks a config file (see
+//!   [`crate::config_schema::validate_config_text`]). Exit code 0 when the
+//!   app would load it exactly as written, 1 when it has problems (each
+//!   printed to stderr), 2 when the file can't be read.
+//! - `--print-config-schema` writes the JSON Schema to stdout.
+//!
+//! Anything else falls through to the normal app start.
+//!
+//! Windows release builds use the GUI subsystem, so they start without a
+//! console. A CLI mode attaches to the parent's console first
+//! ([`attach_parent_console`]) so its output shows up in the cmd or
+//! PowerShell window it was run from; output redirected to a file works
+//! either way.
+
+use std::fs;
+
+use crate::config_schema::{config_schema, validate_config_text};
+
+pub const EXIT_VALID: i32 = 0;
+pub const EXIT_INVALID: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+/// Run the CLI mode named by `args` (without the program name). Returns
+/// the process exit code, or `None` if the app should start normally.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    if matches!(
+        args.first().map(String::as_str),
+        Some("--validate-config" | "--print-config-schema")
+    ) {
+        attach_parent_console();
+    }
+    match args.first().map(String::as_str) {
+        Some("--validate-config") => Some(match args.get(1) {
+            Some(path) => validate_config_file(path),
+            None => {
+                eprintln!("usage: floatview --validate-config <path>");
+                EXIT_USAGE
+            }
+        }),
+        Some("--print-config-schema") => {
+            match serde_json::to_string_pretty(&config_schema()) {
+                Ok(schema) => println!("{schema}"),
+                Err(e) => {
+                    eprintln!("Failed to render schema: {e}");
+                    return Some(EXIT_INVALID);
+                }
+            }
+            Some(EXIT_VALID)
+        }
+        _ => None,
+    }
+}
+
+/// Route stdout/stderr to the console of the process that started us.
+/// Standard handles that are already redirected are kept. Does nothing
+/// when there is no parent console (started from Explorer) and on other
+/// platforms, where the binary always has the terminal it was run from.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Fails only when there is no console to attach to.
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
+
+/// Validate the config file at `path`, reporting to stdout/stderr.
+pub fn validate_config_file(path: &str) -> i32 {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return EXIT_USAGE;
+        }
+    };
+    match validate_config_text(&text) {
+        Ok(()) => {
+            println!("{path}: valid");
+            EXIT_VALID
+        }
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{path}: {error}");
+            }
+            EXIT_INVALID
+        }
+    }
+}
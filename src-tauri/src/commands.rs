@@ -4,20 +4,41 @@
 //! `authorize_command` before doing any work. Commands are thin wrappers
 //! around helpers in `config_io`, `window_state`, etc.
 
-use tauri::{AppHandle, Emitter, WebviewWindow};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
 use tauri_plugin_updater::UpdaterExt;
 use url::Url;
 
+use crate::bookmark_io::{self, BookmarkImport};
+use crate::bookmarks;
 use crate::browsing_data;
-use crate::config::{clamp_opacity, AppConfig, CropConfig};
+use crate::config::{clamp_opacity, AppConfig, CropConfig, WindowPreset};
 use crate::config_io::{
-    persist_recent_url, sanitize_config, save_config, CROP_MIN_DIM, MAX_BOOKMARKS,
+    file_timestamp, flush_saves, persist_last_url, persisted_config, read_config_backup,
+    rotate_config_backups, save_config, ConfigBackup, ConfigCorrection, ConfigLoadReport,
+    CROP_MIN_DIM, MAX_CONFIG_BACKUPS,
 };
+use crate::config_layers::PinnedKey;
+use crate::history::{self, HistoryEntry, MAX_HISTORY_RESULTS, MAX_RECENT_URLS};
+use crate::kiosk::{self, KioskStatus, WRONG_PIN_DELAY};
+use crate::layout::{self, SNAP_PADDING};
+use crate::local_media;
+use crate::omnibox;
 use crate::opacity;
 use crate::ops;
+use crate::profiles;
+use crate::settings_bundle::{self, ImportMode};
+use crate::site_settings::{site_key_for_url, SiteStatus};
 use crate::state::{authorize_command, AppState};
+use crate::suggest::{self, Suggestion, DEFAULT_SUGGESTIONS};
 use crate::urls::{normalize_url, urls_match};
-use crate::window_state::{persist_window_geometry, MIN_WINDOW_SIZE};
+use crate::window_presets;
+use crate::window_state::{
+    clamp_scale_factor, persist_window_geometry, to_logical, to_physical, MIN_WINDOW_SIZE,
+};
 
 #[tauri::command]
 pub async fn get_config(
@@ -25,34 +46,265 @@ pub async fn get_config(
     token: String,
 ) -> Result<AppConfig, String> {
     authorize_command(&state, &token, "get_config")?;
-    let config = state.config.lock().map_err(|e| e.to_string())?.clone();
-    Ok(config)
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(ops::page_config(&config))
+}
+
+/// How the config was obtained at startup. The first call of a run gets
+/// `notified: false` so the UI knows to surface a recovery/reset notice
+/// once; every later call (each page load re-injects the strip) sees
+/// `notified: true` and only renders the status line in Settings.
+#[tauri::command]
+pub async fn get_config_load_report(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<ConfigLoadReport, String> {
+    authorize_command(&state, &token, "get_config_load_report")?;
+    let mut report = state.load_report.lock().map_err(|e| e.to_string())?;
+    let snapshot = report.clone();
+    report.notified = true;
+    Ok(snapshot)
+}
+
+/// Keys pinned by the machine policy or a `FLOATVIEW_*` override. The
+/// settings UI disables their controls; `update_config` would put them
+/// back regardless.
+#[tauri::command]
+pub async fn get_config_policy(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<Vec<PinnedKey>, String> {
+    authorize_command(&state, &token, "get_config_policy")?;
+    Ok(state.layers.pinned().to_vec())
 }
 
+/// Replace the config with the settings UI's copy. Returns the values
+/// that had to be corrected (an invalid hotkey reset to its default, a
+/// `javascript:` home URL, a locked key) so the UI can say so instead of
+/// the change silently not sticking.
 #[tauri::command]
 pub async fn update_config(
     app: AppHandle,
     state: tauri::State<'_, AppState>,
     config: AppConfig,
     token: String,
-) -> Result<(), String> {
+) -> Result<Vec<ConfigCorrection>, String> {
     authorize_command(&state, &token, "update_config")?;
-    let config = sanitize_config(config);
-    let hotkeys_changed = {
-        let mut current = state.config.lock().map_err(|e| e.to_string())?;
-        let changed = current.hotkeys != config.hotkeys;
-        *current = config.clone();
-        save_config(&state, &current);
-        changed
+    ops::update_live_config(&app, |current| {
+        let mut config = config;
+        // The profile list, site settings, bookmarks and window presets
+        // only change
+        // through their own commands; a page holding a stale copy must not
+        // resurrect or drop entries, or undo a captured bookmark title.
+        config.profiles = current.profiles.clone();
+        config.active_profile = current.active_profile.clone();
+        config.site_settings = current.site_settings.clone();
+        config.bookmarks = current.bookmarks.clone();
+        config.bookmark_folders = current.bookmark_folders.clone();
+        config.window_presets = current.window_presets.clone();
+        Ok(config)
+    })
+}
+
+/// Error from the most recent config write, or `None` if it succeeded.
+/// Polled on each page load; the `config-save-status` event covers
+/// changes while a page is up.
+#[tauri::command]
+pub async fn get_config_save_error(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<Option<String>, String> {
+    authorize_command(&state, &token, "get_config_save_error")?;
+    let error = state.save_error.lock().map_err(|e| e.to_string())?.clone();
+    Ok(error)
+}
+
+/// Dated config generations, newest first, for the Settings restore picker.
+#[tauri::command]
+pub async fn list_config_backups(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<Vec<ConfigBackup>, String> {
+    authorize_command(&state, &token, "list_config_backups")?;
+    Ok(crate::config_io::list_config_backups(&state.config_path))
+}
+
+/// Replace the live config with the generation called `name`. Goes through
+/// the same sanitize / re-register / `config-changed` path as
+/// `update_config`. The config being replaced is snapshotted first,
+/// bypassing the hourly limit, so a restore can itself be undone: pending
+/// saves are flushed so the snapshot is the live config, and the copy is
+/// taken before the config lock, which never covers disk I/O.
+#[tauri::command]
+pub async fn restore_config_backup(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "restore_config_backup")?;
+    let restored = read_config_backup(&state.config_path, &name)?;
+    flush_saves(&state);
+    rotate_config_backups(
+        &state.config_path,
+        SystemTime::now(),
+        Duration::ZERO,
+        MAX_CONFIG_BACKUPS,
+    );
+    ops::replace_config(&app, |_| Ok(restored))?;
+    Ok(true)
+}
+
+/// Write the portable settings bundle (see [`crate::settings_bundle`]) to
+/// the user's Downloads folder, or next to `config.json` if there isn't
+/// one. Returns the path written so the UI can show it.
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    include_geometry: bool,
+    token: String,
+) -> Result<String, String> {
+    authorize_command(&state, &token, "export_settings")?;
+    let bundle = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        settings_bundle::export_bundle(&persisted_config(&state, &config), include_geometry)
     };
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    let path = export_path(&app, &state, "floatview-settings", "json")?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write settings: {e}"))?;
+    Ok(path.display().to_string())
+}
 
-    if hotkeys_changed {
-        crate::hotkeys::re_register_hotkeys(&app);
-    }
+/// A timestamped file name in the user's Downloads folder, or next to
+/// `config.json` if there isn't one.
+fn export_path(
+    app: &AppHandle,
+    state: &AppState,
+    stem: &str,
+    extension: &str,
+) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .download_dir()
+        .ok()
+        .filter(|dir| dir.is_dir())
+        .or_else(|| state.config_path.parent().map(Path::to_path_buf))
+        .ok_or("No folder to export to")?;
+    Ok(dir.join(format!(
+        "{stem}-{}.{extension}",
+        file_timestamp(SystemTime::now())
+    )))
+}
 
-    app.emit("config-changed", &config)
-        .map_err(|e| e.to_string())?;
-    Ok(())
+/// Import a settings bundle. `bundle` is the file's text (the settings UI
+/// reads it through a file input); `mode` picks merge or replace. The
+/// result goes through the same sanitize / re-register / `config-changed`
+/// path as `update_config`.
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    bundle: String,
+    mode: ImportMode,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "import_settings")?;
+    let bundle = settings_bundle::parse_bundle(&bundle)?;
+    ops::replace_config(&app, |current| {
+        Ok(settings_bundle::apply_bundle(current, &bundle, mode))
+    })?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn create_profile(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "create_profile")?;
+    ops::edit_profiles(&app, |config| profiles::create_profile(config, &name))?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    from: String,
+    to: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "rename_profile")?;
+    ops::edit_profiles(&app, |config| profiles::rename_profile(config, &from, &to))?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn delete_profile(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "delete_profile")?;
+    ops::edit_profiles(&app, |config| profiles::delete_profile(config, &name))?;
+    Ok(true)
+}
+
+/// Switch to profile `name`. The page navigates away as part of the
+/// switch, so the caller's script context is usually gone before it sees
+/// the result.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "switch_profile")?;
+    ops::switch_profile(&app, &name)?;
+    Ok(true)
+}
+
+/// Save the window as it is now as preset `name`, replacing one of that
+/// name. `hotkey` is blank for none.
+#[tauri::command]
+pub async fn save_window_preset(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    hotkey: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "save_window_preset")?;
+    ops::save_window_preset(&app, &name, &hotkey)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn apply_window_preset(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<WindowPreset, String> {
+    authorize_command(&state, &token, "apply_window_preset")?;
+    ops::apply_window_preset(&app, &name)
+}
+
+#[tauri::command]
+pub async fn delete_window_preset(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "delete_window_preset")?;
+    ops::edit_window_presets(&app, |config| window_presets::delete_preset(config, &name))?;
+    Ok(true)
 }
 
 #[tauri::command]
@@ -63,13 +315,29 @@ pub async fn navigate(
     token: String,
 ) -> Result<bool, String> {
     authorize_command(&state, &token, "navigate")?;
+    // Local files stay out of last_url and history: their folder grant
+    // only lasts the run.
+    if let Some(local) = ops::open_local_file(&state, &url)? {
+        window
+            .navigate(local_media::page_for(local))
+            .map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
     let url_str = normalize_url(&url)?;
+    {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        if !kiosk::allows_str(&config.kiosk, &url_str) {
+            return Err("That page isn't on the kiosk allowlist".to_string());
+        }
+    }
     let parsed = Url::parse(&url_str).map_err(|e| e.to_string())?;
-    window.navigate(parsed).map_err(|e| e.to_string())?;
+    window
+        .navigate(local_media::page_for(parsed))
+        .map_err(|e| e.to_string())?;
     // Record the URL only after navigation has been dispatched, so a parse
-    // or dispatch failure doesn't leave a never-loaded page in last_url /
-    // recents (which would otherwise become the next startup target).
-    persist_recent_url(&state, &url_str)?;
+    // or dispatch failure doesn't leave a never-loaded page in last_url
+    // (which would otherwise become the next startup target).
+    persist_last_url(&state, &url_str)?;
     Ok(true)
 }
 
@@ -132,15 +400,21 @@ pub async fn toggle_locked(
     ops::toggle_locked(&app)
 }
 
+/// Record the page's URL and apply or revert site settings for it.
+/// Returns `true` when the site settings in effect changed, so the page
+/// can re-read the config for crop and auto-refresh.
 #[tauri::command]
 pub async fn set_url(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
     url: String,
     token: String,
-) -> Result<(), String> {
+) -> Result<bool, String> {
     authorize_command(&state, &token, "set_url")?;
     let url = normalize_url(&url)?;
-    persist_recent_url(&state, &url)
+    persist_last_url(&state, &url)?;
+    history::note_visit(&state, &url)?;
+    ops::apply_site_settings(&app, &url)
 }
 
 #[tauri::command]
@@ -193,169 +467,7 @@ pub async fn snap_window(
     token: String,
 ) -> Result<(), String> {
     authorize_command(&state, &token, "snap_window")?;
-
-    let monitor = window
-        .current_monitor()
-        .map_err(|e| e.to_string())?
-        .or(window.primary_monitor().map_err(|e| e.to_string())?)
-        .ok_or("No monitor found")?;
-
-    let scale = window.scale_factor().map_err(|e| e.to_string())?;
-    let mon_pos = monitor.position();
-    let mon_size = monitor.size();
-    let win_size = window.outer_size().map_err(|e| e.to_string())?;
-
-    let padding = (16.0 * scale) as i32;
-    let mx = mon_pos.x;
-    let my = mon_pos.y;
-    let mw = mon_size.width as i32;
-    let mh = mon_size.height as i32;
-    let ww = win_size.width as i32;
-    let wh = win_size.height as i32;
-
-    // Halves/thirds/aspect resize. Corners and center don't take a size
-    // here directly — they restore the pre-snap size if one was saved
-    // (so corner snap after a half/third feels like "back to my normal
-    // size in this corner"), otherwise keep current size.
-    //
-    // For corners/center we recompute position below using the size
-    // we'll actually apply; ww/wh are recomputed after the size lookup.
-    //
-    // Padding budget per layout: edges + inter-tile gaps. Halves use
-    // 3*padding (left edge, gap, right edge), thirds use 4*padding.
-    // Peek (don't consume) the saved pre-snap size: if the resize below
-    // fails we must leave it intact for a retry. It is cleared only after a
-    // corner/center snap succeeds (further down).
-    let restored_size: Option<(i32, i32)> = match position.as_str() {
-        "top-left" | "top-right" | "bottom-left" | "bottom-right" | "center" => state
-            .pre_snap_size
-            .lock()
-            .ok()
-            .and_then(|g| *g)
-            .map(|(w, h)| (w as i32, h as i32)),
-        _ => None,
-    };
-
-    let (eff_w, eff_h) = restored_size.unwrap_or((ww, wh));
-
-    let (mut x, mut y, new_size) = match position.as_str() {
-        "top-left" => (mx + padding, my + padding, restored_size),
-        "top-right" => (mx + mw - eff_w - padding, my + padding, restored_size),
-        "bottom-left" => (mx + padding, my + mh - eff_h - padding, restored_size),
-        "bottom-right" => (
-            mx + mw - eff_w - padding,
-            my + mh - eff_h - padding,
-            restored_size,
-        ),
-        "center" => (mx + (mw - eff_w) / 2, my + (mh - eff_h) / 2, restored_size),
-        "left-half" => {
-            let w = ((mw - 3 * padding) / 2).max(MIN_WINDOW_SIZE);
-            let h = (mh - 2 * padding).max(MIN_WINDOW_SIZE);
-            (mx + padding, my + padding, Some((w, h)))
-        }
-        "right-half" => {
-            let w = ((mw - 3 * padding) / 2).max(MIN_WINDOW_SIZE);
-            let h = (mh - 2 * padding).max(MIN_WINDOW_SIZE);
-            (mx + mw - padding - w, my + padding, Some((w, h)))
-        }
-        "top-half" => {
-            let w = (mw - 2 * padding).max(MIN_WINDOW_SIZE);
-            let h = ((mh - 3 * padding) / 2).max(MIN_WINDOW_SIZE);
-            (mx + padding, my + padding, Some((w, h)))
-        }
-        "bottom-half" => {
-            let w = (mw - 2 * padding).max(MIN_WINDOW_SIZE);
-            let h = ((mh - 3 * padding) / 2).max(MIN_WINDOW_SIZE);
-            (mx + padding, my + mh - padding - h, Some((w, h)))
-        }
-        "left-third" => {
-            let w = ((mw - 4 * padding) / 3).max(MIN_WINDOW_SIZE);
-            let h = (mh - 2 * padding).max(MIN_WINDOW_SIZE);
-            (mx + padding, my + padding, Some((w, h)))
-        }
-        "center-third" => {
-            let w = ((mw - 4 * padding) / 3).max(MIN_WINDOW_SIZE);
-            let h = (mh - 2 * padding).max(MIN_WINDOW_SIZE);
-            (mx + (mw - w) / 2, my + padding, Some((w, h)))
-        }
-        "right-third" => {
-            let w = ((mw - 4 * padding) / 3).max(MIN_WINDOW_SIZE);
-            let h = (mh - 2 * padding).max(MIN_WINDOW_SIZE);
-            (mx + mw - padding - w, my + padding, Some((w, h)))
-        }
-        _ => return Err("Invalid snap position".to_string()),
-    };
-
-    // Halves/thirds (resizing) snaps mark the start of a snap chain.
-    // Save current size so a follow-up corner snap can restore it.
-    if matches!(
-        position.as_str(),
-        "left-half"
-            | "right-half"
-            | "top-half"
-            | "bottom-half"
-            | "left-third"
-            | "center-third"
-            | "right-third"
-    ) {
-        if let Ok(mut pre) = state.pre_snap_size.lock() {
-            if pre.is_none() {
-                *pre = Some((ww as u32, wh as u32));
-            }
-        }
-    }
-
-    // Clamp the final position so the window always lands on the target
-    // monitor, even when a restored pre-snap size (captured on a larger
-    // display) exceeds the current monitor. Mirrors set_aspect_ratio.
-    let (fw, fh) = new_size.unwrap_or((ww, wh));
-    let min_x = mx + padding;
-    let max_x = mx + mw - fw - padding;
-    let min_y = my + padding;
-    let max_y = my + mh - fh - padding;
-    if max_x >= min_x {
-        x = x.clamp(min_x, max_x);
-    }
-    if max_y >= min_y {
-        y = y.clamp(min_y, max_y);
-    }
-
-    // Record the size we're about to apply so the Resized handler can tell
-    // this programmatic resize from a manual drag (see snap_expected_size).
-    if let Ok(mut expected) = state.snap_expected_size.lock() {
-        *expected = new_size.map(|(w, h)| (w as u32, h as u32));
-    }
-
-    if window.is_maximized().unwrap_or(false) {
-        window.unmaximize().map_err(|e| e.to_string())?;
-    }
-    if let Some((w, h)) = new_size {
-        window
-            .set_size(tauri::Size::Physical(tauri::PhysicalSize {
-                width: w as u32,
-                height: h as u32,
-            }))
-            .map_err(|e| e.to_string())?;
-    }
-
-    window
-        .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
-        .map_err(|e| e.to_string())?;
-
-    // A corner/center snap has now successfully consumed the snap chain.
-    // Clear pre_snap_size only here (post-success); we only peeked it above
-    // so a failed resize leaves it intact for a retry.
-    if matches!(
-        position.as_str(),
-        "top-left" | "top-right" | "bottom-left" | "bottom-right" | "center"
-    ) {
-        if let Ok(mut pre) = state.pre_snap_size.lock() {
-            *pre = None;
-        }
-    }
-
-    persist_window_geometry(&window, &state)?;
-    Ok(())
+    ops::snap_window(&window, &state, &position)
 }
 
 /// Parse an "N:M" aspect ratio string into a `(width, height)` pair.
@@ -415,54 +527,47 @@ pub async fn set_aspect_ratio(
         .or(window.primary_monitor().map_err(|e| e.to_string())?)
         .ok_or("No monitor found")?;
 
-    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let scale = clamp_scale_factor(monitor.scale_factor());
     let mon_pos = monitor.position();
     let mon_size = monitor.size();
     let cur_size = window.outer_size().map_err(|e| e.to_string())?;
     let cur_pos = window.outer_position().map_err(|e| e.to_string())?;
 
-    let padding = (16.0 * scale) as i32;
-    let max_w = (mon_size.width as i32 - 2 * padding).max(MIN_WINDOW_SIZE);
-    let max_h = (mon_size.height as i32 - 2 * padding).max(MIN_WINDOW_SIZE);
-
-    let (mut new_w, mut new_h) =
-        aspect_resize(cur_size.width as i32, cur_size.height as i32, rw, rh);
+    let padding = to_physical(SNAP_PADDING, scale);
+    let min_size = to_physical(MIN_WINDOW_SIZE, scale);
+    let monitor_rect = (
+        mon_pos.x,
+        mon_pos.y,
+        mon_size.width as i32,
+        mon_size.height as i32,
+    );
 
     // Defensive: if the window started larger than the monitor, the
-    // shrink-only result might still overflow. Scale both dims down
-    // proportionally to fit.
-    if new_h > max_h {
-        new_h = max_h;
-        new_w = ((new_h as f64) * (rw as f64) / (rh as f64)).round() as i32;
-    }
-    if new_w > max_w {
-        new_w = max_w;
-        new_h = ((new_w as f64) * (rh as f64) / (rw as f64)).round() as i32;
-    }
-    new_w = new_w.max(MIN_WINDOW_SIZE);
-    new_h = new_h.max(MIN_WINDOW_SIZE);
+    // shrink-only result might still overflow. fit_size scales both dims
+    // down proportionally to fit.
+    let (new_w, new_h) = layout::fit_size(
+        monitor_rect,
+        aspect_resize(cur_size.width as i32, cur_size.height as i32, rw, rh),
+        padding,
+        min_size,
+    );
 
     let center_x = cur_pos.x + cur_size.width as i32 / 2;
     let center_y = cur_pos.y + cur_size.height as i32 / 2;
-    let mut new_x = center_x - new_w / 2;
-    let mut new_y = center_y - new_h / 2;
-
-    let min_x = mon_pos.x + padding;
-    let max_x = mon_pos.x + mon_size.width as i32 - new_w - padding;
-    let min_y = mon_pos.y + padding;
-    let max_y = mon_pos.y + mon_size.height as i32 - new_h - padding;
-    if max_x >= min_x {
-        new_x = new_x.clamp(min_x, max_x);
-    }
-    if max_y >= min_y {
-        new_y = new_y.clamp(min_y, max_y);
-    }
+    let (new_x, new_y, _, _) = layout::keep_on_monitor(
+        monitor_rect,
+        (center_x - new_w / 2, center_y - new_h / 2, new_w, new_h),
+        padding,
+    );
 
     // Aspect-ratio snap is also a resize — start of a snap chain. Save
     // pre-snap size if not already saved so a corner snap can restore.
     if let Ok(mut pre) = state.pre_snap_size.lock() {
         if pre.is_none() {
-            *pre = Some((cur_size.width, cur_size.height));
+            *pre = Some((
+                to_logical(cur_size.width as i32, scale) as u32,
+                to_logical(cur_size.height as i32, scale) as u32,
+            ));
         }
     }
 
@@ -665,9 +770,25 @@ pub async fn set_window_title(
     title: String,
 ) -> Result<(), String> {
     authorize_command(&state, &token, "set_window_title")?;
-    window
-        .set_title(&truncate_title(&title))
-        .map_err(|e| e.to_string())
+    // The page sends `document.title` as is; an untitled page shows the
+    // app name but must not be captured as a bookmark title.
+    let shown = if title.trim().is_empty() {
+        "FloatView".to_string()
+    } else {
+        truncate_title(&title)
+    };
+    window.set_title(&shown).map_err(|e| e.to_string())?;
+    if let Ok(url) = window.url() {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        if bookmarks::capture_title(&mut config, url.as_str(), &title) {
+            save_config(&state, &config);
+        }
+        drop(config);
+        if let Ok(url) = normalize_url(url.as_str()) {
+            history::note_title(&state, &url, &title)?;
+        }
+    }
+    Ok(())
 }
 
 /// Truncate a window title to a Win32-safe byte length, respecting UTF-8 char
@@ -695,27 +816,23 @@ fn truncate_title(title: &str) -> String {
     out
 }
 
+/// Bookmark `url`, titled from the window if it's showing that page, and
+/// return the entry's id. Already-bookmarked URLs return the existing id.
 #[tauri::command]
 pub async fn add_bookmark(
+    window: WebviewWindow,
     state: tauri::State<'_, AppState>,
     url: String,
     token: String,
-) -> Result<bool, String> {
+) -> Result<String, String> {
     authorize_command(&state, &token, "add_bookmark")?;
-    let url = normalize_url(&url)?;
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    if config.bookmarks.iter().any(|b| urls_match(b, &url)) {
-        return Ok(true);
-    }
-    if config.bookmarks.len() >= MAX_BOOKMARKS {
-        return Err(format!("Bookmark limit reached (max {MAX_BOOKMARKS})"));
-    }
-    config.bookmarks.push(url);
-    save_config(&state, &config);
-    drop(config);
-    // Returns a value (not unit) so the JS invoke wrapper — which maps IPC
-    // failures to null — can tell success apart from failure.
-    Ok(true)
+    let title = match window.url() {
+        Ok(current) if urls_match(current.as_str(), &url) => window.title().unwrap_or_default(),
+        _ => String::new(),
+    };
+    edit_bookmarks(&state, |config| {
+        bookmarks::add_bookmark(config, &url, &title)
+    })
 }
 
 #[tauri::command]
@@ -725,16 +842,373 @@ pub async fn remove_bookmark(
     token: String,
 ) -> Result<bool, String> {
     authorize_command(&state, &token, "remove_bookmark")?;
-    let url = normalize_url(&url)?;
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    config.bookmarks.retain(|u| !urls_match(u, &url));
-    save_config(&state, &config);
-    drop(config);
+    edit_bookmarks(&state, |config| bookmarks::remove_bookmark(config, &url))?;
     // Returns a value (not unit) so the JS invoke wrapper — which maps IPC
     // failures to null — can tell success apart from failure.
     Ok(true)
 }
 
+/// Remove every bookmark and bookmark folder in the active profile.
+#[tauri::command]
+pub async fn clear_bookmarks(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "clear_bookmarks")?;
+    edit_bookmarks(&state, |config| {
+        config.bookmarks.clear();
+        config.bookmark_folders.clear();
+        Ok(())
+    })?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn rename_bookmark(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    title: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "rename_bookmark")?;
+    edit_bookmarks(&state, |config| {
+        bookmarks::rename_bookmark(config, &id, &title)
+    })?;
+    Ok(true)
+}
+
+/// Move a bookmark into `folder`, or to the top level when `folder` is
+/// `None`.
+#[tauri::command]
+pub async fn move_bookmark(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    folder: Option<String>,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "move_bookmark")?;
+    edit_bookmarks(&state, |config| {
+        bookmarks::move_bookmark(config, &id, folder.as_deref())
+    })?;
+    Ok(true)
+}
+
+/// Move a bookmark to `position` among the bookmarks in its folder.
+#[tauri::command]
+pub async fn reorder_bookmark(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    position: usize,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "reorder_bookmark")?;
+    edit_bookmarks(&state, |config| {
+        bookmarks::reorder_bookmark(config, &id, position)
+    })?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn set_bookmark_tags(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    tags: Vec<String>,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "set_bookmark_tags")?;
+    edit_bookmarks(&state, |config| {
+        bookmarks::set_bookmark_tags(config, &id, &tags)
+    })?;
+    Ok(true)
+}
+
+/// Create an empty bookmark folder and return its stored name.
+#[tauri::command]
+pub async fn create_bookmark_folder(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<String, String> {
+    authorize_command(&state, &token, "create_bookmark_folder")?;
+    edit_bookmarks(&state, |config| bookmarks::create_folder(config, &name))
+}
+
+/// Delete a bookmark folder, moving its bookmarks to the top level.
+#[tauri::command]
+pub async fn delete_bookmark_folder(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "delete_bookmark_folder")?;
+    edit_bookmarks(&state, |config| bookmarks::delete_folder(config, &name))?;
+    Ok(true)
+}
+
+/// Add the bookmarks in a browser export to the active profile's list.
+/// `text` is the file's contents (the settings UI reads it through a file
+/// input): Netscape bookmark HTML, a Chromium `Bookmarks` file, or a
+/// Firefox JSON backup. See [`crate::bookmark_io`].
+#[tauri::command]
+pub async fn import_bookmarks(
+    state: tauri::State<'_, AppState>,
+    text: String,
+    token: String,
+) -> Result<BookmarkImport, String> {
+    authorize_command(&state, &token, "import_bookmarks")?;
+    let imported = bookmark_io::parse_bookmark_file(&text)?;
+    edit_bookmarks(&state, |config| {
+        Ok(bookmark_io::merge_imported(config, imported))
+    })
+}
+
+/// Write the active profile's bookmarks as Netscape bookmark HTML, which
+/// every browser can import, to the Downloads folder. Returns the path.
+#[tauri::command]
+pub async fn export_bookmarks(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<String, String> {
+    authorize_command(&state, &token, "export_bookmarks")?;
+    let html = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        bookmark_io::export_netscape(&config)
+    };
+    let path = export_path(&app, &state, "floatview-bookmarks", "html")?;
+    std::fs::write(&path, html).map_err(|e| format!("Failed to write bookmarks: {e}"))?;
+    Ok(path.display().to_string())
+}
+
+/// The URL to open for text typed in the URL bar: the address itself, or
+/// a search on the engine its keyword or the default names.
+#[tauri::command]
+pub async fn resolve_omnibox_input(
+    state: tauri::State<'_, AppState>,
+    input: String,
+    token: String,
+) -> Result<String, String> {
+    authorize_command(&state, &token, "resolve_omnibox_input")?;
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    if config.allow_local_files {
+        if let Some(path) = local_media::local_input_path(&input) {
+            return Url::from_file_path(&path)
+                .map(String::from)
+                .map_err(|_| "Not an absolute path".to_string());
+        }
+    }
+    omnibox::resolve_omnibox_input(
+        &input,
+        &config.search_engines,
+        &config.default_search_engine,
+    )
+}
+
+/// URL bar suggestions for `query` from bookmarks, recent URLs and
+/// history, best first.
+#[tauri::command]
+pub async fn suggest_urls(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+    token: String,
+) -> Result<Vec<Suggestion>, String> {
+    authorize_command(&state, &token, "suggest_urls")?;
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let entries = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(suggest::suggest(
+        &config.bookmarks,
+        &history::recent_urls(&entries, MAX_RECENT_URLS),
+        &entries,
+        &query,
+        limit.unwrap_or(DEFAULT_SUGGESTIONS),
+        history::now_secs(),
+    ))
+}
+
+/// The newest distinct history URLs, for the Recent dropdown.
+#[tauri::command]
+pub async fn get_recent_urls(
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<Vec<String>, String> {
+    authorize_command(&state, &token, "get_recent_urls")?;
+    let entries = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history::recent_urls(&entries, MAX_RECENT_URLS))
+}
+
+/// History entries matching `query`, newest first.
+#[tauri::command]
+pub async fn search_history(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+    token: String,
+) -> Result<Vec<HistoryEntry>, String> {
+    authorize_command(&state, &token, "search_history")?;
+    let entries = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history::search(
+        &entries,
+        &query,
+        limit.unwrap_or(MAX_HISTORY_RESULTS),
+    ))
+}
+
+/// Delete `url` from history, which also takes it off the Recent list.
+/// Returns whether history had it.
+#[tauri::command]
+pub async fn delete_history_entry(
+    state: tauri::State<'_, AppState>,
+    url: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "delete_history_entry")?;
+    let removed = edit_history(&state, |entries| {
+        if history::delete_url(entries, &url) {
+            vec![url.clone()]
+        } else {
+            Vec::new()
+        }
+    })?;
+    Ok(!removed.is_empty())
+}
+
+/// Delete history last visited in `[from, to)` (Unix seconds; either end
+/// may be open, so no bounds clears everything). Returns how many entries
+/// went.
+#[tauri::command]
+pub async fn clear_history(
+    state: tauri::State<'_, AppState>,
+    from: Option<u64>,
+    to: Option<u64>,
+    token: String,
+) -> Result<usize, String> {
+    authorize_command(&state, &token, "clear_history")?;
+    let removed = edit_history(&state, |entries| {
+        let before: Vec<String> = entries.iter().map(|e| e.url.clone()).collect();
+        history::clear_range(entries, from, to);
+        let kept: HashSet<&str> = entries.iter().map(|e| e.url.as_str()).collect();
+        before
+            .into_iter()
+            .filter(|url| !kept.contains(url.as_str()))
+            .collect()
+    })?;
+    Ok(removed.len())
+}
+
+/// Run a history deletion that returns the removed URLs and rewrite the
+/// store if anything went. Refused while kiosk mode is locked.
+fn edit_history(
+    state: &AppState,
+    edit: impl FnOnce(&mut Vec<HistoryEntry>) -> Vec<String>,
+) -> Result<Vec<String>, String> {
+    {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        ops::ensure_settings_unlocked(state, &config)?;
+    }
+    let mut entries = state.history.lock().map_err(|e| e.to_string())?;
+    let removed = edit(&mut entries);
+    if !removed.is_empty() {
+        history::write_history(&state.history_path, &entries);
+    }
+    Ok(removed)
+}
+
+/// Whether kiosk mode is on, and unlocked or armed for exit this run.
+#[tauri::command]
+pub async fn get_kiosk_status(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<KioskStatus, String> {
+    authorize_command(&state, &token, "get_kiosk_status")?;
+    ops::kiosk_status(&app)
+}
+
+/// Unlock Settings and the URL bar until `lock_kiosk`. Returns whether
+/// the PIN was right; a wrong one is answered after [`WRONG_PIN_DELAY`].
+#[tauri::command]
+pub async fn unlock_kiosk(
+    state: tauri::State<'_, AppState>,
+    pin: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "unlock_kiosk")?;
+    let unlocked = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let ok = config.kiosk.enabled && kiosk::verify_pin(&config.kiosk, &pin);
+        state.kiosk.lock().map_err(|e| e.to_string())?.unlocked = ok;
+        ok
+    };
+    if !unlocked {
+        tokio::time::sleep(WRONG_PIN_DELAY).await;
+    }
+    Ok(unlocked)
+}
+
+/// Hide Settings and the URL bar again; called when Settings closes.
+#[tauri::command]
+pub async fn lock_kiosk(state: tauri::State<'_, AppState>, token: String) -> Result<(), String> {
+    authorize_command(&state, &token, "lock_kiosk")?;
+    state.kiosk.lock().map_err(|e| e.to_string())?.lock();
+    Ok(())
+}
+
+/// Turn kiosk mode off. Needs the `exit_kiosk` hotkey pressed within
+/// [`kiosk::EXIT_ARM_WINDOW`] and the PIN; a wrong PIN disarms, so each
+/// guess takes another press. Refused when policy pins kiosk mode on.
+#[tauri::command]
+pub async fn exit_kiosk(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    pin: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "exit_kiosk")?;
+    if state
+        .layers
+        .pinned()
+        .iter()
+        .any(|key| key.path == "kiosk" || key.path == "kiosk.enabled")
+    {
+        return Err("Kiosk mode is locked by system policy".to_string());
+    }
+    let verified = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let mut session = state.kiosk.lock().map_err(|e| e.to_string())?;
+        if !config.kiosk.enabled {
+            return Ok(true);
+        }
+        if !session.exit_armed(Instant::now()) {
+            return Err("Press the exit kiosk hotkey first".to_string());
+        }
+        let ok = kiosk::verify_pin(&config.kiosk, &pin);
+        if !ok {
+            session.exit_armed_at = None;
+        }
+        ok
+    };
+    if !verified {
+        tokio::time::sleep(WRONG_PIN_DELAY).await;
+        return Ok(false);
+    }
+    ops::leave_kiosk(&app)?;
+    Ok(true)
+}
+
+/// Run one of the [`bookmarks`] edits under the config lock and save if
+/// it succeeded. Refused while kiosk mode is locked.
+fn edit_bookmarks<T>(
+    state: &AppState,
+    edit: impl FnOnce(&mut AppConfig) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    ops::ensure_settings_unlocked(state, &config)?;
+    let result = edit(&mut config)?;
+    save_config(state, &config);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn set_crop(
     app: AppHandle,
@@ -765,8 +1239,7 @@ pub async fn set_crop(
         save_config(&state, &config);
         config.clone()
     };
-    app.emit("config-changed", &snapshot)
-        .map_err(|e| e.to_string())?;
+    ops::emit_config(&app, "config-changed", &snapshot)?;
     Ok(())
 }
 
@@ -786,11 +1259,67 @@ pub async fn clear_crop(
         save_config(&state, &config);
         config.clone()
     };
-    app.emit("config-changed", &snapshot)
-        .map_err(|e| e.to_string())?;
+    ops::emit_config(&app, "config-changed", &snapshot)?;
     Ok(())
 }
 
+/// Which `site_settings` entry is in effect, the key "save as site
+/// default" would use for the current page, and the page zoom.
+#[tauri::command]
+pub async fn get_site_status(
+    window: WebviewWindow,
+    state: tauri::State<'_, AppState>,
+    token: String,
+) -> Result<SiteStatus, String> {
+    authorize_command(&state, &token, "get_site_status")?;
+    let url = window.url().map_err(|e| e.to_string())?;
+    let site = state.site.lock().map_err(|e| e.to_string())?;
+    Ok(SiteStatus {
+        active: site.active_key().map(str::to_string),
+        current: site_key_for_url(url.as_str()),
+        zoom: site.zoom(),
+    })
+}
+
+/// Save the current opacity, crop, auto-refresh, size, always-on-top and
+/// zoom as the default for `site` (an origin or host pattern), or for the
+/// current page's origin when `site` is omitted. Returns the stored key.
+#[tauri::command]
+pub async fn save_site_settings(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    site: Option<String>,
+    token: String,
+) -> Result<String, String> {
+    authorize_command(&state, &token, "save_site_settings")?;
+    ops::save_site_settings(&app, site.as_deref())
+}
+
+#[tauri::command]
+pub async fn forget_site_settings(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    site: String,
+    token: String,
+) -> Result<bool, String> {
+    authorize_command(&state, &token, "forget_site_settings")?;
+    ops::forget_site_settings(&app, &site)?;
+    // Returns a value (not unit) so the JS invoke wrapper — which maps IPC
+    // failures to null — can tell success apart from failure.
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn set_page_zoom(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    zoom: f64,
+    token: String,
+) -> Result<f64, String> {
+    authorize_command(&state, &token, "set_page_zoom")?;
+    ops::set_page_zoom(&app, zoom)
+}
+
 #[tauri::command]
 pub async fn clear_site_data(
     window: WebviewWindow,
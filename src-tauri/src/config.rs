@@ -1,4 +1,14 @@
+use std::collections::BTreeMap;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::history::DEFAULT_HISTORY_RETENTION_DAYS;
+use crate::layout::DEFAULT_NUDGE_STEP;
+use crate::omnibox::{default_search_engines, DEFAULT_SEARCH_ENGINE};
+use crate::urls::{DEFAULT_HOME_URL, DEFAULT_HOST_TRACKING_PARAMS, DEFAULT_TRACKING_PARAMS};
 
 /// Minimum opacity. Below this the window becomes effectively invisible,
 /// which is a click-through-style trap we don't want to reach by accident.
@@ -18,7 +28,9 @@ pub fn clamp_opacity(opacity: f64) -> f64 {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Window geometry and state. `x`/`y`/`width`/`height` are logical pixels
+/// at `scale_factor`; see [`crate::window_state::restore_rect`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WindowConfig {
     pub x: i32,
     pub y: i32,
@@ -27,6 +39,18 @@ pub struct WindowConfig {
     pub always_on_top: bool,
     pub opacity: f64,
     pub locked: bool,
+    /// The scale the geometry was captured at. `None` means the numbers
+    /// are physical pixels, as in configs saved before it was recorded.
+    #[serde(default)]
+    pub scale_factor: Option<f64>,
+    /// The monitor `x`/`y` were on when saved. `None` in configs saved
+    /// before monitors were recorded.
+    #[serde(default)]
+    pub monitor: Option<MonitorInfo>,
+    /// Where the window was on each monitor setup it has been saved on,
+    /// most recent first; see [`crate::window_state::placement_for`].
+    #[serde(default)]
+    pub placements: Vec<WindowPlacement>,
 }
 
 impl Default for WindowConfig {
@@ -39,10 +63,48 @@ impl Default for WindowConfig {
             always_on_top: true,
             opacity: 1.0,
             locked: false,
+            scale_factor: None,
+            monitor: None,
+            placements: Vec::new(),
         }
     }
 }
 
+/// A monitor as recorded with the window geometry. Position and size are
+/// physical pixels in the desktop's coordinate space.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    /// The OS's name for it (`DP-1`, `\\.\DISPLAY2`); empty if unknown.
+    #[serde(default)]
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "default_scale_factor")]
+    pub scale_factor: f64,
+}
+
+fn default_scale_factor() -> f64 {
+    1.0
+}
+
+/// The window's geometry as last saved on one monitor setup, in logical
+/// pixels at `scale_factor` like [`WindowConfig`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    /// [`crate::window_state::setup_key`] of the monitors connected then.
+    pub setup: String,
+    /// The monitor the window was on.
+    pub monitor: MonitorInfo,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default = "default_scale_factor")]
+    pub scale_factor: f64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HotkeyConfig {
     pub toggle_on_top: String,
@@ -64,6 +126,32 @@ pub struct HotkeyConfig {
     /// sibling scripts.
     #[serde(default = "default_show_strip")]
     pub show_strip: String,
+    /// Asks for the PIN to leave kiosk mode. The only hotkey registered
+    /// while kiosk mode is on.
+    #[serde(default = "default_exit_kiosk")]
+    pub exit_kiosk: String,
+    /// Move the window by `nudge_step` logical pixels. These work with
+    /// click-through on, when the window can't be dragged.
+    #[serde(default = "default_nudge_left")]
+    pub nudge_left: String,
+    #[serde(default = "default_nudge_right")]
+    pub nudge_right: String,
+    #[serde(default = "default_nudge_up")]
+    pub nudge_up: String,
+    #[serde(default = "default_nudge_down")]
+    pub nudge_down: String,
+    /// Resize by [`GROW_FACTOR`](crate::layout::GROW_FACTOR) about the
+    /// center, keeping the aspect ratio.
+    #[serde(default = "default_grow_window")]
+    pub grow_window: String,
+    #[serde(default = "default_shrink_window")]
+    pub shrink_window: String,
+    /// Move to the next corner of the monitor, clockwise.
+    #[serde(default = "default_cycle_corner")]
+    pub cycle_corner: String,
+    /// Move to the same relative spot on the next monitor.
+    #[serde(default = "default_next_monitor")]
+    pub next_monitor: String,
 }
 
 fn default_media_mute() -> String {
@@ -78,6 +166,42 @@ fn default_show_strip() -> String {
     "Alt+Shift+S".to_string()
 }
 
+fn default_exit_kiosk() -> String {
+    "Ctrl+Alt+Shift+K".to_string()
+}
+
+fn default_nudge_left() -> String {
+    "Ctrl+Alt+Shift+Left".to_string()
+}
+
+fn default_nudge_right() -> String {
+    "Ctrl+Alt+Shift+Right".to_string()
+}
+
+fn default_nudge_up() -> String {
+    "Ctrl+Alt+Shift+Up".to_string()
+}
+
+fn default_nudge_down() -> String {
+    "Ctrl+Alt+Shift+Down".to_string()
+}
+
+fn default_grow_window() -> String {
+    "Ctrl+Alt+Shift+=".to_string()
+}
+
+fn default_shrink_window() -> String {
+    "Ctrl+Alt+Shift+-".to_string()
+}
+
+fn default_cycle_corner() -> String {
+    "Ctrl+Alt+Shift+C".to_string()
+}
+
+fn default_next_monitor() -> String {
+    "Ctrl+Alt+Shift+N".to_string()
+}
+
 impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
@@ -92,11 +216,20 @@ impl Default for HotkeyConfig {
             media_mute: default_media_mute(),
             zoom_video: default_zoom_video(),
             show_strip: default_show_strip(),
+            exit_kiosk: default_exit_kiosk(),
+            nudge_left: default_nudge_left(),
+            nudge_right: default_nudge_right(),
+            nudge_up: default_nudge_up(),
+            nudge_down: default_nudge_down(),
+            grow_window: default_grow_window(),
+            shrink_window: default_shrink_window(),
+            cycle_corner: default_cycle_corner(),
+            next_monitor: default_next_monitor(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CropConfig {
     pub x: f64,
     pub y: f64,
@@ -104,24 +237,314 @@ pub struct CropConfig {
     pub height: f64,
 }
 
+/// A saved page. Order is the entry's position in `AppConfig::bookmarks`,
+/// which the reorder command rewrites; folders group entries for display
+/// without changing that order.
+///
+/// Schema v1 stored bare URL strings. `config.json` is rewritten by the
+/// v1 -> v2 migration, but policy defaults and settings bundles aren't
+/// versioned the same way, so a bare string still deserializes here as a
+/// bookmark with no id (the sanitizer assigns one) and no title.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BookmarkRepr")]
+pub struct Bookmark {
+    /// Stable handle for the edit commands; unique within one list.
+    pub id: String,
+    pub url: String,
+    /// Page title, captured from `set_window_title` until the user renames
+    /// it. Empty until then; the UI falls back to the URL.
+    pub title: String,
+    /// Name of an entry in `bookmark_folders`, or `None` for the top level.
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    /// Unix seconds. 0 for bookmarks migrated from schema v1, whose
+    /// creation time was never recorded.
+    pub created: u64,
+}
+
+impl Bookmark {
+    /// A new top-level bookmark stamped with a fresh id and the current time.
+    pub fn new(url: String, title: String) -> Self {
+        Self {
+            id: new_bookmark_id(),
+            url,
+            title,
+            folder: None,
+            tags: Vec::new(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// A search engine the URL bar can send non-URL input to; see
+/// [`crate::omnibox`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchEngine {
+    pub name: String,
+    /// Typed before a query (`yt cats`) to search this engine instead of
+    /// the default. Lowercase, no spaces, unique within the list.
+    pub keyword: String,
+    /// http(s) URL with `%s` where the encoded query goes.
+    pub url: String,
+}
+
+/// A user-defined snap layout; see [`crate::layout`]. Once sanitized it
+/// has either a `grid` or `zones`, not both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapLayout {
+    /// Used in positions as `<name>:<zone>`. Lowercase, one word, unique
+    /// within the list.
+    pub name: String,
+    /// Gap at the monitor edges and between tiles, in logical pixels.
+    /// `None` uses [`crate::layout::SNAP_PADDING`].
+    #[serde(default)]
+    pub padding: Option<u32>,
+    #[serde(default)]
+    pub grid: Option<LayoutGrid>,
+    #[serde(default)]
+    pub zones: Vec<LayoutZone>,
+}
+
+/// An even grid of `columns` x `rows` cells, numbered from 1 in reading
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutGrid {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// A named rectangle in percent of the monitor (0-100).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutZone {
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A named window setup the user saved; see [`crate::window_presets`].
+/// Geometry is logical pixels at `scale_factor`, like [`WindowConfig`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowPreset {
+    /// Shown in the snap popup and tray. Unique within the list, ignoring
+    /// case.
+    pub name: String,
+    /// Global hotkey that applies the preset, in the `hotkeys` syntax.
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub scale_factor: Option<f64>,
+    pub opacity: f64,
+    pub always_on_top: bool,
+    pub locked: bool,
+    #[serde(default)]
+    pub crop: Option<CropConfig>,
+}
+
+pub fn new_bookmark_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BookmarkRepr {
+    Url(String),
+    Entry {
+        #[serde(default)]
+        id: String,
+        url: String,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        folder: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        created: u64,
+    },
+}
+
+impl From<BookmarkRepr> for Bookmark {
+    fn from(repr: BookmarkRepr) -> Self {
+        match repr {
+            BookmarkRepr::Url(url) => Self {
+                id: String::new(),
+                url,
+                title: String::new(),
+                folder: None,
+                tags: Vec::new(),
+                created: 0,
+            },
+            BookmarkRepr::Entry {
+                id,
+                url,
+                title,
+                folder,
+                tags,
+                created,
+            } => Self {
+                id,
+                url,
+                title,
+                folder,
+                tags,
+                created,
+            },
+        }
+    }
+}
+
+/// The per-site settings a named profile carries. Everything else in
+/// `AppConfig` (hotkeys, the first-run flag, the profile list itself) is
+/// shared across profiles.
+///
+/// The active profile's settings live in the top-level `AppConfig`
+/// fields, where every existing code path already reads and writes them;
+/// its entry here is refreshed from those fields whenever profiles are
+/// switched or edited. `window.locked` is stored but never swapped in:
+/// click-through is session state, and switching into a locked profile
+/// would hide the window from the mouse with no warning.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    pub name: String,
+    pub window: WindowConfig,
+    #[serde(default = "default_home_url")]
+    pub home_url: String,
+    #[serde(default)]
+    pub last_url: Option<String>,
+    #[serde(default)]
+    pub auto_refresh_minutes: u32,
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub bookmark_folders: Vec<String>,
+    #[serde(default)]
+    pub crop: Option<CropConfig>,
+}
+
+/// Overrides applied while the page is on a matching site; see
+/// [`crate::site_settings`]. A `None` field leaves the global value in
+/// effect. `width` / `height` are in [`WindowConfig`]'s units;
+/// `zoom` is a page zoom factor (1.0 = 100%).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SiteSettings {
+    #[serde(default)]
+    pub opacity: Option<f64>,
+    #[serde(default)]
+    pub crop: Option<CropConfig>,
+    #[serde(default)]
+    pub auto_refresh_minutes: Option<u32>,
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub height: Option<i32>,
+    #[serde(default)]
+    pub always_on_top: Option<bool>,
+    #[serde(default)]
+    pub zoom: Option<f64>,
+}
+
+/// Query parameters removed from URLs before they are stored as a
+/// bookmark, recent URL or history entry; see
+/// [`crate::urls::strip_tracking_params`]. A name ending in `*` matches
+/// every parameter starting with the rest (`utm_*`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackingParams {
+    #[serde(default = "default_true")]
+    pub strip: bool,
+    /// Stripped on every site.
+    #[serde(default = "default_tracking_params")]
+    pub params: Vec<String>,
+    /// Stripped on one host (`youtu.be`), or on a domain and all of its
+    /// subdomains (`*.youtube.com`), on top of `params`.
+    #[serde(default = "default_host_tracking_params")]
+    pub hosts: BTreeMap<String, Vec<String>>,
+    /// Also ignore these parameters when comparing URLs, so a bookmark
+    /// stored without them still matches a page opened with them.
+    #[serde(default)]
+    pub ignore_when_matching: bool,
+}
+
+fn default_tracking_params() -> Vec<String> {
+    DEFAULT_TRACKING_PARAMS
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+fn default_host_tracking_params() -> BTreeMap<String, Vec<String>> {
+    DEFAULT_HOST_TRACKING_PARAMS
+        .iter()
+        .map(|(host, params)| {
+            (
+                host.to_string(),
+                params.iter().map(|p| p.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+impl Default for TrackingParams {
+    fn default() -> Self {
+        Self {
+            strip: true,
+            params: default_tracking_params(),
+            hosts: default_host_tracking_params(),
+            ignore_when_matching: false,
+        }
+    }
+}
+
+/// Kiosk mode; see [`crate::kiosk`]. Set in `config.json` or machine
+/// policy: Settings can't change it, and leaving it takes the
+/// `exit_kiosk` hotkey plus the PIN.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KioskConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Normalized URLs; a page is allowed when it has the same origin as
+    /// an entry and its path starts with the entry's path. Include the
+    /// home page, or Go Home is refused too.
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    /// Plaintext PIN as typed into `config.json` or policy. The sanitizer
+    /// replaces it with `pin_hash`, so it is never written back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin: Option<String>,
+    /// Hex SHA-256 of the PIN; see [`crate::kiosk::pin_hash`]. Without
+    /// one, kiosk mode can't be unlocked or left from the app.
+    #[serde(default)]
+    pub pin_hash: Option<String>,
+}
+
+/// Name of the profile a pre-profiles config is adopted into.
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
 /// Current config schema version. Bump ONLY when a field is renamed,
 /// retyped, or restructured — plain additions are covered by
 /// `#[serde(default)]`. Load code can branch on the stored value to
 /// migrate old files instead of silently resetting user data.
-pub const CONFIG_VERSION: u32 = 1;
+pub const CONFIG_VERSION: u32 = 4;
 
 fn default_config_version() -> u32 {
     // Pre-1.4.7 configs have no version field; they are schema v1.
     CONFIG_VERSION
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppConfig {
     #[serde(default = "default_config_version")]
     pub config_version: u32,
     pub window: WindowConfig,
     pub last_url: Option<String>,
-    pub recent_urls: Option<Vec<String>>,
     pub hotkeys: HotkeyConfig,
     #[serde(default = "default_home_url")]
     pub home_url: String,
@@ -129,14 +552,74 @@ pub struct AppConfig {
     pub first_run: bool,
     #[serde(default)]
     pub auto_refresh_minutes: u32,
+    /// Bookmarks in display order; see [`Bookmark`].
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// Bookmark folder names in display order. A folder can exist with
+    /// nothing in it; every `Bookmark::folder` names an entry here once
+    /// sanitized.
     #[serde(default)]
-    pub bookmarks: Vec<String>,
+    pub bookmark_folders: Vec<String>,
     #[serde(default)]
     pub crop: Option<CropConfig>,
+    /// Named profiles, in tray/menu order. Always holds at least the
+    /// active profile once sanitized; see [`ProfileConfig`].
+    #[serde(default)]
+    pub profiles: Vec<ProfileConfig>,
+    /// Name of the profile whose settings are in the top-level fields.
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// Per-site overrides keyed by origin (`https://host:port`), host
+    /// (`example.com`), or wildcard host (`*.example.com`). Shared across
+    /// profiles.
+    #[serde(default)]
+    pub site_settings: BTreeMap<String, SiteSettings>,
+    /// Days of browsing history to keep; see [`crate::history`].
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u32,
+    /// Engines offered for URL bar searches, in Settings order.
+    #[serde(default = "default_search_engines")]
+    pub search_engines: Vec<SearchEngine>,
+    /// Keyword of the engine used when the input names none.
+    #[serde(default = "default_search_engine")]
+    pub default_search_engine: String,
+    #[serde(default)]
+    pub tracking_params: TrackingParams,
+    /// User-defined snap layouts, in snap menu order; see
+    /// [`crate::layout`].
+    #[serde(default)]
+    pub layouts: Vec<SnapLayout>,
+    /// Saved window presets, in snap popup and tray order. Shared across
+    /// profiles.
+    #[serde(default)]
+    pub window_presets: Vec<WindowPreset>,
+    /// Logical pixels the `nudge_*` hotkeys move the window by.
+    #[serde(default = "default_nudge_step")]
+    pub nudge_step: u32,
+    /// Let the window open local files, served through the
+    /// `floatview-file` protocol; see [`crate::local_media`]. Off by
+    /// default: with it on, anything the user opens exposes its folder to
+    /// the page showing it.
+    #[serde(default)]
+    pub allow_local_files: bool,
+    #[serde(default)]
+    pub kiosk: KioskConfig,
+}
+
+fn default_search_engine() -> String {
+    DEFAULT_SEARCH_ENGINE.to_string()
+}
+
+fn default_history_retention_days() -> u32 {
+    DEFAULT_HISTORY_RETENTION_DAYS
+}
+
+fn default_nudge_step() -> u32 {
+    DEFAULT_NUDGE_STEP
 }
 
 fn default_home_url() -> String {
-    "https://www.google.com".to_string()
+    DEFAULT_HOME_URL.to_string()
 }
 
 fn default_true() -> bool {
@@ -149,13 +632,25 @@ impl Default for AppConfig {
             config_version: CONFIG_VERSION,
             window: WindowConfig::default(),
             last_url: None,
-            recent_urls: Some(Vec::new()),
             hotkeys: HotkeyConfig::default(),
             home_url: default_home_url(),
             first_run: true,
             auto_refresh_minutes: 0,
             bookmarks: Vec::new(),
+            bookmark_folders: Vec::new(),
             crop: None,
+            profiles: Vec::new(),
+            active_profile: None,
+            site_settings: BTreeMap::new(),
+            history_retention_days: DEFAULT_HISTORY_RETENTION_DAYS,
+            search_engines: default_search_engines(),
+            default_search_engine: default_search_engine(),
+            tracking_params: TrackingParams::default(),
+            layouts: Vec::new(),
+            window_presets: Vec::new(),
+            nudge_step: DEFAULT_NUDGE_STEP,
+            allow_local_files: false,
+            kiosk: KioskConfig::default(),
         }
     }
 }
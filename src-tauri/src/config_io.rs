@@ -1,42 +1,80 @@
-//! Config persistence pipeline: load, sanitize, save-via-channel,
+//! Config persistence pipeline: load, migrate, sanitize, save-via-channel,
 //! synchronous shutdown flush, and path discovery.
 //!
 //! All writes go through an atomic `write-tmp -> rename` with a `.bak`
-//! snapshot of the previous file kept for crash recovery. Saves during
-//! normal operation are serialized through a background thread so
-//! command handlers never block on disk I/O while holding the config
-//! mutex. `shutdown()` drops the sender, joins the thread, and writes
-//! one final copy synchronously.
+//! snapshot of the previous file kept for crash recovery, plus a
+//! rate-limited set of dated generations under `backups/` that reach back
+//! far enough to roll back a bad change. A `config.json` that no longer
+//! parses is moved aside and the backups are tried before falling back
+//! to defaults; what happened is recorded in a [`ConfigLoadReport`] the
+//! settings UI can surface. Saves during normal operation are coalesced
+//! by a background thread so command handlers never block on disk I/O
+//! while holding the config mutex. `shutdown()` drops the sender,
+//! joins the thread, and writes one final copy synchronously.
 
-use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
 use tauri::{AppHandle, Manager};
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
-use crate::config::{clamp_opacity, AppConfig, CropConfig};
+use crate::bookmarks::{
+    clean_title, find_folder, normalize_folder_name, normalize_tag, MAX_BOOKMARK_FOLDERS,
+    MAX_BOOKMARK_TAGS,
+};
+use crate::config::{
+    clamp_opacity, new_bookmark_id, AppConfig, Bookmark, CropConfig, HotkeyConfig, MonitorInfo,
+    SearchEngine, SiteSettings, SnapLayout, WindowConfig, WindowPreset, CONFIG_VERSION,
+    MIN_OPACITY,
+};
+use crate::config_layers::{ConfigLayers, UserValues};
+use crate::config_migrate::{
+    migrate_with, stored_version, MigrationError, MigrationStep, MIGRATIONS,
+};
+use crate::history::MAX_HISTORY_RETENTION_DAYS;
+use crate::kiosk::{clean_allowlist_entry, clean_pin, is_pin_hash, pin_hash, MAX_KIOSK_ALLOWLIST};
+use crate::layout::{clean_layout, MAX_LAYOUTS, MAX_NUDGE_STEP};
+use crate::omnibox::{clean_search_engine, find_engine, MAX_SEARCH_ENGINES};
+use crate::profiles::{ensure_active_profile, normalize_profile_name, MAX_PROFILES};
+use crate::site_settings::{
+    clamp_zoom, normalize_site_key, MAX_PAGE_ZOOM, MAX_SITE_SETTINGS, MIN_PAGE_ZOOM,
+};
 use crate::state::AppState;
-use crate::urls::{normalize_url, DEFAULT_HOME_URL};
-use crate::window_state::{normalize_startup_window_size, MAX_WINDOW_SIZE};
+use crate::urls::{
+    clean_tracking_param, normalize_url, url_to_store, urls_match, DEFAULT_HOME_URL,
+    MAX_TRACKING_HOSTS, MAX_TRACKING_PARAMS,
+};
+use crate::window_presets::{
+    clean_preset, find_hotkey_owner, find_preset, normalize_preset_hotkey, MAX_WINDOW_PRESETS,
+};
+use crate::window_state::{
+    normalize_startup_window_size, MAX_SCALE_FACTOR, MAX_WINDOW_PLACEMENTS, MAX_WINDOW_SIZE,
+    MIN_SCALE_FACTOR, MIN_WINDOW_SIZE,
+};
 
 pub const MAX_HOTKEY_LEN: usize = 64;
 
-/// Most-recent-URL history cap, enforced by both `sanitize_config` and
-/// `persist_recent_url`.
-pub const MAX_RECENT_URLS: usize = 10;
-
 /// Bookmark cap, enforced by both `sanitize_config` and `add_bookmark`.
-pub const MAX_BOOKMARKS: usize = 50;
+pub const MAX_BOOKMARKS: usize = 500;
 
 /// Upper bound for `auto_refresh_minutes`. Caps the JS `setInterval` delay
 /// (`minutes * 60_000` ms) well within the 32-bit ceiling so a tampered
 /// config can't overflow it into a near-continuous reload loop. 24h.
 pub const MAX_AUTO_REFRESH_MINUTES: u32 = 1440;
 
-/// Absolute bound for persisted window x/y. Keeps `is_position_visible`'s
+/// Absolute bound for persisted window x/y. Keeps `is_rect_visible`'s
 /// overlap arithmetic far from i32 overflow even on a tampered config.
-const MAX_WINDOW_POS: i32 = MAX_WINDOW_SIZE * 4;
+pub const MAX_WINDOW_POS: i32 = MAX_WINDOW_SIZE * 4;
 
 /// Minimum crop dimension as a fraction of viewport. Below this, applyCrop's
 /// 1/w scale blows up toward infinity; injection.js's interactive selector
@@ -44,6 +82,16 @@ const MAX_WINDOW_POS: i32 = MAX_WINDOW_SIZE * 4;
 /// tampered configs.
 pub const CROP_MIN_DIM: f64 = 0.01;
 
+/// Dated config generations kept under `backups/`. Older ones are pruned
+/// each time a new one is taken.
+pub const MAX_CONFIG_BACKUPS: usize = 10;
+
+/// Minimum age of the newest dated generation before a save takes another.
+/// `.bak` tracks every write, so it is usually as broken as `config.json`
+/// by the time anyone notices; hourly generations reach back ~10 hours of
+/// actual use instead of the last 10 opacity ticks.
+pub const CONFIG_BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 /// Resolve the path where `config.json` lives. Creates the directory if
 /// it doesn't exist yet. Falls back to the CWD if neither platform
 /// `app_config_dir` nor `app_log_dir` is accessible.
@@ -64,98 +112,939 @@ pub fn get_config_path(app: &AppHandle) -> PathBuf {
 /// Best-effort rescue of a corrupt/missing config by resetting bad fields
 /// to their defaults. Specifically:
 ///
-/// - Clamps window geometry (size, position) and opacity.
+/// - Stamps `config_version` with the current schema; by the time a config
+///   reaches here it has been migrated (or deserialized from a newer file,
+///   dropping the fields this build doesn't know), so it *is* current.
+/// - Clamps window geometry (size, position) and opacity; drops a scale
+///   factor that can't be real (the geometry is then read as physical
+///   pixels), recorded monitors that can't be real, out-of-range or repeated per-setup
+///   placements, and placements past the 10 most recent.
 /// - Caps `auto_refresh_minutes` so the JS refresh interval can't overflow.
-/// - Rejects non-http(s) URLs in `home_url`, `last_url`, `recent_urls`,
-///   `bookmarks`; normalizes the rest.
-/// - Enforces 50-bookmark and 10-recent-URL caps.
+/// - Keeps `history_retention_days` between 1 day and 10 years.
+/// - Keeps `nudge_step` between 1 and 500 logical pixels.
+/// - Drops search engines without a name, a one-word keyword, or an
+///   http(s) `%s` URL, and repeated keywords; falls back to the first
+///   engine when `default_search_engine` names none.
+/// - Drops snap layouts with a bad name, padding, grid or zone, and
+///   repeated names; caps the list at 20.
+/// - Drops window presets with a bad name, geometry or scale, and
+///   repeated names; caps the list at 20. A hotkey that doesn't parse or
+///   is another preset's, and a bad crop, are removed from the preset.
+/// - Replaces a plaintext kiosk `pin` with its `pin_hash`, drops a
+///   malformed hash, and rejects non-http(s) kiosk allowlist entries.
+/// - Lowercases tracking parameter names and drops malformed ones, and
+///   host rules keyed by anything but a host or `*.` domain.
+/// - Rejects non-http(s) URLs in `home_url`, `last_url` and `bookmarks`;
+///   normalizes the rest.
+/// - Enforces the 500-bookmark cap.
+/// - Gives each bookmark a unique id, cleans titles, folders and tags,
+///   and adds any folder a bookmark names to `bookmark_folders`.
 /// - Restores missing or too-short hotkeys to their defaults.
 /// - Clamps crop region within `[0, 1]`, enforces a minimum size, and
 ///   drops the crop entirely if any component is non-finite (NaN/Inf
 ///   would panic in `f64::clamp` further down the pipeline).
-pub fn sanitize_config(mut config: AppConfig) -> AppConfig {
-    let (width, height) = normalize_startup_window_size(config.window.width, config.window.height);
-    config.window.width = width;
-    config.window.height = height;
-    config.window.opacity = clamp_opacity(config.window.opacity);
-    config.window.x = config.window.x.clamp(-MAX_WINDOW_POS, MAX_WINDOW_POS);
-    config.window.y = config.window.y.clamp(-MAX_WINDOW_POS, MAX_WINDOW_POS);
-    config.auto_refresh_minutes = config.auto_refresh_minutes.min(MAX_AUTO_REFRESH_MINUTES);
-
-    config.home_url = normalize_url(&config.home_url)
-        .or_else(|_| normalize_url(DEFAULT_HOME_URL))
-        .unwrap_or_else(|_| DEFAULT_HOME_URL.to_string());
-    config.last_url = config
-        .last_url
-        .take()
-        .and_then(|url| normalize_url(&url).ok());
-
-    let mut deduped_recent = Vec::new();
-    let mut seen = HashSet::new();
-    for url in config.recent_urls.take().unwrap_or_default() {
-        if let Ok(normalized) = normalize_url(&url) {
-            if seen.insert(normalized.clone()) {
-                deduped_recent.push(normalized);
-            }
+/// - Applies all of the above to each stored profile, drops profiles
+///   with invalid or duplicate names, and guarantees the active profile
+///   exists (adopting the live settings as "Default" on first run).
+pub fn sanitize_config(config: AppConfig) -> AppConfig {
+    sanitize_config_with_report(config).0
+}
+
+/// One value [`sanitize_config_with_report`] (or a pinned policy key)
+/// changed, for the settings UI and the load log. `field` is a dotted
+/// path into the config JSON, with `[i]` for list entries.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigCorrection {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+    pub reason: String,
+}
+
+/// Collects [`ConfigCorrection`]s while sanitizing.
+#[derive(Debug, Default)]
+pub struct Corrections(Vec<ConfigCorrection>);
+
+impl Corrections {
+    pub fn note(
+        &mut self,
+        field: impl Into<String>,
+        old: impl Serialize,
+        new: impl Serialize,
+        reason: &str,
+    ) {
+        self.0.push(ConfigCorrection {
+            field: field.into(),
+            old: serde_json::to_value(old).unwrap_or_default(),
+            new: serde_json::to_value(new).unwrap_or_default(),
+            reason: reason.to_string(),
+        });
+    }
+
+    pub fn into_vec(self) -> Vec<ConfigCorrection> {
+        self.0
+    }
+}
+
+/// Log what sanitizing (or the policy) changed in a config read from
+/// disk, one line per field.
+pub fn log_corrections(corrections: &[ConfigCorrection]) {
+    for c in corrections {
+        warn!(
+            field = %c.field,
+            old = %c.old,
+            new = %c.new,
+            reason = %c.reason,
+            "Corrected config value"
+        );
+    }
+}
+
+/// [`sanitize_config`], also returning what it changed. Only rejections
+/// and clamps are reported; canonicalizing a valid URL (adding the
+/// scheme or a trailing slash) isn't a correction worth telling anyone
+/// about, except in bookmarks, where it can merge two entries.
+pub fn sanitize_config_with_report(mut config: AppConfig) -> (AppConfig, Vec<ConfigCorrection>) {
+    let mut report = Corrections::default();
+    config.config_version = CONFIG_VERSION;
+    sanitize_window(&mut config.window, "window", &mut report);
+    config.auto_refresh_minutes = sanitize_auto_refresh(
+        config.auto_refresh_minutes,
+        "auto_refresh_minutes",
+        &mut report,
+    );
+
+    config.home_url = sanitize_home_url(&config.home_url, "home_url", &mut report);
+    config.last_url = sanitize_last_url(config.last_url.take(), "last_url", &mut report);
+
+    sanitize_hotkeys(&mut config.hotkeys, &mut report);
+
+    config.bookmark_folders = sanitize_bookmark_folders(
+        std::mem::take(&mut config.bookmark_folders),
+        "bookmark_folders",
+        &mut report,
+    );
+    config.bookmarks = sanitize_bookmarks(
+        std::mem::take(&mut config.bookmarks),
+        &mut config.bookmark_folders,
+        "bookmarks",
+        &mut report,
+    );
+    config.crop = sanitize_crop(config.crop.take(), "crop", &mut report);
+
+    sanitize_profiles(&mut config, &mut report);
+    config.site_settings =
+        sanitize_site_settings(std::mem::take(&mut config.site_settings), &mut report);
+
+    let days = config
+        .history_retention_days
+        .clamp(1, MAX_HISTORY_RETENTION_DAYS);
+    if days != config.history_retention_days {
+        report.note(
+            "history_retention_days",
+            config.history_retention_days,
+            days,
+            "history retention out of range",
+        );
+        config.history_retention_days = days;
+    }
+    let step = config.nudge_step.clamp(1, MAX_NUDGE_STEP);
+    if step != config.nudge_step {
+        report.note(
+            "nudge_step",
+            config.nudge_step,
+            step,
+            "nudge step out of range",
+        );
+        config.nudge_step = step;
+    }
+    sanitize_search_engines(&mut config, &mut report);
+    sanitize_layouts(&mut config, &mut report);
+    sanitize_window_presets(&mut config, &mut report);
+    sanitize_tracking_params(&mut config, &mut report);
+    sanitize_kiosk(&mut config, &mut report);
+
+    (config, report.into_vec())
+}
+
+/// Replace every invalid hotkey with its default.
+fn sanitize_hotkeys(hotkeys: &mut HotkeyConfig, report: &mut Corrections) {
+    let defaults = HotkeyConfig::default();
+    let fields = [
+        (
+            "toggle_on_top",
+            &mut hotkeys.toggle_on_top,
+            defaults.toggle_on_top,
+        ),
+        (
+            "toggle_locked",
+            &mut hotkeys.toggle_locked,
+            defaults.toggle_locked,
+        ),
+        ("opacity_up", &mut hotkeys.opacity_up, defaults.opacity_up),
+        (
+            "opacity_down",
+            &mut hotkeys.opacity_down,
+            defaults.opacity_down,
+        ),
+        (
+            "toggle_visibility",
+            &mut hotkeys.toggle_visibility,
+            defaults.toggle_visibility,
+        ),
+        (
+            "media_play_pause",
+            &mut hotkeys.media_play_pause,
+            defaults.media_play_pause,
+        ),
+        ("media_next", &mut hotkeys.media_next, defaults.media_next),
+        (
+            "media_previous",
+            &mut hotkeys.media_previous,
+            defaults.media_previous,
+        ),
+        ("media_mute", &mut hotkeys.media_mute, defaults.media_mute),
+        ("zoom_video", &mut hotkeys.zoom_video, defaults.zoom_video),
+        ("show_strip", &mut hotkeys.show_strip, defaults.show_strip),
+        ("exit_kiosk", &mut hotkeys.exit_kiosk, defaults.exit_kiosk),
+        ("nudge_left", &mut hotkeys.nudge_left, defaults.nudge_left),
+        (
+            "nudge_right",
+            &mut hotkeys.nudge_right,
+            defaults.nudge_right,
+        ),
+        ("nudge_up", &mut hotkeys.nudge_up, defaults.nudge_up),
+        ("nudge_down", &mut hotkeys.nudge_down, defaults.nudge_down),
+        (
+            "grow_window",
+            &mut hotkeys.grow_window,
+            defaults.grow_window,
+        ),
+        (
+            "shrink_window",
+            &mut hotkeys.shrink_window,
+            defaults.shrink_window,
+        ),
+        (
+            "cycle_corner",
+            &mut hotkeys.cycle_corner,
+            defaults.cycle_corner,
+        ),
+        (
+            "next_monitor",
+            &mut hotkeys.next_monitor,
+            defaults.next_monitor,
+        ),
+    ];
+    for (field, value, fallback) in fields {
+        let clean = sanitize_hotkey(value, &fallback);
+        if clean != value.trim() {
+            report.note(
+                format!("hotkeys.{field}"),
+                &*value,
+                &clean,
+                "not a valid hotkey; reset to default",
+            );
         }
-        if deduped_recent.len() >= MAX_RECENT_URLS {
-            break;
+        *value = clean;
+    }
+}
+
+/// Clamp window geometry (size, position) and opacity.
+fn sanitize_window(window: &mut WindowConfig, field: &str, report: &mut Corrections) {
+    let (width, height) = normalize_startup_window_size(window.width, window.height);
+    if (width, height) != (window.width, window.height) {
+        report.note(
+            format!("{field}.size"),
+            [window.width, window.height],
+            [width, height],
+            "window size out of range",
+        );
+    }
+    window.width = width;
+    window.height = height;
+    // Snapping 0.995 to fully opaque is how opacity always behaves, not a
+    // rejected value; only report values outside the permitted range.
+    let opacity = clamp_opacity(window.opacity);
+    if !(MIN_OPACITY..=1.0).contains(&window.opacity) {
+        report.note(
+            format!("{field}.opacity"),
+            window.opacity.is_finite().then_some(window.opacity),
+            opacity,
+            "opacity out of range",
+        );
+    }
+    window.opacity = opacity;
+    let x = window.x.clamp(-MAX_WINDOW_POS, MAX_WINDOW_POS);
+    let y = window.y.clamp(-MAX_WINDOW_POS, MAX_WINDOW_POS);
+    if (x, y) != (window.x, window.y) {
+        report.note(
+            format!("{field}.position"),
+            [window.x, window.y],
+            [x, y],
+            "window position out of range",
+        );
+    }
+    window.x = x;
+    window.y = y;
+    if window.scale_factor.is_some_and(|s| !valid_scale_factor(s)) {
+        report.note(
+            format!("{field}.scale_factor"),
+            window.scale_factor.filter(|s| s.is_finite()),
+            None::<()>,
+            "invalid scale factor",
+        );
+        window.scale_factor = None;
+    }
+
+    if window.monitor.as_ref().is_some_and(|m| !valid_monitor(m)) {
+        report.note(
+            format!("{field}.monitor"),
+            &window.monitor,
+            None::<()>,
+            "invalid monitor",
+        );
+        window.monitor = None;
+    }
+    let mut setups = HashSet::new();
+    let mut kept = Vec::with_capacity(window.placements.len());
+    for (i, placement) in window.placements.drain(..).enumerate() {
+        let in_range = valid_monitor(&placement.monitor)
+            && valid_scale_factor(placement.scale_factor)
+            && (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&placement.x)
+            && (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&placement.y)
+            && (MIN_WINDOW_SIZE..=MAX_WINDOW_SIZE).contains(&placement.width)
+            && (MIN_WINDOW_SIZE..=MAX_WINDOW_SIZE).contains(&placement.height);
+        if !in_range {
+            report.note(
+                format!("{field}.placements[{i}]"),
+                &placement,
+                None::<()>,
+                "window placement out of range",
+            );
+        } else if setups.insert(placement.setup.clone()) {
+            kept.push(placement);
         }
     }
-    config.recent_urls = Some(deduped_recent);
-
-    config.hotkeys.toggle_on_top = sanitize_hotkey(&config.hotkeys.toggle_on_top, "Alt+Shift+T");
-    config.hotkeys.toggle_locked = sanitize_hotkey(&config.hotkeys.toggle_locked, "Alt+Shift+D");
-    config.hotkeys.opacity_up = sanitize_hotkey(&config.hotkeys.opacity_up, "Alt+Shift+Up");
-    config.hotkeys.opacity_down = sanitize_hotkey(&config.hotkeys.opacity_down, "Alt+Shift+Down");
-    config.hotkeys.toggle_visibility =
-        sanitize_hotkey(&config.hotkeys.toggle_visibility, "Alt+Shift+H");
-    config.hotkeys.media_play_pause =
-        sanitize_hotkey(&config.hotkeys.media_play_pause, "Alt+Shift+P");
-    config.hotkeys.media_next = sanitize_hotkey(&config.hotkeys.media_next, "Alt+Shift+Right");
-    config.hotkeys.media_previous =
-        sanitize_hotkey(&config.hotkeys.media_previous, "Alt+Shift+Left");
-    config.hotkeys.media_mute = sanitize_hotkey(&config.hotkeys.media_mute, "Alt+Shift+M");
-    config.hotkeys.zoom_video = sanitize_hotkey(&config.hotkeys.zoom_video, "Alt+Shift+V");
-    config.hotkeys.show_strip = sanitize_hotkey(&config.hotkeys.show_strip, "Alt+Shift+S");
-
-    let mut deduped_bookmarks = Vec::new();
-    let mut seen_bookmarks = HashSet::new();
-    for url in std::mem::take(&mut config.bookmarks) {
-        if let Ok(normalized) = normalize_url(&url) {
-            if seen_bookmarks.insert(normalized.clone()) {
-                deduped_bookmarks.push(normalized);
-            }
+    if kept.len() > MAX_WINDOW_PLACEMENTS {
+        report.note(
+            format!("{field}.placements"),
+            kept.len(),
+            MAX_WINDOW_PLACEMENTS,
+            "too many window placements; oldest dropped",
+        );
+        kept.truncate(MAX_WINDOW_PLACEMENTS);
+    }
+    window.placements = kept;
+}
+
+/// A recorded monitor with a real size, a position in range and a sane
+/// scale factor.
+fn valid_monitor(monitor: &MonitorInfo) -> bool {
+    (1..=MAX_WINDOW_POS as u32).contains(&monitor.width)
+        && (1..=MAX_WINDOW_POS as u32).contains(&monitor.height)
+        && (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&monitor.x)
+        && (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&monitor.y)
+        && valid_scale_factor(monitor.scale_factor)
+}
+
+fn valid_scale_factor(scale: f64) -> bool {
+    (MIN_SCALE_FACTOR..=MAX_SCALE_FACTOR).contains(&scale)
+}
+
+/// Cap the auto-refresh interval so the JS timer can't overflow.
+fn sanitize_auto_refresh(minutes: u32, field: &str, report: &mut Corrections) -> u32 {
+    let capped = minutes.min(MAX_AUTO_REFRESH_MINUTES);
+    if capped != minutes {
+        report.note(field, minutes, capped, "auto-refresh interval too long");
+    }
+    capped
+}
+
+/// Normalize a home URL, falling back to the default for anything that
+/// isn't http(s).
+fn sanitize_home_url(url: &str, field: &str, report: &mut Corrections) -> String {
+    normalize_url(url).unwrap_or_else(|_| {
+        let fallback =
+            normalize_url(DEFAULT_HOME_URL).unwrap_or_else(|_| DEFAULT_HOME_URL.to_string());
+        report.note(
+            field,
+            url,
+            &fallback,
+            "not an http(s) URL; reset to default",
+        );
+        fallback
+    })
+}
+
+/// Normalize `last_url`, dropping it if it isn't http(s).
+fn sanitize_last_url(url: Option<String>, field: &str, report: &mut Corrections) -> Option<String> {
+    let url = url?;
+    match normalize_url(&url) {
+        Ok(normalized) => Some(normalized),
+        Err(_) => {
+            report.note(field, &url, (), "not an http(s) URL; removed");
+            None
         }
+    }
+}
+
+/// Normalize, dedupe, and cap a bookmark list. Dedup uses `urls_match`,
+/// the same rule `add_bookmark` applies, so `https://a.test/x` and
+/// `https://a.test/x/` from a hand-edited file or an imported bundle
+/// collapse to the first one.
+///
+/// Each rewritten URL is reported, so an entry that vanished as a
+/// duplicate can be traced back to the one it now matches.
+///
+/// Entries without an id (hand-written, or bare URLs from a policy file
+/// or bundle) get one quietly; a repeated id is reported and replaced.
+/// A folder missing from `folders` is added to it while there's room,
+/// so a hand-edited `"folder"` isn't silently flattened.
+fn sanitize_bookmarks(
+    bookmarks: Vec<Bookmark>,
+    folders: &mut Vec<String>,
+    field: &str,
+    report: &mut Corrections,
+) -> Vec<Bookmark> {
+    let mut deduped_bookmarks: Vec<Bookmark> = Vec::new();
+    let mut ids = HashSet::new();
+    let total = bookmarks.len();
+    for (i, mut bookmark) in bookmarks.into_iter().enumerate() {
         if deduped_bookmarks.len() >= MAX_BOOKMARKS {
+            report.note(
+                field,
+                total,
+                MAX_BOOKMARKS,
+                "too many bookmarks; the rest were dropped",
+            );
             break;
         }
+        let entry = format!("{field}[{i}]");
+        match normalize_url(&bookmark.url) {
+            Ok(normalized) => {
+                if deduped_bookmarks
+                    .iter()
+                    .any(|b| urls_match(&b.url, &normalized))
+                {
+                    report.note(entry, &bookmark.url, (), "duplicate bookmark; removed");
+                    continue;
+                }
+                if normalized != bookmark.url {
+                    report.note(
+                        format!("{entry}.url"),
+                        &bookmark.url,
+                        &normalized,
+                        "URL normalized",
+                    );
+                    bookmark.url = normalized;
+                }
+            }
+            Err(_) => {
+                report.note(entry, &bookmark.url, (), "not an http(s) URL; removed");
+                continue;
+            }
+        }
+        if bookmark.id.is_empty() {
+            bookmark.id = new_bookmark_id();
+        } else if ids.contains(&bookmark.id) {
+            let old = std::mem::replace(&mut bookmark.id, new_bookmark_id());
+            report.note(
+                format!("{entry}.id"),
+                old,
+                &bookmark.id,
+                "duplicate bookmark id; replaced",
+            );
+        }
+        ids.insert(bookmark.id.clone());
+        let title = clean_title(&bookmark.title);
+        if title != bookmark.title {
+            report.note(
+                format!("{entry}.title"),
+                &bookmark.title,
+                &title,
+                "control characters, padding, or excess length removed",
+            );
+            bookmark.title = title;
+        }
+        bookmark.folder = sanitize_bookmark_folder(bookmark.folder.take(), folders, &entry, report);
+        bookmark.tags = sanitize_bookmark_tags(std::mem::take(&mut bookmark.tags), &entry, report);
+        deduped_bookmarks.push(bookmark);
+    }
+    deduped_bookmarks
+}
+
+/// Resolve a bookmark's folder against `folders`, adding it if missing.
+fn sanitize_bookmark_folder(
+    folder: Option<String>,
+    folders: &mut Vec<String>,
+    entry: &str,
+    report: &mut Corrections,
+) -> Option<String> {
+    let raw = folder?;
+    let name = match normalize_folder_name(&raw) {
+        Ok(name) => name,
+        Err(_) => {
+            report.note(
+                format!("{entry}.folder"),
+                &raw,
+                (),
+                "invalid folder name; moved to the top level",
+            );
+            return None;
+        }
+    };
+    if let Some(existing) = find_folder(folders, &name) {
+        return Some(existing.clone());
+    }
+    if folders.len() >= MAX_BOOKMARK_FOLDERS {
+        report.note(
+            format!("{entry}.folder"),
+            &raw,
+            (),
+            "too many folders; moved to the top level",
+        );
+        return None;
+    }
+    folders.push(name.clone());
+    Some(name)
+}
+
+/// Drop invalid and repeated tags and cap the list.
+fn sanitize_bookmark_tags(tags: Vec<String>, entry: &str, report: &mut Corrections) -> Vec<String> {
+    let mut clean: Vec<String> = Vec::new();
+    for (i, raw) in tags.into_iter().enumerate() {
+        let field = format!("{entry}.tags[{i}]");
+        match normalize_tag(&raw) {
+            Ok(tag) if clean.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) => {
+                report.note(field, &raw, (), "duplicate tag; removed");
+            }
+            Ok(_) if clean.len() >= MAX_BOOKMARK_TAGS => {
+                report.note(field, &raw, (), "too many tags; removed");
+            }
+            Ok(tag) => clean.push(tag),
+            Err(_) => report.note(field, &raw, (), "invalid tag; removed"),
+        }
+    }
+    clean
+}
+
+/// Drop invalid engines and repeated keywords, cap the list, and point
+/// `default_search_engine` at an engine that exists.
+fn sanitize_search_engines(config: &mut AppConfig, report: &mut Corrections) {
+    let mut clean: Vec<SearchEngine> = Vec::new();
+    for (i, raw) in std::mem::take(&mut config.search_engines)
+        .into_iter()
+        .enumerate()
+    {
+        let field = format!("search_engines[{i}]");
+        match clean_search_engine(&raw) {
+            Ok(engine) if find_engine(&clean, &engine.keyword).is_some() => {
+                report.note(field, &raw, (), "duplicate search keyword; removed");
+            }
+            Ok(_) if clean.len() >= MAX_SEARCH_ENGINES => {
+                report.note(field, &raw, (), "too many search engines; removed");
+            }
+            Ok(engine) => {
+                if engine != raw {
+                    report.note(field, &raw, &engine, "search engine cleaned up");
+                }
+                clean.push(engine);
+            }
+            Err(reason) => report.note(field, &raw, (), &format!("{reason}; removed")),
+        }
+    }
+    config.search_engines = clean;
+
+    let default = config.default_search_engine.trim().to_lowercase();
+    let default = match find_engine(&config.search_engines, &default) {
+        Some(engine) => engine.keyword.clone(),
+        None => config
+            .search_engines
+            .first()
+            .map(|e| e.keyword.clone())
+            .unwrap_or_default(),
+    };
+    if default != config.default_search_engine {
+        report.note(
+            "default_search_engine",
+            &config.default_search_engine,
+            &default,
+            "not the keyword of a search engine",
+        );
+        config.default_search_engine = default;
+    }
+}
+
+fn sanitize_layouts(config: &mut AppConfig, report: &mut Corrections) {
+    let mut clean: Vec<SnapLayout> = Vec::new();
+    for (i, raw) in std::mem::take(&mut config.layouts).into_iter().enumerate() {
+        let field = format!("layouts[{i}]");
+        match clean_layout(&raw) {
+            Ok(layout) if clean.iter().any(|l| l.name == layout.name) => {
+                report.note(field, &raw, (), "duplicate layout name; removed");
+            }
+            Ok(_) if clean.len() >= MAX_LAYOUTS => {
+                report.note(field, &raw, (), "too many layouts; removed");
+            }
+            Ok(layout) => clean.push(layout),
+            Err(reason) => report.note(field, &raw, (), &format!("{reason}; removed")),
+        }
     }
-    config.bookmarks = deduped_bookmarks;
+    config.layouts = clean;
+}
 
+fn sanitize_window_presets(config: &mut AppConfig, report: &mut Corrections) {
+    let mut clean: Vec<WindowPreset> = Vec::new();
+    for (i, raw) in std::mem::take(&mut config.window_presets)
+        .into_iter()
+        .enumerate()
+    {
+        let field = format!("window_presets[{i}]");
+        let mut preset = match clean_preset(&raw) {
+            Ok(preset) if find_preset(&clean, &preset.name).is_some() => {
+                report.note(field, &raw, (), "duplicate preset name; removed");
+                continue;
+            }
+            Ok(_) if clean.len() >= MAX_WINDOW_PRESETS => {
+                report.note(field, &raw, (), "too many window presets; removed");
+                continue;
+            }
+            Ok(preset) => preset,
+            Err(reason) => {
+                report.note(field, &raw, (), &format!("{reason}; removed"));
+                continue;
+            }
+        };
+        if !(MIN_OPACITY..=1.0).contains(&raw.opacity) {
+            report.note(
+                format!("{field}.opacity"),
+                raw.opacity.is_finite().then_some(raw.opacity),
+                preset.opacity,
+                "opacity out of range",
+            );
+        }
+        if let Some(hotkey) = preset.hotkey.take() {
+            match normalize_preset_hotkey(&hotkey) {
+                Ok(Some(keys)) if find_hotkey_owner(&clean, &keys, None).is_some() => {
+                    report.note(
+                        format!("{field}.hotkey"),
+                        &hotkey,
+                        (),
+                        "hotkey used by another preset; removed",
+                    );
+                }
+                Ok(keys) => preset.hotkey = keys,
+                Err(reason) => {
+                    report.note(
+                        format!("{field}.hotkey"),
+                        &hotkey,
+                        (),
+                        &format!("{reason}; removed"),
+                    );
+                }
+            }
+        }
+        preset.crop = sanitize_crop(preset.crop.take(), &format!("{field}.crop"), report);
+        clean.push(preset);
+    }
+    config.window_presets = clean;
+}
+
+/// Clean the global and per-host tracking parameter lists. Host keys are
+/// canonicalized like `site_settings` keys, but origins are refused:
+/// stripping happens per host. Keys that canonicalize alike are merged.
+fn sanitize_tracking_params(config: &mut AppConfig, report: &mut Corrections) {
+    let rules = &mut config.tracking_params;
+    rules.params = sanitize_tracking_names(
+        std::mem::take(&mut rules.params),
+        "tracking_params.params",
+        report,
+    );
+
+    let mut hosts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (raw, params) in std::mem::take(&mut rules.hosts) {
+        let field = format!("tracking_params.hosts.{raw}");
+        let key = match normalize_site_key(&raw) {
+            Ok(key) if key.contains("://") => {
+                report.note(field, &raw, (), "not a host or *.domain; removed");
+                continue;
+            }
+            Ok(key) => key,
+            Err(reason) => {
+                report.note(field, &raw, (), &format!("{reason}; removed"));
+                continue;
+            }
+        };
+        if !hosts.contains_key(&key) && hosts.len() >= MAX_TRACKING_HOSTS {
+            report.note(field, &raw, (), "too many tracking host rules; removed");
+            continue;
+        }
+        let merged = hosts.entry(key).or_default();
+        merged.extend(params);
+        *merged = sanitize_tracking_names(std::mem::take(merged), &field, report);
+    }
+    rules.hosts = hosts;
+}
+
+/// Lowercase, dedupe and cap one list of tracking parameter names.
+fn sanitize_tracking_names(
+    names: Vec<String>,
+    field: &str,
+    report: &mut Corrections,
+) -> Vec<String> {
+    let mut clean: Vec<String> = Vec::new();
+    for (i, raw) in names.into_iter().enumerate() {
+        let entry = format!("{field}[{i}]");
+        match clean_tracking_param(&raw) {
+            Ok(name) if clean.contains(&name) => {}
+            Ok(_) if clean.len() >= MAX_TRACKING_PARAMS => {
+                report.note(entry, &raw, (), "too many tracking parameters; removed");
+            }
+            Ok(name) => clean.push(name),
+            Err(reason) => report.note(entry, &raw, (), &format!("{reason}; removed")),
+        }
+    }
+    clean
+}
+
+/// Hash a plaintext PIN into `pin_hash` and clean up the allowlist. The
+/// hashing itself isn't reported: a policy or hand-written `pin` is the
+/// documented way to set one, not a mistake.
+fn sanitize_kiosk(config: &mut AppConfig, report: &mut Corrections) {
+    let kiosk = &mut config.kiosk;
+    if let Some(pin) = kiosk.pin.take() {
+        match clean_pin(&pin) {
+            Ok(pin) => kiosk.pin_hash = Some(pin_hash(pin)),
+            Err(reason) => report.note("kiosk.pin", "(hidden)", (), &format!("{reason}; ignored")),
+        }
+    }
+    if kiosk
+        .pin_hash
+        .as_deref()
+        .is_some_and(|hash| !is_pin_hash(hash))
+    {
+        report.note(
+            "kiosk.pin_hash",
+            &kiosk.pin_hash,
+            (),
+            "not a PIN hash; removed",
+        );
+        kiosk.pin_hash = None;
+    }
+
+    let mut clean: Vec<String> = Vec::new();
+    for (i, raw) in std::mem::take(&mut kiosk.allowlist).into_iter().enumerate() {
+        let field = format!("kiosk.allowlist[{i}]");
+        match clean_allowlist_entry(&raw) {
+            Ok(url) if clean.contains(&url) => {}
+            Ok(_) if clean.len() >= MAX_KIOSK_ALLOWLIST => {
+                report.note(field, &raw, (), "too many allowlist entries; removed");
+            }
+            Ok(url) => clean.push(url),
+            Err(reason) => report.note(field, &raw, (), &format!("{reason}; removed")),
+        }
+    }
+    kiosk.allowlist = clean;
+}
+
+/// Trim, dedupe (case-insensitively), and cap the folder list.
+fn sanitize_bookmark_folders(
+    folders: Vec<String>,
+    field: &str,
+    report: &mut Corrections,
+) -> Vec<String> {
+    let mut clean: Vec<String> = Vec::new();
+    for (i, raw) in folders.into_iter().enumerate() {
+        let entry = format!("{field}[{i}]");
+        match normalize_folder_name(&raw) {
+            Ok(name) if find_folder(&clean, &name).is_some() => {
+                report.note(entry, &raw, (), "duplicate folder; removed");
+            }
+            Ok(_) if clean.len() >= MAX_BOOKMARK_FOLDERS => {
+                report.note(entry, &raw, (), "too many folders; removed");
+            }
+            Ok(name) => clean.push(name),
+            Err(_) => report.note(entry, &raw, (), "invalid folder name; removed"),
+        }
+    }
+    clean
+}
+
+fn sanitize_crop(
+    crop: Option<CropConfig>,
+    field: &str,
+    report: &mut Corrections,
+) -> Option<CropConfig> {
     // Drop non-finite crop values up-front: f64::clamp panics when max is NaN,
     // and we don't want non-finite values reaching applyCrop anyway.
-    if let Some(crop) = config.crop.take() {
-        if crop.x.is_finite()
-            && crop.y.is_finite()
-            && crop.width.is_finite()
-            && crop.height.is_finite()
-        {
-            let width = crop.width.clamp(CROP_MIN_DIM, 1.0);
-            let height = crop.height.clamp(CROP_MIN_DIM, 1.0);
-            // Ensure crop stays within viewport bounds after width/height clamp.
-            let x = crop.x.clamp(0.0, 1.0 - width);
-            let y = crop.y.clamp(0.0, 1.0 - height);
-            config.crop = Some(CropConfig {
-                x,
-                y,
-                width,
-                height,
-            });
+    let crop = crop?;
+    if !(crop.x.is_finite()
+        && crop.y.is_finite()
+        && crop.width.is_finite()
+        && crop.height.is_finite())
+    {
+        report.note(field, (), (), "crop values must be finite; crop removed");
+        return None;
+    }
+    let width = crop.width.clamp(CROP_MIN_DIM, 1.0);
+    let height = crop.height.clamp(CROP_MIN_DIM, 1.0);
+    // Ensure crop stays within viewport bounds after width/height clamp.
+    let x = crop.x.clamp(0.0, 1.0 - width);
+    let y = crop.y.clamp(0.0, 1.0 - height);
+    let clamped = CropConfig {
+        x,
+        y,
+        width,
+        height,
+    };
+    if clamped != crop {
+        report.note(field, &crop, &clamped, "crop region outside the page");
+    }
+    Some(clamped)
+}
+
+/// Canonicalize site keys (dropping invalid ones and later duplicates),
+/// apply the global field rules to each override, and cap the map.
+fn sanitize_site_settings(
+    sites: BTreeMap<String, SiteSettings>,
+    report: &mut Corrections,
+) -> BTreeMap<String, SiteSettings> {
+    let total = sites.len();
+    let mut sanitized = BTreeMap::new();
+    for (key, mut site) in sites {
+        let field = format!("site_settings.{key}");
+        let Ok(normalized) = normalize_site_key(&key) else {
+            report.note(field, (), (), "not an origin or host pattern; removed");
+            continue;
+        };
+        if sanitized.contains_key(&normalized) {
+            report.note(field, (), (), "duplicate site; removed");
+            continue;
+        }
+        if sanitized.len() >= MAX_SITE_SETTINGS {
+            report.note(
+                "site_settings",
+                total,
+                MAX_SITE_SETTINGS,
+                "too many sites; the rest were dropped",
+            );
+            break;
+        }
+        if let Some(opacity) = site.opacity {
+            let clamped = clamp_opacity(opacity);
+            if !(MIN_OPACITY..=1.0).contains(&opacity) {
+                report.note(
+                    format!("{field}.opacity"),
+                    opacity.is_finite().then_some(opacity),
+                    clamped,
+                    "opacity out of range",
+                );
+            }
+            site.opacity = Some(clamped);
         }
+        for (name, size) in [("width", &mut site.width), ("height", &mut site.height)] {
+            if let Some(value) = *size {
+                let clamped = value.clamp(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE);
+                if clamped != value {
+                    report.note(
+                        format!("{field}.{name}"),
+                        value,
+                        clamped,
+                        "window size out of range",
+                    );
+                }
+                *size = Some(clamped);
+            }
+        }
+        if let Some(zoom) = site.zoom {
+            let clamped = clamp_zoom(zoom);
+            if !(MIN_PAGE_ZOOM..=MAX_PAGE_ZOOM).contains(&zoom) {
+                report.note(
+                    format!("{field}.zoom"),
+                    zoom.is_finite().then_some(zoom),
+                    clamped,
+                    "page zoom out of range",
+                );
+            }
+            site.zoom = Some(clamped);
+        }
+        site.auto_refresh_minutes = site.auto_refresh_minutes.map(|minutes| {
+            sanitize_auto_refresh(minutes, &format!("{field}.auto_refresh_minutes"), report)
+        });
+        site.crop = sanitize_crop(site.crop.take(), &format!("{field}.crop"), report);
+        sanitized.insert(normalized, site);
     }
+    sanitized
+}
 
-    config
+/// Apply the same per-field rules to every stored profile, drop entries
+/// with unusable or duplicate names, cap the list, and make sure the
+/// active profile exists.
+fn sanitize_profiles(config: &mut AppConfig, report: &mut Corrections) {
+    let mut seen = HashSet::new();
+    let mut profiles = Vec::new();
+    let stored = std::mem::take(&mut config.profiles);
+    let total = stored.len();
+    for (i, mut profile) in stored.into_iter().enumerate() {
+        if profiles.len() >= MAX_PROFILES {
+            report.note(
+                "profiles",
+                total,
+                MAX_PROFILES,
+                "too many profiles; the rest were dropped",
+            );
+            break;
+        }
+        let field = format!("profiles[{i}]");
+        let Ok(name) = normalize_profile_name(&profile.name) else {
+            report.note(
+                format!("{field}.name"),
+                &profile.name,
+                (),
+                "invalid profile name; profile removed",
+            );
+            continue;
+        };
+        if !seen.insert(name.to_lowercase()) {
+            report.note(
+                format!("{field}.name"),
+                &profile.name,
+                (),
+                "duplicate profile name; profile removed",
+            );
+            continue;
+        }
+        profile.name = name;
+        sanitize_window(&mut profile.window, &format!("{field}.window"), report);
+        profile.home_url =
+            sanitize_home_url(&profile.home_url, &format!("{field}.home_url"), report);
+        profile.last_url = sanitize_last_url(
+            profile.last_url.take(),
+            &format!("{field}.last_url"),
+            report,
+        );
+        profile.auto_refresh_minutes = sanitize_auto_refresh(
+            profile.auto_refresh_minutes,
+            &format!("{field}.auto_refresh_minutes"),
+            report,
+        );
+        profile.bookmark_folders = sanitize_bookmark_folders(
+            std::mem::take(&mut profile.bookmark_folders),
+            &format!("{field}.bookmark_folders"),
+            report,
+        );
+        profile.bookmarks = sanitize_bookmarks(
+            std::mem::take(&mut profile.bookmarks),
+            &mut profile.bookmark_folders,
+            &format!("{field}.bookmarks"),
+            report,
+        );
+        profile.crop = sanitize_crop(profile.crop.take(), &format!("{field}.crop"), report);
+        profiles.push(profile);
+    }
+    config.profiles = profiles;
+    ensure_active_profile(config);
 }
 
 /// Validate a hotkey string and fall back to the default if it is empty,
@@ -182,80 +1071,795 @@ fn sanitize_hotkey(value: &str, fallback: &str) -> String {
     }
 }
 
-/// Load `config.json` from disk, or return a sanitized default if the file
-/// is missing / unreadable / malformed.
-pub fn load_config(path: &Path) -> AppConfig {
-    if path.exists() {
-        match fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str(&content) {
-                Ok(config) => return sanitize_config(config),
-                Err(e) => warn!("Failed to parse config: {}", e),
-            },
-            Err(e) => warn!("Failed to read config: {}", e),
+/// Where the in-memory config came from at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// `config.json` itself.
+    File,
+    /// A backup, after `config.json` failed to load.
+    Backup,
+    /// Built-in defaults: first run, or nothing on disk was loadable.
+    Defaults,
+}
+
+/// Outcome of [`load_config`], kept on `AppState` so the settings UI can
+/// tell the user their settings were recovered or reset instead of the
+/// change going unnoticed.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigLoadReport {
+    pub source: ConfigSource,
+    /// Why `config.json` was rejected. `None` when it loaded, or when it
+    /// simply didn't exist yet (first run isn't worth a warning).
+    pub error: Option<String>,
+    /// Where the unloadable `config.json` was moved, if it was.
+    pub corrupt_copy: Option<PathBuf>,
+    /// The backup the config was recovered from, if any.
+    pub restored_from: Option<PathBuf>,
+    /// One-line summary for the UI; `None` when there is nothing to say.
+    pub message: Option<String>,
+    /// Set once the UI has fetched the report, so the startup notice
+    /// shows once per run rather than on every page load.
+    pub notified: bool,
+    /// The `recent_urls` list of a file from before the Recent dropdown
+    /// read history, for setup to carry into the history store. Empty
+    /// otherwise.
+    #[serde(skip)]
+    pub legacy_recent_urls: Vec<String>,
+    /// The schema version of a `config.json` written by a newer FloatView.
+    /// While set, this run doesn't write `config.json` at all (see
+    /// `AppState::keep_newer_config`), so the newer file isn't rewritten
+    /// in this build's older schema.
+    pub newer_version: Option<u32>,
+    /// Policy locks and `FLOATVIEW_*` overrides that were ignored because
+    /// their value doesn't fit the config; see
+    /// [`ConfigLayers::rejected`](crate::config_layers::ConfigLayers::rejected).
+    pub rejected_pins: Vec<ConfigCorrection>,
+}
+
+impl ConfigLoadReport {
+    /// A report with only `source` set: nothing went wrong.
+    pub fn new(source: ConfigSource) -> Self {
+        Self {
+            source,
+            error: None,
+            corrupt_copy: None,
+            restored_from: None,
+            message: None,
+            notified: false,
+            legacy_recent_urls: Vec::new(),
+            newer_version: None,
+            rejected_pins: Vec::new(),
         }
     }
-    sanitize_config(AppConfig::default())
 }
 
-/// Write `config` to disk atomically. Copies the existing file to `.bak`
-/// first, writes to a `.tmp` sibling, then renames. On rename failure the
-/// existing file is preserved and the temp is left for manual recovery.
-pub fn do_save_config(path: &Path, config: &AppConfig) {
-    match serde_json::to_string_pretty(config) {
-        Ok(content) => {
-            if path.exists() {
-                let _ = fs::copy(path, path.with_extension("json.bak"));
+/// Load `config.json` from disk, migrating older schemas forward.
+///
+/// If the file exists but can't be read or parsed, it is moved aside as
+/// `config.corrupt-<timestamp>.json` and each of [`backup_candidates`] is
+/// tried in turn. A recovered backup is written straight back to
+/// `config.json` so a crash before the first save can't lose it again.
+/// Only when nothing loads do we fall back to sanitized defaults.
+pub fn load_config(path: &Path) -> (AppConfig, ConfigLoadReport) {
+    load_config_with_migrations(path, MIGRATIONS)
+}
+
+/// [`load_config`] with the policy and environment layers applied: the
+/// layered defaults stand in for the built-in ones when nothing on disk
+/// loaded, and pinned keys are re-imposed on whatever did. Also returns
+/// the values the pinned keys displaced, for [`persisted_config`], and
+/// notes any pinned key whose value was ignored in the report.
+pub fn load_layered_config(
+    path: &Path,
+    layers: &ConfigLayers,
+) -> (AppConfig, UserValues, ConfigLoadReport) {
+    let (config, mut report) = load_config(path);
+    let (config, user) = match report.source {
+        ConfigSource::Defaults => {
+            let seed = layers.policy_defaults();
+            let user = layers.user_values(&sanitize_config(seed.clone()));
+            (layers.enforce(seed), user)
+        }
+        ConfigSource::File | ConfigSource::Backup => {
+            let user = layers.user_values(&config);
+            // Already sanitized (and logged) by `load_config`, so this
+            // only reports keys the policy or environment overrode.
+            let (config, corrections) = layers.enforce_with_report(config);
+            log_corrections(&corrections);
+            (config, user)
+        }
+    };
+    report.rejected_pins = layers.rejected().to_vec();
+    (config, user, report)
+}
+
+/// [`load_config`] with an explicit migration chain. Exposed so the
+/// lifecycle tests can drive the on-disk side effects (the pre-migration
+/// copy) with a synthetic chain; production always passes
+/// [`MIGRATIONS`].
+pub fn load_config_with_migrations(
+    path: &Path,
+    steps: &[MigrationStep],
+) -> (AppConfig, ConfigLoadReport) {
+    if !path.exists() {
+        return (
+            sanitize_config(AppConfig::default()),
+            ConfigLoadReport::new(ConfigSource::Defaults),
+        );
+    }
+
+    let error = match read_config_file(path, path, steps) {
+        Ok(parsed) => {
+            let (config, corrections) = sanitize_config_with_report(parsed.config);
+            log_corrections(&corrections);
+            let mut report = ConfigLoadReport::new(ConfigSource::File);
+            report.legacy_recent_urls = parsed.legacy_recent_urls;
+            if let Some(version) = parsed.newer_version {
+                report.newer_version = Some(version);
+                report.message = Some(format!(
+                    "Your settings file is from a newer version of FloatView (settings version {version}). \
+                     Changes made in this version won't be saved, so the newer file stays intact."
+                ));
             }
-            let tmp_path = path.with_extension("json.tmp");
-            if let Err(e) = fs::write(&tmp_path, content) {
-                warn!("Failed to save config: {}", e);
-                return;
+            return (config, report);
+        }
+        Err(e) => e,
+    };
+    warn!("Failed to load config: {}", error);
+
+    let mut report = ConfigLoadReport::new(ConfigSource::Defaults);
+    report.error = Some(error);
+    report.corrupt_copy = quarantine_corrupt_config(path);
+
+    for candidate in backup_candidates(path) {
+        match read_config_file(&candidate, path, steps) {
+            Ok(parsed) if parsed.newer_version.is_some() => {
+                // Restoring it would mean rewriting it in the older schema.
+                warn!(backup = %candidate.display(), "Backup is from a newer FloatView; skipped");
             }
-            if let Err(e) = fs::rename(&tmp_path, path) {
-                error!("Failed to finalize config save: {}", e);
-                // Keep existing config file; temp file remains for manual recovery
+            Ok(parsed) => {
+                report.legacy_recent_urls = parsed.legacy_recent_urls;
+                let (config, corrections) = sanitize_config_with_report(parsed.config);
+                log_corrections(&corrections);
+                info!(backup = %candidate.display(), "Recovered config from backup");
+                do_save_config(path, &config);
+                report.source = ConfigSource::Backup;
+                report.message = Some(
+                    "Your settings file was damaged; settings were restored from a backup."
+                        .to_string(),
+                );
+                report.restored_from = Some(candidate);
+                return (config, report);
+            }
+            Err(e) => warn!(backup = %candidate.display(), "Backup not usable: {}", e),
+        }
+    }
+
+    report.message = Some(
+        "Your settings file was damaged and no usable backup was found; settings were reset to defaults."
+            .to_string(),
+    );
+    (sanitize_config(AppConfig::default()), report)
+}
+
+/// Re-read `config.json` after it changed on disk: the same read, migrate,
+/// and sanitize steps as [`load_config`], minus the recovery. A file that
+/// won't load here is most likely half-saved by an editor, so it is
+/// reported and left in place rather than quarantined; the watcher tries
+/// again on the next change.
+pub fn reload_config(path: &Path) -> Result<AppConfig, String> {
+    let parsed = read_config_file(path, path, MIGRATIONS)?;
+    let (config, corrections) = sanitize_config_with_report(parsed.config);
+    log_corrections(&corrections);
+    Ok(config)
+}
+
+/// Backups to try, best first, when `config.json` won't load: the `.bak`
+/// from the previous write, then the dated generations newest first.
+/// Missing files are skipped here rather than reported as failures.
+pub fn backup_candidates(path: &Path) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    let bak = path.with_extension("json.bak");
+    if bak.exists() {
+        candidates.push(bak);
+    }
+    let dir = config_backup_dir(path);
+    candidates.extend(
+        list_config_backups(path)
+            .into_iter()
+            .map(|backup| dir.join(backup.name)),
+    );
+    candidates
+}
+
+/// One dated generation, as listed to the settings UI. Only the file name
+/// crosses IPC; `restore_config_backup` resolves it against the backup
+/// directory itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigBackup {
+    /// File name inside [`config_backup_dir`], e.g.
+    /// `config-20261018-003355.json`.
+    pub name: String,
+    /// UTC `YYYYMMDD-HHMMSS` stamp taken from the name.
+    pub created: String,
+    /// File size in bytes.
+    pub size: u64,
+}
+
+/// Directory holding the dated generations: `backups/` next to
+/// `config.json`.
+pub fn config_backup_dir(path: &Path) -> PathBuf {
+    path.with_file_name("backups")
+}
+
+/// Dated generations for `path`, newest first. The name's timestamp orders
+/// them, so the list doesn't depend on file mtimes surviving a copy.
+/// Anything in the directory that doesn't look like one of ours is
+/// ignored.
+pub fn list_config_backups(path: &Path) -> Vec<ConfigBackup> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    let prefix = format!("{stem}-");
+    let Ok(entries) = fs::read_dir(config_backup_dir(path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<ConfigBackup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let created = name.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            let stamp = created.get(..15)?;
+            let well_formed = stamp.bytes().enumerate().all(|(i, b)| {
+                if i == 8 {
+                    b == b'-'
+                } else {
+                    b.is_ascii_digit()
+                }
+            });
+            if !well_formed {
+                return None;
+            }
+            let size = entry.metadata().ok().filter(|m| m.is_file())?.len();
+            Some(ConfigBackup {
+                created: stamp.to_string(),
+                name,
+                size,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+/// Copy the current `config.json` into a new dated generation unless the
+/// newest one is younger than `min_interval`, then prune down to `keep`.
+/// Called by [`do_save_config`] before each write with
+/// [`CONFIG_BACKUP_INTERVAL`]; a restore passes `Duration::ZERO` so the
+/// state it replaces can itself be restored. Returns the new file, if one
+/// was written.
+pub fn rotate_config_backups(
+    path: &Path,
+    now: SystemTime,
+    min_interval: Duration,
+    keep: usize,
+) -> Option<PathBuf> {
+    if !path.exists() {
+        return None;
+    }
+    let dir = config_backup_dir(path);
+    let existing = list_config_backups(path);
+    if let Some(newest) = existing.first() {
+        let fresh = fs::metadata(dir.join(&newest.name))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age < min_interval);
+        if fresh {
+            return None;
+        }
+    }
+
+    if let Err(e) = fs::create_dir_all(&dir) {
+        warn!("Failed to create config backup directory: {}", e);
+        return None;
+    }
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    let stamp = file_timestamp(now);
+    let mut target = dir.join(format!("{stem}-{stamp}.json"));
+    let mut n = 1;
+    while target.exists() {
+        n += 1;
+        target = dir.join(format!("{stem}-{stamp}-{n}.json"));
+    }
+    if let Err(e) = fs::copy(path, &target) {
+        warn!("Failed to write config backup: {}", e);
+        return None;
+    }
+
+    for stale in list_config_backups(path).iter().skip(keep) {
+        if let Err(e) = fs::remove_file(dir.join(&stale.name)) {
+            warn!(name = %stale.name, "Failed to prune config backup: {}", e);
+        }
+    }
+    Some(target)
+}
+
+/// Load one dated generation by the `name` [`list_config_backups`]
+/// returned. The name must match a listed backup exactly, so a caller
+/// can't use it to read arbitrary files. Runs migrations but not
+/// `sanitize_config`; that's the caller's job, same as for a fresh load.
+pub fn read_config_backup(path: &Path, name: &str) -> Result<AppConfig, String> {
+    if !list_config_backups(path).iter().any(|b| b.name == name) {
+        return Err(format!("No such config backup: {name}"));
+    }
+    read_config_file(&config_backup_dir(path).join(name), path, MIGRATIONS).map(|p| p.config)
+}
+
+/// Move an unloadable `config.json` out of the way so the next save can't
+/// overwrite it and the user (or a bug report) still has the original.
+/// Returns the new path, or `None` if the rename failed.
+fn quarantine_corrupt_config(path: &Path) -> Option<PathBuf> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    let stamp = file_timestamp(SystemTime::now());
+    let mut target = path.with_file_name(format!("{stem}.corrupt-{stamp}.json"));
+    // Two failures inside the same second (a crash loop) must not clobber
+    // the first quarantined copy.
+    let mut n = 1;
+    while target.exists() {
+        n += 1;
+        target = path.with_file_name(format!("{stem}.corrupt-{stamp}-{n}.json"));
+    }
+    match fs::rename(path, &target) {
+        Ok(()) => {
+            warn!(path = %target.display(), "Moved unloadable config aside");
+            Some(target)
+        }
+        Err(e) => {
+            error!("Failed to move unloadable config aside: {}", e);
+            None
+        }
+    }
+}
+
+/// A config file as [`parse_config`] read it, with what the migrations
+/// dropped that still has somewhere to go.
+struct ParsedConfig {
+    config: AppConfig,
+    /// See [`ConfigLoadReport::legacy_recent_urls`].
+    legacy_recent_urls: Vec<String>,
+    /// See [`ConfigLoadReport::newer_version`].
+    newer_version: Option<u32>,
+}
+
+/// Read and parse one config file. `copy_base` is the live `config.json`
+/// path, so pre-migration copies of a backup land next to it under the
+/// usual name rather than next to the backup.
+fn read_config_file(
+    source: &Path,
+    copy_base: &Path,
+    steps: &[MigrationStep],
+) -> Result<ParsedConfig, String> {
+    let content = fs::read_to_string(source).map_err(|e| e.to_string())?;
+    parse_config(copy_base, &content, steps)
+}
+
+/// Render `time` as a sortable UTC `YYYYMMDD-HHMMSS` stamp for file names.
+/// Hand-rolled (days-to-civil conversion) to avoid pulling in a date crate
+/// for one format string. Times before the epoch clamp to it.
+pub fn file_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    // Howard Hinnant's civil_from_days, specialized to non-negative days.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Parse raw file contents into an `AppConfig`, running schema migrations
+/// on the untyped JSON first.
+///
+/// Before any migration runs, the original text is copied to
+/// [`versioned_copy_path`] so a buggy step can be rolled back by hand. A
+/// file from a newer FloatView is never migrated backwards: it is copied
+/// aside the same way, then loaded for the fields this build knows about,
+/// and its version is returned so the caller can keep this run from
+/// saving over it.
+///
+/// The v2 -> v3 step drops `recent_urls`; its URLs are taken out first so
+/// setup can move them into the history store the dropdown now reads.
+fn parse_config(
+    path: &Path,
+    content: &str,
+    steps: &[MigrationStep],
+) -> Result<ParsedConfig, String> {
+    let mut value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut newer_version = None;
+    let legacy_recent_urls = match value.get("recent_urls") {
+        Some(serde_json::Value::Array(urls)) if stored_version(&value).is_ok_and(|v| v < 3) => urls
+            .iter()
+            .filter_map(|u| u.as_str())
+            .filter_map(|u| normalize_url(u).ok())
+            .collect(),
+        _ => Vec::new(),
+    };
+    match migrate_with(&mut value, steps) {
+        Ok(outcome) if outcome.migrated() => {
+            keep_versioned_copy(path, outcome.from, content);
+            info!(
+                from = outcome.from,
+                to = outcome.to,
+                "Migrated config schema"
+            );
+        }
+        Ok(_) => {}
+        Err(MigrationError::NewerVersion { found, supported }) => {
+            keep_versioned_copy(path, found, content);
+            warn!(
+                found,
+                supported,
+                "config.json is from a newer FloatView; loading known fields only, not saving"
+            );
+            newer_version = Some(found);
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+    let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(ParsedConfig {
+        config,
+        legacy_recent_urls,
+        newer_version,
+    })
+}
+
+/// Sibling path holding a verbatim copy of `config.json` as it was at
+/// schema `version`, e.g. `config.v1.json`.
+pub fn versioned_copy_path(path: &Path, version: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    path.with_file_name(format!("{stem}.v{version}.json"))
+}
+
+/// Write the pre-migration copy unless one already exists. The first copy
+/// is the one worth keeping: a later run at the same version would only
+/// overwrite it with whatever the (possibly broken) app has saved since.
+fn keep_versioned_copy(path: &Path, version: u32, content: &str) {
+    let copy_path = versioned_copy_path(path, version);
+    if copy_path.exists() {
+        return;
+    }
+    if let Err(e) = fs::write(&copy_path, content) {
+        warn!(path = %copy_path.display(), "Failed to keep pre-migration config copy: {}", e);
+    }
+}
+
+/// Write `config` to disk atomically, logging rather than returning any
+/// failure. For the synchronous callers (startup recovery, shutdown
+/// flush) that have nobody to report to; see [`write_config`].
+pub fn do_save_config(path: &Path, config: &AppConfig) {
+    if let Err(e) = write_config(path, config, None) {
+        error!("Failed to save config: {}", e);
+    }
+}
+
+/// Write `config` to disk atomically. Copies the existing file to `.bak`
+/// first (and to a dated generation, at most once per
+/// [`CONFIG_BACKUP_INTERVAL`]), writes and fsyncs a `.tmp` sibling, then
+/// renames it over `config.json` and fsyncs the directory so the rename
+/// itself survives a power cut. On failure the existing file is
+/// preserved and the temp is left for manual recovery.
+///
+/// When `written` is given, the digest of the new content is recorded in
+/// it before the file is replaced, so the config watcher never sees the new
+/// file without also knowing the app wrote it.
+pub fn write_config(
+    path: &Path,
+    config: &AppConfig,
+    written: Option<&WrittenDigests>,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {e}"))?;
+    if let Some(written) = written {
+        written.record(content_digest(content.as_bytes()));
+    }
+    if path.exists() {
+        let _ = fs::copy(path, path.with_extension("json.bak"));
+        rotate_config_backups(
+            path,
+            SystemTime::now(),
+            CONFIG_BACKUP_INTERVAL,
+            MAX_CONFIG_BACKUPS,
+        );
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    let mut tmp = fs::File::create(&tmp_path).map_err(|e| format!("Failed to save config: {e}"))?;
+    tmp.write_all(content.as_bytes())
+        .and_then(|()| tmp.sync_all())
+        .map_err(|e| format!("Failed to save config: {e}"))?;
+    drop(tmp);
+    sync_parent_dir(path);
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to finalize config save: {e}"))?;
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// How many of the app's own recent writes [`WrittenDigests`] remembers.
+/// More than one, because the watcher may read the previous write after
+/// the saver has already recorded the next one.
+const MAX_WRITTEN_DIGESTS: usize = 8;
+
+/// Digests of the last few `config.json` contents the app wrote or has
+/// taken in (the file as the saver found it, and external edits the
+/// watcher has handled), shared by the saver and the config watcher so
+/// the watcher can skip the app's own saves and the saver never writes
+/// over an edit the watcher hasn't picked up yet.
+#[derive(Debug, Default)]
+pub struct WrittenDigests(Mutex<VecDeque<u64>>);
+
+impl WrittenDigests {
+    pub fn record(&self, digest: u64) {
+        if let Ok(mut digests) = self.0.lock() {
+            if digests.len() >= MAX_WRITTEN_DIGESTS {
+                digests.pop_front();
             }
+            digests.push_back(digest);
+        }
+    }
+
+    pub fn contains(&self, digest: u64) -> bool {
+        self.0
+            .lock()
+            .map(|digests| digests.contains(&digest))
+            .unwrap_or(false)
+    }
+
+    /// Whether `path` holds content none of these digests match: an
+    /// external edit the watcher hasn't handled yet. A missing or
+    /// unreadable file has nothing to lose.
+    pub fn has_unseen_edit(&self, path: &Path) -> bool {
+        fs::read(path).is_ok_and(|bytes| !self.contains(content_digest(&bytes)))
+    }
+}
+
+/// Fingerprint of a config file's bytes, used to tell the app's own
+/// writes apart from external edits. Only ever compared within one
+/// process, so the std hasher's unspecified algorithm is fine.
+pub fn content_digest(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// fsync the directory holding `path` so a just-created or just-renamed
+/// entry is durable. Best-effort: some filesystems refuse to sync a
+/// directory, and the file contents are already on disk by then.
+/// Windows can't open a directory as a file at all, and NTFS journals
+/// the rename itself, so this is Unix-only.
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Err(e) = fs::File::open(dir).and_then(|d| d.sync_all()) {
+            warn!("Failed to sync config directory: {}", e);
         }
-        Err(e) => error!("Failed to serialize config: {}", e),
     }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// How long the saver thread collects queued saves after the first one
+/// arrives before writing. Only the newest config in the window reaches
+/// disk, so a slider drag or a burst of SPA navigations costs one write
+/// instead of dozens.
+pub const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Called by the saver thread when the outcome of a write differs from
+/// the previous one: `Some(error)` when writes start failing (or fail
+/// differently), `None` once one succeeds again.
+pub type SaveStatusCallback = Box<dyn Fn(Option<&str>) + Send>;
+
+/// What the saver thread is asked to do.
+#[derive(Debug)]
+pub enum SaveRequest {
+    /// Write this config; the newest one in a batch wins.
+    Write(Box<AppConfig>),
+    /// Write the pending batch now, then acknowledge on the sender, so
+    /// the caller knows `config.json` holds the live config.
+    Flush(Sender<()>),
+}
+
+/// Spawn the background saver that [`save_config`] feeds.
+///
+/// Each batch starts at the first queued config and ends `debounce`
+/// later; everything received in between replaces it, and only the last
+/// one is written. A [`SaveRequest::Flush`] writes the batch early. When
+/// the sender is dropped (see [`shutdown`]) the current batch is written
+/// immediately and the thread exits, so joining it still guarantees
+/// every queued save has reached disk. Write failures are stored in
+/// `save_error` (cleared on the next success) for the UI to poll, and
+/// pushed through `on_status` as they change. The digest of each write
+/// is recorded in `written` (see [`write_config`]). While `keep_newer` is
+/// set, batches are dropped instead of written (see
+/// [`ConfigLoadReport::newer_version`]). So is a batch that would land on
+/// an external edit made since the last write
+/// ([`WrittenDigests::has_unseen_edit`]): the watcher reloads the edit
+/// into the live config instead, and the next save builds on it.
+pub fn spawn_config_saver(
+    name: &str,
+    path: PathBuf,
+    debounce: Duration,
+    save_error: Arc<Mutex<Option<String>>>,
+    written: Arc<WrittenDigests>,
+    keep_newer: Arc<AtomicBool>,
+    on_status: SaveStatusCallback,
+) -> std::io::Result<(Sender<SaveRequest>, JoinHandle<()>)> {
+    if let Ok(bytes) = fs::read(&path) {
+        written.record(content_digest(&bytes));
+    }
+    let (tx, rx) = mpsc::channel::<SaveRequest>();
+    let handle = std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            let write = |config: AppConfig| {
+                if keep_newer.load(Ordering::Acquire) {
+                    return;
+                }
+                if written.has_unseen_edit(&path) {
+                    warn!("config.json was edited on disk; dropped the pending save");
+                    return;
+                }
+                let result = write_config(&path, &config, Some(&written));
+                if let Err(e) = &result {
+                    error!("{}", e);
+                }
+                if record_save_result(&save_error, result) {
+                    let current = save_error.lock().ok().and_then(|g| g.clone());
+                    on_status(current.as_deref());
+                }
+            };
+            let mut pending: Option<(AppConfig, Instant)> = None;
+            loop {
+                let request = match &pending {
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    Some((_, deadline)) => {
+                        rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    }
+                };
+                match request {
+                    Ok(SaveRequest::Write(config)) => {
+                        let deadline = pending
+                            .take()
+                            .map_or_else(|| Instant::now() + debounce, |(_, d)| d);
+                        pending = Some((*config, deadline));
+                    }
+                    Ok(SaveRequest::Flush(ack)) => {
+                        if let Some((config, _)) = pending.take() {
+                            write(config);
+                        }
+                        let _ = ack.send(());
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        if let Some((config, _)) = pending.take() {
+                            write(config);
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        if let Some((config, _)) = pending.take() {
+                            write(config);
+                        }
+                        break;
+                    }
+                }
+            }
+        })?;
+    Ok((tx, handle))
+}
+
+/// Store the outcome of a write in `save_error`. Returns whether the
+/// stored value changed, so callers only notify on transitions rather
+/// than on every successful write.
+pub fn record_save_result(save_error: &Mutex<Option<String>>, result: Result<(), String>) -> bool {
+    let Ok(mut current) = save_error.lock() else {
+        return false;
+    };
+    let next = result.err();
+    if *current == next {
+        return false;
+    }
+    *current = next;
+    true
 }
 
 /// Queue a save for the background saver thread. Cheap: clones the config
-/// and pushes it onto an unbounded mpsc. A no-op after `shutdown()`.
+/// and pushes it onto an unbounded mpsc; the saver coalesces bursts (see
+/// [`spawn_config_saver`]). A no-op after `shutdown()`.
 pub fn save_config(state: &AppState, config: &AppConfig) {
     if let Ok(guard) = state.save_tx.lock() {
         if let Some(tx) = guard.as_ref() {
-            let _ = tx.send(config.clone());
+            let _ = tx.send(SaveRequest::Write(Box::new(persisted_config(
+                state, config,
+            ))));
         }
     }
 }
 
-/// Update `last_url` / `recent_urls` and enqueue a save if anything changed.
-///
-/// Early-returns without a save if the url is already at the head of recents
-/// and already recorded as `last_url`, avoiding redundant disk writes from
-/// the injection script's 3-second polling loop.
-pub fn persist_recent_url(state: &AppState, url: &str) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    let last_url_unchanged = config.last_url.as_deref() == Some(url);
-    if !last_url_unchanged {
-        config.last_url = Some(url.to_string());
+/// Block until every save queued so far is on disk, instead of up to
+/// [`SAVE_DEBOUNCE`] later. For callers that read or copy `config.json`
+/// and need it current. A no-op after `shutdown()`; never call it while
+/// holding the config mutex.
+pub fn flush_saves(state: &AppState) {
+    let (ack, done) = mpsc::channel();
+    let sent = state
+        .save_tx
+        .lock()
+        .ok()
+        .and_then(|guard| {
+            guard
+                .as_ref()
+                .map(|tx| tx.send(SaveRequest::Flush(ack)).is_ok())
+        })
+        .unwrap_or(false);
+    if sent {
+        let _ = done.recv();
     }
+}
 
-    let recent = config.recent_urls.get_or_insert_with(Vec::new);
-    let recent_unchanged = recent.first().is_some_and(|u| u == url);
-    if !recent_unchanged {
-        recent.retain(|u| u != url);
-        recent.insert(0, url.to_string());
-        if recent.len() > MAX_RECENT_URLS {
-            recent.truncate(MAX_RECENT_URLS);
-        }
+/// `config` as it belongs on disk: with the global values the active
+/// site's overrides displaced (see [`crate::site_settings`]) put back,
+/// and the user's own values in place of policy and environment ones
+/// (see [`crate::config_layers`]).
+pub fn persisted_config(state: &AppState, config: &AppConfig) -> AppConfig {
+    let mut persisted = config.clone();
+    match state.site.lock() {
+        Ok(site) => site.strip(&mut persisted),
+        Err(e) => error!("site session mutex poisoned: {}", e),
     }
+    match state.user_values.lock() {
+        Ok(user) => user.restore(&mut persisted),
+        Err(e) => error!("user values mutex poisoned: {}", e),
+    }
+    persisted
+}
 
-    if last_url_unchanged && recent_unchanged {
+/// Update `last_url` and enqueue a save if it changed. The URL is stored
+/// without tracking parameters; see [`url_to_store`]. The Recent dropdown
+/// reads history instead (see [`crate::history::recent_urls`]).
+///
+/// Early-returns without a save if the url is already recorded as
+/// `last_url`, avoiding redundant disk writes from the injection script's
+/// 3-second polling loop.
+pub fn persist_last_url(state: &AppState, url: &str) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let url = url_to_store(url, &config.tracking_params);
+    if config.last_url.as_deref() == Some(url.as_str()) {
         return Ok(());
     }
-
+    config.last_url = Some(url);
     save_config(state, &config);
     drop(config);
     Ok(())
@@ -264,7 +1868,9 @@ pub fn persist_recent_url(state: &AppState, url: &str) -> Result<(), String> {
 /// Graceful shutdown. Signals background workers, drops the save channel
 /// sender so the saver thread exits its recv loop after draining any
 /// pending messages, joins the thread, then writes the current in-memory
-/// config directly. After this runs `save_config` becomes a no-op. Safe to
+/// config directly (unless `config.json` is a newer FloatView's; see
+/// `AppState::keep_newer_config`, or holds an edit the watcher hasn't
+/// picked up yet). After this runs `save_config` becomes a no-op. Safe to
 /// call multiple times (idempotent on already-taken Options and on a
 /// latched shutdown flag).
 pub fn shutdown(state: &AppState) {
@@ -279,8 +1885,18 @@ pub fn shutdown(state: &AppState) {
             }
         }
     }
+    if state.keep_newer_config.load(Ordering::Acquire)
+        || state.written_digests.has_unseen_edit(&state.config_path)
+    {
+        return;
+    }
     if let Ok(config) = state.config.lock() {
-        do_save_config(&state.config_path, &config);
+        let config = persisted_config(state, &config);
+        let result = write_config(&state.config_path, &config, Some(&state.written_digests));
+        if let Err(e) = &result {
+            error!("{}", e);
+        }
+        record_save_result(&state.save_error, result);
     }
 }
 
@@ -292,21 +1908,119 @@ mod tests {
         DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH, MAX_WINDOW_SIZE, MIN_WINDOW_SIZE,
     };
 
+    fn bookmark(url: &str) -> Bookmark {
+        Bookmark::new(url.to_string(), String::new())
+    }
+
+    #[test]
+    fn sanitize_report_lists_rejected_values() {
+        let mut config = AppConfig {
+            home_url: "javascript:alert(1)".to_string(),
+            bookmarks: vec![
+                bookmark("https://a.test/"),
+                bookmark("ftp://b.test/"),
+                bookmark("https://a.test"),
+            ],
+            ..AppConfig::default()
+        };
+        config.hotkeys.toggle_on_top = "<img src=x>".to_string();
+        config.window.opacity = 0.0;
+
+        let (clean, report) = sanitize_config_with_report(config);
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "window.opacity",
+                "home_url",
+                "hotkeys.toggle_on_top",
+                "bookmarks[1]",
+                "bookmarks[2]",
+            ]
+        );
+        let hotkey = &report[2];
+        assert_eq!(hotkey.old, serde_json::json!("<img src=x>"));
+        assert_eq!(hotkey.new, serde_json::json!(clean.hotkeys.toggle_on_top));
+        assert!(hotkey.reason.contains("hotkey"));
+    }
+
+    #[test]
+    fn sanitize_report_ignores_canonicalization() {
+        let config = AppConfig {
+            home_url: "example.com".to_string(),
+            bookmarks: vec![bookmark("https://b.test/")],
+            ..AppConfig::default()
+        };
+        let (_, report) = sanitize_config_with_report(config);
+        assert!(report.is_empty(), "{report:?}");
+
+        let (_, report) = sanitize_config_with_report(sanitize_config(AppConfig::default()));
+        assert!(report.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn sanitize_report_notes_truncated_bookmarks() {
+        let config = AppConfig {
+            bookmarks: (0..MAX_BOOKMARKS + 5)
+                .map(|i| bookmark(&format!("https://site{i}.test/")))
+                .collect(),
+            ..AppConfig::default()
+        };
+        let (clean, report) = sanitize_config_with_report(config);
+        assert_eq!(clean.bookmarks.len(), MAX_BOOKMARKS);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].field, "bookmarks");
+        assert_eq!(report[0].old, serde_json::json!(MAX_BOOKMARKS + 5));
+    }
+
     #[test]
-    fn sanitize_config_clamps_and_deduplicates() {
+    fn sanitize_site_settings_normalizes_keys_and_clamps_overrides() {
+        let site = |opacity: f64| SiteSettings {
+            opacity: Some(opacity),
+            width: Some(50),
+            zoom: Some(f64::NAN),
+            ..SiteSettings::default()
+        };
+        let config = AppConfig {
+            site_settings: [
+                ("Chat.Test".to_string(), site(0.4)),
+                ("chat.test".to_string(), site(0.6)),
+                ("https://Video.test/watch".to_string(), site(5.0)),
+                ("not a host".to_string(), site(0.5)),
+            ]
+            .into_iter()
+            .collect(),
+            ..AppConfig::default()
+        };
+        let (clean, report) = sanitize_config_with_report(config);
+
+        let keys: Vec<&str> = clean.site_settings.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["chat.test", "https://video.test"]);
+        // "Chat.Test" sorts first, so it's the one kept.
+        let chat = &clean.site_settings["chat.test"];
+        assert_eq!(chat.opacity, Some(0.4));
+        assert_eq!(chat.width, Some(MIN_WINDOW_SIZE));
+        assert_eq!(chat.zoom, Some(1.0));
+        assert_eq!(clean.site_settings["https://video.test"].opacity, Some(1.0));
+
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert!(fields.contains(&"site_settings.chat.test"));
+        assert!(fields.contains(&"site_settings.not a host"));
+        assert!(fields.contains(&"site_settings.https://Video.test/watch.opacity"));
+    }
+
+    #[test]
+    fn sanitize_config_clamps_and_rejects() {
         let mut config = AppConfig::default();
         config.window.width = 10;
         config.window.height = 50_000;
         config.window.opacity = 2.0;
         config.home_url = "javascript:alert(1)".to_string();
         config.last_url = Some("ftp://example.com".to_string());
-        config.recent_urls = Some(vec![
-            "http://example.com".to_string(),
-            "example.com".to_string(),
-            "https://example.com".to_string(),
-            "javascript:alert(1)".to_string(),
-        ]);
         config.hotkeys.toggle_on_top = "".to_string();
+        config.history_retention_days = 0;
+        config.hotkeys.nudge_left = "Ctrl+<".to_string();
+        config.nudge_step = 10_000;
 
         let sanitized = sanitize_config(config);
         assert_eq!(sanitized.window.width, MIN_WINDOW_SIZE);
@@ -314,14 +2028,298 @@ mod tests {
         assert_eq!(sanitized.window.opacity, 1.0);
         assert_eq!(sanitized.home_url, "https://www.google.com/");
         assert!(sanitized.last_url.is_none());
+        assert_eq!(sanitized.hotkeys.toggle_on_top, "Alt+Shift+T");
+        assert_eq!(sanitized.history_retention_days, 1);
+        assert_eq!(sanitized.hotkeys.nudge_left, "Ctrl+Alt+Shift+Left");
+        assert_eq!(sanitized.nudge_step, MAX_NUDGE_STEP);
+    }
+
+    #[test]
+    fn sanitize_search_engines_drops_bad_entries_and_fixes_default() {
+        let engine = |name: &str, keyword: &str, url: &str| SearchEngine {
+            name: name.to_string(),
+            keyword: keyword.to_string(),
+            url: url.to_string(),
+        };
+        let config = AppConfig {
+            search_engines: vec![
+                engine(
+                    "Jellyfin",
+                    "JF",
+                    "http://192.168.1.20:8096/web/#/search.html?query=%s",
+                ),
+                engine("Also JF", "jf", "https://example.com/?q=%s"),
+                engine("No slot", "ns", "https://example.com/"),
+                engine("Script", "js", "javascript:%s"),
+            ],
+            default_search_engine: "ddg".to_string(),
+            ..AppConfig::default()
+        };
+
+        let (sanitized, report) = sanitize_config_with_report(config);
+        assert_eq!(sanitized.search_engines.len(), 1);
+        assert_eq!(sanitized.search_engines[0].keyword, "jf");
+        assert_eq!(sanitized.default_search_engine, "jf");
+        let reasons: Vec<&str> = report.iter().map(|c| c.reason.as_str()).collect();
+        assert!(reasons.contains(&"duplicate search keyword; removed"));
+        assert!(reasons.contains(&"search URL must be http(s) and contain %s; removed"));
+        assert!(reasons.contains(&"not the keyword of a search engine"));
+    }
+
+    #[test]
+    fn sanitize_layouts_drops_bad_and_repeated_layouts() {
+        use crate::config::{LayoutGrid, SnapLayout};
+        let grid = |name: &str, columns: u32| SnapLayout {
+            name: name.to_string(),
+            padding: None,
+            grid: Some(LayoutGrid { columns, rows: 2 }),
+            zones: Vec::new(),
+        };
+        let mut config = AppConfig {
+            layouts: vec![grid("Quad", 2), grid("quad", 3), grid("wide", 0)],
+            ..AppConfig::default()
+        };
+        config
+            .layouts
+            .extend((0..MAX_LAYOUTS).map(|i| grid(&format!("g{i}"), 2)));
+        let (sanitized, report) = sanitize_config_with_report(config);
+        assert_eq!(sanitized.layouts.len(), MAX_LAYOUTS);
+        assert_eq!(sanitized.layouts[0].name, "quad");
         assert_eq!(
-            sanitized.recent_urls.unwrap_or_default(),
-            vec![
-                "http://example.com/".to_string(),
-                "https://example.com/".to_string(),
+            sanitized.layouts[0].grid,
+            Some(LayoutGrid {
+                columns: 2,
+                rows: 2
+            })
+        );
+        let reasons: Vec<&str> = report.iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "duplicate layout name; removed",
+                "grid must have 1 to 12 columns and rows; removed",
+                "too many layouts; removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_window_presets_keeps_what_it_can() {
+        let preset = |name: &str, hotkey: Option<&str>| WindowPreset {
+            name: name.to_string(),
+            hotkey: hotkey.map(str::to_string),
+            x: 0,
+            y: 0,
+            width: 640,
+            height: 360,
+            scale_factor: Some(1.0),
+            opacity: 0.8,
+            always_on_top: true,
+            locked: false,
+            crop: None,
+        };
+        let config = AppConfig {
+            window_presets: vec![
+                preset("Corner mini", Some("Alt+Shift+1")),
+                preset("corner MINI", None),
+                WindowPreset {
+                    width: 0,
+                    ..preset("Tiny", None)
+                },
+                preset("Side panel", Some("shift+alt+1")),
+                WindowPreset {
+                    opacity: 0.0,
+                    crop: Some(CropConfig {
+                        x: f64::NAN,
+                        y: 0.0,
+                        width: 1.0,
+                        height: 1.0,
+                    }),
+                    ..preset("Review", Some("Alt+nope"))
+                },
+            ],
+            ..AppConfig::default()
+        };
+        let (sanitized, report) = sanitize_config_with_report(config);
+        let names: Vec<&str> = sanitized
+            .window_presets
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["Corner mini", "Side panel", "Review"]);
+        assert_eq!(sanitized.window_presets[1].hotkey, None);
+        let review = &sanitized.window_presets[2];
+        assert_eq!((review.opacity, review.hotkey.clone()), (MIN_OPACITY, None));
+        assert!(review.crop.is_none());
+        let reasons: Vec<&str> = report.iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "duplicate preset name; removed",
+                "preset geometry out of range; removed",
+                "hotkey used by another preset; removed",
+                "opacity out of range",
+                "hotkey did not parse; removed",
+                "crop values must be finite; crop removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_window_drops_bad_monitors_and_placements() {
+        use crate::config::{MonitorInfo, WindowPlacement};
+        let monitor = |scale_factor: f64| MonitorInfo {
+            name: "DP-1".to_string(),
+            x: 0,
+            y: 0,
+            width: 2560,
+            height: 1440,
+            scale_factor,
+        };
+        let placement = |setup: &str, width: i32| WindowPlacement {
+            setup: setup.to_string(),
+            monitor: monitor(1.0),
+            x: 10,
+            y: 10,
+            width,
+            height: 450,
+            scale_factor: 1.0,
+        };
+        let mut config = AppConfig::default();
+        config.window.scale_factor = Some(0.0);
+        config.window.monitor = Some(monitor(f64::NAN));
+        let mut unscaled = placement("d", 800);
+        unscaled.scale_factor = f64::INFINITY;
+        config.window.placements = vec![
+            placement("a", 800),
+            placement("a", 900),
+            placement("b", 5),
+            unscaled,
+        ];
+        config
+            .window
+            .placements
+            .extend((0..MAX_WINDOW_PLACEMENTS).map(|i| placement(&format!("c{i}"), 800)));
+        let (sanitized, report) = sanitize_config_with_report(config);
+        assert_eq!(sanitized.window.scale_factor, None);
+        assert_eq!(sanitized.window.monitor, None);
+        let placements = &sanitized.window.placements;
+        assert_eq!(placements.len(), MAX_WINDOW_PLACEMENTS);
+        assert_eq!(
+            (placements[0].setup.as_str(), placements[0].width),
+            ("a", 800)
+        );
+        assert!(placements.iter().all(|p| p.setup != "b" && p.setup != "d"));
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "window.scale_factor",
+                "window.monitor",
+                "window.placements[2]",
+                "window.placements[3]",
+                "window.placements"
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_tracking_params_cleans_names_and_host_keys() {
+        let mut config = AppConfig::default();
+        config.tracking_params.params = vec![
+            " UTM_* ".to_string(),
+            "utm_*".to_string(),
+            "a b".to_string(),
+        ];
+        config.tracking_params.hosts = BTreeMap::from([
+            ("*.YouTube.com".to_string(), vec!["si".to_string()]),
+            (
+                "*.youtube.com".to_string(),
+                vec!["SI".to_string(), "pp".to_string()],
+            ),
+            ("https://example.com".to_string(), vec!["x".to_string()]),
+            ("exa mple".to_string(), vec!["x".to_string()]),
+        ]);
+        let (sanitized, report) = sanitize_config_with_report(config);
+        let rules = &sanitized.tracking_params;
+        assert_eq!(rules.params, ["utm_*"]);
+        assert_eq!(
+            rules.hosts,
+            BTreeMap::from([(
+                "*.youtube.com".to_string(),
+                vec!["si".to_string(), "pp".to_string()]
+            )])
+        );
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "tracking_params.params[2]",
+                "tracking_params.hosts.exa mple",
+                "tracking_params.hosts.https://example.com",
             ]
         );
-        assert_eq!(sanitized.hotkeys.toggle_on_top, "Alt+Shift+T");
+    }
+
+    #[test]
+    fn sanitize_kiosk_hashes_pin_and_cleans_allowlist() {
+        let config = AppConfig {
+            kiosk: crate::config::KioskConfig {
+                enabled: true,
+                allowlist: vec![
+                    "grafana.lan/d/lobby".to_string(),
+                    "https://grafana.lan/d/lobby".to_string(),
+                    "file:///etc/passwd".to_string(),
+                ],
+                pin: Some(" 2468 ".to_string()),
+                pin_hash: None,
+            },
+            ..AppConfig::default()
+        };
+        let (sanitized, report) = sanitize_config_with_report(config);
+        assert_eq!(sanitized.kiosk.pin, None);
+        assert!(crate::kiosk::verify_pin(&sanitized.kiosk, "2468"));
+        assert_eq!(
+            sanitized.kiosk.allowlist,
+            vec!["https://grafana.lan/d/lobby".to_string()]
+        );
+        assert_eq!(report.len(), 1, "{report:?}");
+        assert_eq!(report[0].field, "kiosk.allowlist[2]");
+        assert!(!serde_json::to_string(&sanitized).unwrap().contains("2468"));
+
+        let short = AppConfig {
+            kiosk: crate::config::KioskConfig {
+                pin: Some("12".to_string()),
+                pin_hash: Some("not-a-hash".to_string()),
+                ..sanitized.kiosk.clone()
+            },
+            ..sanitized
+        };
+        let (sanitized, report) = sanitize_config_with_report(short);
+        assert_eq!(sanitized.kiosk.pin_hash, None);
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["kiosk.pin", "kiosk.pin_hash"]);
+    }
+
+    #[test]
+    fn file_timestamp_formats_utc_civil_time() {
+        use std::time::Duration;
+        assert_eq!(file_timestamp(UNIX_EPOCH), "19700101-000000");
+        // 2000-02-29 (leap day) 12:34:56 UTC.
+        let leap = UNIX_EPOCH + Duration::from_secs(951_827_696);
+        assert_eq!(file_timestamp(leap), "20000229-123456");
+        // 2026-10-18 00:33:55 UTC.
+        let recent = UNIX_EPOCH + Duration::from_secs(1_792_283_635);
+        assert_eq!(file_timestamp(recent), "20261018-003355");
+    }
+
+    #[test]
+    fn sanitize_config_stamps_current_schema_version() {
+        let config = AppConfig {
+            config_version: CONFIG_VERSION + 3,
+            ..AppConfig::default()
+        };
+        assert_eq!(sanitize_config(config).config_version, CONFIG_VERSION);
     }
 
     #[test]
@@ -387,12 +2385,118 @@ mod tests {
     #[test]
     fn sanitize_config_enforces_bookmark_limit() {
         let config = AppConfig {
-            bookmarks: (0..100)
-                .map(|i| format!("https://site{}.com/", i))
+            bookmarks: (0..MAX_BOOKMARKS + 50)
+                .map(|i| bookmark(&format!("https://site{}.com/", i)))
                 .collect(),
             ..AppConfig::default()
         };
         let sanitized = sanitize_config(config);
-        assert_eq!(sanitized.bookmarks.len(), 50);
+        assert_eq!(sanitized.bookmarks.len(), MAX_BOOKMARKS);
+    }
+
+    #[test]
+    fn sanitize_config_cleans_profiles_and_adopts_default() {
+        use crate::config::{ProfileConfig, WindowConfig, DEFAULT_PROFILE_NAME};
+
+        let profile = |name: &str| ProfileConfig {
+            name: name.to_string(),
+            window: WindowConfig::default(),
+            home_url: "javascript:alert(1)".to_string(),
+            last_url: None,
+            auto_refresh_minutes: 99_999,
+            bookmarks: vec![bookmark("example.com"), bookmark("ftp://x")],
+            bookmark_folders: Vec::new(),
+            crop: None,
+        };
+        let config = AppConfig {
+            profiles: vec![profile(" Grafana "), profile("grafana"), profile("")],
+            active_profile: None,
+            ..AppConfig::default()
+        };
+
+        let sanitized = sanitize_config(config);
+        let names: Vec<&str> = sanitized.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec![DEFAULT_PROFILE_NAME, "Grafana"]);
+        assert_eq!(
+            sanitized.active_profile.as_deref(),
+            Some(DEFAULT_PROFILE_NAME)
+        );
+        let grafana = &sanitized.profiles[1];
+        assert_eq!(grafana.home_url, "https://www.google.com/");
+        assert_eq!(grafana.auto_refresh_minutes, MAX_AUTO_REFRESH_MINUTES);
+        assert_eq!(grafana.bookmarks.len(), 1);
+        assert_eq!(grafana.bookmarks[0].url, "https://example.com/");
+    }
+
+    #[test]
+    fn sanitize_bookmarks_fills_ids_and_reconciles_folders() {
+        let mut config: AppConfig = serde_json::from_value(serde_json::json!({
+            "window": serde_json::to_value(WindowConfig::default()).unwrap(),
+            "last_url": null,
+            "hotkeys": serde_json::to_value(HotkeyConfig::default()).unwrap(),
+            "bookmark_folders": [" Work ", "work", ""],
+            "bookmarks": [
+                "https://bare.test/",
+                { "id": "x", "url": "https://a.test/", "title": " A\u{7} ", "folder": "WORK",
+                  "tags": ["tv", "TV", " "] },
+                { "id": "x", "url": "https://b.test/", "folder": "Later" }
+            ]
+        }))
+        .expect("deserializes");
+        config.bookmarks[0].created = 7;
+
+        let (clean, report) = sanitize_config_with_report(config);
+        assert_eq!(clean.bookmark_folders, vec!["Work", "Later"]);
+        let [bare, a, b] = &clean.bookmarks[..] else {
+            panic!("expected three bookmarks: {:?}", clean.bookmarks);
+        };
+        assert!(!bare.id.is_empty(), "a bare URL gets an id");
+        assert_eq!(bare.created, 7);
+        assert_eq!(a.id, "x");
+        assert_ne!(b.id, "x", "a repeated id is replaced");
+        assert_eq!(a.title, "A");
+        assert_eq!(a.folder.as_deref(), Some("Work"));
+        assert_eq!(a.tags, vec!["tv"]);
+        assert_eq!(b.folder.as_deref(), Some("Later"));
+
+        let fields: Vec<&str> = report.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "bookmark_folders[1]",
+                "bookmark_folders[2]",
+                "bookmarks[1].title",
+                "bookmarks[1].tags[1]",
+                "bookmarks[1].tags[2]",
+                "bookmarks[2].id",
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_bookmarks_reports_rewritten_urls() {
+        let config = AppConfig {
+            bookmarks: ["example.com", "https://example.com/", "https://b.test/"]
+                .into_iter()
+                .map(bookmark)
+                .collect(),
+            ..AppConfig::default()
+        };
+
+        let (clean, report) = sanitize_config_with_report(config);
+        let urls: Vec<&str> = clean.bookmarks.iter().map(|b| b.url.as_str()).collect();
+        assert_eq!(urls, ["https://example.com/", "https://b.test/"]);
+        let notes: Vec<(&str, &str)> = report
+            .iter()
+            .map(|c| (c.field.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            notes,
+            [
+                ("bookmarks[0].url", "URL normalized"),
+                ("bookmarks[1]", "duplicate bookmark; removed"),
+            ]
+        );
+        assert_eq!(report[0].new, "https://example.com/");
     }
 }
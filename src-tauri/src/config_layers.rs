@@ -0,0 +1,507 @@
+//! Layered config resolution: built-in defaults, then an optional
+//! machine-wide policy file, then the user's `config.json`, then
+//! `FLOATVIEW_*` environment overrides.
+//!
+//! The policy file lets IT pre-seed a shared machine:
+//!
+//! ```json
+//! {
+//!   "defaults": { "home_url": "https://jellyfin.lan/", "bookmarks": ["https://grafana.lan/"] },
+//!   "locked": ["home_url"]
+//! }
+//! ```
+//!
+//! `defaults` is merged over the built-in defaults and used wherever the
+//! app would otherwise start from `AppConfig::default()` (first run, or a
+//! config that couldn't be recovered). Each `locked` entry is a dotted
+//! path (`home_url`, `window.opacity`, `hotkeys.toggle_on_top`, or a whole
+//! section like `hotkeys`) whose policy value is re-imposed on every load,
+//! reload, and settings change, so the user file can't override it.
+//!
+//! Environment overrides are applied last and win over everything for the
+//! run. `FLOATVIEW_HOME_URL` sets `home_url`; a double underscore nests,
+//! so `FLOATVIEW_WINDOW__OPACITY=0.8` sets `window.opacity`. Values that
+//! parse as JSON (numbers, booleans, arrays) are used as such; anything
+//! else is taken as a string. Overridden keys are treated as locked too:
+//! a change made in Settings would be undone at the next start anyway.
+//!
+//! Pinned values hold for the run only: saves put the user's own value
+//! back in their place ([`UserValues`]), so `config.json` never picks up
+//! a policy or environment value and unsetting a variable brings the
+//! user's setting back.
+//!
+//! Everything here is pure apart from [`ConfigLayers::load`] reading the
+//! policy file; the result is kept on `AppState` for the life of the app.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::config_io::{sanitize_config_with_report, ConfigCorrection, Corrections};
+
+/// Prefix of the environment variables read as overrides.
+pub const ENV_PREFIX: &str = "FLOATVIEW_";
+
+/// Where the machine-wide policy file lives on this platform.
+pub fn policy_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let base = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(base).join("FloatView").join("policy.json")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/FloatView/policy.json")
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/floatview/policy.json")
+    }
+}
+
+/// On-disk shape of the policy file. Both sections are optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PolicyFile {
+    #[serde(default)]
+    pub defaults: Map<String, Value>,
+    #[serde(default)]
+    pub locked: Vec<String>,
+}
+
+/// Where a pinned key's value comes from, for the settings UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinSource {
+    Policy,
+    Environment,
+}
+
+/// A key the user can't change, and the value it is held at.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PinnedKey {
+    pub path: String,
+    pub source: PinSource,
+    #[serde(skip)]
+    pub value: Value,
+}
+
+/// The resolved non-user layers.
+#[derive(Debug, Clone)]
+pub struct ConfigLayers {
+    /// Built-in defaults with the policy's `defaults` merged over them.
+    defaults: Value,
+    /// Policy-locked keys first, then environment overrides, in the
+    /// order they are applied.
+    pinned: Vec<PinnedKey>,
+    /// Pinned keys dropped because their value doesn't fit the config.
+    rejected: Vec<ConfigCorrection>,
+}
+
+impl Default for ConfigLayers {
+    /// No policy and no overrides: built-in defaults only.
+    fn default() -> Self {
+        Self::from_parts(PolicyFile::default(), Vec::new())
+    }
+}
+
+impl ConfigLayers {
+    /// Read the policy file at `policy` (a missing file is not an error)
+    /// and collect `FLOATVIEW_*` overrides from `env`. Problems with
+    /// either are logged and the offending part skipped.
+    pub fn load(policy: &Path, env: impl IntoIterator<Item = (String, String)>) -> Self {
+        let policy = match fs::read_to_string(policy) {
+            Ok(content) => match serde_json::from_str::<PolicyFile>(&content) {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!(path = %policy.display(), "Ignoring unreadable policy file: {}", e);
+                    PolicyFile::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PolicyFile::default(),
+            Err(e) => {
+                warn!(path = %policy.display(), "Failed to read policy file: {}", e);
+                PolicyFile::default()
+            }
+        };
+        Self::from_parts(policy, parse_env_overrides(env))
+    }
+
+    /// Build the layers from an already-parsed policy and env overrides
+    /// (as `(dotted path, value)` pairs). Each pinned value is checked on
+    /// its own, so one that doesn't deserialize (`FLOATVIEW_WINDOW__WIDTH=wide`)
+    /// is dropped and reported in [`Self::rejected`] while every other
+    /// lock and override still applies.
+    pub fn from_parts(policy: PolicyFile, env: Vec<(String, Value)>) -> Self {
+        let builtin = serde_json::to_value(AppConfig::default()).unwrap_or(Value::Null);
+        let mut defaults = builtin.clone();
+        merge_value(&mut defaults, &Value::Object(policy.defaults));
+
+        let mut pinned = Vec::new();
+        for path in policy.locked {
+            match get_path(&defaults, &path) {
+                Some(value) => pinned.push(PinnedKey {
+                    path,
+                    source: PinSource::Policy,
+                    value: value.clone(),
+                }),
+                None => warn!(key = %path, "Ignoring unknown locked policy key"),
+            }
+        }
+        for (path, value) in env {
+            if get_path(&defaults, &path).is_none() {
+                warn!(key = %path, "Ignoring unknown {}* override", ENV_PREFIX);
+                continue;
+            }
+            pinned.push(PinnedKey {
+                path,
+                source: PinSource::Environment,
+                value,
+            });
+        }
+        let mut rejected = Corrections::default();
+        pinned.retain(|key| {
+            let mut probe = builtin.clone();
+            set_path(&mut probe, &key.path, key.value.clone());
+            match serde_json::from_value::<AppConfig>(probe) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!(key = %key.path, "Ignoring invalid pinned value: {}", e);
+                    rejected.note(
+                        key.path.clone(),
+                        &key.value,
+                        Value::Null,
+                        "not a valid value for this key; not applied",
+                    );
+                    false
+                }
+            }
+        });
+        Self {
+            defaults,
+            pinned,
+            rejected: rejected.into_vec(),
+        }
+    }
+
+    /// The starting config in place of `AppConfig::default()`: built-in
+    /// defaults, policy defaults, and pinned keys, sanitized.
+    pub fn defaults(&self) -> AppConfig {
+        self.enforce(self.policy_defaults())
+    }
+
+    /// Built-in defaults with the policy's merged over them, before the
+    /// pinned keys are re-imposed.
+    pub fn policy_defaults(&self) -> AppConfig {
+        serde_json::from_value(self.defaults.clone()).unwrap_or_else(|e| {
+            warn!("Policy defaults don't form a valid config: {}", e);
+            AppConfig::default()
+        })
+    }
+
+    /// `user`'s values for the pinned keys. Call with a config as the user
+    /// wrote it (read from `config.json`, a restored backup), before
+    /// [`Self::enforce`] replaces them.
+    pub fn user_values(&self, user: &AppConfig) -> UserValues {
+        if self.pinned.is_empty() {
+            return UserValues::default();
+        }
+        let Ok(value) = serde_json::to_value(user) else {
+            return UserValues::default();
+        };
+        UserValues(
+            self.pinned
+                .iter()
+                .filter_map(|key| {
+                    get_path(&value, &key.path).map(|v| (key.path.clone(), v.clone()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Re-impose every pinned key on `config`, then sanitize. Values that
+    /// don't deserialize were already dropped by [`Self::from_parts`].
+    pub fn enforce(&self, config: AppConfig) -> AppConfig {
+        self.enforce_with_report(config).0
+    }
+
+    /// [`Self::enforce`], also returning what changed: one correction per
+    /// pinned key whose value differed, then the sanitizer's.
+    pub fn enforce_with_report(&self, config: AppConfig) -> (AppConfig, Vec<ConfigCorrection>) {
+        if self.pinned.is_empty() {
+            return sanitize_config_with_report(config);
+        }
+        let Ok(mut value) = serde_json::to_value(&config) else {
+            return sanitize_config_with_report(config);
+        };
+        let mut report = Corrections::default();
+        for key in &self.pinned {
+            let current = get_path(&value, &key.path).cloned().unwrap_or_default();
+            if current != key.value {
+                let reason = match key.source {
+                    PinSource::Policy => "locked by system policy",
+                    PinSource::Environment => "set by an environment variable",
+                };
+                report.note(key.path.clone(), &current, &key.value, reason);
+            }
+            set_path(&mut value, &key.path, key.value.clone());
+        }
+        match serde_json::from_value(value) {
+            Ok(enforced) => {
+                let (config, sanitized) = sanitize_config_with_report(enforced);
+                let mut corrections = report.into_vec();
+                corrections.extend(sanitized);
+                (config, corrections)
+            }
+            Err(e) => {
+                warn!("Pinned config values are invalid; not applied: {}", e);
+                sanitize_config_with_report(config)
+            }
+        }
+    }
+
+    /// Keys the user can't change, for the settings UI.
+    pub fn pinned(&self) -> &[PinnedKey] {
+        &self.pinned
+    }
+
+    /// Pinned keys that were dropped for an invalid value, one
+    /// correction each (`new` is null: nothing was applied).
+    pub fn rejected(&self) -> &[ConfigCorrection] {
+        &self.rejected
+    }
+}
+
+/// The user's own values for the pinned keys, which the live config holds
+/// pinned values in place of; kept on `AppState` beside the layers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserValues(Vec<(String, Value)>);
+
+impl UserValues {
+    /// Put the user's values back over the pinned ones: turns the live
+    /// config into what belongs on disk. A config the values don't fit
+    /// (which `user_values` can't produce) is left as it was.
+    pub fn restore(&self, config: &mut AppConfig) {
+        if self.0.is_empty() {
+            return;
+        }
+        let Ok(mut value) = serde_json::to_value(&*config) else {
+            return;
+        };
+        for (path, user) in &self.0 {
+            set_path(&mut value, path, user.clone());
+        }
+        match serde_json::from_value(value) {
+            Ok(restored) => *config = restored,
+            Err(e) => warn!("User values for pinned keys don't fit the config: {}", e),
+        }
+    }
+}
+
+/// Collect `FLOATVIEW_*` variables as `(dotted path, value)` pairs.
+pub fn parse_env_overrides(
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, Value)> {
+    let mut overrides: Vec<(String, Value)> = env
+        .into_iter()
+        .filter_map(|(name, raw)| {
+            let key = name.strip_prefix(ENV_PREFIX)?;
+            if key.is_empty() {
+                return None;
+            }
+            let path = key.to_ascii_lowercase().replace("__", ".");
+            let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+            Some((path, value))
+        })
+        .collect();
+    // Env iteration order is unspecified; sort so the result is stable.
+    overrides.sort_by(|a, b| a.0.cmp(&b.0));
+    overrides
+}
+
+/// Deep-merge `overlay` into `base`: objects merge key by key, anything
+/// else replaces.
+fn merge_value(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| v.get(key))
+}
+
+fn set_path(value: &mut Value, path: &str, new: Value) {
+    let mut current = value;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        let Value::Object(map) = current else {
+            return;
+        };
+        if keys.peek().is_none() {
+            map.insert(key.to_string(), new);
+            return;
+        }
+        current = map
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_io::sanitize_config;
+    use serde_json::json;
+
+    fn policy(value: Value) -> PolicyFile {
+        serde_json::from_value(value).expect("policy")
+    }
+
+    #[test]
+    fn policy_defaults_seed_first_run_config() {
+        let layers = ConfigLayers::from_parts(
+            policy(json!({
+                "defaults": {
+                    "home_url": "https://jellyfin.lan/",
+                    "bookmarks": ["https://grafana.lan/"],
+                    "window": { "opacity": 0.8 }
+                }
+            })),
+            Vec::new(),
+        );
+        let config = layers.defaults();
+        assert_eq!(config.home_url, "https://jellyfin.lan/");
+        assert_eq!(config.bookmarks[0].url, "https://grafana.lan/");
+        assert!(
+            !config.bookmarks[0].id.is_empty(),
+            "bare policy URLs get ids"
+        );
+        assert_eq!(config.window.opacity, 0.8);
+        // Untouched siblings keep their built-in defaults.
+        assert_eq!(config.window.width, 1280);
+    }
+
+    #[test]
+    fn locked_keys_override_the_user_file() {
+        let layers = ConfigLayers::from_parts(
+            policy(json!({
+                "defaults": { "home_url": "https://jellyfin.lan/" },
+                "locked": ["home_url", "hotkeys.toggle_on_top", "no_such_key"]
+            })),
+            Vec::new(),
+        );
+        let mut user = AppConfig {
+            home_url: "https://elsewhere.test/".to_string(),
+            ..AppConfig::default()
+        };
+        user.hotkeys.toggle_on_top = "Ctrl+Alt+T".to_string();
+        user.hotkeys.toggle_locked = "Ctrl+Alt+D".to_string();
+
+        let (config, report) = layers.enforce_with_report(user);
+        assert_eq!(config.home_url, "https://jellyfin.lan/");
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].field, "home_url");
+        assert_eq!(report[0].reason, "locked by system policy");
+        assert_eq!(config.hotkeys.toggle_on_top, "Alt+Shift+T");
+        assert_eq!(config.hotkeys.toggle_locked, "Ctrl+Alt+D", "not locked");
+        let paths: Vec<&str> = layers.pinned().iter().map(|k| k.path.as_str()).collect();
+        assert_eq!(paths, vec!["home_url", "hotkeys.toggle_on_top"]);
+    }
+
+    #[test]
+    fn env_overrides_win_last_and_parse_json_scalars() {
+        let env = parse_env_overrides(vec![
+            (
+                "FLOATVIEW_HOME_URL".to_string(),
+                "https://env.test/".to_string(),
+            ),
+            ("FLOATVIEW_WINDOW__OPACITY".to_string(), "0.5".to_string()),
+            (
+                "FLOATVIEW_WINDOW__ALWAYS_ON_TOP".to_string(),
+                "false".to_string(),
+            ),
+            ("FLOATVIEW_".to_string(), "ignored".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+        assert_eq!(env.len(), 3);
+
+        let layers = ConfigLayers::from_parts(
+            policy(json!({
+                "defaults": { "home_url": "https://policy.test/" },
+                "locked": ["home_url"]
+            })),
+            env,
+        );
+        let config = layers.enforce(AppConfig::default());
+        assert_eq!(config.home_url, "https://env.test/");
+        assert_eq!(config.window.opacity, 0.5);
+        assert!(!config.window.always_on_top);
+        assert!(layers
+            .pinned()
+            .iter()
+            .any(|k| k.path == "window.opacity" && k.source == PinSource::Environment));
+    }
+
+    #[test]
+    fn invalid_pinned_values_are_sanitized_or_skipped() {
+        let layers = ConfigLayers::from_parts(
+            policy(json!({
+                "defaults": { "home_url": "https://policy.test/" },
+                "locked": ["home_url"]
+            })),
+            vec![
+                ("window.opacity".to_string(), json!(0.8)),
+                ("window.width".to_string(), json!("wide")),
+            ],
+        );
+        let user = AppConfig {
+            home_url: "https://user.test/".to_string(),
+            ..AppConfig::default()
+        };
+        // Only the string width is dropped; the other locks still hold.
+        let config = layers.enforce(user);
+        assert_eq!(config.home_url, "https://policy.test/");
+        assert_eq!(config.window.opacity, 0.8);
+        assert_eq!(config.window.width, AppConfig::default().window.width);
+        assert!(layers.pinned().iter().all(|k| k.path != "window.width"));
+        let rejected: Vec<&str> = layers.rejected().iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(rejected, ["window.width"]);
+
+        let layers = ConfigLayers::from_parts(
+            policy(json!({
+                "defaults": { "home_url": "javascript:alert(1)" },
+                "locked": ["home_url"]
+            })),
+            Vec::new(),
+        );
+        assert!(layers
+            .enforce(AppConfig::default())
+            .home_url
+            .starts_with("https://"));
+    }
+
+    #[test]
+    fn missing_policy_file_is_empty() {
+        let layers =
+            ConfigLayers::load(Path::new("/nonexistent/floatview/policy.json"), Vec::new());
+        assert!(layers.pinned().is_empty());
+        assert_eq!(
+            layers.defaults().home_url,
+            sanitize_config(AppConfig::default()).home_url
+        );
+    }
+}
@@ -0,0 +1,438 @@
+//! Versioned schema migrations for `config.json`.
+//!
+//! `load_config` parses the file into a raw `serde_json::Value` and runs it
+//! through [`migrate`] before deserializing into `AppConfig`. Each entry in
+//! [`MIGRATIONS`] rewrites a vN document into vN+1 in place, so a field
+//! rename or restructure becomes a step here instead of a parse failure
+//! that silently resets the user's bookmarks to defaults.
+//!
+//! Adding a step: bump [`CONFIG_VERSION`](crate::config::CONFIG_VERSION),
+//! append the `vN -> vN+1` function to [`MIGRATIONS`], and add a fixture
+//! test that feeds a real vN document through it. `migrations_cover_every_version` pins the table length to
+//! the version constant so the two can't drift.
+//!
+//! Migrations only ever run forwards. A file stamped with a version newer
+//! than this build understands is reported as
+//! [`MigrationError::NewerVersion`] and left untouched for the caller to
+//! preserve.
+
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::config::new_bookmark_id;
+
+/// One `vN -> vN+1` transform over the raw config document. Receives the
+/// top-level JSON object; returns a human-readable reason on failure.
+pub type MigrationStep = fn(&mut serde_json::Map<String, Value>) -> Result<(), String>;
+
+/// The migration chain. `MIGRATIONS[i]` upgrades schema `i + 1` to `i + 2`,
+/// so the table always has `CONFIG_VERSION - 1` entries.
+pub const MIGRATIONS: &[MigrationStep] = &[
+    v1_structured_bookmarks,
+    v2_recent_from_history,
+    v3_logical_site_sizes,
+];
+
+/// v1 -> v2: bookmarks go from bare URL strings to objects carrying an
+/// id, title, folder, tags and creation time, in the top-level list and
+/// in every profile's. Titles start empty (the next visit fills them in)
+/// and `created` is 0 because v1 never recorded it. Entries that are
+/// already objects are left for the sanitizer to check.
+fn v1_structured_bookmarks(obj: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+    fn convert(list: &mut Value) {
+        let Some(entries) = list.as_array_mut() else {
+            return;
+        };
+        for entry in entries {
+            if let Value::String(url) = entry {
+                *entry = json!({
+                    "id": new_bookmark_id(),
+                    "url": url,
+                    "title": "",
+                    "folder": null,
+                    "tags": [],
+                    "created": 0,
+                });
+            }
+        }
+    }
+    if let Some(list) = obj.get_mut("bookmarks") {
+        convert(list);
+    }
+    if let Some(Value::Array(profiles)) = obj.get_mut("profiles") {
+        for profile in profiles {
+            if let Some(list) = profile.get_mut("bookmarks") {
+                convert(list);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// v2 -> v3: the Recent dropdown reads the history store, so the
+/// `recent_urls` list the config used to carry is dropped. The loader
+/// takes the URLs out before this runs and setup adds them to
+/// `history.jsonl`; see
+/// [`ConfigLoadReport::legacy_recent_urls`](crate::config_io::ConfigLoadReport::legacy_recent_urls).
+fn v2_recent_from_history(obj: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+    obj.remove("recent_urls");
+    Ok(())
+}
+
+/// v3 -> v4: per-site `width` / `height` are in the window's units, which
+/// became logical pixels once `window.scale_factor` was recorded. A file
+/// without that scale still holds physical sizes, so they are divided by
+/// the scale of the monitor the window was saved on (1.0 when the file
+/// predates recording monitors too). Files that already carry a scale
+/// were written with logical sizes and are left alone.
+fn v3_logical_site_sizes(obj: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+    let window = obj.get("window");
+    if window
+        .and_then(|w| w.get("scale_factor"))
+        .is_some_and(|s| !s.is_null())
+    {
+        return Ok(());
+    }
+    let scale = window
+        .and_then(|w| w.pointer("/monitor/scale_factor"))
+        .and_then(Value::as_f64)
+        .filter(|s| s.is_finite() && *s > 0.0)
+        .unwrap_or(1.0);
+    if scale == 1.0 {
+        return Ok(());
+    }
+    let Some(Value::Object(sites)) = obj.get_mut("site_settings") else {
+        return Ok(());
+    };
+    for site in sites.values_mut() {
+        for key in ["width", "height"] {
+            if let Some(px) = site.get(key).and_then(Value::as_f64) {
+                site[key] = json!((px / scale).round() as i64);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Why a raw config document could not be brought up to the current schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The document's top level isn't a JSON object.
+    NotAnObject,
+    /// `config_version` is present but not a positive integer.
+    InvalidVersion,
+    /// The file was written by a newer FloatView. We never run steps
+    /// backwards, so the caller must decide how to preserve it.
+    NewerVersion { found: u32, supported: u32 },
+    /// A step rejected the document.
+    StepFailed { from: u32, reason: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "config is not a JSON object"),
+            Self::InvalidVersion => write!(f, "config_version is not a positive integer"),
+            Self::NewerVersion { found, supported } => write!(
+                f,
+                "config schema v{found} is newer than this build supports (v{supported})"
+            ),
+            Self::StepFailed { from, reason } => {
+                write!(f, "migration v{from} -> v{} failed: {reason}", from + 1)
+            }
+        }
+    }
+}
+
+/// Result of a successful [`migrate`]: the schema the document was stored
+/// at and the schema it is at now. Equal when nothing had to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationOutcome {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl MigrationOutcome {
+    /// Did at least one step run?
+    pub fn migrated(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// Read the stored schema version. Files written before 1.4.7 have no
+/// `config_version` field; they are schema v1.
+pub fn stored_version(value: &Value) -> Result<u32, MigrationError> {
+    let obj = value.as_object().ok_or(MigrationError::NotAnObject)?;
+    match obj.get("config_version") {
+        None | Some(Value::Null) => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| *n >= 1)
+            .ok_or(MigrationError::InvalidVersion),
+    }
+}
+
+/// Bring `value` up to [`CONFIG_VERSION`](crate::config::CONFIG_VERSION)
+/// using the production chain.
+pub fn migrate(value: &mut Value) -> Result<MigrationOutcome, MigrationError> {
+    migrate_with(value, MIGRATIONS)
+}
+
+/// Bring `value` up to schema `steps.len() + 1`, running every step from
+/// its stored version onwards and stamping the new `config_version`.
+/// Leaves `value` unmodified on error. Split from [`migrate`] so the
+/// runner itself can be tested against a synthetic chain.
+pub fn migrate_with(
+    value: &mut Value,
+    steps: &[MigrationStep],
+) -> Result<MigrationOutcome, MigrationError> {
+    let target = steps.len() as u32 + 1;
+    let from = stored_version(value)?;
+    if from > target {
+        return Err(MigrationError::NewerVersion {
+            found: from,
+            supported: target,
+        });
+    }
+    if from == target {
+        return Ok(MigrationOutcome { from, to: target });
+    }
+
+    // Work on a copy so a failing step can't leave a half-migrated
+    // document behind for the caller to deserialize.
+    let mut working = value.clone();
+    let obj = working.as_object_mut().ok_or(MigrationError::NotAnObject)?;
+    for (version, step) in (from..target).zip(&steps[(from - 1) as usize..]) {
+        step(obj).map_err(|reason| MigrationError::StepFailed {
+            from: version,
+            reason,
+        })?;
+    }
+    obj.insert("config_version".to_string(), Value::from(target));
+    *value = working;
+    Ok(MigrationOutcome { from, to: target })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CONFIG_VERSION;
+
+    fn rename_home(obj: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+        if let Some(v) = obj.remove("home") {
+            obj.insert("home_url".to_string(), v);
+        }
+        Ok(())
+    }
+
+    fn append_marker(obj: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+        let trail = obj
+            .entry("trail")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        trail
+            .as_array_mut()
+            .ok_or("trail is not an array")?
+            .push(Value::from("v3"));
+        Ok(())
+    }
+
+    fn reject(_: &mut serde_json::Map<String, Value>) -> Result<(), String> {
+        Err("nope".to_string())
+    }
+
+    #[test]
+    fn migrations_cover_every_version() {
+        assert_eq!(MIGRATIONS.len() as u32 + 1, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn stored_version_defaults_missing_field_to_v1() {
+        assert_eq!(stored_version(&json!({})), Ok(1));
+        assert_eq!(stored_version(&json!({ "config_version": null })), Ok(1));
+        assert_eq!(stored_version(&json!({ "config_version": 4 })), Ok(4));
+    }
+
+    #[test]
+    fn stored_version_rejects_garbage() {
+        assert_eq!(stored_version(&json!([])), Err(MigrationError::NotAnObject));
+        for bad in [json!(0), json!(-1), json!("2"), json!(1.5)] {
+            assert_eq!(
+                stored_version(&json!({ "config_version": bad })),
+                Err(MigrationError::InvalidVersion)
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_with_runs_steps_in_order_and_stamps_version() {
+        let mut doc = json!({ "home": "https://a.test/" });
+        let outcome = migrate_with(&mut doc, &[rename_home, append_marker]).expect("migrate");
+        assert_eq!(outcome, MigrationOutcome { from: 1, to: 3 });
+        assert!(outcome.migrated());
+        assert_eq!(
+            doc,
+            json!({ "home_url": "https://a.test/", "trail": ["v3"], "config_version": 3 })
+        );
+    }
+
+    #[test]
+    fn migrate_with_skips_steps_already_applied() {
+        let mut doc = json!({ "config_version": 2, "home": "kept" });
+        let outcome = migrate_with(&mut doc, &[rename_home, append_marker]).expect("migrate");
+        assert_eq!(outcome, MigrationOutcome { from: 2, to: 3 });
+        // The v1 -> v2 rename must not run against a v2 document.
+        assert_eq!(doc["home"], "kept");
+        assert_eq!(doc["trail"], json!(["v3"]));
+    }
+
+    #[test]
+    fn migrate_with_is_a_noop_at_current_version() {
+        let original = json!({ "config_version": 3, "home": "x" });
+        let mut doc = original.clone();
+        let outcome = migrate_with(&mut doc, &[rename_home, append_marker]).expect("migrate");
+        assert!(!outcome.migrated());
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn migrate_with_refuses_newer_file() {
+        let original = json!({ "config_version": 9 });
+        let mut doc = original.clone();
+        assert_eq!(
+            migrate_with(&mut doc, &[rename_home]),
+            Err(MigrationError::NewerVersion {
+                found: 9,
+                supported: 2
+            })
+        );
+        assert_eq!(doc, original, "a refused document must be left untouched");
+    }
+
+    #[test]
+    fn migrate_with_leaves_document_intact_when_a_step_fails() {
+        let original = json!({ "home": "https://a.test/" });
+        let mut doc = original.clone();
+        let err = migrate_with(&mut doc, &[rename_home, reject]).unwrap_err();
+        assert_eq!(
+            err,
+            MigrationError::StepFailed {
+                from: 2,
+                reason: "nope".to_string()
+            }
+        );
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn migrate_accepts_pre_versioned_v1_fixture() {
+        // A real pre-1.4.7 file: no config_version, no media_mute /
+        // zoom_video / show_strip hotkeys, no crop. The v1 -> v2 step
+        // restructures its bookmarks; serde defaults fill the other gaps
+        // at deserialize time.
+        let mut doc = json!({
+            "window": {
+                "x": 1920, "y": 100, "width": 800, "height": 450,
+                "always_on_top": true, "opacity": 0.8, "locked": false
+            },
+            "last_url": "http://192.168.1.20:8096/",
+            "recent_urls": ["http://192.168.1.20:8096/"],
+            "hotkeys": {
+                "toggle_on_top": "Alt+Shift+T",
+                "toggle_locked": "Alt+Shift+D",
+                "opacity_up": "Alt+Shift+Up",
+                "opacity_down": "Alt+Shift+Down",
+                "toggle_visibility": "Alt+Shift+H",
+                "media_play_pause": "Alt+Shift+P",
+                "media_next": "Alt+Shift+Right",
+                "media_previous": "Alt+Shift+Left"
+            },
+            "home_url": "http://192.168.1.20:8096/",
+            "bookmarks": ["http://192.168.1.20:8096/"]
+        });
+        let outcome = migrate(&mut doc).expect("migrate");
+        assert_eq!(outcome.to, CONFIG_VERSION);
+        let config: crate::config::AppConfig =
+            serde_json::from_value(doc).expect("v1 fixture deserializes");
+        assert_eq!(config.bookmarks.len(), 1);
+        assert_eq!(config.bookmarks[0].url, "http://192.168.1.20:8096/");
+        assert_eq!(config.hotkeys.media_mute, "Alt+Shift+M");
+    }
+
+    #[test]
+    fn v3_physical_site_sizes_become_logical() {
+        let mut doc = json!({
+            "config_version": 3,
+            "window": {
+                "x": 0, "y": 0, "width": 1600, "height": 900,
+                "always_on_top": true, "opacity": 1.0, "locked": false,
+                "monitor": { "x": 0, "y": 0, "width": 3840, "height": 2160, "scale_factor": 2.0 }
+            },
+            "site_settings": {
+                "youtube.com": { "width": 1280, "height": 721, "opacity": 0.5 },
+                "example.com": { "zoom": 1.5 }
+            }
+        });
+        migrate(&mut doc).expect("migrate");
+        let site = &doc["site_settings"]["youtube.com"];
+        assert_eq!(site["width"], 640);
+        assert_eq!(site["height"], 361);
+        assert_eq!(site["opacity"], 0.5);
+        assert!(doc["site_settings"]["example.com"].get("width").is_none());
+
+        // Written by a build that already stored logical sizes.
+        let mut logical = json!({
+            "config_version": 3,
+            "window": { "scale_factor": 2.0, "monitor": { "scale_factor": 2.0 } },
+            "site_settings": { "youtube.com": { "width": 640 } }
+        });
+        migrate(&mut logical).expect("migrate");
+        assert_eq!(logical["site_settings"]["youtube.com"]["width"], 640);
+    }
+
+    #[test]
+    fn v2_recent_urls_are_dropped() {
+        let mut doc = json!({
+            "config_version": 2,
+            "last_url": "https://a.test/",
+            "recent_urls": ["https://a.test/", "https://b.test/"]
+        });
+        let outcome = migrate(&mut doc).expect("migrate");
+        assert_eq!(outcome.from, 2);
+        assert!(doc.get("recent_urls").is_none());
+        assert_eq!(doc["last_url"], "https://a.test/");
+    }
+
+    #[test]
+    fn v1_bookmark_strings_become_structured_entries() {
+        let mut doc = json!({
+            "bookmarks": ["https://a.test/", "https://b.test/x"],
+            "profiles": [
+                { "name": "Default", "bookmarks": ["https://a.test/"] },
+                { "name": "Empty" }
+            ]
+        });
+        let outcome = migrate(&mut doc).expect("migrate");
+        assert_eq!(
+            outcome,
+            MigrationOutcome {
+                from: 1,
+                to: CONFIG_VERSION
+            }
+        );
+
+        let top = doc["bookmarks"].as_array().expect("bookmarks array");
+        assert_eq!(top.len(), 2, "order and count are preserved");
+        assert_eq!(top[1]["url"], "https://b.test/x");
+        assert_eq!(top[1]["title"], "");
+        assert_eq!(top[1]["folder"], Value::Null);
+        assert_eq!(top[1]["created"], 0);
+        let ids: Vec<&str> = top.iter().filter_map(|b| b["id"].as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1], "each entry gets its own id");
+
+        assert_eq!(doc["profiles"][0]["bookmarks"][0]["url"], "https://a.test/");
+        assert!(doc["profiles"][1].get("bookmarks").is_none());
+    }
+}
@@ -0,0 +1,920 @@
+//! JSON Schema for `config.json`, and the checks behind
+//! `floatview --validate-config`.
+//!
+//! The schema is built by hand from the same constants `sanitize_config`
+//! enforces (`MAX_BOOKMARKS`, the window-size bounds,
+//! …) rather than derived, so the limits can't drift from the sanitizer.
+//! A generated copy ships as `config.schema.json` next to the app; the
+//! `shipped_schema_is_current` test fails if it falls behind this file.
+//!
+//! [`validate_schema`] implements only the keywords [`config_schema`]
+//! uses (`type`, `properties`, `required`, `additionalProperties`,
+//! `maxProperties`, `items`, `anyOf`, local `$ref`, numeric and length
+//! bounds). Anything
+//! the schema can't express (URL schemes, hotkey syntax, duplicate
+//! bookmarks) is caught by running the file through the sanitizer and
+//! treating every correction as an error.
+
+use serde_json::{json, Map, Value};
+
+use crate::bookmarks::{
+    MAX_BOOKMARK_FOLDERS, MAX_BOOKMARK_TAGS, MAX_BOOKMARK_TITLE_LEN, MAX_FOLDER_NAME_LEN,
+    MAX_TAG_LEN,
+};
+use crate::config::{AppConfig, CONFIG_VERSION, MIN_OPACITY};
+use crate::config_io::{
+    sanitize_config_with_report, CROP_MIN_DIM, MAX_AUTO_REFRESH_MINUTES, MAX_BOOKMARKS,
+    MAX_HOTKEY_LEN, MAX_WINDOW_POS,
+};
+use crate::config_migrate::{migrate_with, MIGRATIONS};
+use crate::history::MAX_HISTORY_RETENTION_DAYS;
+use crate::kiosk::{MAX_KIOSK_ALLOWLIST, MAX_PIN_LEN, MIN_PIN_LEN};
+use crate::layout::{
+    MAX_GRID_SIZE, MAX_LAYOUTS, MAX_LAYOUT_NAME_LEN, MAX_LAYOUT_PADDING, MAX_LAYOUT_ZONES,
+    MAX_NUDGE_STEP,
+};
+use crate::omnibox::{MAX_ENGINE_KEYWORD_LEN, MAX_ENGINE_NAME_LEN, MAX_SEARCH_ENGINES};
+use crate::profiles::{MAX_PROFILES, MAX_PROFILE_NAME_LEN};
+use crate::site_settings::{MAX_PAGE_ZOOM, MAX_SITE_SETTINGS, MIN_PAGE_ZOOM};
+use crate::urls::{MAX_TRACKING_HOSTS, MAX_TRACKING_PARAMS, MAX_TRACKING_PARAM_LEN};
+use crate::window_presets::{MAX_PRESET_NAME_LEN, MAX_WINDOW_PRESETS};
+use crate::window_state::{
+    MAX_SCALE_FACTOR, MAX_WINDOW_PLACEMENTS, MAX_WINDOW_SIZE, MIN_SCALE_FACTOR, MIN_WINDOW_SIZE,
+};
+
+/// `$id` of the generated schema; also what a provisioning script can put
+/// in a config's `$schema` key.
+pub const SCHEMA_ID: &str = "https://floatview.app/schema/config.schema.json";
+
+fn url_field(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn bookmark_list(description: &str) -> Value {
+    json!({
+        "type": "array",
+        "items": { "$ref": "#/$defs/Bookmark" },
+        "maxItems": MAX_BOOKMARKS,
+        "description": description,
+    })
+}
+
+fn folder_list() -> Value {
+    json!({
+        "type": "array",
+        "items": { "type": "string", "minLength": 1, "maxLength": MAX_FOLDER_NAME_LEN },
+        "maxItems": MAX_BOOKMARK_FOLDERS,
+        "description": "Bookmark folder names in display order.",
+    })
+}
+
+fn bookmark_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["url"],
+        "properties": {
+            "id": { "type": "string", "description": "Assigned when missing." },
+            "url": url_field("http(s) URL."),
+            "title": { "type": "string", "maxLength": MAX_BOOKMARK_TITLE_LEN },
+            "folder": {
+                "type": ["string", "null"],
+                "maxLength": MAX_FOLDER_NAME_LEN,
+                "description": "An entry of bookmark_folders; null for the top level.",
+            },
+            "tags": {
+                "type": "array",
+                "items": { "type": "string", "minLength": 1, "maxLength": MAX_TAG_LEN },
+                "maxItems": MAX_BOOKMARK_TAGS,
+            },
+            "created": { "type": "integer", "minimum": 0, "description": "Unix seconds." },
+        },
+    })
+}
+
+fn search_engine_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["name", "keyword", "url"],
+        "properties": {
+            "name": { "type": "string", "minLength": 1, "maxLength": MAX_ENGINE_NAME_LEN },
+            "keyword": {
+                "type": "string",
+                "minLength": 1,
+                "maxLength": MAX_ENGINE_KEYWORD_LEN,
+                "description": "Lowercase, no spaces; typed before a query to use this engine.",
+            },
+            "url": url_field("http(s) URL with %s where the query goes."),
+        },
+    })
+}
+
+fn layout_schema() -> Value {
+    let name = json!({
+        "type": "string",
+        "minLength": 1,
+        "maxLength": MAX_LAYOUT_NAME_LEN,
+        "description": "Letters, digits, - and _.",
+    });
+    let percent = json!({ "type": "number", "minimum": 0, "maximum": 100 });
+    let count = json!({ "type": "integer", "minimum": 1, "maximum": MAX_GRID_SIZE });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["name"],
+        "properties": {
+            "name": name,
+            "padding": {
+                "type": ["integer", "null"],
+                "minimum": 0,
+                "maximum": MAX_LAYOUT_PADDING,
+                "description": "Gap at the monitor edges and between tiles, in logical pixels.",
+            },
+            "grid": {
+                "anyOf": [
+                    { "type": "null" },
+                    {
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["columns", "rows"],
+                        "properties": { "columns": count, "rows": count },
+                    },
+                ],
+                "description": "Even grid; cells are numbered from 1 in reading order.",
+            },
+            "zones": {
+                "type": "array",
+                "maxItems": MAX_LAYOUT_ZONES,
+                "items": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["name", "x", "y", "width", "height"],
+                    "properties": {
+                        "name": name,
+                        "x": percent,
+                        "y": percent,
+                        "width": percent,
+                        "height": percent,
+                    },
+                },
+                "description": "Named rectangles in percent of the monitor. Use either \
+                    this or grid.",
+            },
+        },
+    })
+}
+
+fn kiosk_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "enabled": { "type": "boolean" },
+            "allowlist": {
+                "type": "array",
+                "items": { "type": "string" },
+                "maxItems": MAX_KIOSK_ALLOWLIST,
+                "description": "http(s) URLs; pages with the same origin and a path at or \
+                    below an entry's are allowed. Include home_url.",
+            },
+            "pin": {
+                "type": "string",
+                "minLength": MIN_PIN_LEN,
+                "maxLength": MAX_PIN_LEN,
+                "description": "Plaintext PIN; replaced with pin_hash on the next save.",
+            },
+            "pin_hash": {
+                "type": ["string", "null"],
+                "minLength": 64,
+                "maxLength": 64,
+                "description": "Hex SHA-256 written by the app; set pin instead.",
+            },
+        },
+    })
+}
+
+fn tracking_params_schema() -> Value {
+    let names = |description: &str| {
+        json!({
+            "type": "array",
+            "items": { "type": "string", "minLength": 1, "maxLength": MAX_TRACKING_PARAM_LEN },
+            "maxItems": MAX_TRACKING_PARAMS,
+            "description": description,
+        })
+    };
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "strip": {
+                "type": "boolean",
+                "description": "Remove these parameters before storing bookmarks, recent URLs \
+                    and history.",
+            },
+            "params": names("Stripped everywhere; a trailing * matches a prefix (utm_*)."),
+            "hosts": {
+                "type": "object",
+                "maxProperties": MAX_TRACKING_HOSTS,
+                "additionalProperties": names("Stripped on this host only."),
+                "description": "Keyed by host (youtu.be) or wildcard host (*.youtube.com).",
+            },
+            "ignore_when_matching": {
+                "type": "boolean",
+                "description": "Also ignore these parameters when comparing URLs.",
+            },
+        },
+    })
+}
+
+fn window_preset_schema() -> Value {
+    let position = json!({
+        "type": "integer",
+        "minimum": -MAX_WINDOW_POS,
+        "maximum": MAX_WINDOW_POS,
+    });
+    let size = json!({
+        "type": "integer",
+        "minimum": MIN_WINDOW_SIZE,
+        "maximum": MAX_WINDOW_SIZE,
+    });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": [
+            "name", "x", "y", "width", "height", "opacity", "always_on_top", "locked",
+        ],
+        "properties": {
+            "name": { "type": "string", "minLength": 1, "maxLength": MAX_PRESET_NAME_LEN },
+            "hotkey": {
+                "type": ["string", "null"],
+                "maxLength": MAX_HOTKEY_LEN,
+                "description": "Global hotkey that applies the preset, like Ctrl+Alt+1.",
+            },
+            "x": position,
+            "y": position,
+            "width": size,
+            "height": size,
+            "scale_factor": {
+                "type": ["number", "null"],
+                "minimum": MIN_SCALE_FACTOR,
+                "maximum": MAX_SCALE_FACTOR,
+                "description": "Display scale x/y/width/height are logical pixels at; \
+                    null means physical pixels.",
+            },
+            "opacity": { "type": "number", "minimum": MIN_OPACITY, "maximum": 1.0 },
+            "always_on_top": { "type": "boolean" },
+            "locked": { "type": "boolean", "description": "Click-through mode." },
+            "crop": optional_crop(),
+        },
+    })
+}
+
+fn auto_refresh_field() -> Value {
+    json!({
+        "type": "integer",
+        "minimum": 0,
+        "maximum": MAX_AUTO_REFRESH_MINUTES,
+        "description": "Reload the page every N minutes; 0 disables.",
+    })
+}
+
+fn optional_crop() -> Value {
+    json!({ "anyOf": [{ "type": "null" }, { "$ref": "#/$defs/CropConfig" }] })
+}
+
+fn window_schema() -> Value {
+    let position = json!({
+        "type": "integer",
+        "minimum": -MAX_WINDOW_POS,
+        "maximum": MAX_WINDOW_POS,
+    });
+    let size = json!({
+        "type": "integer",
+        "minimum": MIN_WINDOW_SIZE,
+        "maximum": MAX_WINDOW_SIZE,
+    });
+    let scale = json!({
+        "type": "number",
+        "minimum": MIN_SCALE_FACTOR,
+        "maximum": MAX_SCALE_FACTOR,
+    });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["x", "y", "width", "height", "always_on_top", "opacity", "locked"],
+        "properties": {
+            "x": position,
+            "y": position,
+            "width": size,
+            "height": size,
+            "always_on_top": { "type": "boolean" },
+            "opacity": { "type": "number", "minimum": MIN_OPACITY, "maximum": 1.0 },
+            "locked": {
+                "type": "boolean",
+                "description": "Click-through mode. Always cleared at startup.",
+            },
+            "scale_factor": {
+                "type": ["number", "null"],
+                "minimum": MIN_SCALE_FACTOR,
+                "maximum": MAX_SCALE_FACTOR,
+                "description": "Display scale the geometry was saved at; x/y/width/height are logical pixels at this scale. Null (older configs) means physical pixels. Written by the app.",
+            },
+            "monitor": {
+                "anyOf": [{ "type": "null" }, { "$ref": "#/$defs/MonitorInfo" }],
+                "description": "The monitor x/y were on when saved. Written by the app.",
+            },
+            "placements": {
+                "type": "array",
+                "maxItems": MAX_WINDOW_PLACEMENTS,
+                "items": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["setup", "monitor", "x", "y", "width", "height"],
+                    "properties": {
+                        "setup": { "type": "string" },
+                        "monitor": { "$ref": "#/$defs/MonitorInfo" },
+                        "x": position,
+                        "y": position,
+                        "width": size,
+                        "height": size,
+                        "scale_factor": scale,
+                    },
+                },
+                "description": "Geometry per monitor setup, most recent first. Written by the app.",
+            },
+        },
+    })
+}
+
+fn monitor_schema() -> Value {
+    let coordinate = json!({
+        "type": "integer",
+        "minimum": -MAX_WINDOW_POS,
+        "maximum": MAX_WINDOW_POS,
+    });
+    let length = json!({ "type": "integer", "minimum": 1, "maximum": MAX_WINDOW_POS });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["x", "y", "width", "height"],
+        "properties": {
+            "name": { "type": "string" },
+            "x": coordinate,
+            "y": coordinate,
+            "width": length,
+            "height": length,
+            "scale_factor": {
+                "type": "number",
+                "minimum": MIN_SCALE_FACTOR,
+                "maximum": MAX_SCALE_FACTOR,
+            },
+        },
+    })
+}
+
+fn hotkeys_schema() -> Value {
+    let hotkey = json!({
+        "type": "string",
+        "minLength": 1,
+        "maxLength": MAX_HOTKEY_LEN,
+        "description": "Modifier(s) and a key joined with '+', e.g. \"Alt+Shift+T\".",
+    });
+    let fields = [
+        "toggle_on_top",
+        "toggle_locked",
+        "opacity_up",
+        "opacity_down",
+        "toggle_visibility",
+        "media_play_pause",
+        "media_next",
+        "media_previous",
+        "media_mute",
+        "zoom_video",
+        "show_strip",
+        "exit_kiosk",
+        "nudge_left",
+        "nudge_right",
+        "nudge_up",
+        "nudge_down",
+        "grow_window",
+        "shrink_window",
+        "cycle_corner",
+        "next_monitor",
+    ];
+    let properties: Map<String, Value> = fields
+        .iter()
+        .map(|field| (field.to_string(), hotkey.clone()))
+        .collect();
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        // The later additions have serde defaults and may be omitted.
+        "required": &fields[..8],
+        "properties": properties,
+    })
+}
+
+fn crop_schema() -> Value {
+    let offset = json!({ "type": "number", "minimum": 0.0, "maximum": 1.0 });
+    let extent = json!({ "type": "number", "minimum": CROP_MIN_DIM, "maximum": 1.0 });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["x", "y", "width", "height"],
+        "description": "Visible region as fractions of the page.",
+        "properties": { "x": offset, "y": offset, "width": extent, "height": extent },
+    })
+}
+
+fn profile_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["name", "window"],
+        "properties": {
+            "name": { "type": "string", "minLength": 1, "maxLength": MAX_PROFILE_NAME_LEN },
+            "window": { "$ref": "#/$defs/WindowConfig" },
+            "home_url": url_field("http(s) URL."),
+            "last_url": { "type": ["string", "null"] },
+            "auto_refresh_minutes": auto_refresh_field(),
+            "bookmarks": bookmark_list("In display order."),
+            "bookmark_folders": folder_list(),
+            "crop": optional_crop(),
+        },
+    })
+}
+
+fn site_schema() -> Value {
+    let size = json!({
+        "type": ["integer", "null"],
+        "minimum": MIN_WINDOW_SIZE,
+        "maximum": MAX_WINDOW_SIZE,
+    });
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "description": "Overrides for one site; null or absent keeps the global value.",
+        "properties": {
+            "opacity": { "type": ["number", "null"], "minimum": MIN_OPACITY, "maximum": 1.0 },
+            "crop": optional_crop(),
+            "auto_refresh_minutes": {
+                "type": ["integer", "null"],
+                "minimum": 0,
+                "maximum": MAX_AUTO_REFRESH_MINUTES,
+            },
+            "width": size,
+            "height": size,
+            "always_on_top": { "type": ["boolean", "null"] },
+            "zoom": {
+                "type": ["number", "null"],
+                "minimum": MIN_PAGE_ZOOM,
+                "maximum": MAX_PAGE_ZOOM,
+                "description": "Page zoom factor; 1.0 is 100%.",
+            },
+        },
+    })
+}
+
+/// The JSON Schema (draft 2020-12) for `config.json`.
+pub fn config_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": SCHEMA_ID,
+        "title": "FloatView config.json",
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["window", "hotkeys"],
+        "properties": {
+            "$schema": { "type": "string" },
+            "config_version": { "type": "integer", "minimum": 1, "maximum": CONFIG_VERSION },
+            "window": { "$ref": "#/$defs/WindowConfig" },
+            "last_url": { "type": ["string", "null"] },
+            "hotkeys": { "$ref": "#/$defs/HotkeyConfig" },
+            "home_url": url_field("http(s) URL opened by Go Home."),
+            "first_run": { "type": "boolean" },
+            "auto_refresh_minutes": auto_refresh_field(),
+            "bookmarks": bookmark_list("In display order, no duplicate URLs."),
+            "bookmark_folders": folder_list(),
+            "crop": optional_crop(),
+            "profiles": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/ProfileConfig" },
+                "maxItems": MAX_PROFILES,
+            },
+            "active_profile": {
+                "type": ["string", "null"],
+                "maxLength": MAX_PROFILE_NAME_LEN,
+            },
+            "history_retention_days": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_HISTORY_RETENTION_DAYS,
+                "description": "Days of browsing history kept in history.jsonl.",
+            },
+            "nudge_step": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_NUDGE_STEP,
+                "description": "Logical pixels the nudge hotkeys move the window by.",
+            },
+            "search_engines": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/SearchEngine" },
+                "maxItems": MAX_SEARCH_ENGINES,
+            },
+            "default_search_engine": {
+                "type": "string",
+                "maxLength": MAX_ENGINE_KEYWORD_LEN,
+                "description": "Keyword of the engine used for plain searches.",
+            },
+            "tracking_params": { "$ref": "#/$defs/TrackingParams" },
+            "layouts": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/SnapLayout" },
+                "maxItems": MAX_LAYOUTS,
+                "description": "Snap layouts; snap to one as <name>:<cell or zone>.",
+            },
+            "window_presets": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/WindowPreset" },
+                "maxItems": MAX_WINDOW_PRESETS,
+                "description": "Saved window setups, applied from the snap popup, tray, \
+                    or their hotkeys.",
+            },
+            "allow_local_files": {
+                "type": "boolean",
+                "description": "Let the window open local files, served through the \
+                    floatview-file protocol.",
+            },
+            "kiosk": { "$ref": "#/$defs/KioskConfig" },
+            "site_settings": {
+                "type": "object",
+                "maxProperties": MAX_SITE_SETTINGS,
+                "additionalProperties": { "$ref": "#/$defs/SiteSettings" },
+                "description": "Keyed by origin (https://host:port), host (example.com), \
+                    or wildcard host (*.example.com).",
+            },
+        },
+        "$defs": {
+            "WindowConfig": window_schema(),
+            "MonitorInfo": monitor_schema(),
+            "HotkeyConfig": hotkeys_schema(),
+            "CropConfig": crop_schema(),
+            "ProfileConfig": profile_schema(),
+            "SiteSettings": site_schema(),
+            "Bookmark": bookmark_schema(),
+            "SearchEngine": search_engine_schema(),
+            "TrackingParams": tracking_params_schema(),
+            "SnapLayout": layout_schema(),
+            "WindowPreset": window_preset_schema(),
+            "KioskConfig": kiosk_schema(),
+        },
+    })
+}
+
+/// Check `value` against `schema`, returning one message per violation
+/// as `<json path>: <problem>`.
+pub fn validate_schema(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, schema, value, "$", &mut errors);
+    errors
+}
+
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => root
+            .pointer(&format!("/$defs/{name}"))
+            .unwrap_or(&Value::Null),
+        None => schema,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = resolve(root, schema);
+
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array) {
+        let matched = options.iter().any(|option| {
+            let mut scratch = Vec::new();
+            check(root, option, value, path, &mut scratch);
+            scratch.is_empty()
+        });
+        if !matched {
+            errors.push(format!("{path}: does not match any allowed form"));
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.iter().any(|t| type_matches(t, value)) {
+            errors.push(format!(
+                "{path}: expected {}, found {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    match value {
+        Value::Object(map) => check_object(root, schema, map, path, errors),
+        Value::Array(items) => {
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    errors.push(format!("{path}: more than {max} items"));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(root, item_schema, item, &format!("{path}[{i}]"), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    errors.push(format!("{path}: shorter than {min} characters"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    errors.push(format!("{path}: longer than {max} characters"));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    errors.push(format!("{path}: below the minimum of {min}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    errors.push(format!("{path}: above the maximum of {max}"));
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+fn check_object(
+    root: &Value,
+    schema: &Value,
+    map: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for required in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !map.contains_key(required) {
+            errors.push(format!("{path}: missing required key \"{required}\""));
+        }
+    }
+    if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+        if map.len() as u64 > max {
+            errors.push(format!("{path}: more than {max} entries"));
+        }
+    }
+    let additional = schema.get("additionalProperties");
+    for (key, child) in map {
+        let child_path = format!("{path}.{key}");
+        match (properties.and_then(|p| p.get(key)), additional) {
+            (Some(child_schema), _) => check(root, child_schema, child, &child_path, errors),
+            (None, Some(Value::Bool(false))) => errors.push(format!("{child_path}: unknown key")),
+            (None, Some(extra @ Value::Object(_))) => {
+                check(root, extra, child, &child_path, errors)
+            }
+            (None, _) => {}
+        }
+    }
+}
+
+/// Everything `--validate-config` checks, in order: the text parses as
+/// JSON, older schemas migrate forward, the result matches
+/// [`config_schema`], it deserializes, and `sanitize_config` would leave
+/// it unchanged. Returns every problem found, or `Ok` for a file the app
+/// would load exactly as written.
+pub fn validate_config_text(text: &str) -> Result<(), Vec<String>> {
+    let mut value: Value =
+        serde_json::from_str(text).map_err(|e| vec![format!("not valid JSON: {e}")])?;
+    migrate_with(&mut value, MIGRATIONS).map_err(|e| vec![e.to_string()])?;
+
+    let mut errors = validate_schema(&config_schema(), &value);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    if let Some(map) = value.as_object_mut() {
+        map.remove("$schema");
+    }
+    let config: AppConfig = serde_json::from_value(value).map_err(|e| vec![e.to_string()])?;
+    let (_, corrections) = sanitize_config_with_report(config);
+    errors.extend(corrections.into_iter().map(|c| {
+        format!(
+            "{}: {} (would become {})",
+            c.field,
+            c.reason,
+            if c.new.is_null() {
+                "nothing".to_string()
+            } else {
+                c.new.to_string()
+            }
+        )
+    }));
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProfileConfig;
+    use crate::config_io::sanitize_config;
+
+    fn valid_config() -> Value {
+        serde_json::to_value(sanitize_config(AppConfig::default())).unwrap()
+    }
+
+    #[test]
+    fn schema_covers_every_serialized_field() {
+        let schema = config_schema();
+        let config = valid_config();
+        for key in config.as_object().unwrap().keys() {
+            assert!(schema["properties"].get(key).is_some(), "missing {key}");
+        }
+        let defs = [
+            ("WindowConfig", config["window"].clone()),
+            ("HotkeyConfig", config["hotkeys"].clone()),
+            (
+                "ProfileConfig",
+                serde_json::to_value(ProfileConfig {
+                    name: "x".to_string(),
+                    window: Default::default(),
+                    home_url: String::new(),
+                    last_url: None,
+                    auto_refresh_minutes: 0,
+                    bookmarks: Vec::new(),
+                    bookmark_folders: Vec::new(),
+                    crop: None,
+                })
+                .unwrap(),
+            ),
+            (
+                "SiteSettings",
+                serde_json::to_value(crate::site_settings::capture_site_settings(
+                    &AppConfig::default(),
+                    1.0,
+                ))
+                .unwrap(),
+            ),
+            (
+                "Bookmark",
+                serde_json::to_value(crate::config::Bookmark::new(
+                    "https://a.test/".to_string(),
+                    String::new(),
+                ))
+                .unwrap(),
+            ),
+            ("SearchEngine", config["search_engines"][0].clone()),
+            ("TrackingParams", config["tracking_params"].clone()),
+            ("KioskConfig", config["kiosk"].clone()),
+            (
+                "SnapLayout",
+                serde_json::to_value(crate::config::SnapLayout {
+                    name: "quad".to_string(),
+                    padding: None,
+                    grid: None,
+                    zones: Vec::new(),
+                })
+                .unwrap(),
+            ),
+            (
+                "WindowPreset",
+                serde_json::to_value(crate::config::WindowPreset {
+                    name: "Side panel".to_string(),
+                    hotkey: Some("Alt+1".to_string()),
+                    x: 0,
+                    y: 0,
+                    width: 480,
+                    height: 900,
+                    scale_factor: Some(1.0),
+                    opacity: 1.0,
+                    always_on_top: true,
+                    locked: false,
+                    crop: None,
+                })
+                .unwrap(),
+            ),
+        ];
+        for (def, sample) in defs {
+            for key in sample.as_object().unwrap().keys() {
+                assert!(
+                    schema["$defs"][def]["properties"].get(key).is_some(),
+                    "{def} missing {key}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sanitized_default_config_is_valid() {
+        let text = serde_json::to_string_pretty(&valid_config()).unwrap();
+        assert_eq!(validate_config_text(&text), Ok(()));
+    }
+
+    #[test]
+    fn schema_rejects_typos_types_and_limits() {
+        let mut config = valid_config();
+        config["home_ur1"] = json!("https://typo.test/");
+        config["window"]["width"] = json!(MAX_WINDOW_SIZE + 1);
+        config["window"]["opacity"] = json!("high");
+        config["bookmarks"] = json!(vec![json!({ "url": "https://a.test/" }); MAX_BOOKMARKS + 1]);
+        config["bookmark_folders"] = json!([""]);
+        config["crop"] = json!({ "x": 0.0, "y": 0.0, "width": 2.0, "height": 0.5 });
+        config["site_settings"] =
+            json!({ "chat.test": { "opacity": 0.4, "zoom": 9.0, "colour": 1 } });
+
+        let errors = validate_schema(&config_schema(), &config);
+        for expected in [
+            "$.home_ur1: unknown key",
+            "$.window.width: above the maximum",
+            "$.window.opacity: expected number, found string",
+            "$.bookmarks: more than",
+            "$.bookmark_folders[0]: shorter than",
+            "$.crop: does not match",
+            "$.site_settings.chat.test.zoom: above the maximum",
+            "$.site_settings.chat.test.colour: unknown key",
+        ] {
+            assert!(
+                errors.iter().any(|e| e.starts_with(expected)),
+                "{expected} not in {errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sanitizer_rules_catch_what_the_schema_cannot() {
+        let mut config = valid_config();
+        config["home_url"] = json!("javascript:alert(1)");
+        config["hotkeys"]["toggle_on_top"] = json!("<img src=x>");
+        let errors = validate_config_text(&config.to_string()).unwrap_err();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].starts_with("home_url: "));
+        assert!(errors[1].starts_with("hotkeys.toggle_on_top: "));
+    }
+
+    #[test]
+    fn validation_reports_unparseable_and_missing_sections() {
+        assert!(validate_config_text("{ nope").is_err());
+        let errors = validate_config_text("{}").unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("\"window\"")));
+        assert!(errors.iter().any(|e| e.contains("\"hotkeys\"")));
+    }
+
+    #[test]
+    fn shipped_schema_is_current() {
+        let shipped = include_str!("../config.schema.json");
+        let generated = serde_json::to_string_pretty(&config_schema()).unwrap() + "\n";
+        assert!(
+            shipped == generated,
+            "config.schema.json is stale; regenerate it with `floatview --print-config-schema > src-tauri/config.schema.json`"
+        );
+    }
+}
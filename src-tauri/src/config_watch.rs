@@ -0,0 +1,132 @@
+//! Live reload of hand edits to `config.json`.
+//!
+//! A background thread stats the file once per [`CONFIG_POLL_INTERVAL`]
+//! (no notify/inotify dependency; one `metadata` call a second is
+//! nothing) and reads it only when its size or mtime moves. A change
+//! whose content matches a digest the saver recorded in
+//! `AppState::written_digests` is the app's own write and is skipped;
+//! anything else goes through [`reload_config`] and
+//! [`crate::ops::apply_reloaded_config`], which diffs it against the
+//! in-memory config and applies only what changed.
+//!
+//! Until then the saver holds off: a save still waiting out its debounce
+//! when the edit lands is dropped rather than written over it (see
+//! [`WrittenDigests::has_unseen_edit`]). Once the edit is handled the
+//! watcher records its digest with the app's own, and saves resume on
+//! top of the reloaded config.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::config_io::{content_digest, reload_config, WrittenDigests};
+use crate::state::AppState;
+
+/// How often the watcher stats `config.json`. Also bounds how long
+/// shutdown waits for the thread to notice the flag.
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The cheap part of a change check: size and modification time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(FileStamp {
+        len: meta.len(),
+        modified: meta.modified().ok(),
+    })
+}
+
+/// Change detector for one config file. Kept free of Tauri types so the
+/// own-write filtering can be exercised against a temp directory.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    written: Arc<WrittenDigests>,
+    stamp: Option<FileStamp>,
+    digest: Option<u64>,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path`, treating its current content as already
+    /// seen. `written` is the digest list the saver fills in.
+    pub fn new(path: PathBuf, written: Arc<WrittenDigests>) -> Self {
+        let stamp = file_stamp(&path);
+        let digest = fs::read(&path).ok().map(|bytes| content_digest(&bytes));
+        Self {
+            path,
+            written,
+            stamp,
+            digest,
+        }
+    }
+
+    /// Check the file once. Returns `true` when its content changed since
+    /// the last check and the new content isn't something the app wrote
+    /// itself. A file that is missing or unreadable (mid-rename, say)
+    /// returns `false`; it is picked up once it reappears.
+    pub fn poll(&mut self) -> bool {
+        let stamp = file_stamp(&self.path);
+        if stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        let Ok(bytes) = fs::read(&self.path) else {
+            return false;
+        };
+        let digest = content_digest(&bytes);
+        if self.digest == Some(digest) {
+            // Touched or rewritten with identical content.
+            return false;
+        }
+        self.digest = Some(digest);
+        !self.written.contains(digest)
+    }
+
+    /// Mark the edit [`ConfigWatcher::poll`] last reported as handled, so
+    /// the saver may write over it again. Called once it's been applied,
+    /// or rejected as invalid (the next save then replaces it, as before).
+    pub fn acknowledge(&self) {
+        if let Some(digest) = self.digest {
+            self.written.record(digest);
+        }
+    }
+}
+
+/// Spawn the watcher thread. It exits on the first tick after
+/// `shutdown_flag` is latched.
+pub fn spawn_config_watcher(app: &AppHandle) -> std::io::Result<JoinHandle<()>> {
+    let state = app.state::<AppState>();
+    let mut watcher = ConfigWatcher::new(state.config_path.clone(), state.written_digests.clone());
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("floatview-config-watcher".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(CONFIG_POLL_INTERVAL);
+            let state = app.state::<AppState>();
+            if state.shutdown_flag.load(Ordering::Acquire) {
+                return;
+            }
+            if !watcher.poll() {
+                continue;
+            }
+            match reload_config(&state.config_path) {
+                Ok(config) => match crate::ops::apply_reloaded_config(&app, config) {
+                    Ok(true) => info!("Applied external edit to config.json"),
+                    Ok(false) => {}
+                    Err(e) => warn!("Failed to apply edited config.json: {}", e),
+                },
+                Err(e) => warn!("Ignoring edited config.json that failed to load: {}", e),
+            }
+            watcher.acknowledge();
+        })
+}
@@ -0,0 +1,363 @@
+//! `floatview://` links: open a page and set up the window from outside
+//! the app, e.g. a Stream Deck button or a launcher shortcut.
+//!
+//! ```text
+//! floatview://open?url=jellyfin.lan:8096&opacity=0.6&position=top-right&profile=tv
+//! ```
+//!
+//! Every parameter is optional; a bare `floatview://open` just brings the
+//! window forward. `url` goes through [`normalize_url`], `opacity`
+//! through [`clamp_opacity`], `position` must be a snap preset or
+//! `<layout>:<zone>` ([`normalize_position`]), and `profile` must be a
+//! valid profile name (that the layout or profile exists is checked when
+//! the link is applied). Unknown actions and
+//! parameters are rejected rather than ignored, so a typo doesn't look
+//! like it worked.
+//!
+//! The OS starts `floatview <link>`; a second instance hands its argv to
+//! the running one through `tauri_plugin_single_instance`, and
+//! [`find_deep_link`] picks the link out. On Linux the scheme is claimed
+//! with a `.desktop` file declaring `x-scheme-handler/floatview`
+//! ([`register_scheme_handler`]); other platforms need the installer to
+//! register it.
+//!
+//! Parsing here is pure; [`crate::ops::open_deep_link`] applies a link.
+
+use url::Url;
+
+use crate::config::clamp_opacity;
+use crate::layout::normalize_position;
+use crate::profiles::normalize_profile_name;
+use crate::urls::{normalize_url, MAX_URL_LEN};
+
+/// The URL scheme FloatView handles.
+pub const SCHEME: &str = "floatview";
+
+/// Longest link accepted: room for a maximal `url` parameter, encoded.
+pub const MAX_DEEP_LINK_LEN: usize = 3 * MAX_URL_LEN;
+
+/// What a `floatview://open` link asks for, validated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeepLink {
+    pub url: Option<String>,
+    pub opacity: Option<f64>,
+    pub position: Option<String>,
+    pub profile: Option<String>,
+}
+
+/// The first argument that is a `floatview:` link, if any. `args` is a
+/// full argv; the program name never matches.
+pub fn find_deep_link(args: &[String]) -> Option<&str> {
+    args.iter().map(String::as_str).find(|arg| {
+        arg.get(..SCHEME.len() + 1)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{SCHEME}:")))
+    })
+}
+
+/// Parse and validate a `floatview://open?…` link.
+pub fn parse_deep_link(raw: &str) -> Result<DeepLink, String> {
+    let raw = raw.trim();
+    if raw.len() > MAX_DEEP_LINK_LEN {
+        return Err("Link is too long".to_string());
+    }
+    let link = Url::parse(raw).map_err(|_| "Invalid link".to_string())?;
+    if link.scheme() != SCHEME {
+        return Err(format!("Not a {SCHEME}:// link"));
+    }
+    // `floatview://open?…` parses with `open` as the host;
+    // `floatview:open?…` has it as the path.
+    let action = match link.host_str() {
+        Some(host) if link.path().trim_matches('/').is_empty() => host,
+        Some(_) => return Err("Unknown link action".to_string()),
+        None => link.path().trim_matches('/'),
+    };
+    if !action.eq_ignore_ascii_case("open") {
+        return Err(format!("Unknown link action \"{action}\""));
+    }
+
+    let mut parsed = DeepLink::default();
+    for (key, value) in link.query_pairs() {
+        let duplicate = || format!("\"{key}\" is given more than once");
+        match key.as_ref() {
+            "url" => {
+                if parsed.url.is_some() {
+                    return Err(duplicate());
+                }
+                parsed.url = Some(normalize_url(&value)?);
+            }
+            "opacity" => {
+                if parsed.opacity.is_some() {
+                    return Err(duplicate());
+                }
+                let opacity = value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|o| o.is_finite())
+                    .ok_or_else(|| format!("Invalid opacity \"{value}\""))?;
+                parsed.opacity = Some(clamp_opacity(opacity));
+            }
+            "position" => {
+                if parsed.position.is_some() {
+                    return Err(duplicate());
+                }
+                parsed.position = Some(normalize_position(&value)?);
+            }
+            "profile" => {
+                if parsed.profile.is_some() {
+                    return Err(duplicate());
+                }
+                parsed.profile = Some(normalize_profile_name(&value)?);
+            }
+            _ => return Err(format!("Unknown link parameter \"{key}\"")),
+        }
+    }
+    Ok(parsed)
+}
+
+/// File name of the scheme handler entry in `~/.local/share/applications`.
+#[cfg(target_os = "linux")]
+pub const DESKTOP_FILE_NAME: &str = "floatview-url-handler.desktop";
+
+/// A `.desktop` entry that runs `exe` with the link as its argument for
+/// `x-scheme-handler/floatview`. Hidden from menus; the packaged launcher
+/// entry stays the one users see.
+#[cfg(target_os = "linux")]
+pub fn desktop_entry(exe: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=FloatView\n\
+         Comment=Open {SCHEME}:// links in FloatView\n\
+         Exec={} %u\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/{SCHEME};\n",
+        quote_exec_arg(exe)
+    )
+}
+
+/// Quote one `Exec` argument per the Desktop Entry spec. Two passes, in
+/// the reverse of the order a reader undoes them: the Exec rules (double
+/// quotes, with `"`, `` ` ``, `$` and `\` backslash-escaped, and `%`
+/// doubled), then the string-value escapes every key gets, which double
+/// each of those backslashes again. So a literal `\` is written `\\\\`.
+#[cfg(target_os = "linux")]
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    escape_desktop_string(&quoted)
+}
+
+/// The Desktop Entry `string` escapes: `\\`, `\n`, `\t` and `\r`.
+#[cfg(target_os = "linux")]
+fn escape_desktop_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Write [`desktop_entry`] for `exe` into `dir` unless it's already there
+/// as written. Returns whether the file changed.
+#[cfg(target_os = "linux")]
+pub fn write_desktop_entry(dir: &std::path::Path, exe: &str) -> std::io::Result<bool> {
+    let path = dir.join(DESKTOP_FILE_NAME);
+    let entry = desktop_entry(exe);
+    if std::fs::read_to_string(&path).is_ok_and(|current| current == entry) {
+        return Ok(false);
+    }
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, entry)?;
+    Ok(true)
+}
+
+/// Claim `floatview://` for this executable: write the `.desktop` entry
+/// under the user's data dir and make it the scheme's default handler.
+/// Runs at every start so a moved AppImage re-registers; when the entry
+/// is unchanged nothing is written or run. Failures are logged, never
+/// fatal.
+#[cfg(target_os = "linux")]
+pub fn register_scheme_handler<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    use tauri::Manager;
+    use tracing::{info, warn};
+
+    // Inside an AppImage, current_exe is in a temporary mount; the
+    // runtime puts the image's own path in $APPIMAGE.
+    let exe = match std::env::var("APPIMAGE") {
+        Ok(path) if !path.is_empty() => path,
+        _ => match std::env::current_exe() {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(e) => {
+                warn!("Deep links: can't locate the executable: {}", e);
+                return;
+            }
+        },
+    };
+    let dir = match app.path().data_dir() {
+        Ok(dir) => dir.join("applications"),
+        Err(e) => {
+            warn!("Deep links: can't resolve the data dir: {}", e);
+            return;
+        }
+    };
+    match write_desktop_entry(&dir, &exe) {
+        Ok(false) => return,
+        Ok(true) => info!(dir = %dir.display(), "Registered {SCHEME}:// handler"),
+        Err(e) => {
+            warn!(dir = %dir.display(), "Deep links: failed to write handler: {}", e);
+            return;
+        }
+    }
+    let mime = format!("x-scheme-handler/{SCHEME}");
+    let commands: [(&str, Vec<&std::ffi::OsStr>); 2] = [
+        (
+            "xdg-mime",
+            vec![
+                "default".as_ref(),
+                DESKTOP_FILE_NAME.as_ref(),
+                mime.as_ref(),
+            ],
+        ),
+        ("update-desktop-database", vec![dir.as_os_str()]),
+    ];
+    for (program, args) in commands {
+        match std::process::Command::new(program).args(args).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!(%program, %status, "Deep links: handler registration step failed"),
+            Err(e) => warn!(%program, "Deep links: can't run: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (link, expected) where `Err` only checks that the link is refused.
+    fn cases() -> Vec<(&'static str, Result<DeepLink, ()>)> {
+        vec![
+            ("floatview://open", Ok(DeepLink::default())),
+            ("floatview://open/", Ok(DeepLink::default())),
+            ("floatview:open", Ok(DeepLink::default())),
+            ("FLOATVIEW://OPEN", Ok(DeepLink::default())),
+            (
+                "floatview://open?url=jellyfin.lan:8096&opacity=0.6&position=Top-Right&profile=tv",
+                Ok(DeepLink {
+                    url: Some("https://jellyfin.lan:8096/".to_string()),
+                    opacity: Some(0.6),
+                    position: Some("top-right".to_string()),
+                    profile: Some("tv".to_string()),
+                }),
+            ),
+            (
+                "floatview://open?url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1%26c%3D2",
+                Ok(DeepLink {
+                    url: Some("https://example.com/a?b=1&c=2".to_string()),
+                    ..DeepLink::default()
+                }),
+            ),
+            (
+                "floatview://open?opacity=0",
+                Ok(DeepLink {
+                    opacity: Some(clamp_opacity(0.0)),
+                    ..DeepLink::default()
+                }),
+            ),
+            (
+                "floatview://open?opacity=7",
+                Ok(DeepLink {
+                    opacity: Some(1.0),
+                    ..DeepLink::default()
+                }),
+            ),
+            ("floatview://open?url=javascript:alert(1)", Err(())),
+            ("floatview://open?url=file:///etc/passwd", Err(())),
+            ("floatview://open?opacity=NaN", Err(())),
+            ("floatview://open?opacity=half", Err(())),
+            ("floatview://open?position=middle", Err(())),
+            (
+                "floatview://open?position=Quad:2",
+                Ok(DeepLink {
+                    position: Some("quad:2".to_string()),
+                    ..DeepLink::default()
+                }),
+            ),
+            ("floatview://open?profile=", Err(())),
+            ("floatview://open?opactiy=0.5", Err(())),
+            ("floatview://open?url=a.test&url=b.test", Err(())),
+            ("floatview://close", Err(())),
+            ("floatview://open/extra", Err(())),
+            ("https://open/?url=a.test", Err(())),
+            ("not a link", Err(())),
+        ]
+    }
+
+    #[test]
+    fn parse_deep_link_validates_every_parameter() {
+        for (i, (link, expected)) in cases().into_iter().enumerate() {
+            let got = parse_deep_link(link).map_err(|_| ());
+            assert_eq!(got, expected, "case #{i}: parse_deep_link({link:?})");
+        }
+        let long = format!(
+            "floatview://open?url=https://a.test/{}",
+            "a".repeat(MAX_DEEP_LINK_LEN)
+        );
+        assert!(parse_deep_link(&long).is_err());
+    }
+
+    #[test]
+    fn find_deep_link_skips_other_arguments() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            find_deep_link(&args(&[
+                "/usr/bin/floatview",
+                "floatview://open?position=center"
+            ])),
+            Some("floatview://open?position=center")
+        );
+        assert_eq!(
+            find_deep_link(&args(&["floatview", "--flag", "FloatView:open"])),
+            Some("FloatView:open")
+        );
+        assert_eq!(find_deep_link(&args(&["floatview", "floatvie"])), None);
+        assert_eq!(find_deep_link(&args(&["floatview"])), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn desktop_entry_declares_scheme_and_quotes_exec() {
+        let entry = desktop_entry("/opt/Float View/float$view%1");
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nMimeType=x-scheme-handler/floatview;\n"));
+        assert!(entry.contains("\nExec=\"/opt/Float View/float\\\\$view%%1\" %u\n"));
+        assert!(entry.contains("\nNoDisplay=true\n"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn exec_escapes_backslashes_for_both_passes() {
+        // Unescaping the string value gives `"C:\\x\"y"`, which the Exec
+        // rules then read as the one argument `C:\x"y`.
+        assert_eq!(quote_exec_arg(r#"C:\x"y"#), r#""C:\\\\x\\"y""#);
+        assert_eq!(quote_exec_arg("a\tb"), r#""a\tb""#);
+    }
+}
@@ -0,0 +1,350 @@
+//! Browsing history: one entry per URL with its title, visit count and
+//! last-visit time, kept in `history.jsonl` next to `config.json`.
+//!
+//! The store lives outside the config so a visit doesn't rewrite the whole
+//! settings file (and its backups). Each visit appends the updated entry
+//! as one JSON line; on load, a later line for the same URL replaces the
+//! earlier one. Deleting, clearing, or pruning rewrites the file with one
+//! line per entry, and so does a load that found superseded lines, which
+//! keeps the file from growing without bound.
+//!
+//! The Recent dropdown is a view over the top of this store
+//! ([`recent_urls`]), so deleting from history takes pages off it too.
+//! Entries store URLs without their tracking parameters
+//! ([`crate::urls::url_to_store`]).
+//!
+//! The edit functions are pure over a `Vec<HistoryEntry>` held newest
+//! first; the file functions log failures and carry on, since losing a
+//! history line isn't worth interrupting navigation for.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::bookmarks::clean_title;
+use crate::state::AppState;
+use crate::urls::url_to_store;
+
+/// File name of the history store, in the config directory.
+pub const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// Entry cap. The oldest entries go first past this.
+pub const MAX_HISTORY_ENTRIES: usize = 10_000;
+
+/// Default and upper bound for `history_retention_days`.
+pub const DEFAULT_HISTORY_RETENTION_DAYS: u32 = 90;
+pub const MAX_HISTORY_RETENTION_DAYS: u32 = 3650;
+
+/// Most results `search_history` returns.
+pub const MAX_HISTORY_RESULTS: usize = 500;
+
+/// How many URLs the Recent dropdown lists.
+pub const MAX_RECENT_URLS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    pub visit_count: u32,
+    /// Unix seconds.
+    pub last_visit: u64,
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `history.jsonl` beside `config_path`.
+pub fn history_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(HISTORY_FILE_NAME)
+}
+
+/// Record a visit to `url` (already normalized). Returns the updated
+/// entry for [`append_history`], or `None` when `url` is already the most
+/// recent entry: the page polls `set_url`, and staying on a page isn't a
+/// new visit.
+pub fn record_visit(entries: &mut Vec<HistoryEntry>, url: &str, now: u64) -> Option<HistoryEntry> {
+    if entries.first().is_some_and(|e| e.url == url) {
+        return None;
+    }
+    let entry = match entries.iter().position(|e| e.url == url) {
+        Some(index) => {
+            let mut entry = entries.remove(index);
+            entry.visit_count = entry.visit_count.saturating_add(1);
+            entry.last_visit = now;
+            entry
+        }
+        None => HistoryEntry {
+            url: url.to_string(),
+            title: String::new(),
+            visit_count: 1,
+            last_visit: now,
+        },
+    };
+    entries.insert(0, entry.clone());
+    Some(entry)
+}
+
+/// Add the `urls` history doesn't have yet ahead of the existing entries,
+/// as visits a second apart ending at `now` so the order survives the
+/// sort on load. Used for the `recent_urls` list of a config from before
+/// the Recent dropdown read history, so an upgrade keeps that list.
+/// Returns whether anything was added.
+pub fn import_urls(entries: &mut Vec<HistoryEntry>, urls: &[String], now: u64) -> bool {
+    let mut added: Vec<HistoryEntry> = Vec::new();
+    for url in urls {
+        if entries.iter().chain(&added).any(|e| e.url == *url) {
+            continue;
+        }
+        added.push(HistoryEntry {
+            url: url.clone(),
+            title: String::new(),
+            visit_count: 1,
+            last_visit: now.saturating_sub(added.len() as u64),
+        });
+    }
+    let any = !added.is_empty();
+    entries.splice(0..0, added);
+    any
+}
+
+/// Set the title of `url`'s entry. Returns the updated entry when the
+/// title changed.
+pub fn set_title(entries: &mut [HistoryEntry], url: &str, title: &str) -> Option<HistoryEntry> {
+    let entry = entries.iter_mut().find(|e| e.url == url)?;
+    if title.is_empty() || entry.title == title {
+        return None;
+    }
+    entry.title = title.to_string();
+    Some(entry.clone())
+}
+
+/// Entries whose URL or title contains every whitespace-separated word of
+/// `query` (case-insensitively), newest first, at most `limit`. An empty
+/// query lists the newest entries.
+pub fn search(entries: &[HistoryEntry], query: &str, limit: usize) -> Vec<HistoryEntry> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    entries
+        .iter()
+        .filter(|e| {
+            let url = e.url.to_lowercase();
+            let title = e.title.to_lowercase();
+            words
+                .iter()
+                .all(|w| url.contains(w.as_str()) || title.contains(w.as_str()))
+        })
+        .take(limit.min(MAX_HISTORY_RESULTS))
+        .cloned()
+        .collect()
+}
+
+/// Remove `url`'s entry. Returns whether there was one.
+pub fn delete_url(entries: &mut Vec<HistoryEntry>, url: &str) -> bool {
+    let before = entries.len();
+    entries.retain(|e| e.url != url);
+    entries.len() != before
+}
+
+/// Remove entries last visited in `[from, to)` (Unix seconds; `None` is
+/// unbounded). Returns how many went.
+pub fn clear_range(entries: &mut Vec<HistoryEntry>, from: Option<u64>, to: Option<u64>) -> usize {
+    let before = entries.len();
+    entries.retain(|e| {
+        let inside = from.is_none_or(|f| e.last_visit >= f) && to.is_none_or(|t| e.last_visit < t);
+        !inside
+    });
+    before - entries.len()
+}
+
+/// Drop entries older than `retention_days` and past
+/// [`MAX_HISTORY_ENTRIES`]. Returns whether anything was removed.
+pub fn prune(entries: &mut Vec<HistoryEntry>, now: u64, retention_days: u32) -> bool {
+    let before = entries.len();
+    let cutoff = now.saturating_sub(u64::from(retention_days) * 86_400);
+    entries.retain(|e| e.last_visit >= cutoff);
+    entries.truncate(MAX_HISTORY_ENTRIES);
+    entries.len() != before
+}
+
+/// The newest `n` URLs, for the Recent dropdown.
+pub fn recent_urls(entries: &[HistoryEntry], n: usize) -> Vec<String> {
+    entries.iter().take(n).map(|e| e.url.clone()).collect()
+}
+
+/// Record a navigation reported by `set_url` and write it out: one
+/// appended line normally, a full rewrite when the visit also pushed
+/// something past the retention limit.
+pub fn note_visit(state: &AppState, url: &str) -> Result<(), String> {
+    let (url, retention) = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        (
+            url_to_store(url, &config.tracking_params),
+            config.history_retention_days,
+        )
+    };
+    let mut entries = state.history.lock().map_err(|e| e.to_string())?;
+    let now = now_secs();
+    if let Some(entry) = record_visit(&mut entries, &url, now) {
+        if prune(&mut entries, now, retention) {
+            write_history(&state.history_path, &entries);
+        } else {
+            append_history(&state.history_path, &entry);
+        }
+    }
+    Ok(())
+}
+
+/// Record the page title reported by `set_window_title` for `url`.
+pub fn note_title(state: &AppState, url: &str, title: &str) -> Result<(), String> {
+    let url = url_to_store(
+        url,
+        &state
+            .config
+            .lock()
+            .map_err(|e| e.to_string())?
+            .tracking_params,
+    );
+    let mut entries = state.history.lock().map_err(|e| e.to_string())?;
+    if let Some(entry) = set_title(&mut entries, &url, &clean_title(title)) {
+        append_history(&state.history_path, &entry);
+    }
+    Ok(())
+}
+
+/// Read the store, newest first. Unreadable lines are skipped; a file
+/// with superseded or bad lines is compacted in place. A missing file is
+/// an empty history.
+pub fn load_history(path: &Path) -> Vec<HistoryEntry> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            warn!(path = %path.display(), "Failed to read history: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut by_url: HashMap<String, HistoryEntry> = HashMap::new();
+    let mut lines = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        lines += 1;
+        match serde_json::from_str::<HistoryEntry>(line) {
+            Ok(entry) => {
+                by_url.insert(entry.url.clone(), entry);
+            }
+            Err(e) => warn!("Skipping unreadable history line: {}", e),
+        }
+    }
+    let mut entries: Vec<HistoryEntry> = by_url.into_values().collect();
+    entries.sort_by(|a, b| b.last_visit.cmp(&a.last_visit).then(a.url.cmp(&b.url)));
+    if lines != entries.len() {
+        write_history(path, &entries);
+    }
+    entries
+}
+
+/// Append one entry line.
+pub fn append_history(path: &Path, entry: &HistoryEntry) {
+    let result = serde_json::to_string(entry)
+        .map_err(|e| e.to_string())
+        .and_then(|line| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!(path = %path.display(), "Failed to append history: {}", e);
+    }
+}
+
+/// Replace the store with `entries`, one line each, via a temp file and
+/// rename so a crash mid-write leaves the old file intact.
+pub fn write_history(path: &Path, entries: &[HistoryEntry]) {
+    let mut content = String::new();
+    for entry in entries {
+        if let Ok(line) = serde_json::to_string(entry) {
+            content.push_str(&line);
+            content.push('\n');
+        }
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    let result = fs::write(&tmp, content).and_then(|()| fs::rename(&tmp, path));
+    if let Err(e) = result {
+        warn!(path = %path.display(), "Failed to write history: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.url.as_str()).collect()
+    }
+
+    #[test]
+    fn visits_move_to_the_front_and_count() {
+        let mut entries = Vec::new();
+        assert!(record_visit(&mut entries, "https://a.test/", 10).is_some());
+        assert!(record_visit(&mut entries, "https://b.test/", 20).is_some());
+        assert!(
+            record_visit(&mut entries, "https://b.test/", 25).is_none(),
+            "polling the same page is not a visit"
+        );
+        let again = record_visit(&mut entries, "https://a.test/", 30).expect("revisit");
+        assert_eq!(again.visit_count, 2);
+        assert_eq!(again.last_visit, 30);
+        assert_eq!(urls(&entries), ["https://a.test/", "https://b.test/"]);
+        assert_eq!(recent_urls(&entries, 1), ["https://a.test/"]);
+    }
+
+    #[test]
+    fn search_matches_every_word_in_url_or_title() {
+        let mut entries = Vec::new();
+        record_visit(&mut entries, "https://jellyfin.lan/web", 1);
+        record_visit(&mut entries, "https://grafana.lan/d/abc", 2);
+        set_title(
+            &mut entries,
+            "https://grafana.lan/d/abc",
+            "Node Exporter Full",
+        );
+
+        assert_eq!(
+            urls(&search(&entries, "LAN", 10)),
+            ["https://grafana.lan/d/abc", "https://jellyfin.lan/web"]
+        );
+        assert_eq!(
+            urls(&search(&entries, "grafana node", 10)),
+            ["https://grafana.lan/d/abc"]
+        );
+        assert!(search(&entries, "jellyfin node", 10).is_empty());
+        assert_eq!(search(&entries, "", 1).len(), 1);
+    }
+
+    #[test]
+    fn clear_range_and_prune_drop_by_time() {
+        let mut entries = Vec::new();
+        for (i, t) in [100, 200, 300, 400].into_iter().enumerate() {
+            record_visit(&mut entries, &format!("https://{i}.test/"), t);
+        }
+        assert_eq!(clear_range(&mut entries, Some(200), Some(400)), 2);
+        assert_eq!(urls(&entries), ["https://3.test/", "https://0.test/"]);
+
+        let day = 86_400;
+        assert!(prune(&mut entries, 400 + day, 1));
+        assert_eq!(urls(&entries), ["https://3.test/"]);
+        assert!(!prune(&mut entries, 400 + day, 1));
+        assert_eq!(clear_range(&mut entries, None, None), 1);
+    }
+}
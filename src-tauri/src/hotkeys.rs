@@ -12,12 +12,15 @@ use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut,
 use tracing::{error, warn};
 
 use crate::actions::{
-    do_media_action, do_opacity_change, do_toggle_always_on_top, do_toggle_locked,
+    do_apply_window_preset, do_arm_kiosk_exit, do_cycle_corner, do_media_action,
+    do_move_to_next_monitor, do_nudge_window, do_opacity_change, do_scale_window,
+    do_toggle_always_on_top, do_toggle_locked,
 };
 use crate::injection::{
     MEDIA_MUTE_SCRIPT, MEDIA_NEXT_SCRIPT, MEDIA_PLAY_PAUSE_SCRIPT, MEDIA_PREVIOUS_SCRIPT,
     SHOW_STRIP_SCRIPT, ZOOM_VIDEO_SCRIPT,
 };
+use crate::layout::GROW_FACTOR;
 use crate::state::AppState;
 
 /// Lowercased keyname → `Code` lookup. Populated lazily on first parse.
@@ -136,11 +139,20 @@ pub fn parse_hotkey(s: &str) -> Option<(Modifiers, Code)> {
 /// Register all configured global hotkeys. Each binding is independent:
 /// a registration failure on one doesn't prevent the others from being
 /// set up, and a bad parse just skips that binding with a warning.
+///
+/// Window presets with a `hotkey` are registered after the built-in
+/// bindings, so a preset bound to the same keys as one of those loses.
+///
+/// In kiosk mode only `exit_kiosk` is registered, and only then.
 pub fn register_hotkeys(app: &AppHandle) {
-    let hotkeys = {
+    let (hotkeys, presets, kiosk) = {
         let state = app.state::<AppState>();
         let hotkeys = match state.config.lock() {
-            Ok(config) => config.hotkeys.clone(),
+            Ok(config) => (
+                config.hotkeys.clone(),
+                config.window_presets.clone(),
+                config.kiosk.enabled,
+            ),
             Err(e) => {
                 error!("Failed to lock config while registering hotkeys: {}", e);
                 return;
@@ -149,6 +161,14 @@ pub fn register_hotkeys(app: &AppHandle) {
         hotkeys
     };
 
+    if kiosk {
+        register_one(app, &hotkeys.exit_kiosk, "exit_kiosk", {
+            let app_h = app.clone();
+            move || do_arm_kiosk_exit(&app_h)
+        });
+        return;
+    }
+
     register_one(app, &hotkeys.toggle_on_top, "toggle_on_top", {
         let app_h = app.clone();
         move || do_toggle_always_on_top(&app_h)
@@ -212,6 +232,50 @@ pub fn register_hotkeys(app: &AppHandle) {
         let app_h = app.clone();
         move || do_media_action(&app_h, SHOW_STRIP_SCRIPT)
     });
+
+    let nudges = [
+        (&hotkeys.nudge_left, "nudge_left", (-1, 0)),
+        (&hotkeys.nudge_right, "nudge_right", (1, 0)),
+        (&hotkeys.nudge_up, "nudge_up", (0, -1)),
+        (&hotkeys.nudge_down, "nudge_down", (0, 1)),
+    ];
+    for (hotkey, name, direction) in nudges {
+        let app_h = app.clone();
+        register_one(app, hotkey, name, move || {
+            do_nudge_window(&app_h, direction)
+        });
+    }
+
+    register_one(app, &hotkeys.grow_window, "grow_window", {
+        let app_h = app.clone();
+        move || do_scale_window(&app_h, GROW_FACTOR)
+    });
+
+    register_one(app, &hotkeys.shrink_window, "shrink_window", {
+        let app_h = app.clone();
+        move || do_scale_window(&app_h, 1.0 / GROW_FACTOR)
+    });
+
+    register_one(app, &hotkeys.cycle_corner, "cycle_corner", {
+        let app_h = app.clone();
+        move || do_cycle_corner(&app_h)
+    });
+
+    register_one(app, &hotkeys.next_monitor, "next_monitor", {
+        let app_h = app.clone();
+        move || do_move_to_next_monitor(&app_h)
+    });
+
+    for preset in presets {
+        let Some(hotkey) = preset.hotkey else {
+            continue;
+        };
+        let app_h = app.clone();
+        let name = preset.name;
+        register_one(app, &hotkey, "window_preset", move || {
+            do_apply_window_preset(&app_h, &name)
+        });
+    }
 }
 
 /// Drop every currently registered global shortcut and re-register from
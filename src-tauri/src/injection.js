@@ -120,10 +120,25 @@
             .replace(/Shift\+/g, '⇧');
     }
 
+    // The URL of what the window shows. On the built-in media player
+    // (local_media.rs) that's the media it plays, so history, bookmarks
+    // and the URL bar see the video's URL rather than the player's.
+    function currentPageUrl() {
+        const loc = window.location;
+        const isApp = loc.protocol === 'tauri:' || loc.hostname === 'tauri.localhost';
+        if (isApp && loc.pathname === '/player.html') {
+            const src = new URLSearchParams(loc.search).get('src');
+            if (src) return src;
+        }
+        return loc.href;
+    }
+
     let stripVisible = false;
     let dwellTimer = null;
     let hideTimer = null;
     let config = null;
+    // From get_kiosk_status / the 'kiosk' update; see [15b].
+    let kioskStatus = { active: false, unlocked: false, exit_armed: false };
 
     // --------------------------------------------------------------------
     // [2] Media-element interaction tracking
@@ -414,6 +429,11 @@
                 0 0 18px -3px var(--fv-accent-glow);
         }
 
+        /* Settings can't be written to disk; stays until a save succeeds. */
+        .btn.save-error {
+            color: rgba(244, 67, 54, 0.9);
+        }
+
         /* Brief visual "nope" for feature buttons that couldn't act
            (e.g. zoom-to-video with no detectable video). */
         .btn.zoom-not-found {
@@ -629,6 +649,85 @@
             color: rgba(255,255,255,0.65);
         }
 
+        .recent-item.selected {
+            background: rgba(200, 140, 80, 0.18);
+        }
+
+        .suggest-title,
+        .suggest-url {
+            display: block;
+            overflow: hidden;
+            text-overflow: ellipsis;
+        }
+
+        .suggest-url {
+            font-size: 11px;
+            color: rgba(255,255,255,0.55);
+        }
+
+        .suggest-source {
+            float: right;
+            margin-left: 8px;
+            font-size: 10px;
+            text-transform: uppercase;
+            letter-spacing: 0.04em;
+            color: rgba(255,255,255,0.45);
+        }
+
+        .bookmark-folder {
+            padding: 8px 16px 4px;
+            font-size: 11px;
+            font-weight: 600;
+            letter-spacing: 0.04em;
+            text-transform: uppercase;
+            color: rgba(255,255,255,0.5);
+        }
+
+        .bookmark-rename {
+            flex: 1;
+            min-width: 0;
+            font: inherit;
+            color: #fff;
+            background: rgba(0,0,0,0.3);
+            border: 1px solid rgba(200, 140, 80, 0.6);
+            border-radius: 4px;
+            padding: 2px 6px;
+        }
+
+        .history-list {
+            max-height: 220px;
+            overflow-y: auto;
+            border-radius: var(--fv-radius-sm);
+            background: rgba(0,0,0,0.18);
+        }
+
+        .history-item {
+            display: flex;
+            align-items: center;
+            gap: 8px;
+            padding: 6px 8px 6px 12px;
+            font-size: 12px;
+            border-bottom: 1px solid rgba(255,255,255,0.05);
+        }
+
+        .history-item:last-child {
+            border-bottom: none;
+        }
+
+        .history-item-text {
+            flex: 1;
+            min-width: 0;
+            color: #fff;
+            cursor: pointer;
+            white-space: nowrap;
+            overflow: hidden;
+            text-overflow: ellipsis;
+        }
+
+        .history-item-text:hover {
+            color: var(--fv-accent);
+        }
+
         .recent-empty {
             padding: 16px;
             font-size: 13px;
@@ -1224,6 +1323,28 @@
             display: block;
         }
 
+        .kiosk-modal {
+            width: 360px;
+        }
+
+        .kiosk-modal h2 {
+            font-size: 20px;
+            font-weight: 600;
+            margin-bottom: 12px;
+        }
+
+        .kiosk-modal p {
+            font-size: 14px;
+            line-height: 1.6;
+            color: rgba(255,255,255,0.8);
+            margin-bottom: 12px;
+        }
+
+        .kiosk-modal .update-status {
+            min-height: 20px;
+            margin-top: 8px;
+        }
+
         .tutorial-step h2 {
             font-size: 20px;
             font-weight: 600;
@@ -1429,6 +1550,32 @@
             grid-auto-rows: 24px;
         }
 
+        /* Columns are set per layout: the grid's own for grid layouts. */
+        .snap-grid.layout-grid {
+            grid-auto-rows: 24px;
+        }
+
+        .snap-grid.preset-grid {
+            grid-template-columns: 1fr;
+            grid-auto-rows: 24px;
+        }
+
+        .snap-cell.preset-cell {
+            max-width: 220px;
+            padding: 0 8px;
+            text-align: left;
+            white-space: nowrap;
+            overflow: hidden;
+            text-overflow: ellipsis;
+        }
+
+        .snap-layout-name {
+            font-size: 10px;
+            color: rgba(255,255,255,0.6);
+            padding: 2px 4px 0;
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        }
+
         .snap-cell {
             background: rgba(255,255,255,0.06);
             border: none;
@@ -1488,6 +1635,8 @@
         .snap-popup.visible .snap-section:nth-child(2) { animation-delay: 0.04s; }
         .snap-popup.visible .snap-section:nth-child(3) { animation-delay: 0.08s; }
         .snap-popup.visible .snap-section:nth-child(4) { animation-delay: 0.12s; }
+        .snap-popup.visible .snap-section:nth-child(5) { animation-delay: 0.16s; }
+        .snap-popup.visible .snap-section:nth-child(6) { animation-delay: 0.20s; }
 
         .crop-overlay {
             position: fixed;
@@ -1549,6 +1698,12 @@
             color: #fff;
         }
 
+        .settings-inline {
+            display: flex;
+            align-items: center;
+            gap: 8px;
+        }
+
         .volume-popup {
             position: fixed;
             top: 0;
@@ -1779,6 +1934,12 @@
     bookmarksDropdown.id = 'bookmarks-dropdown';
     shadow.appendChild(bookmarksDropdown);
 
+    const suggestDropdown = document.createElement('div');
+    suggestDropdown.className = 'recent-dropdown';
+    suggestDropdown.id = 'suggest-dropdown';
+    suggestDropdown.setAttribute('role', 'listbox');
+    shadow.appendChild(suggestDropdown);
+
     // --------------------------------------------------------------------
     // [5] Popups (snap, recent, bookmarks, context menu)
     // --------------------------------------------------------------------
@@ -1823,8 +1984,18 @@
                 <button class="snap-cell aspect-cell" data-aspect="9:16" title="Resize to 9:16">9:16</button>
             </div>
         </div>
+        <div class="snap-section" id="snap-layouts" style="display:none;">
+            <div class="snap-section-label">Layouts</div>
+        </div>
+        <div class="snap-section" id="snap-presets" style="display:none;">
+            <div class="snap-section-label">Presets</div>
+            <div class="snap-grid preset-grid"></div>
+        </div>
     `);
     shadow.appendChild(snapPopup);
+    const snapLayouts = snapPopup.querySelector('#snap-layouts');
+    const snapPresets = snapPopup.querySelector('#snap-presets');
+    const snapPresetGrid = snapPresets.querySelector('.preset-grid');
 
     const volumePopup = document.createElement('div');
     volumePopup.className = 'volume-popup';
@@ -1854,6 +2025,49 @@
         <button class="settings-close-btn" id="btn-close-settings-x" title="Close (Esc)">${icons.close}</button>
         <div class="settings-scroll">
         <div class="settings-title">Settings</div>
+        <div class="settings-row" id="config-corrections-row" style="display:none;">
+            <span class="update-status error" id="config-corrections" style="white-space:pre-line;"></span>
+        </div>
+
+        <div class="settings-section">
+            <div class="settings-section-title">Profile</div>
+            <div class="settings-row">
+                <span class="settings-label">Profile</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-profile"></select>
+                    <button class="settings-btn" id="btn-profile-switch">Switch</button>
+                </div>
+            </div>
+            <div class="settings-row" style="flex-direction:column;align-items:stretch;gap:8px;">
+                <input type="text" class="url-display" id="setting-profile-name" placeholder="Profile name" maxlength="40" style="width:100%;height:36px;">
+                <div class="settings-inline">
+                    <button class="settings-btn" id="btn-profile-new">New</button>
+                    <button class="settings-btn" id="btn-profile-rename">Rename</button>
+                    <button class="settings-btn danger" id="btn-profile-delete">Delete</button>
+                    <span class="update-status error" id="profile-status"></span>
+                </div>
+            </div>
+        </div>
+
+        <div class="settings-section">
+            <div class="settings-section-title">Window Presets</div>
+            <div class="settings-row">
+                <span class="settings-label">Preset</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-preset"></select>
+                    <button class="settings-btn" id="btn-preset-apply">Apply</button>
+                </div>
+            </div>
+            <div class="settings-row" style="flex-direction:column;align-items:stretch;gap:8px;">
+                <input type="text" class="url-display" id="setting-preset-name" placeholder="Preset name" maxlength="40" style="width:100%;height:36px;">
+                <input type="text" class="url-display" id="setting-preset-hotkey" placeholder="Hotkey (optional), e.g. Ctrl+Alt+1" maxlength="64" style="width:100%;height:36px;">
+                <div class="settings-inline">
+                    <button class="settings-btn" id="btn-preset-save">Save Current Window</button>
+                    <button class="settings-btn danger" id="btn-preset-delete">Delete</button>
+                    <span class="update-status error" id="preset-status"></span>
+                </div>
+            </div>
+        </div>
 
         <div class="settings-section">
             <div class="settings-section-title">Window</div>
@@ -1884,12 +2098,54 @@
             </div>
         </div>
 
+        <div class="settings-section">
+            <div class="settings-section-title">This Site</div>
+            <div class="settings-row">
+                <span class="settings-label" id="site-status" style="white-space:normal;"></span>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Page Zoom</span>
+                <select class="settings-select" id="setting-page-zoom">
+                    <option value="0.5">50%</option>
+                    <option value="0.67">67%</option>
+                    <option value="0.75">75%</option>
+                    <option value="0.9">90%</option>
+                    <option value="1">100%</option>
+                    <option value="1.1">110%</option>
+                    <option value="1.25">125%</option>
+                    <option value="1.5">150%</option>
+                    <option value="1.75">175%</option>
+                    <option value="2">200%</option>
+                </select>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Current opacity, crop, size and refresh</span>
+                <div class="settings-inline">
+                    <button class="settings-btn" id="btn-site-save">Save for Site</button>
+                    <button class="settings-btn danger" id="btn-site-forget">Forget</button>
+                </div>
+            </div>
+            <div class="settings-row" id="site-error-row" style="display:none;">
+                <span class="update-status error" id="site-error"></span>
+            </div>
+        </div>
+
         <div class="settings-section">
             <div class="settings-section-title">Keyboard Shortcuts</div>
             <div id="hotkey-list"></div>
             <div class="settings-row">
                 <span class="settings-label" style="color:rgba(255,255,255,0.5);font-size:12px;">Click a binding to record a new combination. Modifier required (Ctrl/Alt/Shift).</span>
             </div>
+            <div class="settings-row">
+                <span class="settings-label" title="How far the nudge shortcuts move the window">Nudge Step</span>
+                <select class="settings-select" id="setting-nudge-step">
+                    <option value="5">5 px</option>
+                    <option value="10">10 px</option>
+                    <option value="20">20 px</option>
+                    <option value="50">50 px</option>
+                    <option value="100">100 px</option>
+                </select>
+            </div>
             <div class="settings-row">
                 <span class="settings-label">Reset all to defaults</span>
                 <button class="settings-btn" id="btn-reset-hotkeys">Reset</button>
@@ -1902,13 +2158,113 @@
                 <span class="settings-label">Home URL</span>
                 <input type="text" class="url-display" id="setting-home-url" placeholder="https://www.google.com" style="width:100%;height:36px;">
             </div>
+            <div class="settings-row">
+                <span class="settings-label">Search With</span>
+                <select class="settings-select" id="setting-search-engine"></select>
+            </div>
+            <div class="settings-row" style="flex-direction:column;align-items:stretch;gap:8px;">
+                <span class="settings-label" title="Type a keyword before your search, e.g. yt cats">Search Engines &amp; Keywords</span>
+                <div class="history-list" id="search-engine-list"></div>
+                <div class="settings-inline">
+                    <input type="text" class="url-display" id="setting-engine-name" placeholder="Name" maxlength="40" style="width:30%;height:36px;">
+                    <input type="text" class="url-display" id="setting-engine-keyword" placeholder="Keyword" maxlength="16" style="width:20%;height:36px;">
+                    <input type="text" class="url-display" id="setting-engine-url" placeholder="https://example.com/?q=%s" style="flex:1;height:36px;">
+                    <button class="settings-btn" id="btn-add-engine">Add</button>
+                </div>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label" title="Type a path or file:// URL into the URL bar. Pages you open can read other files in the same folder.">Open Local Files</span>
+                <div class="toggle-switch" id="setting-local-files"></div>
+            </div>
+        </div>
+
+        <div class="settings-section">
+            <div class="settings-section-title">History</div>
+            <div class="settings-row">
+                <span class="settings-label">Keep History For</span>
+                <select class="settings-select" id="setting-history-retention">
+                    <option value="7">1 week</option>
+                    <option value="30">30 days</option>
+                    <option value="90">90 days</option>
+                    <option value="365">1 year</option>
+                    <option value="3650">10 years</option>
+                </select>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label" title="utm_*, fbclid, YouTube's si and the like, from bookmarks and history">Strip Tracking Parameters</span>
+                <div class="toggle-switch" id="setting-strip-tracking"></div>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label" title="A bookmark matches the page even if one has tracking parameters">Ignore Them When Matching</span>
+                <div class="toggle-switch" id="setting-ignore-tracking"></div>
+            </div>
+            <div class="settings-row" style="flex-direction:column;align-items:stretch;gap:8px;">
+                <input type="text" class="url-display" id="setting-history-search" placeholder="Search history" aria-label="Search history" style="width:100%;height:36px;">
+                <div class="history-list" id="history-results"></div>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Clear History</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-history-range">
+                        <option value="3600">Last hour</option>
+                        <option value="86400">Last day</option>
+                        <option value="604800">Last week</option>
+                        <option value="all">All time</option>
+                    </select>
+                    <button class="settings-btn danger" id="btn-clear-history">Clear</button>
+                </div>
+            </div>
         </div>
 
         <div class="settings-section">
             <div class="settings-section-title">Data</div>
+            <div class="settings-row" id="config-policy-row" style="display:none;">
+                <span class="update-status" id="config-policy"></span>
+            </div>
+            <div class="settings-row" id="config-status-row" style="display:none;">
+                <span class="update-status error" id="config-status"></span>
+            </div>
+            <div class="settings-row" id="config-save-row" style="display:none;">
+                <span class="update-status error" id="config-save-status"></span>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Export Settings</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-export-geometry">
+                        <option value="no">Without window position</option>
+                        <option value="yes">With window position</option>
+                    </select>
+                    <button class="settings-btn" id="btn-export-settings">Export</button>
+                </div>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Import Settings</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-import-mode">
+                        <option value="merge">Merge bookmarks</option>
+                        <option value="replace">Replace bookmarks</option>
+                    </select>
+                    <button class="settings-btn" id="btn-import-settings">Import…</button>
+                    <input type="file" id="input-import-settings" accept=".json,application/json" style="display:none;">
+                </div>
+            </div>
             <div class="settings-row">
-                <span class="settings-label">Clear Recent URLs</span>
-                <button class="settings-btn danger" id="btn-clear-recent">Clear</button>
+                <span class="settings-label">Browser Bookmarks</span>
+                <div class="settings-inline">
+                    <button class="settings-btn" id="btn-import-bookmarks" title="Bookmark HTML from any browser, or a Chrome/Edge Bookmarks or Firefox backup JSON file">Import…</button>
+                    <input type="file" id="input-import-bookmarks" accept=".html,.htm,.json,text/html,application/json" style="display:none;">
+                    <button class="settings-btn" id="btn-export-bookmarks">Export HTML</button>
+                </div>
+            </div>
+            <div class="settings-row" id="transfer-status-row" style="display:none;">
+                <span class="update-status" id="transfer-status"></span>
+            </div>
+            <div class="settings-row">
+                <span class="settings-label">Restore Backup</span>
+                <div class="settings-inline">
+                    <select class="settings-select" id="setting-backup"></select>
+                    <button class="settings-btn" id="btn-restore-backup">Restore</button>
+                </div>
             </div>
             <div class="settings-row">
                 <span class="settings-label">Clear Bookmarks</span>
@@ -2032,6 +2388,25 @@
     `);
     shadow.appendChild(tutorialModal);
 
+    // Kiosk PIN prompt, shown in place of Settings and the URL bar while
+    // kiosk mode is locked, and by the exit_kiosk hotkey.
+    const kioskModal = document.createElement('div');
+    kioskModal.className = 'tutorial-modal kiosk-modal hidden';
+    setInner(kioskModal, `
+        <h2>Kiosk Mode</h2>
+        <p id="kiosk-message">Enter the PIN to open Settings.</p>
+        <input type="password" class="url-display" id="kiosk-pin" placeholder="PIN" aria-label="Kiosk PIN" autocomplete="off" maxlength="32" style="width:100%;height:36px;">
+        <div class="update-status error" id="kiosk-error"></div>
+        <div class="tutorial-nav">
+            <button class="tutorial-btn" id="kiosk-cancel">Cancel</button>
+            <div class="tutorial-nav-buttons">
+                <button class="tutorial-btn" id="kiosk-exit">Exit Kiosk</button>
+                <button class="tutorial-btn primary" id="kiosk-unlock">Unlock</button>
+            </div>
+        </div>
+    `);
+    shadow.appendChild(kioskModal);
+
     const contextMenu = document.createElement('div');
     contextMenu.className = 'context-menu';
     setInner(contextMenu, `
@@ -2222,6 +2597,30 @@
         }
     }
 
+    // Re-apply the config values that live in the page rather than the
+    // window: the auto-refresh timer and the crop. Used when the config
+    // changed under a loaded page (an external edit, site settings).
+    function applyPageConfig(cfg) {
+        startAutoRefresh(cfg.auto_refresh_minutes || 0);
+        if (zoomVideoActive) return;
+        if (cfg.crop) {
+            const c = cfg.crop;
+            applyCrop(c.x, c.y, c.width, c.height, true, false);
+        } else if (cropActive) {
+            removeCrop(false);
+        }
+    }
+
+    // The site settings in effect changed (set_url / save / forget
+    // returned true): Rust has already moved the window, so only the
+    // in-page parts need refreshing.
+    async function refreshSiteConfig() {
+        const freshConfig = await invoke('get_config');
+        if (!freshConfig) return;
+        config = freshConfig;
+        applyPageConfig(freshConfig);
+    }
+
     window.addEventListener('resize', () => {
         if (cropActive && !zoomVideoActive && config && config.crop) {
             applyCrop(config.crop.x, config.crop.y, config.crop.width, config.crop.height, false, false);
@@ -2739,10 +3138,13 @@
     // [13] URL tracking + recent dropdown
     // --------------------------------------------------------------------
 
-    function updateRecentDropdown() {
+    // The list is the top of the history store, so it is fetched each time
+    // the dropdown opens rather than kept in config.
+    async function updateRecentDropdown() {
+        const urls = (await invoke('get_recent_urls')) || [];
         recentDropdown.replaceChildren();
 
-        if (!config || !config.recent_urls || config.recent_urls.length === 0) {
+        if (urls.length === 0) {
             const empty = document.createElement('div');
             empty.className = 'recent-empty';
             empty.textContent = 'No recent URLs';
@@ -2750,8 +3152,8 @@
             return;
         }
 
-        const currentUrl = window.location.href;
-        config.recent_urls.forEach((url) => {
+        const currentUrl = currentPageUrl();
+        urls.forEach((url) => {
             const item = document.createElement('div');
             item.className = 'recent-item';
             if (url === currentUrl) {
@@ -2783,6 +3185,7 @@
     // --------------------------------------------------------------------
 
     function showStrip() {
+        if (kioskLocked()) return;
         cancelHide();
         if (stripVisible) return;
         stripVisible = true;
@@ -2795,6 +3198,7 @@
         strip.style.transform = '';
         strip.style.transition = '';
         strip.classList.remove('visible');
+        relockKiosk();
     }
 
     function scheduleHide() {
@@ -2959,11 +3363,11 @@
         anchorBelow(recentDropdown, btnRecent);
     }
 
-    btnRecent.addEventListener('click', (e) => {
+    btnRecent.addEventListener('click', async (e) => {
         e.stopPropagation();
         const isVisible = recentDropdown.classList.contains('visible');
         if (!isVisible) {
-            updateRecentDropdown();
+            await updateRecentDropdown();
             positionDropdown();
         }
         recentDropdown.classList.toggle('visible', !isVisible);
@@ -3053,9 +3457,88 @@
     // If the URL input loses focus while the mouse is outside the strip,
     // schedule a hide so the strip doesn't stay open indefinitely.
     urlInput.addEventListener('blur', () => {
+        hideSuggestions();
         if (!_stripHovered) scheduleHide();
     });
 
+    // Autocomplete. Rust ranks bookmarks, recent URLs and history by
+    // frecency; the list follows the typed text, arrow keys pick an entry
+    // and Enter opens it. Responses that arrive after newer typing are
+    // dropped.
+    const SUGGEST_DELAY = 80;
+    const SOURCE_LABELS = { bookmark: 'Bookmark', recent: 'Recent', history: 'History' };
+    let _suggestions = [];
+    let _suggestIndex = -1;
+    let _suggestTimer = null;
+    let _suggestSeq = 0;
+
+    function hideSuggestions() {
+        clearTimeout(_suggestTimer);
+        _suggestSeq++;
+        _suggestions = [];
+        _suggestIndex = -1;
+        suggestDropdown.classList.remove('visible');
+    }
+
+    function renderSuggestions() {
+        suggestDropdown.replaceChildren();
+        _suggestions.forEach((s, i) => {
+            const item = document.createElement('div');
+            item.className = 'recent-item';
+            item.setAttribute('role', 'option');
+            item.classList.toggle('selected', i === _suggestIndex);
+            const source = document.createElement('span');
+            source.className = 'suggest-source';
+            source.textContent = SOURCE_LABELS[s.source] || '';
+            const title = document.createElement('span');
+            title.className = 'suggest-title';
+            title.textContent = s.title || s.url;
+            item.append(source, title);
+            if (s.title) {
+                const url = document.createElement('span');
+                url.className = 'suggest-url';
+                url.textContent = s.url;
+                item.appendChild(url);
+            }
+            // mousedown, not click: keep focus in the URL bar so its blur
+            // handler doesn't tear the list down first.
+            item.addEventListener('mousedown', async (e) => {
+                e.preventDefault();
+                hideSuggestions();
+                urlInput.value = s.url;
+                await navigateToUrl(s.url);
+            });
+            suggestDropdown.appendChild(item);
+            if (i === _suggestIndex) item.scrollIntoView({ block: 'nearest' });
+        });
+        if (_suggestions.length === 0) {
+            suggestDropdown.classList.remove('visible');
+            return;
+        }
+        anchorBelow(suggestDropdown, urlInput);
+        suggestDropdown.style.minWidth = urlInput.getBoundingClientRect().width + 'px';
+        suggestDropdown.classList.add('visible');
+        recentDropdown.classList.remove('visible');
+        bookmarksDropdown.classList.remove('visible');
+    }
+
+    urlInput.addEventListener('input', () => {
+        clearTimeout(_suggestTimer);
+        const query = urlInput.value.trim();
+        if (!query) {
+            hideSuggestions();
+            return;
+        }
+        _suggestTimer = setTimeout(async () => {
+            const seq = ++_suggestSeq;
+            const results = await invoke('suggest_urls', { query });
+            if (seq !== _suggestSeq || shadow.activeElement !== urlInput) return;
+            _suggestions = results || [];
+            _suggestIndex = -1;
+            renderSuggestions();
+        }, SUGGEST_DELAY);
+    });
+
     // Browser-URL-bar selection behavior: the first click into the field
     // selects the whole URL (the common intent is replace-or-copy), and a
     // second click places the caret normally. The mouseup guard is needed
@@ -3081,19 +3564,44 @@
     });
 
     urlInput.addEventListener('keydown', async (e) => {
+        if (_suggestions.length && (e.key === 'ArrowDown' || e.key === 'ArrowUp')) {
+            e.preventDefault();
+            const step = e.key === 'ArrowDown' ? 1 : -1;
+            const n = _suggestions.length;
+            // -1 is the typed text; cycle through it.
+            _suggestIndex = ((_suggestIndex + 1 + step + n + 1) % (n + 1)) - 1;
+            renderSuggestions();
+            return;
+        }
+        if (e.key === 'Escape' && suggestDropdown.classList.contains('visible')) {
+            e.stopPropagation();
+            hideSuggestions();
+            return;
+        }
         if (e.key === 'Enter') {
-            let url = urlInput.value.trim();
-            if (!url) return;
-            if (!url.match(/^https?:\/\//)) {
-                if (url.includes(' ') || (!url.includes('.') && !url.includes(':'))) {
-                    url = 'https://duckduckgo.com/?q=' + encodeURIComponent(url);
-                } else {
-                    url = 'https://' + url;
-                }
+            const picked = _suggestions[_suggestIndex];
+            hideSuggestions();
+            if (picked) {
+                urlInput.value = picked.url;
+                await navigateToUrl(picked.url);
+                return;
             }
-            if (url) {
-                await navigateToUrl(url);
+            const raw = urlInput.value.trim();
+            if (!raw) return;
+            // Rust decides URL vs. search (engines, keywords). The local
+            // heuristic only runs when the command channel is unavailable.
+            let url = await invoke('resolve_omnibox_input', { input: raw });
+            if (url === null) {
+                url = raw;
+                if (!url.match(/^https?:\/\//)) {
+                    if (url.includes(' ') || (!url.includes('.') && !url.includes(':'))) {
+                        url = 'https://duckduckgo.com/?q=' + encodeURIComponent(url);
+                    } else {
+                        url = 'https://' + url;
+                    }
+                }
             }
+            await navigateToUrl(url);
         }
     });
 
@@ -3178,10 +3686,72 @@
         snapPopup.style.left = Math.min(rect.left, maxLeft) + 'px';
     }
 
+    // The user's `layouts` from config.json: a button per grid cell (laid
+    // out like the grid) or per named zone. Positions are `<layout>:<zone>`.
+    function renderSnapLayouts() {
+        const layouts = (config && config.layouts) || [];
+        snapLayouts.style.display = layouts.length ? '' : 'none';
+        snapLayouts.querySelectorAll('.snap-layout-name, .layout-grid').forEach(el => el.remove());
+        layouts.forEach(layout => {
+            const name = document.createElement('div');
+            name.className = 'snap-layout-name';
+            name.textContent = layout.name;
+            const grid = document.createElement('div');
+            grid.className = 'snap-grid layout-grid';
+            const cells = [];
+            if (layout.grid) {
+                grid.style.gridTemplateColumns = `repeat(${layout.grid.columns}, 24px)`;
+                for (let n = 1; n <= layout.grid.columns * layout.grid.rows; n++) cells.push(String(n));
+            } else {
+                grid.style.gridTemplateColumns = 'repeat(3, 1fr)';
+                (layout.zones || []).forEach(zone => cells.push(zone.name));
+            }
+            cells.forEach(zone => {
+                const cell = document.createElement('button');
+                cell.className = 'snap-cell aspect-cell';
+                cell.dataset.pos = `${layout.name}:${zone}`;
+                cell.title = `${layout.name}: ${zone}`;
+                cell.textContent = zone;
+                grid.appendChild(cell);
+            });
+            snapLayouts.append(name, grid);
+        });
+    }
+
+    // Saved window presets, one button each; saved and deleted in Settings.
+    function renderSnapPresets() {
+        const presets = (config && config.window_presets) || [];
+        snapPresets.style.display = presets.length ? '' : 'none';
+        snapPresetGrid.textContent = '';
+        presets.forEach(preset => {
+            const cell = document.createElement('button');
+            cell.className = 'snap-cell aspect-cell preset-cell';
+            cell.dataset.preset = preset.name;
+            cell.title = preset.hotkey ? `${preset.name} (${preset.hotkey})` : preset.name;
+            cell.textContent = preset.name;
+            snapPresetGrid.appendChild(cell);
+        });
+    }
+
+    // Mirror an applied preset into the strip, as the tray and hotkey
+    // paths do through `__floatViewUpdate`.
+    async function applyWindowPreset(name) {
+        const preset = await invoke('apply_window_preset', { name });
+        if (!preset) return false;
+        ['always_on_top', 'locked', 'opacity', 'crop'].forEach(key => {
+            window.__floatViewUpdate(key, preset[key]);
+        });
+        return true;
+    }
+
     btnSnap.addEventListener('click', (e) => {
         e.stopPropagation();
         const isVisible = snapPopup.classList.contains('visible');
-        if (!isVisible) positionSnapPopup();
+        if (!isVisible) {
+            renderSnapLayouts();
+            renderSnapPresets();
+            positionSnapPopup();
+        }
         snapPopup.classList.toggle('visible', !isVisible);
         recentDropdown.classList.remove('visible');
         bookmarksDropdown.classList.remove('visible');
@@ -3190,10 +3760,12 @@
 
     snapPopup.addEventListener('click', async (e) => {
         e.stopPropagation();
-        const target = e.target.closest('[data-pos], [data-aspect]');
+        const target = e.target.closest('[data-pos], [data-aspect], [data-preset]');
         if (!target) return;
         snapPopup.classList.remove('visible');
-        if (target.dataset.pos) {
+        if (target.dataset.preset) {
+            await applyWindowPreset(target.dataset.preset);
+        } else if (target.dataset.pos) {
             await invoke('snap_window', { position: target.dataset.pos });
         } else if (target.dataset.aspect) {
             await invoke('set_aspect_ratio', { ratio: target.dataset.aspect });
@@ -3251,11 +3823,11 @@
 
     // Bookmark functions
     function isBookmarked(url) {
-        return !!(config && config.bookmarks && config.bookmarks.some(b => urlsMatch(b, url)));
+        return !!(config && config.bookmarks && config.bookmarks.some(b => bookmarkMatches(b.url, url)));
     }
 
     function updateBookmarkIcon() {
-        const currentUrl = window.location.href;
+        const currentUrl = currentPageUrl();
         const active = isBookmarked(currentUrl);
         setInner(btnBookmark, active ? icons.bookmarkActive : icons.bookmark);
         btnBookmark.classList.toggle('active', active);
@@ -3265,6 +3837,9 @@
     // [14] Bookmark dropdown
     // --------------------------------------------------------------------
 
+    // Top-level bookmarks first, then one headed group per folder, each
+    // in stored order. Folders with nothing in them are skipped here;
+    // they still exist in config.bookmark_folders.
     function updateBookmarksDropdown() {
         bookmarksDropdown.replaceChildren();
         if (!config || !config.bookmarks || config.bookmarks.length === 0) {
@@ -3274,61 +3849,101 @@
             bookmarksDropdown.appendChild(empty);
             return;
         }
-        config.bookmarks.forEach((url) => {
-            const item = document.createElement('div');
-            item.className = 'recent-item';
-            item.style.display = 'flex';
-            item.style.justifyContent = 'space-between';
-            item.style.alignItems = 'center';
-            item.style.gap = '8px';
-            const label = document.createElement('span');
-            label.style.overflow = 'hidden';
-            label.style.textOverflow = 'ellipsis';
-            label.style.whiteSpace = 'nowrap';
-            label.style.flex = '1';
-            label.textContent = url;
-            const removeBtn = document.createElement('span');
-            removeBtn.textContent = '×';
-            removeBtn.style.cursor = 'pointer';
-            removeBtn.style.opacity = '0.4';
-            removeBtn.style.fontSize = '16px';
-            removeBtn.style.flexShrink = '0';
-            removeBtn.style.padding = '2px 6px';
-            removeBtn.style.borderRadius = '4px';
-            removeBtn.style.lineHeight = '1';
-            removeBtn.style.marginRight = '-4px';
-            removeBtn.addEventListener('mouseenter', () => { removeBtn.style.opacity = '1'; });
-            removeBtn.addEventListener('mouseleave', () => { removeBtn.style.opacity = '0.4'; });
-            removeBtn.addEventListener('click', async (e) => {
-                e.stopPropagation();
-                // Only drop the local copy if Rust confirmed the removal
-                // (invoke maps IPC failure to null); otherwise the dropdown
-                // would diverge from the persisted bookmark list.
-                const ok = await invoke('remove_bookmark', { url });
-                if (ok === null) return;
-                if (config) {
-                    config.bookmarks = config.bookmarks.filter(u => u !== url && !urlsMatch(u, url));
-                }
-                updateBookmarksDropdown();
-                updateBookmarkIcon();
-            });
-            item.appendChild(label);
-            item.appendChild(removeBtn);
-            // Keyboard access: tabbable, Enter/Space activates.
-            item.tabIndex = 0;
-            const activate = async () => {
-                bookmarksDropdown.classList.remove('visible');
-                await navigateToUrl(url);
-            };
-            item.addEventListener('click', activate);
-            item.addEventListener('keydown', (e) => {
-                if (e.key === 'Enter' || e.key === ' ') {
-                    e.preventDefault();
-                    activate();
+        const groups = [null, ...(config.bookmark_folders || [])];
+        groups.forEach((folder) => {
+            const entries = config.bookmarks.filter(b => (b.folder || null) === folder);
+            if (entries.length === 0) return;
+            if (folder !== null) {
+                const heading = document.createElement('div');
+                heading.className = 'bookmark-folder';
+                heading.textContent = folder;
+                bookmarksDropdown.appendChild(heading);
+            }
+            entries.forEach(b => bookmarksDropdown.appendChild(bookmarkItem(b)));
+        });
+    }
+
+    function bookmarkItem(bookmark) {
+        const url = bookmark.url;
+        const item = document.createElement('div');
+        item.className = 'recent-item';
+        item.style.display = 'flex';
+        item.style.justifyContent = 'space-between';
+        item.style.alignItems = 'center';
+        item.style.gap = '8px';
+        item.title = url;
+        const label = document.createElement('span');
+        label.style.overflow = 'hidden';
+        label.style.textOverflow = 'ellipsis';
+        label.style.whiteSpace = 'nowrap';
+        label.style.flex = '1';
+        label.textContent = bookmark.title || url;
+        // Double-click renames in place. Enter saves, Escape cancels; an
+        // empty title falls back to the URL until the next visit.
+        label.addEventListener('dblclick', (e) => {
+            e.stopPropagation();
+            const input = document.createElement('input');
+            input.className = 'bookmark-rename';
+            input.value = bookmark.title;
+            input.placeholder = url;
+            input.addEventListener('click', ev => ev.stopPropagation());
+            input.addEventListener('keydown', async (ev) => {
+                ev.stopPropagation();
+                if (ev.key === 'Escape') {
+                    updateBookmarksDropdown();
+                } else if (ev.key === 'Enter') {
+                    ev.preventDefault();
+                    const ok = await invoke('rename_bookmark', { id: bookmark.id, title: input.value });
+                    if (ok !== null) await refreshBookmarksFromRust();
+                    updateBookmarksDropdown();
                 }
             });
-            bookmarksDropdown.appendChild(item);
+            item.replaceChild(input, label);
+            input.focus();
+            input.select();
         });
+        const removeBtn = document.createElement('span');
+        removeBtn.textContent = '×';
+        removeBtn.style.cursor = 'pointer';
+        removeBtn.style.opacity = '0.4';
+        removeBtn.style.fontSize = '16px';
+        removeBtn.style.flexShrink = '0';
+        removeBtn.style.padding = '2px 6px';
+        removeBtn.style.borderRadius = '4px';
+        removeBtn.style.lineHeight = '1';
+        removeBtn.style.marginRight = '-4px';
+        removeBtn.addEventListener('mouseenter', () => { removeBtn.style.opacity = '1'; });
+        removeBtn.addEventListener('mouseleave', () => { removeBtn.style.opacity = '0.4'; });
+        removeBtn.addEventListener('click', async (e) => {
+            e.stopPropagation();
+            // Only drop the local copy if Rust confirmed the removal
+            // (invoke maps IPC failure to null); otherwise the dropdown
+            // would diverge from the persisted bookmark list.
+            const ok = await invoke('remove_bookmark', { url });
+            if (ok === null) return;
+            if (config) {
+                config.bookmarks = config.bookmarks.filter(b => !bookmarkMatches(b.url, url));
+            }
+            updateBookmarksDropdown();
+            updateBookmarkIcon();
+        });
+        item.appendChild(label);
+        item.appendChild(removeBtn);
+        // Keyboard access: tabbable, Enter/Space activates.
+        item.tabIndex = 0;
+        const activate = async () => {
+            bookmarksDropdown.classList.remove('visible');
+            await navigateToUrl(url);
+        };
+        item.addEventListener('click', activate);
+        item.addEventListener('keydown', (e) => {
+            if (e.target !== item) return;
+            if (e.key === 'Enter' || e.key === ' ') {
+                e.preventDefault();
+                activate();
+            }
+        });
+        return item;
     }
 
     function positionBookmarksDropdown() {
@@ -3348,8 +3963,17 @@
     // bookmark would read as "already bookmarked" against a plain `host`
     // URL and the star icon would lie. This previously diverged from the
     // Rust side, which always compared userinfo.
-    function urlsMatch(a, b) {
+    //
+    // With `rules` (config.tracking_params) and `ignore_when_matching`
+    // on, tracking parameters are stripped from both sides first, like
+    // Rust `urls_match_with`.
+    function urlsMatch(a, b, rules) {
         if (a === b) return true;
+        if (rules && rules.ignore_when_matching) {
+            a = stripTrackingParams(a, rules);
+            b = stripTrackingParams(b, rules);
+            if (a === b) return true;
+        }
         try {
             const ua = new URL(a);
             const ub = new URL(b);
@@ -3361,9 +3985,59 @@
         } catch { return false; }
     }
 
-    async function getBookmarksFromRust() {
+    // Mirrors Rust `strip_tracking_params` (truth table: STRIP_CASES in
+    // url_fixtures.rs): drop query parameters named in `rules.params` or
+    // in a `rules.hosts` entry for this host. Names compare lowercased
+    // after decoding; a trailing `*` matches a prefix.
+    function stripTrackingParams(url, rules) {
+        let parsed;
+        try { parsed = new URL(url); } catch { return url; }
+        if (!parsed.search || !rules) return url;
+        const host = parsed.hostname;
+        const patterns = (rules.params || []).slice();
+        for (const [pattern, params] of Object.entries(rules.hosts || {})) {
+            const matches = pattern.startsWith('*.')
+                ? host === pattern.slice(2) || host.endsWith(pattern.slice(1))
+                : host === pattern;
+            if (matches) patterns.push(...params);
+        }
+        const isTracking = (segment) => {
+            let name = segment.split('=')[0];
+            try { name = decodeURIComponent(name.replace(/\+/g, ' ')); } catch { /* keep raw */ }
+            name = name.toLowerCase();
+            return name !== '' && patterns.some(p => {
+                p = String(p).toLowerCase();
+                return p.endsWith('*') ? name.startsWith(p.slice(0, -1)) : name === p;
+            });
+        };
+        const segments = parsed.search.slice(1).split('&');
+        const kept = segments.filter(segment => !isTracking(segment));
+        if (kept.length === segments.length) return url;
+        parsed.search = kept.length ? '?' + kept.join('&') : '';
+        return parsed.href;
+    }
+
+    // The form Rust stores a bookmark or history URL in (`url_to_store`).
+    function urlToStore(url, rules) {
+        return rules && rules.strip ? stripTrackingParams(url, rules) : url;
+    }
+
+    // Whether stored bookmark URL `stored` is the page at `url`, the way
+    // Rust's add/remove_bookmark decide it.
+    function bookmarkMatches(stored, url) {
+        const rules = config && config.tracking_params;
+        return urlsMatch(stored, urlToStore(url, rules), rules);
+    }
+
+    // Bookmarks are edited only through their own commands (titles are
+    // even filled in by Rust as pages load), so re-read them after each
+    // change rather than patching the local copy.
+    async function refreshBookmarksFromRust() {
         const freshConfig = await invoke('get_config');
-        return freshConfig ? freshConfig.bookmarks || [] : config ? config.bookmarks || [] : [];
+        if (freshConfig && config) {
+            config.bookmarks = freshConfig.bookmarks || [];
+            config.bookmark_folders = freshConfig.bookmark_folders || [];
+        }
     }
 
     function bookmarkPop() {
@@ -3374,7 +4048,7 @@
 
     btnBookmark.addEventListener('click', async (e) => {
         e.stopPropagation();
-        const currentUrl = window.location.href;
+        const currentUrl = currentPageUrl();
         bookmarkPop();
         if (isBookmarked(currentUrl)) {
             // Gate the local update on Rust confirming (null = IPC failure)
@@ -3382,16 +4056,14 @@
             const ok = await invoke('remove_bookmark', { url: currentUrl });
             if (ok === null) return;
             if (config) {
-                config.bookmarks = config.bookmarks.filter(u => u !== currentUrl && !urlsMatch(u, currentUrl));
+                config.bookmarks = config.bookmarks.filter(b => !bookmarkMatches(b.url, currentUrl));
             }
             updateBookmarkIcon();
             updateBookmarksDropdown();
         } else {
             const ok = await invoke('add_bookmark', { url: currentUrl });
             if (ok === null) return;
-            if (config) {
-                config.bookmarks = await getBookmarksFromRust();
-            }
+            await refreshBookmarksFromRust();
             updateBookmarkIcon();
             updateBookmarksDropdown();
         }
@@ -3402,6 +4074,11 @@
         e.stopPropagation();
         const isVisible = bookmarksDropdown.classList.contains('visible');
         if (!isVisible) {
+            // Pick up titles captured since the list was last fetched.
+            refreshBookmarksFromRust().then(() => {
+                updateBookmarksDropdown();
+                positionBookmarksDropdown();
+            });
             updateBookmarksDropdown();
             positionBookmarksDropdown();
         }
@@ -3415,9 +4092,11 @@
     // [15] Window title observer
     // --------------------------------------------------------------------
 
-    let _lastTitle = '';
+    let _lastTitle = null;
     function updateWindowTitle() {
-        const title = document.title || 'FloatView';
+        // Sent as is: Rust shows "FloatView" for an untitled page but
+        // must not capture that as a bookmark title.
+        const title = document.title;
         if (title !== _lastTitle) {
             _lastTitle = title;
             invoke('set_window_title', { title });
@@ -3441,14 +4120,16 @@
     setInterval(updateWindowTitle, 10000);
 
     // Track URL changes on navigation (back/forward/spa navigation)
-    let _lastTrackedUrl = window.location.href;
+    let _lastTrackedUrl = currentPageUrl();
     function trackUrlChange() {
-        const currentUrl = window.location.href;
+        const currentUrl = currentPageUrl();
         if (currentUrl !== _lastTrackedUrl && /^https?:\/\//i.test(currentUrl)) {
             _lastTrackedUrl = currentUrl;
             urlInput.value = currentUrl;
             updateBookmarkIcon();
-            invoke('set_url', { url: currentUrl });
+            invoke('set_url', { url: currentUrl }).then((siteChanged) => {
+                if (siteChanged) refreshSiteConfig();
+            });
         }
     }
     window.addEventListener('popstate', trackUrlChange);
@@ -3484,15 +4165,73 @@
     const settingOpacity = settingsModal.querySelector('#setting-opacity');
     const settingOpacityValue = settingsModal.querySelector('#setting-opacity-value');
     const settingHomeUrl = settingsModal.querySelector('#setting-home-url');
-    const btnClearRecent = settingsModal.querySelector('#btn-clear-recent');
+    const settingSearchEngine = settingsModal.querySelector('#setting-search-engine');
+    const searchEngineList = settingsModal.querySelector('#search-engine-list');
+    const settingEngineName = settingsModal.querySelector('#setting-engine-name');
+    const settingEngineKeyword = settingsModal.querySelector('#setting-engine-keyword');
+    const settingEngineUrl = settingsModal.querySelector('#setting-engine-url');
+    const btnAddEngine = settingsModal.querySelector('#btn-add-engine');
+    // Keys pinned by the machine policy file or a FLOATVIEW_* override.
+    // Their controls are disabled; Rust re-imposes them on every update
+    // regardless, so this is only about not offering a dead control.
+    let pinnedKeys = [];
+    const settingProfile = settingsModal.querySelector('#setting-profile');
+    const settingProfileName = settingsModal.querySelector('#setting-profile-name');
+    const btnProfileSwitch = settingsModal.querySelector('#btn-profile-switch');
+    const btnProfileNew = settingsModal.querySelector('#btn-profile-new');
+    const btnProfileRename = settingsModal.querySelector('#btn-profile-rename');
+    const btnProfileDelete = settingsModal.querySelector('#btn-profile-delete');
+    const profileStatus = settingsModal.querySelector('#profile-status');
+    const settingPreset = settingsModal.querySelector('#setting-preset');
+    const settingPresetName = settingsModal.querySelector('#setting-preset-name');
+    const settingPresetHotkey = settingsModal.querySelector('#setting-preset-hotkey');
+    const btnPresetApply = settingsModal.querySelector('#btn-preset-apply');
+    const btnPresetSave = settingsModal.querySelector('#btn-preset-save');
+    const btnPresetDelete = settingsModal.querySelector('#btn-preset-delete');
+    const presetStatus = settingsModal.querySelector('#preset-status');
+    const settingHistoryRetention = settingsModal.querySelector('#setting-history-retention');
+    const settingLocalFiles = settingsModal.querySelector('#setting-local-files');
+    const settingStripTracking = settingsModal.querySelector('#setting-strip-tracking');
+    const settingIgnoreTracking = settingsModal.querySelector('#setting-ignore-tracking');
+    const settingHistorySearch = settingsModal.querySelector('#setting-history-search');
+    const historyResults = settingsModal.querySelector('#history-results');
+    const settingHistoryRange = settingsModal.querySelector('#setting-history-range');
+    const btnClearHistory = settingsModal.querySelector('#btn-clear-history');
     const btnClearBookmarks = settingsModal.querySelector('#btn-clear-bookmarks');
     const btnClearSiteData = settingsModal.querySelector('#btn-clear-site-data');
     const btnCloseSettings = settingsModal.querySelector('#btn-close-settings');
     const hotkeyList = settingsModal.querySelector('#hotkey-list');
     const btnResetHotkeys = settingsModal.querySelector('#btn-reset-hotkeys');
+    const settingNudgeStep = settingsModal.querySelector('#setting-nudge-step');
     const btnCheckUpdates = settingsModal.querySelector('#btn-check-updates');
     const updateStatus = settingsModal.querySelector('#update-status');
     const settingsVersion = settingsModal.querySelector('#settings-version');
+    const configCorrectionsRow = settingsModal.querySelector('#config-corrections-row');
+    const configCorrections = settingsModal.querySelector('#config-corrections');
+    const configPolicyRow = settingsModal.querySelector('#config-policy-row');
+    const configPolicy = settingsModal.querySelector('#config-policy');
+    const configStatusRow = settingsModal.querySelector('#config-status-row');
+    const configStatus = settingsModal.querySelector('#config-status');
+    const configSaveRow = settingsModal.querySelector('#config-save-row');
+    const configSaveStatus = settingsModal.querySelector('#config-save-status');
+    const settingBackup = settingsModal.querySelector('#setting-backup');
+    const settingExportGeometry = settingsModal.querySelector('#setting-export-geometry');
+    const btnExportSettings = settingsModal.querySelector('#btn-export-settings');
+    const settingImportMode = settingsModal.querySelector('#setting-import-mode');
+    const btnImportSettings = settingsModal.querySelector('#btn-import-settings');
+    const inputImportSettings = settingsModal.querySelector('#input-import-settings');
+    const transferStatusRow = settingsModal.querySelector('#transfer-status-row');
+    const transferStatus = settingsModal.querySelector('#transfer-status');
+    const btnRestoreBackup = settingsModal.querySelector('#btn-restore-backup');
+    const btnImportBookmarks = settingsModal.querySelector('#btn-import-bookmarks');
+    const inputImportBookmarks = settingsModal.querySelector('#input-import-bookmarks');
+    const btnExportBookmarks = settingsModal.querySelector('#btn-export-bookmarks');
+    const siteStatus = settingsModal.querySelector('#site-status');
+    const settingPageZoom = settingsModal.querySelector('#setting-page-zoom');
+    const btnSiteSave = settingsModal.querySelector('#btn-site-save');
+    const btnSiteForget = settingsModal.querySelector('#btn-site-forget');
+    const siteErrorRow = settingsModal.querySelector('#site-error-row');
+    const siteError = settingsModal.querySelector('#site-error');
 
     // Load version into settings footer
     (async () => {
@@ -3595,6 +4334,15 @@
         { field: 'media_mute',        label: 'Mute',                    default: 'Alt+Shift+M' },
         { field: 'zoom_video',        label: 'Zoom to Video',           default: 'Alt+Shift+V' },
         { field: 'show_strip',        label: 'Force-show Control Strip', default: 'Alt+Shift+S' },
+        { field: 'exit_kiosk',        label: 'Exit Kiosk Mode',         default: 'Ctrl+Alt+Shift+K' },
+        { field: 'nudge_left',        label: 'Nudge Window Left',       default: 'Ctrl+Alt+Shift+Left' },
+        { field: 'nudge_right',       label: 'Nudge Window Right',      default: 'Ctrl+Alt+Shift+Right' },
+        { field: 'nudge_up',          label: 'Nudge Window Up',         default: 'Ctrl+Alt+Shift+Up' },
+        { field: 'nudge_down',        label: 'Nudge Window Down',       default: 'Ctrl+Alt+Shift+Down' },
+        { field: 'grow_window',       label: 'Grow Window',             default: 'Ctrl+Alt+Shift+=' },
+        { field: 'shrink_window',     label: 'Shrink Window',           default: 'Ctrl+Alt+Shift+-' },
+        { field: 'cycle_corner',      label: 'Next Corner',             default: 'Ctrl+Alt+Shift+C' },
+        { field: 'next_monitor',      label: 'Next Monitor',            default: 'Ctrl+Alt+Shift+N' },
     ];
 
     // Tracks isDefault per field across renders so we can fire the
@@ -3619,7 +4367,8 @@
             newState[def.field] = isDefault;
             // Reset icon only renders when the binding diverges from
             // default — avoids visual noise for the common case.
-            const resetBtn = isDefault
+            const locked = isPinned('hotkeys.' + def.field);
+            const resetBtn = isDefault || locked
                 ? ''
                 : '<button class="hotkey-reset-btn" data-reset-hotkey="' + def.field +
                   '" title="Reset to ' + formatKey(def.default) + '">' + icons.refresh + '</button>';
@@ -3628,7 +4377,9 @@
                     '<span class="settings-label">' + def.label + '</span>' +
                     '<div class="hotkey-controls">' +
                         resetBtn +
-                        '<button class="hotkey-btn" data-hotkey="' + def.field + '" title="Click to rebind"></button>' +
+                        '<button class="hotkey-btn" data-hotkey="' + def.field + '"' +
+                            (locked ? ' disabled title="Managed by your administrator"' : ' title="Click to rebind"') +
+                            '></button>' +
                     '</div>' +
                 '</div>'
             );
@@ -3789,7 +4540,7 @@
         if (!config) return;
         if (!config.hotkeys) config.hotkeys = {};
         config.hotkeys[field] = value;
-        await invoke('update_config', { config });
+        await updateConfig();
         // Re-render synchronously instead of waiting on the
         // config-changed event round-trip — that path can race with
         // the user's next action and leave the row showing the new
@@ -3804,7 +4555,7 @@
         if (!config.hotkeys) config.hotkeys = {};
         if (config.hotkeys[field] === def.default) return;
         config.hotkeys[field] = def.default;
-        await invoke('update_config', { config });
+        await updateConfig();
         renderHotkeyRows();
     }
 
@@ -3829,7 +4580,7 @@
             const defaults = {};
             for (const def of HOTKEY_DEFINITIONS) defaults[def.field] = def.default;
             config.hotkeys = defaults;
-            await invoke('update_config', { config });
+            await updateConfig();
             renderHotkeyRows();
         });
     }
@@ -3870,6 +4621,7 @@
     function _activeModal() {
         if (settingsModal.classList.contains('visible')) return settingsModal;
         if (tutorialModal.classList.contains('visible')) return tutorialModal;
+        if (kioskModal.classList.contains('visible')) return kioskModal;
         return null;
     }
 
@@ -3902,9 +4654,21 @@
             settingOpacity.value = opacityToSlider(config.window.opacity);
             settingOpacityValue.textContent = Math.round(config.window.opacity * 100);
             renderHotkeyRows();
+            renderNudgeStep();
             settingHomeUrl.value = config.home_url || 'https://www.google.com';
             settingAutoRefresh.value = String(config.auto_refresh_minutes || 0);
-        }
+            settingHistoryRetention.value = String(config.history_retention_days || 90);
+            renderTrackingToggles();
+            settingLocalFiles.classList.toggle('active', !!config.allow_local_files);
+            renderSearchEngines();
+            renderProfiles();
+            renderPresets();
+            fillPresetFields();
+        }
+        settingHistorySearch.value = '';
+        loadHistory();
+        loadConfigBackups();
+        loadSiteStatus();
         settingsModal.classList.remove('hidden');
         settingsModal.classList.add('visible');
         modalOverlay.classList.add('visible');
@@ -3921,13 +4685,209 @@
         restoreModalFocus();
     }
 
+    // Profiles. The select only picks which profile the buttons act on;
+    // "Switch" makes it active, which moves the window and navigates, so
+    // this script instance is replaced by the next page's.
+    function renderProfiles(selected) {
+        const profiles = (config && config.profiles) || [];
+        const active = config && config.active_profile;
+        const keep = selected || settingProfile.value || active;
+        settingProfile.textContent = '';
+        for (const profile of profiles) {
+            const option = document.createElement('option');
+            option.value = profile.name;
+            option.textContent = profile.name === active ? profile.name + ' (active)' : profile.name;
+            settingProfile.appendChild(option);
+        }
+        settingProfile.value = profiles.some(p => p.name === keep) ? keep : (active || '');
+        updateProfileButtons();
+    }
+
+    function updateProfileButtons() {
+        const isActive = !!config && settingProfile.value === config.active_profile;
+        btnProfileSwitch.disabled = isActive;
+        btnProfileDelete.disabled = isActive;
+    }
+
+    async function profileCommand(cmd, args, failure, selectAfter) {
+        profileStatus.textContent = '';
+        const ok = await invoke(cmd, args);
+        if (!ok) {
+            profileStatus.textContent = failure;
+            return;
+        }
+        const freshConfig = await invoke('get_config');
+        if (freshConfig) config = freshConfig;
+        settingProfileName.value = '';
+        renderProfiles(selectAfter);
+    }
+
+    settingProfile.addEventListener('change', updateProfileButtons);
+
+    btnProfileSwitch.addEventListener('click', async () => {
+        const name = settingProfile.value;
+        if (!name) return;
+        profileStatus.textContent = '';
+        if (!await invoke('switch_profile', { name })) {
+            profileStatus.textContent = 'Could not switch profile';
+        }
+    });
+
+    btnProfileNew.addEventListener('click', async () => {
+        const name = settingProfileName.value.trim();
+        if (!name) return;
+        await profileCommand('create_profile', { name },
+            'Could not create profile (name in use or invalid)', name);
+    });
+
+    btnProfileRename.addEventListener('click', async () => {
+        const from = settingProfile.value;
+        const to = settingProfileName.value.trim();
+        if (!from || !to) return;
+        await profileCommand('rename_profile', { from, to },
+            'Could not rename profile (name in use or invalid)', to);
+    });
+
+    btnProfileDelete.addEventListener('click', async () => {
+        const name = settingProfile.value;
+        if (!name) return;
+        await profileCommand('delete_profile', { name }, 'Could not delete profile', null);
+    });
+
+    // Window presets. Selecting one fills the name and hotkey fields, so
+    // Save overwrites it with the window as it is now.
+    function renderPresets(selected) {
+        const presets = (config && config.window_presets) || [];
+        const keep = selected || settingPreset.value;
+        settingPreset.textContent = '';
+        for (const preset of presets) {
+            const option = document.createElement('option');
+            option.value = preset.name;
+            option.textContent = preset.hotkey ? `${preset.name} (${preset.hotkey})` : preset.name;
+            settingPreset.appendChild(option);
+        }
+        const current = presets.find(p => p.name === keep) || presets[0];
+        settingPreset.value = current ? current.name : '';
+        btnPresetApply.disabled = !current;
+        btnPresetDelete.disabled = !current;
+        // Falling back to another preset (or to none, after a delete) must
+        // not leave the old preset's name and hotkey in the fields.
+        if (!current || current.name !== keep) fillPresetFields();
+    }
+
+    function fillPresetFields() {
+        const presets = (config && config.window_presets) || [];
+        const preset = presets.find(p => p.name === settingPreset.value);
+        settingPresetName.value = preset ? preset.name : '';
+        settingPresetHotkey.value = (preset && preset.hotkey) || '';
+    }
+
+    settingPreset.addEventListener('change', fillPresetFields);
+
+    btnPresetApply.addEventListener('click', async () => {
+        const name = settingPreset.value;
+        if (!name) return;
+        presetStatus.textContent = '';
+        if (!await applyWindowPreset(name)) {
+            presetStatus.textContent = 'Could not apply preset';
+        }
+    });
+
+    btnPresetSave.addEventListener('click', async () => {
+        const name = settingPresetName.value.trim();
+        if (!name) return;
+        presetStatus.textContent = '';
+        const hotkey = settingPresetHotkey.value.trim();
+        if (!await invoke('save_window_preset', { name, hotkey })) {
+            presetStatus.textContent = 'Could not save preset (invalid name or hotkey, or hotkey in use)';
+            return;
+        }
+        const freshConfig = await invoke('get_config');
+        if (freshConfig) config = freshConfig;
+        renderPresets(name);
+    });
+
+    btnPresetDelete.addEventListener('click', async () => {
+        const name = settingPreset.value;
+        if (!name) return;
+        presetStatus.textContent = '';
+        if (!await invoke('delete_window_preset', { name })) {
+            presetStatus.textContent = 'Could not delete preset';
+            return;
+        }
+        const freshConfig = await invoke('get_config');
+        if (freshConfig) config = freshConfig;
+        renderPresets();
+    });
+
     settingHomeUrl.addEventListener('change', async () => {
         if (config) {
             config.home_url = settingHomeUrl.value.trim() || 'https://www.google.com';
-            await invoke('update_config', { config });
+            await updateConfig();
+        }
+    });
+
+    // Search engines. Rust validates each edit (name, one-word keyword,
+    // http(s) URL with %s) and reports anything it drops as a correction.
+    function renderSearchEngines() {
+        const engines = (config && config.search_engines) || [];
+        settingSearchEngine.replaceChildren();
+        searchEngineList.replaceChildren();
+        engines.forEach((engine, i) => {
+            const option = document.createElement('option');
+            option.value = engine.keyword;
+            option.textContent = engine.name;
+            settingSearchEngine.appendChild(option);
+
+            const item = document.createElement('div');
+            item.className = 'history-item';
+            const text = document.createElement('span');
+            text.className = 'history-item-text';
+            text.textContent = engine.name + ' \u00b7 ' + engine.keyword;
+            text.title = engine.url;
+            const del = document.createElement('button');
+            del.className = 'settings-btn';
+            del.textContent = '×';
+            del.title = 'Remove search engine';
+            del.setAttribute('aria-label', 'Remove ' + engine.name);
+            del.disabled = isPinned('search_engines');
+            del.addEventListener('click', async () => {
+                config.search_engines.splice(i, 1);
+                await updateConfig();
+                renderSearchEngines();
+            });
+            item.append(text, del);
+            searchEngineList.appendChild(item);
+        });
+        settingSearchEngine.value = config ? config.default_search_engine : '';
+    }
+
+    settingSearchEngine.addEventListener('change', async () => {
+        if (config) {
+            config.default_search_engine = settingSearchEngine.value;
+            await updateConfig();
         }
     });
 
+    btnAddEngine.addEventListener('click', async () => {
+        if (!config) return;
+        const engine = {
+            name: settingEngineName.value.trim(),
+            keyword: settingEngineKeyword.value.trim().toLowerCase(),
+            url: settingEngineUrl.value.trim(),
+        };
+        if (!engine.name || !engine.keyword || !engine.url) return;
+        config.search_engines = (config.search_engines || []).concat([engine]);
+        const corrections = await updateConfig();
+        if (corrections === null) return;
+        if (!Array.isArray(corrections) || corrections.length === 0) {
+            settingEngineName.value = '';
+            settingEngineKeyword.value = '';
+            settingEngineUrl.value = '';
+        }
+        renderSearchEngines();
+    });
+
     settingOntop.addEventListener('click', async () => {
         const result = await invoke('toggle_always_on_top');
         // Leave the UI untouched on IPC failure (null) — otherwise the
@@ -3971,26 +4931,336 @@
     settingAutoRefresh.addEventListener('change', async () => {
         if (config) {
             config.auto_refresh_minutes = parseInt(settingAutoRefresh.value, 10) || 0;
-            await invoke('update_config', { config });
+            await updateConfig();
             startAutoRefresh(config.auto_refresh_minutes);
         }
     });
 
-    btnClearRecent.addEventListener('click', async () => {
+    // Site settings. "Save for Site" stores the current opacity, crop,
+    // auto-refresh, window size, always-on-top and zoom for this page's
+    // origin; Rust applies them whenever a page on that site loads and
+    // restores the global values on leaving.
+    function showSiteError(message) {
+        siteError.textContent = message || '';
+        siteErrorRow.style.display = message ? '' : 'none';
+    }
+
+    async function loadSiteStatus() {
+        const status = await invoke('get_site_status');
+        if (!status) {
+            siteStatus.textContent = 'Site settings are unavailable.';
+            btnSiteSave.disabled = true;
+            btnSiteForget.disabled = true;
+            return;
+        }
+        if (status.active) {
+            siteStatus.textContent = 'Using saved settings for ' + status.active
+                + '. Changes last until you leave the site unless you save them.';
+        } else if (status.current) {
+            siteStatus.textContent = 'No saved settings for ' + status.current + '.';
+        } else {
+            siteStatus.textContent = 'This page is not on a website.';
+        }
+        btnSiteSave.disabled = !status.current;
+        btnSiteForget.disabled = !status.active;
+        btnSiteForget.dataset.site = status.active || '';
+        // Show the nearest listed zoom level.
+        let nearest = settingPageZoom.options[0];
+        for (const option of settingPageZoom.options) {
+            if (Math.abs(option.value - status.zoom) < Math.abs(nearest.value - status.zoom)) {
+                nearest = option;
+            }
+        }
+        settingPageZoom.value = nearest.value;
+    }
+
+    settingPageZoom.addEventListener('change', async () => {
+        showSiteError('');
+        if (await invoke('set_page_zoom', { zoom: parseFloat(settingPageZoom.value) }) === null) {
+            showSiteError('Could not change the page zoom');
+        }
+    });
+
+    btnSiteSave.addEventListener('click', async () => {
+        showSiteError('');
+        if (!await invoke('save_site_settings', {})) {
+            showSiteError('Could not save settings for this site');
+            return;
+        }
+        await refreshSiteConfig();
+        await loadSiteStatus();
+    });
+
+    btnSiteForget.addEventListener('click', async () => {
+        const site = btnSiteForget.dataset.site;
+        if (!site) return;
+        showSiteError('');
+        if (!await invoke('forget_site_settings', { site })) {
+            showSiteError('Could not forget settings for ' + site);
+            return;
+        }
+        await refreshSiteConfig();
+        await loadSiteStatus();
         if (config) {
-            config.recent_urls = [];
-            await invoke('update_config', { config });
-            updateRecentDropdown();
+            settingOpacity.value = opacityToSlider(config.window.opacity);
+            settingOpacityValue.textContent = Math.round(config.window.opacity * 100);
+            settingOntop.classList.toggle('active', config.window.always_on_top);
+            settingAutoRefresh.value = String(config.auto_refresh_minutes || 0);
         }
     });
 
+    // Backup names carry a UTC YYYYMMDD-HHMMSS stamp; show it in local time.
+    function formatBackupStamp(stamp) {
+        const m = /^(\d{4})(\d{2})(\d{2})-(\d{2})(\d{2})(\d{2})$/.exec(stamp || '');
+        if (!m) return stamp || '';
+        const date = new Date(Date.UTC(+m[1], +m[2] - 1, +m[3], +m[4], +m[5], +m[6]));
+        return date.toLocaleString(undefined, {
+            year: 'numeric', month: 'short', day: 'numeric',
+            hour: '2-digit', minute: '2-digit',
+        });
+    }
+
+    async function loadConfigBackups() {
+        const backups = await invoke('list_config_backups') || [];
+        settingBackup.textContent = '';
+        if (backups.length === 0) {
+            const option = document.createElement('option');
+            option.textContent = 'No backups yet';
+            settingBackup.appendChild(option);
+        }
+        for (const backup of backups) {
+            const option = document.createElement('option');
+            option.value = backup.name;
+            option.textContent = formatBackupStamp(backup.created);
+            settingBackup.appendChild(option);
+        }
+        settingBackup.disabled = backups.length === 0;
+        btnRestoreBackup.disabled = backups.length === 0;
+    }
+
+    function showTransferStatus(message, isError) {
+        transferStatus.textContent = message;
+        transferStatus.className = isError ? 'update-status error' : 'update-status';
+        transferStatusRow.style.display = message ? '' : 'none';
+    }
+
+    btnExportSettings.addEventListener('click', async () => {
+        const path = await invoke('export_settings', {
+            includeGeometry: settingExportGeometry.value === 'yes',
+        });
+        showTransferStatus(path ? 'Exported to ' + path : 'Export failed', !path);
+    });
+
+    btnImportSettings.addEventListener('click', () => {
+        inputImportSettings.value = '';
+        inputImportSettings.click();
+    });
+
+    inputImportSettings.addEventListener('change', async () => {
+        const file = inputImportSettings.files && inputImportSettings.files[0];
+        if (!file) return;
+        let text;
+        try {
+            text = await file.text();
+        } catch {
+            showTransferStatus('Could not read ' + file.name, true);
+            return;
+        }
+        const ok = await invoke('import_settings', { bundle: text, mode: settingImportMode.value });
+        if (!ok) {
+            showTransferStatus('Import failed: not a valid FloatView settings file', true);
+            return;
+        }
+        const freshConfig = await invoke('get_config');
+        if (freshConfig) {
+            config = freshConfig;
+            updateBookmarksDropdown();
+            updateBookmarkIcon();
+            startAutoRefresh(config.auto_refresh_minutes || 0);
+            openSettings();
+        }
+        showTransferStatus('Imported settings from ' + file.name, false);
+    });
+
+    btnImportBookmarks.addEventListener('click', () => {
+        inputImportBookmarks.value = '';
+        inputImportBookmarks.click();
+    });
+
+    inputImportBookmarks.addEventListener('change', async () => {
+        const file = inputImportBookmarks.files && inputImportBookmarks.files[0];
+        if (!file) return;
+        let text;
+        try {
+            text = await file.text();
+        } catch {
+            showTransferStatus('Could not read ' + file.name, true);
+            return;
+        }
+        const summary = await invoke('import_bookmarks', { text });
+        if (!summary) {
+            showTransferStatus('Import failed: not a bookmark file', true);
+            return;
+        }
+        await refreshBookmarksFromRust();
+        updateBookmarksDropdown();
+        updateBookmarkIcon();
+        const skipped = [];
+        if (summary.duplicates) skipped.push(summary.duplicates + ' already bookmarked');
+        if (summary.rejected) skipped.push(summary.rejected + ' not http(s)');
+        if (summary.over_limit) skipped.push(summary.over_limit + ' over the limit');
+        showTransferStatus(
+            'Imported ' + summary.added + ' bookmark' + (summary.added === 1 ? '' : 's')
+                + ' from ' + file.name + (skipped.length ? ' (skipped ' + skipped.join(', ') + ')' : ''),
+            false,
+        );
+    });
+
+    btnExportBookmarks.addEventListener('click', async () => {
+        const path = await invoke('export_bookmarks');
+        showTransferStatus(path ? 'Exported bookmarks to ' + path : 'Export failed', !path);
+    });
+
+    btnRestoreBackup.addEventListener('click', async () => {
+        const name = settingBackup.value;
+        if (!name) return;
+        btnRestoreBackup.disabled = true;
+        const ok = await invoke('restore_config_backup', { name });
+        const freshConfig = ok ? await invoke('get_config') : null;
+        if (freshConfig) {
+            config = freshConfig;
+            updateBookmarksDropdown();
+            updateBookmarkIcon();
+            startAutoRefresh(config.auto_refresh_minutes || 0);
+            // Re-populate every settings control (and the backup list,
+            // which now includes the config that was just replaced).
+            openSettings();
+        } else {
+            btnRestoreBackup.disabled = false;
+        }
+    });
+
+    // nudge_step can be any value from 1 to 500 in config.json; one the
+    // list doesn't offer gets its own option rather than a blank select.
+    function renderNudgeStep() {
+        const step = String(config.nudge_step || 20);
+        settingNudgeStep.querySelectorAll('option[data-custom]').forEach(o => o.remove());
+        if (![...settingNudgeStep.options].some(o => o.value === step)) {
+            const option = document.createElement('option');
+            option.value = step;
+            option.textContent = step + ' px';
+            option.dataset.custom = '';
+            const next = [...settingNudgeStep.options].find(o => parseInt(o.value, 10) > parseInt(step, 10));
+            settingNudgeStep.insertBefore(option, next || null);
+        }
+        settingNudgeStep.value = step;
+    }
+
+    settingNudgeStep.addEventListener('change', async () => {
+        if (config) {
+            config.nudge_step = parseInt(settingNudgeStep.value, 10) || 20;
+            await updateConfig();
+        }
+    });
+
+    settingHistoryRetention.addEventListener('change', async () => {
+        if (config) {
+            config.history_retention_days = parseInt(settingHistoryRetention.value, 10) || 90;
+            await updateConfig();
+        }
+    });
+
+    function renderTrackingToggles() {
+        const rules = (config && config.tracking_params) || {};
+        settingStripTracking.classList.toggle('active', !!rules.strip);
+        settingIgnoreTracking.classList.toggle('active', !!rules.ignore_when_matching);
+    }
+
+    // The parameter lists themselves are edited in config.json.
+    function bindTrackingToggle(toggle, field) {
+        toggle.addEventListener('click', async () => {
+            if (!config || !config.tracking_params || isPinned('tracking_params.' + field)) return;
+            config.tracking_params[field] = !config.tracking_params[field];
+            renderTrackingToggles();
+            await updateConfig();
+            renderTrackingToggles();
+            updateBookmarkIcon();
+        });
+    }
+    bindTrackingToggle(settingStripTracking, 'strip');
+    bindTrackingToggle(settingIgnoreTracking, 'ignore_when_matching');
+
+    settingLocalFiles.addEventListener('click', async () => {
+        if (!config || isPinned('allow_local_files')) return;
+        config.allow_local_files = !config.allow_local_files;
+        settingLocalFiles.classList.toggle('active', config.allow_local_files);
+        await updateConfig();
+        settingLocalFiles.classList.toggle('active', !!config.allow_local_files);
+    });
+
+    // History lives in Rust (history.jsonl); Settings shows a search over
+    // it. The Recent dropdown reads the same store, so deleting or clearing
+    // takes pages off it too.
+    async function loadHistory() {
+        const entries = await invoke('search_history', { query: settingHistorySearch.value, limit: 100 });
+        historyResults.replaceChildren();
+        if (!entries || entries.length === 0) {
+            const empty = document.createElement('div');
+            empty.className = 'recent-empty';
+            empty.textContent = settingHistorySearch.value.trim() ? 'No matches' : 'No history';
+            historyResults.appendChild(empty);
+            return;
+        }
+        entries.forEach((entry) => {
+            const item = document.createElement('div');
+            item.className = 'history-item';
+            const text = document.createElement('span');
+            text.className = 'history-item-text';
+            text.textContent = entry.title || entry.url;
+            text.title = entry.url + '\n' + new Date(entry.last_visit * 1000).toLocaleString() +
+                ' · ' + entry.visit_count + (entry.visit_count === 1 ? ' visit' : ' visits');
+            text.addEventListener('click', async () => {
+                closeSettings();
+                await navigateToUrl(entry.url);
+            });
+            const del = document.createElement('button');
+            del.className = 'settings-btn';
+            del.textContent = '×';
+            del.title = 'Delete from history';
+            del.setAttribute('aria-label', 'Delete ' + entry.url + ' from history');
+            del.addEventListener('click', async () => {
+                const ok = await invoke('delete_history_entry', { url: entry.url });
+                if (ok === null) return;
+                await loadHistory();
+            });
+            item.append(text, del);
+            historyResults.appendChild(item);
+        });
+    }
+
+    let _historySearchTimer = null;
+    settingHistorySearch.addEventListener('input', () => {
+        clearTimeout(_historySearchTimer);
+        _historySearchTimer = setTimeout(loadHistory, 200);
+    });
+
+    btnClearHistory.addEventListener('click', async () => {
+        const range = settingHistoryRange.value;
+        const from = range === 'all' ? null : Math.floor(Date.now() / 1000) - parseInt(range, 10);
+        const removed = await invoke('clear_history', { from, to: null });
+        if (removed === null) return;
+        await loadHistory();
+    });
+
     btnClearBookmarks.addEventListener('click', async () => {
+        const ok = await invoke('clear_bookmarks');
+        if (ok === null) return;
         if (config) {
             config.bookmarks = [];
-            await invoke('update_config', { config });
-            updateBookmarksDropdown();
-            updateBookmarkIcon();
+            config.bookmark_folders = [];
         }
+        updateBookmarksDropdown();
+        updateBookmarkIcon();
     });
 
     btnClearSiteData.addEventListener('click', async () => {
@@ -4009,7 +5279,9 @@
     btnCloseSettings.addEventListener('click', closeSettings);
     settingsModal.querySelector('#btn-close-settings-x').addEventListener('click', closeSettings);
     modalOverlay.addEventListener('click', () => {
-        if (tutorialActive) {
+        if (kioskModal.classList.contains('visible')) {
+            closeKioskPrompt();
+        } else if (tutorialActive) {
             dismissTutorial();
         } else {
             closeSettings();
@@ -4099,12 +5371,18 @@
         // Lock on (where e.key would be 'L') and on non-QWERTY layouts.
         if (e.ctrlKey && e.code === 'KeyL') {
             e.preventDefault();
+            if (kioskLocked()) {
+                openKioskPrompt();
+                return;
+            }
             showStrip();
             urlInput.focus();
             urlInput.select();
         }
         if (e.key === 'Escape') {
-            if (cropOverlayEl) {
+            if (kioskModal.classList.contains('visible')) {
+                closeKioskPrompt();
+            } else if (cropOverlayEl) {
                 exitCropSelection();
             } else if (cropActive) {
                 removeCrop();
@@ -4136,7 +5414,7 @@
         restoreModalFocus();
         if (config) {
             config.first_run = false;
-            await invoke('update_config', { config });
+            await updateConfig();
         }
     }
 
@@ -4169,6 +5447,109 @@
         focusModal(tutorialModal);
     }
 
+    // --------------------------------------------------------------------
+    // [15b] Kiosk mode
+    // --------------------------------------------------------------------
+
+    // While kiosk mode is locked the strip stays hidden, and Settings or
+    // Ctrl+L ask for the PIN instead. Unlocking lasts until the strip
+    // hides again. The exit_kiosk hotkey arms "Exit Kiosk" for a minute;
+    // Rust checks both the PIN and the arming, this only mirrors them.
+    const kioskMessage = kioskModal.querySelector('#kiosk-message');
+    const kioskPin = kioskModal.querySelector('#kiosk-pin');
+    const kioskError = kioskModal.querySelector('#kiosk-error');
+    const btnKioskExit = kioskModal.querySelector('#kiosk-exit');
+    const btnKioskUnlock = kioskModal.querySelector('#kiosk-unlock');
+
+    function kioskLocked() {
+        return kioskStatus.active && !kioskStatus.unlocked;
+    }
+
+    function applyKioskStatus(status) {
+        kioskStatus = status || { active: false, unlocked: false, exit_armed: false };
+        if (kioskLocked()) {
+            if (!settingsModal.classList.contains('hidden')) closeSettings();
+            hideStrip();
+        }
+        if (kioskStatus.exit_armed) {
+            openKioskPrompt();
+        } else if (!kioskStatus.active && kioskModal.classList.contains('visible')) {
+            closeKioskPrompt();
+        }
+    }
+
+    function openKioskPrompt() {
+        const exiting = kioskStatus.exit_armed;
+        kioskMessage.textContent = exiting
+            ? 'Enter the PIN to leave kiosk mode.'
+            : 'Enter the PIN to open Settings.';
+        btnKioskExit.style.display = exiting ? '' : 'none';
+        kioskError.textContent = '';
+        kioskPin.value = '';
+        kioskModal.classList.remove('hidden');
+        kioskModal.classList.add('visible');
+        modalOverlay.classList.add('visible');
+        focusModal(kioskModal);
+    }
+
+    function closeKioskPrompt() {
+        kioskPin.value = '';
+        kioskModal.classList.remove('visible');
+        kioskModal.classList.add('hidden');
+        modalOverlay.classList.remove('visible');
+        restoreModalFocus();
+    }
+
+    function relockKiosk() {
+        if (!kioskStatus.active || !kioskStatus.unlocked) return;
+        kioskStatus.unlocked = false;
+        invoke('lock_kiosk');
+    }
+
+    async function unlockKiosk() {
+        btnKioskUnlock.disabled = true;
+        const ok = await invoke('unlock_kiosk', { pin: kioskPin.value });
+        btnKioskUnlock.disabled = false;
+        if (!ok) {
+            kioskError.textContent = 'Wrong PIN';
+            kioskPin.select();
+            return;
+        }
+        kioskStatus.unlocked = true;
+        closeKioskPrompt();
+        showStrip();
+        openSettings();
+    }
+
+    async function exitKiosk() {
+        btnKioskExit.disabled = true;
+        const ok = await invoke('exit_kiosk', { pin: kioskPin.value });
+        btnKioskExit.disabled = false;
+        if (!ok) {
+            // A wrong PIN (or a timed-out hotkey) disarms the exit in Rust.
+            kioskStatus.exit_armed = false;
+            btnKioskExit.style.display = 'none';
+            kioskError.textContent = 'Wrong PIN. Press the exit hotkey to try again.';
+            kioskPin.value = '';
+            return;
+        }
+        closeKioskPrompt();
+        applyKioskStatus(null);
+    }
+
+    btnKioskUnlock.addEventListener('click', unlockKiosk);
+    btnKioskExit.addEventListener('click', exitKiosk);
+    kioskModal.querySelector('#kiosk-cancel').addEventListener('click', closeKioskPrompt);
+    kioskPin.addEventListener('keydown', (e) => {
+        if (e.key !== 'Enter') return;
+        e.preventDefault();
+        if (kioskStatus.exit_armed) {
+            exitKiosk();
+        } else {
+            unlockKiosk();
+        }
+    });
+
     // --------------------------------------------------------------------
     // [16] Error-page detection + auto-recovery
     // --------------------------------------------------------------------
@@ -4210,7 +5591,6 @@
                 btnLock.classList.toggle('active', config.window.locked);
                 opacitySlider.value = opacityToSlider(config.window.opacity);
                 applyContentOpacity(config.window.opacity);
-                updateRecentDropdown();
                 updateBookmarkIcon();
                 updateBookmarksDropdown();
                 if (config.window.locked) {
@@ -4220,11 +5600,15 @@
                     container.style.display = '';
                 }
 
-                if (config.first_run) {
+                if (config.first_run && !(config.kiosk && config.kiosk.enabled)) {
                     showTutorial();
                 }
                 startAutoRefresh(config.auto_refresh_minutes || 0);
             }
+            await loadConfigStatus();
+            await loadConfigPolicy();
+            applyKioskStatus(await invoke('get_kiosk_status'));
+            showSaveError(await invoke('get_config_save_error'));
         } catch (e) {
             console.warn('Failed to load config:', e);
         }
@@ -4254,10 +5638,19 @@
         }
 
         // Pre-fill URL bar with current page URL (prefer actual URL over config)
-        const currentUrl = window.location.href;
+        const currentUrl = currentPageUrl();
         if (currentUrl && /^https?:\/\//i.test(currentUrl) && currentUrl !== 'about:blank') {
             urlInput.value = currentUrl;
-            await invoke('set_url', { url: currentUrl });
+            // A true result means this page entered or left a site with
+            // saved settings; the crop below must come from the fresh
+            // config, and the refresh timer restarts with it.
+            if (await invoke('set_url', { url: currentUrl })) {
+                const freshConfig = await invoke('get_config');
+                if (freshConfig) {
+                    config = freshConfig;
+                    startAutoRefresh(config.auto_refresh_minutes || 0);
+                }
+            }
         } else if (config && config.last_url) {
             urlInput.value = config.last_url;
         }
@@ -4271,6 +5664,99 @@
         }
     }
 
+    // Surface how Rust obtained the config at startup (recovered from a
+    // backup, or reset to defaults after a damaged config.json). The first
+    // fetch of a run opens Settings so the notice can't go unseen; every
+    // later page load just keeps the status line under Data current.
+    // Skipped while the first-run tutorial is up — a reset to defaults
+    // re-arms it, and stacking both modals helps nobody.
+    async function loadConfigStatus() {
+        const report = await invoke('get_config_load_report');
+        if (!report || !report.message) return;
+        configStatus.textContent = report.message;
+        configStatusRow.style.display = '';
+        if (!report.notified && !(config && config.first_run)) {
+            container.style.display = '';
+            showStrip();
+            openSettings();
+        }
+    }
+
+    function isPinned(path) {
+        return pinnedKeys.some(k => k.path === path || path.startsWith(k.path + '.'));
+    }
+
+    function applyPolicyLocks() {
+        const managed = 'Managed by your administrator';
+        settingHomeUrl.disabled = isPinned('home_url');
+        settingHomeUrl.title = settingHomeUrl.disabled ? managed : '';
+        settingAutoRefresh.disabled = isPinned('auto_refresh_minutes');
+        settingAutoRefresh.title = settingAutoRefresh.disabled ? managed : '';
+        settingSearchEngine.disabled = isPinned('default_search_engine');
+        settingSearchEngine.title = settingSearchEngine.disabled ? managed : '';
+        btnAddEngine.disabled = isPinned('search_engines');
+        btnAddEngine.title = btnAddEngine.disabled ? managed : '';
+        settingNudgeStep.disabled = isPinned('nudge_step');
+        settingNudgeStep.title = settingNudgeStep.disabled ? managed : '';
+        settingHistoryRetention.disabled = isPinned('history_retention_days');
+        settingHistoryRetention.title = settingHistoryRetention.disabled ? managed : '';
+        settingLocalFiles.title = isPinned('allow_local_files') ? managed : '';
+        settingStripTracking.title = isPinned('tracking_params.strip') ? managed : '';
+        settingIgnoreTracking.title = isPinned('tracking_params.ignore_when_matching') ? managed : '';
+        configPolicy.textContent = pinnedKeys.length
+            ? 'Some settings are managed by a system policy or environment variable: ' +
+              pinnedKeys.map(k => k.path).join(', ')
+            : '';
+        configPolicyRow.style.display = pinnedKeys.length ? '' : 'none';
+    }
+
+    async function loadConfigPolicy() {
+        const keys = await invoke('get_config_policy');
+        pinnedKeys = Array.isArray(keys) ? keys : [];
+        applyPolicyLocks();
+        renderHotkeyRows();
+    }
+
+    // Push the cached config to Rust. Whatever it had to correct (an
+    // invalid hotkey reset to its default, a non-http home URL, a locked
+    // key) is listed at the top of Settings, so a change that didn't
+    // stick doesn't look like it did. `config-changed` brings the
+    // corrected values back into `config`.
+    async function updateConfig() {
+        const corrections = await invoke('update_config', { config });
+        const list = Array.isArray(corrections) ? corrections : [];
+        showCorrections(list);
+        if (list.length) {
+            const fresh = await invoke('get_config');
+            if (fresh) config = fresh;
+            // Re-render so the controls show the corrected values.
+            if (!settingsModal.classList.contains('hidden')) openSettings();
+        }
+        return corrections;
+    }
+
+    function formatCorrectionValue(value) {
+        if (value === null || value === undefined) return '(none)';
+        return typeof value === 'string' ? value : JSON.stringify(value);
+    }
+
+    function showCorrections(corrections) {
+        configCorrections.textContent = corrections.map(c =>
+            c.field + ': ' + formatCorrectionValue(c.old) + ' \u2192 ' +
+            formatCorrectionValue(c.new) + ' (' + c.reason + ')'
+        ).join('\n');
+        configCorrectionsRow.style.display = corrections.length ? '' : 'none';
+    }
+
+    // Reflect the saver thread's last write outcome: a red Settings button
+    // on the strip plus the error text under Data. `null` clears both.
+    function showSaveError(error) {
+        btnSettings.classList.toggle('save-error', !!error);
+        btnSettings.title = error ? 'Settings (changes are not being saved)' : 'Settings';
+        configSaveStatus.textContent = error ? 'Settings could not be saved: ' + error : '';
+        configSaveRow.style.display = error ? '' : 'none';
+    }
+
     // Global callback for Rust to update UI reliably via eval()
     window.__floatViewUpdate = function(key, value) {
         switch(key) {
@@ -4293,11 +5779,28 @@
             case 'opacity':
                 syncOpacityUI(value);
                 break;
+            case 'crop':
+                if (config) config.crop = value;
+                if (zoomVideoActive) break;
+                if (value) {
+                    applyCrop(value.x, value.y, value.width, value.height, true, false);
+                } else if (cropActive) {
+                    removeCrop(false);
+                }
+                break;
             case 'open_settings':
                 container.style.display = '';
+                if (kioskLocked()) {
+                    openKioskPrompt();
+                    break;
+                }
                 showStrip();
                 openSettings();
                 break;
+            case 'kiosk':
+                container.style.display = '';
+                applyKioskStatus(value);
+                break;
             case 'bookmarks':
                 if (config) config.bookmarks = value;
                 updateBookmarkIcon();
@@ -4343,7 +5846,6 @@
 
         listen('config-changed', (event) => {
             config = event.payload;
-            updateRecentDropdown();
             updateBookmarkIcon();
             updateBookmarksDropdown();
             // Keep the rebinding UI in sync if the modal is open and the
@@ -4351,9 +5853,22 @@
             // flow (e.g. external config edit, reset button).
             if (!settingsModal.classList.contains('hidden')) {
                 renderHotkeyRows();
+                renderProfiles();
+                renderPresets();
             }
         });
 
+        // config.json was edited on disk. `config-changed` has already
+        // refreshed the cached config; re-apply the bits that otherwise
+        // only take effect on the next page load.
+        listen('config-reloaded', (event) => {
+            applyPageConfig(event.payload || {});
+        });
+
+        listen('config-save-status', (event) => {
+            showSaveError(event.payload);
+        });
+
         listen('open-settings', () => {
             container.style.display = '';
             showStrip();
@@ -0,0 +1,280 @@
+//! Kiosk mode: a locked-down display that only shows allowlisted pages.
+//!
+//! With `kiosk.enabled` set, every navigation is checked against
+//! [`allows`]: `commands::navigate`, `ops::navigate_home`, the startup
+//! page, and page-initiated navigations (links, redirects, scripts) via
+//! the webview's navigation handler. `home_url` gets no exemption, so
+//! Go Home fails unless the allowlist covers it. The control strip stays
+//! hidden, `update_config` is refused, and only the `exit_kiosk` hotkey
+//! is registered.
+//!
+//! The PIN unlocks Settings and the URL bar for the session
+//! ([`KioskSession::unlocked`], relocked when Settings closes). Leaving
+//! kiosk mode takes the hotkey and then the PIN within
+//! [`EXIT_ARM_WINDOW`]; a page can't leave it through commands alone.
+//!
+//! Only a SHA-256 of the PIN is stored. Nothing here touches the window
+//! or the disk.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::KioskConfig;
+use crate::urls::normalize_url;
+
+pub const MIN_PIN_LEN: usize = 4;
+pub const MAX_PIN_LEN: usize = 32;
+pub const MAX_KIOSK_ALLOWLIST: usize = 100;
+
+/// How long after the `exit_kiosk` hotkey the PIN can end kiosk mode.
+pub const EXIT_ARM_WINDOW: Duration = Duration::from_secs(60);
+
+/// Wait before answering a wrong PIN, to slow down guessing.
+pub const WRONG_PIN_DELAY: Duration = Duration::from_secs(1);
+
+/// Mixed into the hash so a `pin_hash` isn't a plain SHA-256 lookup.
+const PIN_HASH_DOMAIN: &str = "floatview-kiosk-pin:";
+
+/// Hex SHA-256 of `pin`, as stored in [`KioskConfig::pin_hash`]. Unsalted
+/// so the same policy PIN always yields the same stored value.
+pub fn pin_hash(pin: &str) -> String {
+    let digest = Sha256::digest(format!("{PIN_HASH_DOMAIN}{pin}").as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Whether `pin` matches the configured PIN. Always false without one.
+pub fn verify_pin(kiosk: &KioskConfig, pin: &str) -> bool {
+    kiosk
+        .pin_hash
+        .as_deref()
+        .is_some_and(|hash| hash == pin_hash(pin.trim()))
+}
+
+/// Check a PIN typed into `config.json` or policy: printable and
+/// [`MIN_PIN_LEN`]..=[`MAX_PIN_LEN`] characters. The error is the
+/// correction reason.
+pub fn clean_pin(pin: &str) -> Result<&str, &'static str> {
+    let pin = pin.trim();
+    let len = pin.chars().count();
+    if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&len) || pin.chars().any(char::is_control) {
+        return Err("kiosk PIN must be 4 to 32 characters");
+    }
+    Ok(pin)
+}
+
+/// Whether a stored `pin_hash` has the shape [`pin_hash`] produces.
+pub fn is_pin_hash(hash: &str) -> bool {
+    hash.len() == 64
+        && hash
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Normalize one allowlist entry for the sanitizer.
+pub fn clean_allowlist_entry(entry: &str) -> Result<String, &'static str> {
+    normalize_url(entry).map_err(|_| "kiosk allowlist entries must be http(s) URLs")
+}
+
+/// Whether kiosk mode lets the window show `url`. Everything is allowed
+/// when kiosk mode is off. Otherwise the app's own pages and
+/// `about:blank` are, and any page under an allowlist entry: same
+/// origin, and a path equal to the entry's or below it.
+pub fn allows(kiosk: &KioskConfig, url: &Url) -> bool {
+    if !kiosk.enabled || is_app_page(url) {
+        return true;
+    }
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    kiosk
+        .allowlist
+        .iter()
+        .filter_map(|entry| Url::parse(entry).ok())
+        .any(|entry| under_entry(&entry, url))
+}
+
+/// [`allows`] for a URL string; unparseable URLs are refused.
+pub fn allows_str(kiosk: &KioskConfig, url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| allows(kiosk, &url))
+}
+
+/// FloatView's own bundled page (or the blank page before it), as
+/// opposed to a site.
+pub fn is_app_page(url: &Url) -> bool {
+    url.scheme() == "tauri"
+        || url.host_str() == Some("tauri.localhost")
+        || url.as_str() == "about:blank"
+}
+
+/// Same origin, and `url`'s path is the entry's path or a segment below
+/// it: `/dash` covers `/dash` and `/dash/cpu`, not `/dashboard`.
+fn under_entry(entry: &Url, url: &Url) -> bool {
+    if entry.origin() != url.origin() {
+        return false;
+    }
+    let prefix = entry.path().trim_end_matches('/');
+    match url.path().strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Per-run kiosk state on `AppState`. Never saved: a restart is locked.
+#[derive(Debug, Default)]
+pub struct KioskSession {
+    /// The PIN was entered; Settings and the URL bar are usable until
+    /// [`KioskSession::lock`].
+    pub unlocked: bool,
+    /// When the `exit_kiosk` hotkey was last pressed.
+    pub exit_armed_at: Option<Instant>,
+}
+
+impl KioskSession {
+    /// Whether kiosk restrictions apply right now.
+    pub fn is_locked(&self, kiosk: &KioskConfig) -> bool {
+        kiosk.enabled && !self.unlocked
+    }
+
+    pub fn lock(&mut self) {
+        self.unlocked = false;
+        self.exit_armed_at = None;
+    }
+
+    pub fn arm_exit(&mut self, now: Instant) {
+        self.exit_armed_at = Some(now);
+    }
+
+    /// Whether the hotkey was pressed within [`EXIT_ARM_WINDOW`] of `now`.
+    pub fn exit_armed(&self, now: Instant) -> bool {
+        self.exit_armed_at
+            .is_some_and(|at| now.saturating_duration_since(at) <= EXIT_ARM_WINDOW)
+    }
+
+    pub fn status(&self, kiosk: &KioskConfig, now: Instant) -> KioskStatus {
+        KioskStatus {
+            active: kiosk.enabled,
+            unlocked: kiosk.enabled && self.unlocked,
+            exit_armed: kiosk.enabled && self.exit_armed(now),
+        }
+    }
+}
+
+/// What the page needs to know to hide or show its controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct KioskStatus {
+    pub active: bool,
+    pub unlocked: bool,
+    pub exit_armed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (url, allowed) with the allowlist below.
+    const ALLOW_CASES: &[(&str, bool)] = &[
+        ("https://grafana.lan/d/lobby", true),
+        ("https://grafana.lan/d/lobby?refresh=30s", true),
+        ("https://grafana.lan/d/lobby/panel", true),
+        ("https://grafana.lan/d/lobbyist", false),
+        ("https://grafana.lan/d/other", false),
+        ("http://grafana.lan/d/lobby", false),
+        ("https://status.example.com/", true),
+        ("https://status.example.com/incidents/42", true),
+        ("https://status.example.com:8443/", false),
+        ("https://evil.example.com/", false),
+        ("https://wiki.lan/ops/rota", true),
+        ("https://wiki.lan/ops/", true),
+        ("https://wiki.lan/opsec", false),
+        ("https://wiki.lan/", false),
+        ("tauri://localhost/index.html", true),
+        ("http://tauri.localhost/index.html", true),
+        ("about:blank", true),
+        ("file:///etc/passwd", false),
+        ("data:text/html,hi", false),
+    ];
+
+    fn kiosk() -> KioskConfig {
+        KioskConfig {
+            enabled: true,
+            allowlist: vec![
+                "https://grafana.lan/d/lobby".to_string(),
+                "https://status.example.com/".to_string(),
+                "https://wiki.lan/ops/".to_string(),
+            ],
+            pin: None,
+            pin_hash: Some(pin_hash("2468")),
+        }
+    }
+
+    #[test]
+    fn allowlist_matches_origin_and_path_prefix() {
+        let kiosk = kiosk();
+        for (i, (url, expected)) in ALLOW_CASES.iter().enumerate() {
+            assert_eq!(
+                allows_str(&kiosk, url),
+                *expected,
+                "case #{i}: allows({url:?})"
+            );
+        }
+    }
+
+    #[test]
+    fn everything_is_allowed_when_disabled() {
+        assert!(!allows_str(&kiosk(), "not a url"));
+        let off = KioskConfig {
+            enabled: false,
+            ..kiosk()
+        };
+        assert!(allows_str(&off, "https://evil.example.com/"));
+    }
+
+    #[test]
+    fn pin_is_checked_against_hash() {
+        let kiosk = kiosk();
+        assert!(verify_pin(&kiosk, "2468"));
+        assert!(verify_pin(&kiosk, " 2468 "));
+        assert!(!verify_pin(&kiosk, "1357"));
+        assert!(!verify_pin(
+            &KioskConfig {
+                pin_hash: None,
+                ..kiosk
+            },
+            ""
+        ));
+        assert!(is_pin_hash(&pin_hash("2468")));
+        assert!(!is_pin_hash("2468"));
+        assert_eq!(clean_pin(" 2468 "), Ok("2468"));
+        assert!(clean_pin("123").is_err());
+        assert!(clean_pin(&"9".repeat(MAX_PIN_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn exit_arm_expires() {
+        let kiosk = kiosk();
+        let start = Instant::now();
+        let mut session = KioskSession::default();
+        assert!(session.is_locked(&kiosk));
+        assert!(!session.exit_armed(start));
+
+        session.arm_exit(start);
+        assert!(session.exit_armed(start + EXIT_ARM_WINDOW));
+        assert!(!session.exit_armed(start + EXIT_ARM_WINDOW + Duration::from_secs(1)));
+
+        session.unlocked = true;
+        assert!(!session.is_locked(&kiosk));
+        session.lock();
+        assert!(session.is_locked(&kiosk));
+        assert_eq!(
+            session.status(&kiosk, start),
+            KioskStatus {
+                active: true,
+                unlocked: false,
+                exit_armed: false,
+            }
+        );
+    }
+}
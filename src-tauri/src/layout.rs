@@ -0,0 +1,732 @@
+//! Snap layouts: where `snap_window` puts the window.
+//!
+//! A position is either one of the built-in [`PRESETS`] (`top-left`,
+//! `left-half`, `center-third`, …) or `<layout>:<zone>` naming a zone of
+//! one of the user's `layouts`:
+//!
+//! - A grid layout (`"grid": { "columns": 3, "rows": 2 }`) numbers its
+//!   cells from 1 in reading order. `grid:2` is one cell; `grid:1-5` is
+//!   the block spanning cells 1 and 5.
+//! - A zones layout lists named rectangles in percent of the monitor
+//!   (`{ "name": "main", "x": 0, "y": 0, "width": 70, "height": 100 }`).
+//!
+//! Padding is the gap at the monitor edges and between tiles, in logical
+//! pixels ([`SNAP_PADDING`] unless the layout sets its own). Grid cells
+//! are all the same size, with the outer ones flush against the padding.
+//! A zone is inset by the padding where it meets the monitor edge and by
+//! half of it elsewhere, so neighbouring zones end up one padding apart.
+//!
+//! Corners and center are anchors: they move the window without resizing
+//! it. Everything else resizes it to the zone.
+//!
+//! The keyboard moves share the same math: [`nudge_rect`],
+//! [`scale_rect`] and [`next_corner`] for the hotkeys, with
+//! [`fit_size`] and [`keep_on_monitor`] doing the clamping for them and
+//! for `set_aspect_ratio`.
+//!
+//! Nothing here touches the window: [`resolve`] turns a position into a
+//! [`Snap`] and [`target_rect`] does the math on physical rectangles.
+//! [`crate::ops::snap_window`] does the window I/O.
+
+use crate::config::{LayoutGrid, LayoutZone, SnapLayout};
+use crate::window_state::Rect;
+
+/// Gap kept between a snapped window and the monitor edges (and between
+/// tiles), in logical pixels, for the presets and layouts without their
+/// own `padding`.
+pub const SNAP_PADDING: i32 = 16;
+
+pub const MAX_LAYOUTS: usize = 20;
+pub const MAX_LAYOUT_ZONES: usize = 24;
+pub const MAX_LAYOUT_NAME_LEN: usize = 32;
+/// Most columns (and rows) in a grid layout.
+pub const MAX_GRID_SIZE: u32 = 12;
+pub const MAX_LAYOUT_PADDING: u32 = 200;
+
+/// Default and upper bound for `nudge_step`, in logical pixels.
+pub const DEFAULT_NUDGE_STEP: u32 = 20;
+pub const MAX_NUDGE_STEP: u32 = 500;
+
+/// How much one press of the grow hotkey scales the window; shrink
+/// divides by it, so the two undo each other.
+pub const GROW_FACTOR: f64 = 1.1;
+
+/// The order `cycle_corner` visits the corners in: clockwise from the
+/// top left.
+pub const CORNER_CYCLE: [Anchor; 4] = [
+    Anchor::TopLeft,
+    Anchor::TopRight,
+    Anchor::BottomRight,
+    Anchor::BottomLeft,
+];
+
+/// How far off a corner (physical pixels) the window may be and still
+/// count as in it; some window managers shift a placed window by a pixel.
+const CORNER_SLOP: i32 = 2;
+
+/// Where in the monitor an anchor puts the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/// Where a snap puts the window, independent of any monitor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    /// Keep the window's size; place it at the anchor.
+    Anchor(Anchor),
+    /// The block of cells from `first` to `last` (0-based, reading order)
+    /// of an even grid.
+    Cells {
+        columns: u32,
+        rows: u32,
+        first: u32,
+        last: u32,
+    },
+    /// A rectangle in percent of the monitor.
+    Percent {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+impl Zone {
+    /// Whether snapping here resizes the window. Resizing snaps start a
+    /// snap chain; anchors end one (see `AppState::pre_snap_size`).
+    pub fn resizes(&self) -> bool {
+        !matches!(self, Zone::Anchor(_))
+    }
+}
+
+const fn cell(columns: u32, rows: u32, index: u32) -> Zone {
+    Zone::Cells {
+        columns,
+        rows,
+        first: index,
+        last: index,
+    }
+}
+
+/// The built-in positions, in snap menu order.
+pub const PRESETS: &[(&str, Zone)] = &[
+    ("top-left", Zone::Anchor(Anchor::TopLeft)),
+    ("top-right", Zone::Anchor(Anchor::TopRight)),
+    ("bottom-left", Zone::Anchor(Anchor::BottomLeft)),
+    ("bottom-right", Zone::Anchor(Anchor::BottomRight)),
+    ("center", Zone::Anchor(Anchor::Center)),
+    ("left-half", cell(2, 1, 0)),
+    ("right-half", cell(2, 1, 1)),
+    ("top-half", cell(1, 2, 0)),
+    ("bottom-half", cell(1, 2, 1)),
+    ("left-third", cell(3, 1, 0)),
+    ("center-third", cell(3, 1, 1)),
+    ("right-third", cell(3, 1, 2)),
+];
+
+/// A resolved position: the zone and its padding in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub zone: Zone,
+    pub padding: i32,
+}
+
+/// The built-in position name for `anchor`, e.g. `top-left`.
+pub fn anchor_name(anchor: Anchor) -> &'static str {
+    PRESETS
+        .iter()
+        .find(|(_, zone)| *zone == Zone::Anchor(anchor))
+        .map_or("center", |(name, _)| name)
+}
+
+/// The built-in position called `name`, if any.
+pub fn preset(name: &str) -> Option<Zone> {
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, zone)| *zone)
+}
+
+/// Check the shape of a position without looking at any layouts, for
+/// input that's checked before the config is at hand (deep links). Returns
+/// it lowercased; whether the layout and zone exist is up to [`resolve`].
+pub fn normalize_position(position: &str) -> Result<String, String> {
+    let position = position.trim().to_ascii_lowercase();
+    if preset(&position).is_some() {
+        return Ok(position);
+    }
+    match position.split_once(':') {
+        Some((layout, zone)) if is_layout_word(layout) && !zone.is_empty() => Ok(position),
+        _ => Err(format!("Unknown position \"{position}\"")),
+    }
+}
+
+/// Resolve a position against the user's layouts. Layout and zone names
+/// match ignoring case.
+pub fn resolve(layouts: &[SnapLayout], position: &str) -> Result<Snap, String> {
+    let position = position.trim();
+    if let Some(zone) = preset(&position.to_ascii_lowercase()) {
+        return Ok(Snap {
+            zone,
+            padding: SNAP_PADDING,
+        });
+    }
+    let (name, zone) = position
+        .split_once(':')
+        .ok_or_else(|| format!("Unknown position \"{position}\""))?;
+    let layout = layouts
+        .iter()
+        .find(|l| l.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("No layout named \"{}\"", name.trim()))?;
+    let zone = zone.trim();
+    let resolved = match &layout.grid {
+        Some(grid) => grid_cells(grid, zone),
+        None => layout
+            .zones
+            .iter()
+            .find(|z| z.name.eq_ignore_ascii_case(zone))
+            .map(|z| Zone::Percent {
+                x: z.x,
+                y: z.y,
+                width: z.width,
+                height: z.height,
+            }),
+    }
+    .ok_or_else(|| format!("Layout \"{}\" has no zone \"{zone}\"", layout.name))?;
+    Ok(Snap {
+        zone: resolved,
+        padding: layout
+            .padding
+            .map_or(SNAP_PADDING, |p| p.min(MAX_LAYOUT_PADDING) as i32),
+    })
+}
+
+/// `n` or `a-b` (1-based cell numbers) within `grid`.
+fn grid_cells(grid: &LayoutGrid, zone: &str) -> Option<Zone> {
+    let count = grid.columns.checked_mul(grid.rows)?;
+    let number = |s: &str| {
+        s.trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=count).contains(n))
+    };
+    let (first, last) = match zone.split_once('-') {
+        Some((a, b)) => (number(a)?, number(b)?),
+        None => (number(zone)?, number(zone)?),
+    };
+    Some(Zone::Cells {
+        columns: grid.columns,
+        rows: grid.rows,
+        first: first - 1,
+        last: last - 1,
+    })
+}
+
+/// Where `zone` puts a window of `size` on `monitor`, with `padding`
+/// around and between tiles and no side below `min_size`. All in physical
+/// pixels. The result is pulled back inside the padded monitor whenever
+/// it fits there.
+pub fn target_rect(
+    monitor: Rect,
+    size: (i32, i32),
+    zone: &Zone,
+    padding: i32,
+    min_size: i32,
+) -> Rect {
+    let (mx, my, mw, mh) = monitor;
+    let p = padding;
+    let (x, y, width, height) = match *zone {
+        Zone::Anchor(anchor) => {
+            let (w, h) = size;
+            let (x, y) = match anchor {
+                Anchor::TopLeft => (mx + p, my + p),
+                Anchor::TopRight => (mx + mw - w - p, my + p),
+                Anchor::BottomLeft => (mx + p, my + mh - h - p),
+                Anchor::BottomRight => (mx + mw - w - p, my + mh - h - p),
+                Anchor::Center => (mx + (mw - w) / 2, my + (mh - h) / 2),
+            };
+            (x, y, w, h)
+        }
+        Zone::Cells {
+            columns,
+            rows,
+            first,
+            last,
+        } => {
+            let columns = columns.max(1);
+            let (c0, c1) = sorted(first % columns, last % columns);
+            let (r0, r1) = sorted(first / columns, last / columns);
+            let (x, width) = grid_span(mx, mw, columns, c0, c1, p, min_size);
+            let (y, height) = grid_span(my, mh, rows.max(1), r0, r1, p, min_size);
+            (x, y, width, height)
+        }
+        Zone::Percent {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let (x, width) = percent_span(mx, mw, x, width, p, min_size);
+            let (y, height) = percent_span(my, mh, y, height, p, min_size);
+            (x, y, width, height)
+        }
+    };
+    keep_on_monitor(monitor, (x, y, width, height), p)
+}
+
+/// `size` shrunk to fit inside `monitor` less `padding` on every side,
+/// keeping its aspect ratio, and with no side below `min_size`.
+pub fn fit_size(monitor: Rect, size: (i32, i32), padding: i32, min_size: i32) -> (i32, i32) {
+    let (width, height) = (size.0.max(1) as f64, size.1.max(1) as f64);
+    let max_w = (monitor.2 - 2 * padding).max(min_size);
+    let max_h = (monitor.3 - 2 * padding).max(min_size);
+    let (mut w, mut h) = size;
+    if h > max_h {
+        h = max_h;
+        w = (h as f64 * width / height).round() as i32;
+    }
+    if w > max_w {
+        w = max_w;
+        h = (w as f64 * height / width).round() as i32;
+    }
+    (w.max(min_size), h.max(min_size))
+}
+
+/// `rect` moved (not resized) to lie inside `monitor` less `padding` on
+/// every side. An axis the window doesn't fit along is left alone.
+pub fn keep_on_monitor(monitor: Rect, rect: Rect, padding: i32) -> Rect {
+    let (mx, my, mw, mh) = monitor;
+    let (x, y, width, height) = rect;
+    (
+        clamp_into(x, mx + padding, mx + mw - width - padding),
+        clamp_into(y, my + padding, my + mh - height - padding),
+        width,
+        height,
+    )
+}
+
+/// `rect` moved by `offset`, stopping at the monitor edges.
+pub fn nudge_rect(monitor: Rect, rect: Rect, offset: (i32, i32)) -> Rect {
+    let (x, y, width, height) = rect;
+    keep_on_monitor(
+        monitor,
+        (
+            x.saturating_add(offset.0),
+            y.saturating_add(offset.1),
+            width,
+            height,
+        ),
+        0,
+    )
+}
+
+/// `rect` scaled by `factor` about its center, keeping its aspect ratio:
+/// never below `min_size` on either side, never past `monitor` less
+/// `padding`, and pulled back inside it.
+pub fn scale_rect(monitor: Rect, rect: Rect, factor: f64, padding: i32, min_size: i32) -> Rect {
+    let (x, y, width, height) = rect;
+    let smallest = width.min(height).max(1) as f64;
+    let factor = factor.max(min_size as f64 / smallest);
+    let size = (
+        (width as f64 * factor).round() as i32,
+        (height as f64 * factor).round() as i32,
+    );
+    let (w, h) = fit_size(monitor, size, padding, min_size);
+    let center = (x + width / 2, y + height / 2);
+    keep_on_monitor(monitor, (center.0 - w / 2, center.1 - h / 2, w, h), padding)
+}
+
+/// The corner `cycle_corner` goes to next: the one after the corner the
+/// window is in along [`CORNER_CYCLE`], or, when it's in none, the
+/// corner of the quarter of the monitor its center is in.
+pub fn next_corner(monitor: Rect, rect: Rect, padding: i32) -> Anchor {
+    let (x, y, width, height) = rect;
+    let in_corner = CORNER_CYCLE.iter().position(|&anchor| {
+        let (cx, cy, _, _) =
+            target_rect(monitor, (width, height), &Zone::Anchor(anchor), padding, 0);
+        (cx - x).abs() <= CORNER_SLOP && (cy - y).abs() <= CORNER_SLOP
+    });
+    if let Some(i) = in_corner {
+        return CORNER_CYCLE[(i + 1) % CORNER_CYCLE.len()];
+    }
+    let (mx, my, mw, mh) = monitor;
+    let right = x as i64 + width as i64 / 2 >= mx as i64 + mw as i64 / 2;
+    let bottom = y as i64 + height as i64 / 2 >= my as i64 + mh as i64 / 2;
+    match (right, bottom) {
+        (false, false) => Anchor::TopLeft,
+        (true, false) => Anchor::TopRight,
+        (false, true) => Anchor::BottomLeft,
+        (true, true) => Anchor::BottomRight,
+    }
+}
+
+fn sorted(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+/// Start and length of tiles `from..=to` of `count` along one axis. The
+/// tiles share the padded length evenly; the first and last sit against
+/// the padding and the rest are spread evenly between them.
+fn grid_span(
+    start: i32,
+    len: i32,
+    count: u32,
+    from: u32,
+    to: u32,
+    padding: i32,
+    min_size: i32,
+) -> (i32, i32) {
+    let count = count as i64;
+    let (len64, p) = (len as i64, padding as i64);
+    let tile = ((len64 - (count + 1) * p) / count).max(min_size as i64);
+    let offset = |i: u32| -> i64 {
+        if count == 1 {
+            0
+        } else {
+            i as i64 * (len64 - 2 * p - tile) / (count - 1)
+        }
+    };
+    let begin = start as i64 + p + offset(from);
+    let end = start as i64 + p + offset(to) + tile;
+    (begin as i32, (end - begin) as i32)
+}
+
+/// Start and length of a percent span along one axis, inset by the full
+/// padding at the monitor edges and half of it inside.
+fn percent_span(
+    start: i32,
+    len: i32,
+    from: f64,
+    span: f64,
+    padding: i32,
+    min_size: i32,
+) -> (i32, i32) {
+    let at = |percent: f64| start + (len as f64 * percent.clamp(0.0, 100.0) / 100.0).round() as i32;
+    let to = from + span;
+    let inset_start = if from <= 0.0 { padding } else { padding / 2 };
+    let inset_end = if to >= 100.0 {
+        padding
+    } else {
+        padding - padding / 2
+    };
+    let begin = at(from) + inset_start;
+    let end = at(to) - inset_end;
+    (begin, (end - begin).max(min_size))
+}
+
+/// `value` clamped to `min..=max`, or left alone when the range is empty
+/// (the window is bigger than the space).
+fn clamp_into(value: i32, min: i32, max: i32) -> i32 {
+    if max >= min {
+        value.clamp(min, max)
+    } else {
+        value
+    }
+}
+
+fn is_layout_word(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_LAYOUT_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Trim and validate one layout for the sanitizer: a name of letters,
+/// digits, `-` and `_` (lowercased), padding up to
+/// [`MAX_LAYOUT_PADDING`], and either a grid of 1 to [`MAX_GRID_SIZE`]
+/// columns and rows or 1 to [`MAX_LAYOUT_ZONES`] uniquely named zones
+/// inside the monitor. The error is the correction reason.
+pub fn clean_layout(layout: &SnapLayout) -> Result<SnapLayout, &'static str> {
+    let name = layout.name.trim().to_ascii_lowercase();
+    if !is_layout_word(&name) {
+        return Err("layout name must be one word of letters, digits, - or _");
+    }
+    if layout.padding.is_some_and(|p| p > MAX_LAYOUT_PADDING) {
+        return Err("layout padding must be 0 to 200");
+    }
+    let mut clean = SnapLayout {
+        name,
+        padding: layout.padding,
+        grid: layout.grid,
+        zones: Vec::new(),
+    };
+    match (&layout.grid, layout.zones.is_empty()) {
+        (Some(grid), true) => {
+            let range = 1..=MAX_GRID_SIZE;
+            if !range.contains(&grid.columns) || !range.contains(&grid.rows) {
+                return Err("grid must have 1 to 12 columns and rows");
+            }
+        }
+        (None, false) => {
+            if layout.zones.len() > MAX_LAYOUT_ZONES {
+                return Err("too many zones in layout");
+            }
+            for zone in &layout.zones {
+                let zone = clean_zone(zone)?;
+                if clean
+                    .zones
+                    .iter()
+                    .any(|z| z.name.eq_ignore_ascii_case(&zone.name))
+                {
+                    return Err("repeated zone name in layout");
+                }
+                clean.zones.push(zone);
+            }
+        }
+        _ => return Err("layout needs a grid or zones, not both"),
+    }
+    Ok(clean)
+}
+
+fn clean_zone(zone: &LayoutZone) -> Result<LayoutZone, &'static str> {
+    let name = zone.name.trim().to_string();
+    if !is_layout_word(&name) {
+        return Err("zone name must be one word of letters, digits, - or _");
+    }
+    let inside = |start: f64, len: f64| {
+        start.is_finite() && len.is_finite() && start >= 0.0 && len > 0.0 && start + len <= 100.0
+    };
+    if !inside(zone.x, zone.width) || !inside(zone.y, zone.height) {
+        return Err("zone must lie within the monitor (0-100%)");
+    }
+    Ok(LayoutZone { name, ..*zone })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: Rect = (0, 0, 1920, 1080);
+    const WINDOW: (i32, i32) = (800, 450);
+
+    fn snap(position: &str) -> Rect {
+        let zone = preset(position).unwrap();
+        target_rect(MONITOR, WINDOW, &zone, 16, 200)
+    }
+
+    fn grid(name: &str, columns: u32, rows: u32) -> SnapLayout {
+        SnapLayout {
+            name: name.to_string(),
+            padding: None,
+            grid: Some(LayoutGrid { columns, rows }),
+            zones: Vec::new(),
+        }
+    }
+
+    fn zone(name: &str, x: f64, y: f64, width: f64, height: f64) -> LayoutZone {
+        LayoutZone {
+            name: name.to_string(),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn zones(name: &str, padding: Option<u32>, list: Vec<LayoutZone>) -> SnapLayout {
+        SnapLayout {
+            name: name.to_string(),
+            padding,
+            grid: None,
+            zones: list,
+        }
+    }
+
+    /// The presets land where the hard-coded positions used to.
+    #[test]
+    fn presets_match_the_classic_positions() {
+        let cases: &[(&str, Rect)] = &[
+            ("top-left", (16, 16, 800, 450)),
+            ("top-right", (1104, 16, 800, 450)),
+            ("bottom-left", (16, 614, 800, 450)),
+            ("bottom-right", (1104, 614, 800, 450)),
+            ("center", (560, 315, 800, 450)),
+            ("left-half", (16, 16, 936, 1048)),
+            ("right-half", (968, 16, 936, 1048)),
+            ("top-half", (16, 16, 1888, 516)),
+            ("bottom-half", (16, 548, 1888, 516)),
+            ("left-third", (16, 16, 618, 1048)),
+            ("center-third", (651, 16, 618, 1048)),
+            ("right-third", (1286, 16, 618, 1048)),
+        ];
+        assert_eq!(cases.len(), PRESETS.len());
+        for (position, expected) in cases {
+            assert_eq!(snap(position), *expected, "{position}");
+        }
+    }
+
+    #[test]
+    fn anchors_keep_the_window_on_the_monitor() {
+        let zone = Zone::Anchor(Anchor::BottomRight);
+        let monitor = (-1280, 200, 1280, 720);
+        assert_eq!(
+            target_rect(monitor, (2000, 450), &zone, 16, 200),
+            (-2016, 454, 2000, 450),
+            "too wide to fit: x is left alone"
+        );
+        assert_eq!(
+            target_rect(monitor, (400, 300), &Zone::Anchor(Anchor::Center), 0, 200),
+            (-840, 410, 400, 300)
+        );
+        assert!(!zone.resizes());
+        assert!(preset("left-half").unwrap().resizes());
+    }
+
+    #[test]
+    fn grid_cells_and_spans() {
+        let layouts = [grid("quad", 2, 2), grid("six", 3, 2)];
+        let rect = |position: &str| {
+            let snap = resolve(&layouts, position).unwrap();
+            target_rect(MONITOR, WINDOW, &snap.zone, snap.padding, 200)
+        };
+        assert_eq!(rect("quad:1"), (16, 16, 936, 516));
+        assert_eq!(rect("quad:4"), (968, 548, 936, 516));
+        assert_eq!(rect("QUAD:2-3"), (16, 16, 1888, 1048), "span is a block");
+        assert_eq!(
+            rect("six:5"),
+            (651, 548, 618, 516),
+            "columns line up with thirds"
+        );
+        assert_eq!(rect("six:1-5"), (16, 16, 1253, 1048));
+        assert!(resolve(&layouts, "quad:5").is_err());
+        assert!(resolve(&layouts, "quad:0").is_err());
+        assert!(resolve(&layouts, "quad:x").is_err());
+        assert!(resolve(&layouts, "nine:1").is_err());
+        assert!(resolve(&layouts, "sideways").is_err());
+    }
+
+    #[test]
+    fn percent_zones_share_one_padding_between_them() {
+        let layouts = [zones(
+            "reader",
+            Some(10),
+            vec![
+                zone("main", 0.0, 0.0, 70.0, 100.0),
+                zone("side", 70.0, 0.0, 30.0, 50.0),
+                zone("tiny", 99.0, 99.0, 1.0, 1.0),
+            ],
+        )];
+        let rect = |position: &str| {
+            let snap = resolve(&layouts, position).unwrap();
+            assert_eq!(snap.padding, 10);
+            target_rect(MONITOR, WINDOW, &snap.zone, snap.padding, 200)
+        };
+        let main = rect("reader:main");
+        let side = rect("reader:Side");
+        assert_eq!(main, (10, 10, 1329, 1060));
+        assert_eq!(side, (1349, 10, 561, 525));
+        assert_eq!(side.0 - (main.0 + main.2), 10);
+        assert_eq!(
+            rect("reader:tiny"),
+            (1710, 870, 200, 200),
+            "grown to the minimum, then pulled back on screen"
+        );
+        assert!(resolve(&layouts, "reader:footer").is_err());
+    }
+
+    #[test]
+    fn normalize_position_checks_shape_only() {
+        assert_eq!(normalize_position(" Top-Right ").unwrap(), "top-right");
+        assert_eq!(normalize_position("Quad:3").unwrap(), "quad:3");
+        assert!(normalize_position("middle").is_err());
+        assert!(normalize_position("quad:").is_err());
+        assert!(normalize_position("a b:1").is_err());
+    }
+
+    #[test]
+    fn clean_layout_checks_each_field() {
+        let mut named = grid(" Quad ", 2, 2);
+        named.padding = Some(8);
+        assert_eq!(clean_layout(&named).unwrap().name, "quad");
+        assert!(clean_layout(&grid("a:b", 2, 2)).is_err());
+        assert!(clean_layout(&grid("quad", 0, 2)).is_err());
+        assert!(clean_layout(&grid("quad", 2, MAX_GRID_SIZE + 1)).is_err());
+        named.padding = Some(MAX_LAYOUT_PADDING + 1);
+        assert!(clean_layout(&named).is_err());
+
+        assert!(clean_layout(&zones("empty", None, Vec::new())).is_err());
+        let mut both = grid("both", 2, 2);
+        both.zones = vec![zone("a", 0.0, 0.0, 50.0, 50.0)];
+        assert!(clean_layout(&both).is_err());
+        let ok = zones("ok", None, vec![zone(" a ", 0.0, 0.0, 50.0, 50.0)]);
+        assert_eq!(clean_layout(&ok).unwrap().zones[0].name, "a");
+        for bad in [
+            vec![zone("a", 60.0, 0.0, 50.0, 50.0)],
+            vec![zone("a", 0.0, 0.0, 0.0, 50.0)],
+            vec![zone("a", f64::NAN, 0.0, 50.0, 50.0)],
+            vec![
+                zone("a", 0.0, 0.0, 10.0, 10.0),
+                zone("A", 0.0, 0.0, 10.0, 10.0),
+            ],
+            vec![zone("", 0.0, 0.0, 10.0, 10.0)],
+        ] {
+            assert!(
+                clean_layout(&zones("bad", None, bad.clone())).is_err(),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn nudge_stops_at_the_monitor_edges() {
+        let rect = (100, 100, 800, 450);
+        assert_eq!(nudge_rect(MONITOR, rect, (-20, 0)), (80, 100, 800, 450));
+        assert_eq!(nudge_rect(MONITOR, rect, (0, 20)), (100, 120, 800, 450));
+        assert_eq!(
+            nudge_rect(MONITOR, (10, 620, 800, 450), (-20, 20)),
+            (0, 630, 800, 450)
+        );
+        assert_eq!(
+            nudge_rect(MONITOR, (1110, 0, 800, 450), (20, 0)),
+            (1120, 0, 800, 450)
+        );
+        let second = (1920, 0, 1920, 1080);
+        assert_eq!(
+            nudge_rect(second, (1930, 10, 800, 450), (-20, -20)),
+            (1920, 0, 800, 450)
+        );
+    }
+
+    #[test]
+    fn scaling_keeps_aspect_and_center_within_limits() {
+        let grown = scale_rect(MONITOR, (560, 315, 800, 450), GROW_FACTOR, 16, 200);
+        assert_eq!(grown, (520, 293, 880, 495));
+        assert_eq!(
+            scale_rect(MONITOR, grown, 1.0 / GROW_FACTOR, 16, 200),
+            (560, 315, 800, 450)
+        );
+        // The smaller side stops at the minimum, the other keeps the ratio.
+        assert_eq!(
+            scale_rect(MONITOR, (100, 100, 210, 420), 1.0 / GROW_FACTOR, 16, 200),
+            (105, 110, 200, 400)
+        );
+        // Too big for the padded monitor: shrunk to fit and pulled in.
+        assert_eq!(
+            scale_rect(MONITOR, (100, 100, 1800, 900), GROW_FACTOR, 16, 200),
+            (16, 78, 1888, 944)
+        );
+        assert_eq!(fit_size(MONITOR, (3000, 1000), 16, 200), (1888, 629));
+        assert_eq!(fit_size(MONITOR, (100, 50), 16, 200), (200, 200));
+    }
+
+    #[test]
+    fn corners_cycle_clockwise() {
+        let next = |rect: Rect| next_corner(MONITOR, rect, 16);
+        assert_eq!(next(snap("top-left")), Anchor::TopRight);
+        assert_eq!(next(snap("top-right")), Anchor::BottomRight);
+        assert_eq!(next(snap("bottom-right")), Anchor::BottomLeft);
+        assert_eq!(next(snap("bottom-left")), Anchor::TopLeft);
+        assert_eq!(next((17, 15, 800, 450)), Anchor::TopRight);
+        // Not in a corner: the nearest one.
+        assert_eq!(next((100, 700, 800, 300)), Anchor::BottomLeft);
+        assert_eq!(next(snap("center")), Anchor::BottomRight);
+        assert_eq!(anchor_name(Anchor::TopRight), "top-right");
+        assert_eq!(anchor_name(Anchor::Center), "center");
+    }
+}
@@ -14,10 +14,28 @@
 //! - [`state`]       : `AppState`, token auth, tray-item mutator
 //! - [`config`]      : serde config types
 //! - [`config_io`]   : load/save/sanitize/shutdown of `config.json`
+//! - [`config_schema`]: JSON Schema for `config.json` + `--validate-config` checks
+//! - [`cli`]         : command-line modes that run instead of the app
+//! - [`config_layers`]: policy-file defaults/locks and `FLOATVIEW_*` overrides
+//! - [`config_watch`]: live reload of external edits to `config.json`
+//! - [`config_migrate`]: versioned `vN -> vN+1` transforms over raw config JSON
+//! - [`profiles`]    : named profiles (create/rename/delete/switch) over `AppConfig`
+//! - [`bookmarks`]   : bookmark add/rename/move/reorder and folders over `AppConfig`
+//! - [`bookmark_io`] : browser bookmark import (HTML, Chromium, Firefox) and HTML export
+//! - [`settings_bundle`]: portable export/import bundle (hotkeys, bookmarks, …)
+//! - [`site_settings`]: per-site overrides matched by origin or host pattern
+//! - [`history`]     : browsing history store (`history.jsonl`), search and pruning
+//! - [`suggest`]     : frecency-ranked URL bar suggestions
 //! - [`urls`]        : URL normalization and match helpers
+//! - [`omnibox`]     : URL bar input to URL or search, search engines and keywords
+//! - [`kiosk`]       : kiosk-mode URL allowlist, PIN check and session lock
+//! - [`deep_link`]   : `floatview://` link parsing and Linux scheme registration
+//! - [`local_media`] : `floatview-file` protocol for local files, media player URLs
 //! - [`logging`]     : tracing subscriber setup
 //! - [`injection`]   : webview init script + media-control JS snippets
-//! - [`window_state`]: geometry clamping, persistence, startup restore
+//! - [`layout`]      : snap presets and user layouts (grids, percent zones) as pure rect math
+//! - [`window_presets`]: named window setups (geometry, opacity, on-top, lock, crop)
+//! - [`window_state`]: geometry clamping, persistence, per-monitor-setup placements, restore
 //! - [`browsing_data`]: thin wrapper around WebView2 clear-all-data
 //! - [`opacity`]     : per-platform opacity native interop
 //! - [`ops`]         : strict toggle / opacity / navigate core shared by
@@ -28,52 +46,94 @@
 //! - [`tray`]        : tray icon + menu
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tauri::webview::PageLoadEvent;
 use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 pub mod actions;
+pub mod bookmark_io;
+pub mod bookmarks;
 pub mod browsing_data;
+pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod config_io;
+pub mod config_layers;
+pub mod config_migrate;
+pub mod config_schema;
+pub mod config_watch;
+pub mod deep_link;
+pub mod history;
 pub mod hotkeys;
 pub mod injection;
+pub mod kiosk;
+pub mod layout;
+pub mod local_media;
 pub mod logging;
+pub mod omnibox;
 pub mod opacity;
 pub mod ops;
+pub mod profiles;
+pub mod settings_bundle;
+pub mod site_settings;
 pub mod state;
+pub mod suggest;
 pub mod tray;
 pub mod urls;
+pub mod window_presets;
 pub mod window_state;
 
 #[cfg(test)]
 pub mod url_fixtures;
 
-use crate::config_io::{do_save_config, get_config_path, load_config, shutdown};
+use crate::config_io::{
+    get_config_path, load_layered_config, shutdown, spawn_config_saver, write_config,
+    WrittenDigests, SAVE_DEBOUNCE,
+};
+use crate::config_layers::{policy_path, ConfigLayers};
+use crate::deep_link::find_deep_link;
+use crate::history::{history_path, import_urls, load_history, now_secs, prune, write_history};
 use crate::injection::{build_injection_script, USER_AGENT};
+use crate::kiosk::KioskSession;
 use crate::logging::{init_logging, LoggingState};
+use crate::site_settings::SiteSession;
 use crate::state::AppState;
 use crate::urls::{normalize_url, DEFAULT_HOME_URL};
 use crate::window_state::{
-    apply_window_state, persist_window_geometry, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH,
-    MIN_INNER_HEIGHT, MIN_INNER_WIDTH,
+    apply_window_state, persist_window_geometry, MonitorWatch, DEFAULT_WINDOW_HEIGHT,
+    DEFAULT_WINDOW_WIDTH, MIN_INNER_HEIGHT, MIN_INNER_WIDTH,
 };
 
 /// Build and run the FloatView application. Blocks until the Tauri event
 /// loop exits. The binary wrapper is just `fn main() { floatview::run() }`.
 pub fn run() {
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.show();
                 let _ = window.set_focus();
             }
+            if let Some(link) = find_deep_link(&args) {
+                actions::do_open_deep_link(app, link);
+            }
         }))
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .register_asynchronous_uri_scheme_protocol(
+            crate::local_media::SCHEME,
+            |ctx, request, responder| {
+                // Serving reads the file (up to 32 MB at once), which can
+                // take a while on a slow disk or network share; keep it off
+                // the main thread so the UI doesn't freeze meanwhile.
+                let app = ctx.app_handle().clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(crate::local_media::serve(&app, &request));
+                });
+            },
+        )
         .setup(|app| {
             if let Some(guard) = init_logging(app.handle()) {
                 app.manage(LoggingState { _guard: guard });
@@ -83,21 +143,39 @@ pub fn run() {
 
             info!("FloatView setup started");
             let config_path = get_config_path(app.handle());
-            let config = load_config(&config_path);
-            info!(path = %config_path.display(), "Configuration loaded");
+            let layers = ConfigLayers::load(&policy_path(), std::env::vars());
+            let (config, user_values, load_report) = load_layered_config(&config_path, &layers);
+            info!(
+                path = %config_path.display(),
+                source = ?load_report.source,
+                "Configuration loaded"
+            );
+            let history_path = history_path(&config_path);
+            let mut history = load_history(&history_path);
+            let imported = import_urls(&mut history, &load_report.legacy_recent_urls, now_secs());
+            let pruned = prune(&mut history, now_secs(), config.history_retention_days);
+            if imported || pruned {
+                write_history(&history_path, &history);
+            }
             let command_token = Uuid::new_v4().to_string();
             let injection_script = build_injection_script(&command_token, &config.home_url);
 
-            let (save_tx, save_rx) = std::sync::mpsc::channel::<config::AppConfig>();
-            let saver_path = config_path.clone();
-            let save_thread = std::thread::Builder::new()
-                .name("floatview-config-saver".to_string())
-                .spawn(move || {
-                    while let Ok(cfg) = save_rx.recv() {
-                        do_save_config(&saver_path, &cfg);
-                    }
-                })
-                .expect("failed to spawn config saver thread");
+            let save_error = Arc::new(Mutex::new(None));
+            let written_digests = Arc::new(WrittenDigests::default());
+            let keep_newer_config = Arc::new(AtomicBool::new(load_report.newer_version.is_some()));
+            let status_app = app.handle().clone();
+            let (save_tx, save_thread) = spawn_config_saver(
+                "floatview-config-saver",
+                config_path.clone(),
+                SAVE_DEBOUNCE,
+                save_error.clone(),
+                written_digests.clone(),
+                keep_newer_config.clone(),
+                Box::new(move |error| {
+                    let _ = status_app.emit("config-save-status", error);
+                }),
+            )
+            .expect("failed to spawn config saver thread");
 
             let state = AppState {
                 config: Mutex::new(config.clone()),
@@ -109,9 +187,26 @@ pub fn run() {
                 tray: Mutex::new(None),
                 pre_snap_size: Mutex::new(None),
                 snap_expected_size: Mutex::new(None),
+                save_error,
+                load_report: Mutex::new(load_report),
+                written_digests,
+                keep_newer_config,
+                layers,
+                user_values: Mutex::new(user_values),
+                site: Mutex::new(SiteSession::default()),
+                history: Mutex::new(history),
+                history_path,
+                kiosk: Mutex::new(KioskSession::default()),
+                local_folders: Mutex::new(Vec::new()),
+                // Started by the OS for a `floatview://` link.
+                pending_deep_link: Mutex::new(
+                    find_deep_link(&std::env::args().collect::<Vec<_>>()).map(str::to_string),
+                ),
             };
             app.manage(state);
 
+            let nav_app = app.handle().clone();
+            let load_app = app.handle().clone();
             let window =
                 WebviewWindowBuilder::new(app, "main", WebviewUrl::App("index.html".into()))
                     .title("FloatView")
@@ -126,6 +221,25 @@ pub fn run() {
                     .always_on_top(true)
                     .initialization_script(&injection_script)
                     .user_agent(USER_AGENT)
+                    .on_navigation(move |url| crate::ops::check_navigation(&nav_app, url))
+                    .on_page_load(move |_, payload| {
+                        // The startup link runs once the startup navigation
+                        // has landed, so that navigation can't replace it.
+                        if payload.event() != PageLoadEvent::Finished
+                            || crate::kiosk::is_app_page(payload.url())
+                        {
+                            return;
+                        }
+                        let state = load_app.state::<AppState>();
+                        let link = state
+                            .pending_deep_link
+                            .lock()
+                            .ok()
+                            .and_then(|mut l| l.take());
+                        if let Some(link) = link {
+                            actions::do_open_deep_link(&load_app, &link);
+                        }
+                    })
                     .build()?;
 
             apply_window_state(&window, &config);
@@ -154,11 +268,13 @@ pub fn run() {
                 opacity::set_window_opacity(&window_for_opacity, opacity);
             });
 
-            let nav_url = config
-                .last_url
-                .clone()
-                .and_then(|u| normalize_url(&u).ok())
-                .or_else(|| normalize_url(&config.home_url).ok())
+            // In kiosk mode, the first of these the allowlist permits.
+            let nav_url = [config.last_url.as_deref(), Some(config.home_url.as_str())]
+                .into_iter()
+                .flatten()
+                .filter_map(|u| normalize_url(u).ok())
+                .chain(config.kiosk.allowlist.iter().cloned())
+                .find(|u| crate::kiosk::allows_str(&config.kiosk, u))
                 .unwrap_or_else(|| DEFAULT_HOME_URL.to_string());
             let window_clone = window.clone();
             std::thread::spawn(move || {
@@ -166,6 +282,9 @@ pub fn run() {
                 let _ = window_clone.eval(crate::injection::js_navigate(&nav_url));
             });
 
+            #[cfg(target_os = "linux")]
+            crate::deep_link::register_scheme_handler(app.handle());
+
             let app_handle = app.handle().clone();
             let window_clone = window.clone();
             window.on_window_event(move |event| {
@@ -211,6 +330,18 @@ pub fn run() {
                             }
                         }
                     }
+                    tauri::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        // Moving to a display with different scaling: the
+                        // OS resizes the window to keep its apparent size.
+                        // That isn't a manual resize, so expect it like a
+                        // snap and keep `pre_snap_size` (it's logical and
+                        // needs no conversion). The geometry saver records
+                        // the new scale on its next tick.
+                        let state = app_handle.state::<AppState>();
+                        if let Ok(mut expected) = state.snap_expected_size.lock() {
+                            *expected = Some((new_inner_size.width, new_inner_size.height));
+                        };
+                    }
                     _ => {}
                 }
             });
@@ -218,6 +349,11 @@ pub fn run() {
             hotkeys::register_hotkeys(app.handle());
             tray::setup_tray(app.handle())?;
 
+            // Started after the startup writes above so they count as
+            // already seen rather than as an external edit.
+            config_watch::spawn_config_watcher(app.handle())
+                .expect("failed to spawn config watcher thread");
+
             // Background update check. Runs on startup after a small
             // grace period so it doesn't fight the webview for network
             // or compete with the initial page load, then repeats every
@@ -290,7 +426,10 @@ pub fn run() {
             // one constant, adjust the other to keep the cadence ~30s.
             // TICK was 1s historically; 3s cuts the wakeup count by 3x
             // (from ~86k/day to ~29k/day) while keeping shutdown latency
-            // imperceptible.
+            // imperceptible. Each tick also checks the connected monitors,
+            // so a dock or undock restores the window's placement for the
+            // new setup within a tick, before a save can record where the
+            // OS moved it.
             let app_handle_geom = app.handle().clone();
             let window_geom = window.clone();
             std::thread::Builder::new()
@@ -299,12 +438,14 @@ pub fn run() {
                     const TICK: Duration = Duration::from_secs(3);
                     const SAVE_EVERY: u32 = 10;
                     let mut ticks: u32 = 0;
+                    let mut monitors = MonitorWatch::default();
                     loop {
                         std::thread::sleep(TICK);
                         let state = app_handle_geom.state::<AppState>();
                         if state.shutdown_flag.load(Ordering::Acquire) {
                             return;
                         }
+                        monitors.check(&window_geom, &state);
                         ticks = ticks.wrapping_add(1);
                         if ticks.is_multiple_of(SAVE_EVERY) {
                             if let Err(e) = persist_window_geometry(&window_geom, &state) {
@@ -319,6 +460,20 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_config,
+            commands::get_config_load_report,
+            commands::get_config_policy,
+            commands::get_config_save_error,
+            commands::list_config_backups,
+            commands::restore_config_backup,
+            commands::export_settings,
+            commands::import_settings,
+            commands::create_profile,
+            commands::rename_profile,
+            commands::delete_profile,
+            commands::switch_profile,
+            commands::save_window_preset,
+            commands::apply_window_preset,
+            commands::delete_window_preset,
             commands::update_config,
             commands::navigate,
             commands::navigate_home,
@@ -343,9 +498,32 @@ pub fn run() {
             commands::set_window_title,
             commands::add_bookmark,
             commands::remove_bookmark,
+            commands::clear_bookmarks,
+            commands::resolve_omnibox_input,
+            commands::suggest_urls,
+            commands::get_recent_urls,
+            commands::search_history,
+            commands::delete_history_entry,
+            commands::clear_history,
+            commands::get_kiosk_status,
+            commands::unlock_kiosk,
+            commands::lock_kiosk,
+            commands::exit_kiosk,
+            commands::rename_bookmark,
+            commands::move_bookmark,
+            commands::reorder_bookmark,
+            commands::set_bookmark_tags,
+            commands::create_bookmark_folder,
+            commands::delete_bookmark_folder,
+            commands::import_bookmarks,
+            commands::export_bookmarks,
             commands::set_crop,
             commands::clear_crop,
             commands::clear_site_data,
+            commands::get_site_status,
+            commands::save_site_settings,
+            commands::forget_site_settings,
+            commands::set_page_zoom,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -378,7 +556,8 @@ pub fn clear_startup_click_through<R: tauri::Runtime>(
 ///
 /// The sequence matches production: mutate config → apply to window →
 /// release mutex → synchronous disk write. Returns `true` if a change
-/// was applied.
+/// was applied. The write is skipped when `config.json` is a newer
+/// FloatView's; that file is left alone for the run.
 pub fn clear_startup_click_through_with<F: FnOnce()>(state: &AppState, apply_to_window: F) -> bool {
     let save_path = state.config_path.clone();
     let snapshot = match state.config.lock() {
@@ -398,7 +577,13 @@ pub fn clear_startup_click_through_with<F: FnOnce()>(state: &AppState, apply_to_
     };
     match snapshot {
         Some(cfg) => {
-            do_save_config(&save_path, &cfg);
+            // Recorded like a saver write, so the saver doesn't take it
+            // for an external edit.
+            if !state.keep_newer_config.load(Ordering::Acquire) {
+                if let Err(e) = write_config(&save_path, &cfg, Some(&state.written_digests)) {
+                    error!("Failed to save config: {}", e);
+                }
+            }
             true
         }
         None => false,
@@ -419,13 +604,21 @@ mod lifecycle_tests {
     use std::fs;
     use std::path::PathBuf;
     use std::sync::atomic::{AtomicBool, Ordering};
-    use std::sync::Mutex;
-    use std::thread::Builder as ThreadBuilder;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
 
     use uuid::Uuid;
 
-    use crate::config::AppConfig;
-    use crate::config_io::{do_save_config, save_config, shutdown};
+    use crate::config::{AppConfig, Bookmark, SiteSettings, CONFIG_VERSION};
+    use crate::config_io::{
+        config_backup_dir, do_save_config, flush_saves, list_config_backups, load_config,
+        load_config_with_migrations, load_layered_config, read_config_backup, reload_config,
+        rotate_config_backups, save_config, shutdown, spawn_config_saver, versioned_copy_path,
+        write_config, ConfigLoadReport, ConfigSource, SaveRequest, WrittenDigests, SAVE_DEBOUNCE,
+    };
+    use crate::config_layers::{ConfigLayers, PolicyFile};
+    use crate::config_watch::ConfigWatcher;
+    use crate::site_settings::SiteSession;
     use crate::state::AppState;
 
     /// Unique temp directory for one test; cleaned up on drop.
@@ -462,23 +655,33 @@ mod lifecycle_tests {
         }
 
         fn with(mutate: impl FnOnce(&mut AppConfig)) -> Self {
+            Self::with_layers(ConfigLayers::default(), mutate)
+        }
+
+        /// A user file from `mutate`, loaded under `layers`.
+        fn with_layers(layers: ConfigLayers, mutate: impl FnOnce(&mut AppConfig)) -> Self {
             let temp = TempDir::new();
             let config_path = temp.config_path();
 
             let mut config = AppConfig::default();
             mutate(&mut config);
             do_save_config(&config_path, &config);
-
-            let (save_tx, save_rx) = std::sync::mpsc::channel::<AppConfig>();
-            let saver_path = config_path.clone();
-            let save_thread = ThreadBuilder::new()
-                .name("floatview-test-saver".to_string())
-                .spawn(move || {
-                    while let Ok(cfg) = save_rx.recv() {
-                        do_save_config(&saver_path, &cfg);
-                    }
-                })
-                .expect("spawn saver");
+            let user_values = layers.user_values(&config);
+            let config = layers.enforce(config);
+
+            let save_error = Arc::new(Mutex::new(None));
+            let written_digests = Arc::new(WrittenDigests::default());
+            let keep_newer_config = Arc::new(AtomicBool::new(false));
+            let (save_tx, save_thread) = spawn_config_saver(
+                "floatview-test-saver",
+                config_path.clone(),
+                SAVE_DEBOUNCE,
+                save_error.clone(),
+                written_digests.clone(),
+                keep_newer_config.clone(),
+                Box::new(|_| {}),
+            )
+            .expect("spawn saver");
 
             let state = AppState {
                 config: Mutex::new(config),
@@ -490,6 +693,18 @@ mod lifecycle_tests {
                 tray: Mutex::new(None),
                 pre_snap_size: Mutex::new(None),
                 snap_expected_size: Mutex::new(None),
+                save_error,
+                load_report: Mutex::new(ConfigLoadReport::new(ConfigSource::File)),
+                written_digests,
+                keep_newer_config,
+                layers,
+                user_values: Mutex::new(user_values),
+                site: Mutex::new(SiteSession::default()),
+                history: Mutex::new(Vec::new()),
+                history_path: crate::history::history_path(&temp.config_path()),
+                kiosk: Mutex::new(Default::default()),
+                local_folders: Mutex::new(Vec::new()),
+                pending_deep_link: Mutex::new(None),
             };
 
             StateFixture { state, temp }
@@ -539,6 +754,110 @@ mod lifecycle_tests {
         assert_eq!(fx.read_disk().window.opacity, 0.2);
     }
 
+    /// Poll until `check` passes; the saver writes on its own schedule.
+    fn wait_for(mut check: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !check() {
+            assert!(Instant::now() < deadline, "timed out waiting for saver");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn saver_coalesces_a_burst_into_one_write() {
+        let fx = StateFixture::with(|c| c.window.opacity = 0.9);
+
+        // A slider drag: many saves well inside one debounce window.
+        for step in 1..=20 {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.window.opacity = 0.9 - f64::from(step) * 0.03;
+            save_config(&fx.state, &cfg);
+        }
+        let expected = fx.read_memory().window.opacity;
+        wait_for(|| fx.read_disk().window.opacity == expected);
+
+        // Had any intermediate value been written, `.bak` would hold it
+        // instead of the state from before the drag.
+        let bak_path = fx.temp.config_path().with_extension("json.bak");
+        let bak: AppConfig =
+            serde_json::from_str(&fs::read_to_string(&bak_path).expect("read bak"))
+                .expect("parse bak");
+        assert_eq!(bak.window.opacity, 0.9);
+    }
+
+    #[test]
+    fn shutdown_writes_pending_batch_without_waiting_for_debounce() {
+        let fx = StateFixture::with(|c| c.window.opacity = 0.9);
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.window.opacity = 0.3;
+            save_config(&fx.state, &cfg);
+        }
+        // The first half of shutdown(), by hand, so its final synchronous
+        // write can't mask whether the saver drained the batch itself.
+        let started = Instant::now();
+        fx.state.save_tx.lock().unwrap().take();
+        let handle = fx.state.save_thread.lock().unwrap().take().unwrap();
+        handle.join().expect("join saver");
+        assert!(
+            started.elapsed() < SAVE_DEBOUNCE,
+            "drain must not sleep out the window"
+        );
+        assert_eq!(fx.read_disk().window.opacity, 0.3);
+    }
+
+    #[test]
+    fn saver_reports_write_failures_and_recovery() {
+        let temp = TempDir::new();
+        let dir = temp.path.join("missing");
+        let path = dir.join("config.json");
+        let save_error = Arc::new(Mutex::new(None));
+        let seen: Arc<Mutex<Vec<Option<String>>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_saver = seen.clone();
+        let (tx, handle) = spawn_config_saver(
+            "floatview-test-saver",
+            path.clone(),
+            Duration::ZERO,
+            save_error.clone(),
+            Arc::new(WrittenDigests::default()),
+            Arc::new(AtomicBool::new(false)),
+            Box::new(move |error| {
+                seen_by_saver
+                    .lock()
+                    .unwrap()
+                    .push(error.map(str::to_string));
+            }),
+        )
+        .expect("spawn saver");
+
+        // The directory doesn't exist, so the write fails.
+        tx.send(SaveRequest::Write(Box::default())).unwrap();
+        wait_for(|| save_error.lock().unwrap().is_some());
+
+        fs::create_dir_all(&dir).expect("create config dir");
+        tx.send(SaveRequest::Write(Box::default())).unwrap();
+        drop(tx);
+        handle.join().expect("join saver");
+
+        assert!(path.exists());
+        assert!(save_error.lock().unwrap().is_none());
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2, "one notification per transition");
+        assert!(seen[0].is_some());
+        assert!(seen[1].is_none());
+    }
+
+    #[test]
+    fn flush_saves_writes_the_pending_batch_before_returning() {
+        let fx = StateFixture::new();
+        let mut config = fx.state.config.lock().unwrap().clone();
+        config.home_url = "https://flushed.test/".to_string();
+        save_config(&fx.state, &config);
+        // Well inside the debounce window: only the flush can have written.
+        flush_saves(&fx.state);
+        assert_eq!(fx.read_disk().home_url, "https://flushed.test/");
+    }
+
     #[test]
     fn shutdown_is_idempotent() {
         let fx = StateFixture::new();
@@ -652,16 +971,21 @@ mod lifecycle_tests {
 
     #[test]
     fn config_save_creates_bak_file_with_prior_state() {
-        // `do_save_config` must copy the prior file to `.bak` before
+        // `write_config` must copy the prior file to `.bak` before
         // writing the new one, so partial writes have a fallback.
-        // Use the synchronous `do_save_config` directly so the test
-        // doesn't race with the saver thread and so `shutdown`'s final
-        // sync save doesn't overwrite the bak we want to inspect.
+        // Write synchronously so the test doesn't race with the saver
+        // thread, recording the digest as the saver would so `shutdown`
+        // doesn't take the write for an external edit.
         let fx = StateFixture::with(|c| c.window.opacity = 0.9);
 
         let mut next = fx.read_memory();
         next.window.opacity = 0.5;
-        do_save_config(&fx.state.config_path, &next);
+        write_config(
+            &fx.state.config_path,
+            &next,
+            Some(&fx.state.written_digests),
+        )
+        .expect("write");
 
         // Drain the saver thread cleanly.
         shutdown(&fx.state);
@@ -710,4 +1034,662 @@ mod lifecycle_tests {
         // will be reaped by the Drop impl and a channel hang-up).
         drop(fx);
     }
+
+    #[test]
+    fn load_config_keeps_pre_migration_copy() {
+        // Synthetic two-step chain (v1 -> v2 -> v3) so the on-disk side
+        // effects are exercised independently of the production chain.
+        fn step(obj: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+            obj.insert("first_run".to_string(), serde_json::Value::Bool(false));
+            Ok(())
+        }
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let mut v1 = serde_json::to_value(AppConfig::default()).unwrap();
+        v1.as_object_mut().unwrap().remove("config_version");
+        v1["window"]["opacity"] = serde_json::Value::from(0.5);
+        let original = serde_json::to_string_pretty(&v1).unwrap();
+        fs::write(&path, &original).expect("write fixture");
+
+        let (config, _) = load_config_with_migrations(&path, &[step, step]);
+
+        assert!(!config.first_run, "migration steps must have run");
+        assert_eq!(config.window.opacity, 0.5);
+        let copy = versioned_copy_path(&path, 1);
+        assert_eq!(
+            fs::read_to_string(&copy).expect("pre-migration copy"),
+            original,
+            "copy must be the untouched pre-migration text"
+        );
+        // Loading never rewrites config.json itself; the first save does.
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn load_config_does_not_copy_when_already_current() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        do_save_config(&path, &AppConfig::default());
+
+        let _ = load_config(&path);
+
+        assert!(!versioned_copy_path(&path, CONFIG_VERSION).exists());
+    }
+
+    #[test]
+    fn load_config_refuses_to_downgrade_newer_file() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let mut newer = serde_json::to_value(AppConfig::default()).unwrap();
+        newer["config_version"] = serde_json::Value::from(CONFIG_VERSION + 5);
+        newer["home_url"] = serde_json::Value::from("https://newer.test/");
+        newer["field_from_the_future"] = serde_json::Value::from(true);
+        let original = serde_json::to_string_pretty(&newer).unwrap();
+        fs::write(&path, &original).expect("write fixture");
+
+        let (config, report) = load_config(&path);
+        assert_eq!(report.source, ConfigSource::File);
+
+        // Known fields survive rather than resetting to defaults...
+        assert_eq!(config.home_url, "https://newer.test/");
+        // ...the in-memory copy is stamped with the schema this build
+        // will actually write...
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        // ...and the newer file is preserved verbatim for the newer app.
+        let copy = versioned_copy_path(&path, CONFIG_VERSION + 5);
+        assert_eq!(fs::read_to_string(copy).expect("newer copy"), original);
+    }
+
+    #[test]
+    fn newer_config_is_byte_identical_after_a_save() {
+        let fx = StateFixture::new();
+        let path = fx.temp.config_path();
+        let mut newer = serde_json::to_value(AppConfig::default()).unwrap();
+        newer["config_version"] = serde_json::Value::from(99);
+        newer["field_from_the_future"] = serde_json::Value::from(true);
+        let original = serde_json::to_string_pretty(&newer).unwrap();
+        fs::write(&path, &original).expect("write fixture");
+
+        let (config, report) = load_config(&path);
+        assert_eq!(report.newer_version, Some(99));
+        assert!(report.message.is_some(), "the UI is told why nothing saves");
+
+        // What setup does with the report.
+        fx.state
+            .keep_newer_config
+            .store(report.newer_version.is_some(), Ordering::Release);
+        let mut changed = config;
+        changed.window.opacity = 0.5;
+        *fx.state.config.lock().unwrap() = changed.clone();
+        save_config(&fx.state, &changed);
+        flush_saves(&fx.state);
+        shutdown(&fx.state);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    /// Write a config with a distinctive opacity to `path`.
+    fn write_config_with_opacity(path: &std::path::Path, opacity: f64) {
+        let mut config = AppConfig::default();
+        config.window.opacity = opacity;
+        fs::write(path, serde_json::to_string_pretty(&config).unwrap()).expect("write config");
+    }
+
+    /// Files in the temp dir whose names contain `needle`.
+    fn files_containing(temp: &TempDir, needle: &str) -> Vec<PathBuf> {
+        fs::read_dir(&temp.path)
+            .expect("read temp dir")
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.to_string_lossy().contains(needle))
+            .collect()
+    }
+
+    #[test]
+    fn load_config_reports_missing_file_as_quiet_defaults() {
+        let temp = TempDir::new();
+        let (config, report) = load_config(&temp.config_path());
+        assert_eq!(report.source, ConfigSource::Defaults);
+        assert!(report.error.is_none(), "first run is not an error");
+        assert!(report.message.is_none());
+        assert_eq!(config.window.opacity, 1.0);
+    }
+
+    #[test]
+    fn load_config_recovers_truncated_file_from_bak() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        write_config_with_opacity(&path.with_extension("json.bak"), 0.35);
+        fs::write(&path, r#"{"window": {"x": 1, "y""#).expect("write truncated config");
+
+        let (config, report) = load_config(&path);
+
+        assert_eq!(config.window.opacity, 0.35);
+        assert_eq!(report.source, ConfigSource::Backup);
+        assert!(report.error.is_some());
+        assert!(report.message.is_some());
+        assert_eq!(report.restored_from, Some(path.with_extension("json.bak")));
+
+        // The broken file is kept aside verbatim, not overwritten...
+        let corrupt = report.corrupt_copy.expect("corrupt file moved aside");
+        assert_eq!(files_containing(&temp, ".corrupt-"), vec![corrupt.clone()]);
+        assert_eq!(
+            fs::read_to_string(&corrupt).unwrap(),
+            r#"{"window": {"x": 1, "y""#
+        );
+        // ...and the recovered config is already back on disk.
+        let on_disk: AppConfig =
+            serde_json::from_str(&fs::read_to_string(&path).expect("config.json rewritten"))
+                .expect("parse rewritten config");
+        assert_eq!(on_disk.window.opacity, 0.35);
+    }
+
+    #[test]
+    fn load_config_falls_back_to_defaults_when_bak_is_also_corrupt() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        fs::write(path.with_extension("json.bak"), "not json").expect("write bak");
+        fs::write(&path, "{").expect("write config");
+
+        let (config, report) = load_config(&path);
+
+        assert_eq!(config.window.opacity, 1.0);
+        assert_eq!(report.source, ConfigSource::Defaults);
+        assert!(report.error.is_some());
+        assert!(report.message.is_some());
+        assert!(report.restored_from.is_none());
+        assert_eq!(files_containing(&temp, ".corrupt-").len(), 1);
+        assert!(!path.exists(), "nothing valid to write back yet");
+    }
+
+    #[test]
+    fn load_config_does_not_clobber_earlier_corrupt_copy() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        fs::write(&path, "first broken").expect("write config");
+        let _ = load_config(&path);
+        fs::write(&path, "second broken").expect("write config");
+        let _ = load_config(&path);
+
+        let mut contents: Vec<String> = files_containing(&temp, ".corrupt-")
+            .iter()
+            .map(|p| fs::read_to_string(p).unwrap())
+            .collect();
+        contents.sort();
+        assert_eq!(contents, vec!["first broken", "second broken"]);
+    }
+
+    #[test]
+    fn do_save_config_takes_at_most_one_dated_backup_per_interval() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        // The first write has no previous file to back up.
+        do_save_config(&path, &AppConfig::default());
+        assert!(list_config_backups(&path).is_empty());
+
+        for opacity in [0.5, 0.6, 0.7] {
+            let mut config = AppConfig::default();
+            config.window.opacity = opacity;
+            do_save_config(&path, &config);
+        }
+        let backups = list_config_backups(&path);
+        assert_eq!(backups.len(), 1, "later saves inside the hour are skipped");
+        // The generation holds the file as it was before the first
+        // rate-limited save, not the latest write.
+        let kept = read_config_backup(&path, &backups[0].name).expect("read backup");
+        assert_eq!(kept.window.opacity, AppConfig::default().window.opacity);
+    }
+
+    #[test]
+    fn rotate_config_backups_prunes_oldest_generations() {
+        use std::time::{Duration, SystemTime};
+
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        do_save_config(&path, &AppConfig::default());
+        let start = SystemTime::now();
+        for hour in 0..13u64 {
+            let now = start + Duration::from_secs(hour * 3_600);
+            assert!(rotate_config_backups(&path, now, Duration::ZERO, 10).is_some());
+        }
+
+        let backups = list_config_backups(&path);
+        assert_eq!(backups.len(), 10);
+        let newest = crate::config_io::file_timestamp(start + Duration::from_secs(12 * 3_600));
+        let oldest_kept = crate::config_io::file_timestamp(start + Duration::from_secs(3 * 3_600));
+        assert_eq!(backups[0].created, newest);
+        assert_eq!(backups[9].created, oldest_kept);
+    }
+
+    #[test]
+    fn list_config_backups_ignores_foreign_files() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let dir = config_backup_dir(&path);
+        fs::create_dir_all(&dir).expect("create backup dir");
+        for name in [
+            "config-20260101-120000.json",
+            "config-notastamp.json",
+            "other-20260101-120000.json",
+            "config-20260101-120000.json.tmp",
+        ] {
+            fs::write(dir.join(name), "{}").expect("write file");
+        }
+        let names: Vec<String> = list_config_backups(&path)
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names, vec!["config-20260101-120000.json"]);
+    }
+
+    #[test]
+    fn read_config_backup_rejects_unlisted_names() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        write_config_with_opacity(&path, 0.5);
+        fs::create_dir_all(config_backup_dir(&path)).expect("create backup dir");
+        assert!(read_config_backup(&path, "../config.json").is_err());
+        assert!(read_config_backup(&path, "config-20260101-120000.json").is_err());
+    }
+
+    #[test]
+    fn load_config_falls_back_to_dated_backup_when_bak_is_corrupt() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let dir = config_backup_dir(&path);
+        fs::create_dir_all(&dir).expect("create backup dir");
+        let mut older = AppConfig::default();
+        older.window.opacity = 0.3;
+        let mut newer = AppConfig::default();
+        newer.window.opacity = 0.6;
+        fs::write(
+            dir.join("config-20260101-100000.json"),
+            serde_json::to_string(&older).unwrap(),
+        )
+        .expect("write backup");
+        fs::write(
+            dir.join("config-20260101-110000.json"),
+            serde_json::to_string(&newer).unwrap(),
+        )
+        .expect("write backup");
+        fs::write(path.with_extension("json.bak"), "{ broken").expect("write bak");
+        fs::write(&path, "{ also broken").expect("write config");
+
+        let (config, report) = load_config(&path);
+        assert_eq!(report.source, ConfigSource::Backup);
+        assert_eq!(config.window.opacity, 0.6, "newest generation wins");
+        assert_eq!(
+            report.restored_from,
+            Some(dir.join("config-20260101-110000.json"))
+        );
+    }
+
+    #[test]
+    fn config_watcher_skips_own_writes_and_reports_external_edits() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let written = Arc::new(WrittenDigests::default());
+        write_config(&path, &AppConfig::default(), Some(&written)).expect("write");
+        let mut watcher = ConfigWatcher::new(path.clone(), written.clone());
+        assert!(!watcher.poll());
+
+        let own = AppConfig {
+            home_url: "https://own.test/".to_string(),
+            ..AppConfig::default()
+        };
+        write_config(&path, &own, Some(&written)).expect("write");
+        assert!(!watcher.poll(), "the app's own save is not an edit");
+
+        let edited = fs::read_to_string(&path)
+            .expect("read config")
+            .replace("https://own.test/", "https://edited.test/");
+        fs::write(&path, edited).expect("edit config");
+        assert!(watcher.poll());
+        assert!(!watcher.poll(), "each edit is reported once");
+        assert_eq!(
+            reload_config(&path).expect("reload").home_url,
+            "https://edited.test/"
+        );
+    }
+
+    #[test]
+    fn config_watcher_skips_saver_writes() {
+        let fx = StateFixture::new();
+        let mut watcher = ConfigWatcher::new(
+            fx.state.config_path.clone(),
+            fx.state.written_digests.clone(),
+        );
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.window.opacity = 0.5;
+            save_config(&fx.state, &cfg);
+        }
+        shutdown(&fx.state);
+        assert_eq!(fx.read_disk().window.opacity, 0.5);
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn external_edit_during_debounce_survives_the_pending_save() {
+        let fx = StateFixture::with(|c| c.home_url = "https://start.test/".to_string());
+        let mut watcher = ConfigWatcher::new(
+            fx.state.config_path.clone(),
+            fx.state.written_digests.clone(),
+        );
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.window.opacity = 0.5;
+            save_config(&fx.state, &cfg);
+        }
+        // A hand edit lands while that save waits out its debounce.
+        let edited = fs::read_to_string(&fx.state.config_path)
+            .unwrap()
+            .replace("https://start.test/", "https://edited.test/");
+        fs::write(&fx.state.config_path, edited).unwrap();
+        flush_saves(&fx.state);
+        assert_eq!(fx.read_disk().home_url, "https://edited.test/");
+        assert_eq!(
+            fx.read_disk().window.opacity,
+            1.0,
+            "the stale save was dropped"
+        );
+
+        // The watcher still reports the edit, and once it's taken in the
+        // app's saves go through again.
+        assert!(watcher.poll());
+        watcher.acknowledge();
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.home_url = "https://edited.test/".to_string();
+            save_config(&fx.state, &cfg);
+        }
+        flush_saves(&fx.state);
+        let disk = fx.read_disk();
+        assert_eq!(
+            (disk.home_url.as_str(), disk.window.opacity),
+            ("https://edited.test/", 0.5)
+        );
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn site_overrides_never_reach_disk() {
+        let fx = StateFixture::new();
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.site_settings.insert(
+                "chat.test".to_string(),
+                SiteSettings {
+                    opacity: Some(0.4),
+                    auto_refresh_minutes: Some(5),
+                    ..SiteSettings::default()
+                },
+            );
+            let mut site = fx.state.site.lock().unwrap();
+            assert!(site.navigate(&mut cfg, "https://chat.test/room"));
+            drop(site);
+            assert_eq!(cfg.window.opacity, 0.4);
+            // A change to a field the site doesn't override is still saved.
+            cfg.home_url = "https://home.test/".to_string();
+            save_config(&fx.state, &cfg);
+        }
+        shutdown(&fx.state);
+
+        let disk = fx.read_disk();
+        assert_eq!(disk.window.opacity, 1.0);
+        assert_eq!(disk.auto_refresh_minutes, 0);
+        assert_eq!(disk.home_url, "https://home.test/");
+        assert_eq!(disk.site_settings["chat.test"].opacity, Some(0.4));
+    }
+
+    #[test]
+    fn reload_config_leaves_a_half_written_file_in_place() {
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        fs::write(&path, "{ \"window\": ").expect("write config");
+
+        assert!(reload_config(&path).is_err());
+        assert_eq!(
+            fs::read_to_string(&path).expect("read config"),
+            "{ \"window\": "
+        );
+        assert!(files_containing(&temp, ".corrupt-").is_empty());
+    }
+
+    #[test]
+    fn layered_load_seeds_first_run_and_locks_existing_files() {
+        let policy: PolicyFile = serde_json::from_value(serde_json::json!({
+            "defaults": {
+                "home_url": "https://jellyfin.lan/",
+                "bookmarks": ["https://grafana.lan/"]
+            },
+            "locked": ["home_url"]
+        }))
+        .expect("policy");
+        let layers = ConfigLayers::from_parts(policy, Vec::new());
+        let temp = TempDir::new();
+        let path = temp.config_path();
+
+        let (config, _, report) = load_layered_config(&path, &layers);
+        assert_eq!(report.source, ConfigSource::Defaults);
+        assert_eq!(config.home_url, "https://jellyfin.lan/");
+        assert_eq!(config.bookmarks[0].url, "https://grafana.lan/");
+
+        let user = AppConfig {
+            home_url: "https://elsewhere.test/".to_string(),
+            bookmarks: vec![Bookmark::new(
+                "https://mine.test/".to_string(),
+                String::new(),
+            )],
+            ..AppConfig::default()
+        };
+        do_save_config(&path, &user);
+        let (config, _, report) = load_layered_config(&path, &layers);
+        assert_eq!(report.source, ConfigSource::File);
+        assert_eq!(config.home_url, "https://jellyfin.lan/", "locked");
+        assert_eq!(config.bookmarks.len(), 1, "only seeded");
+        assert_eq!(config.bookmarks[0].url, "https://mine.test/");
+    }
+
+    /// An environment override holds for the run; saves keep the user's
+    /// own value on disk.
+    #[test]
+    fn saves_keep_the_user_value_under_an_env_override() {
+        let layers = ConfigLayers::from_parts(
+            PolicyFile::default(),
+            vec![("window.opacity".to_string(), serde_json::json!(0.8))],
+        );
+        let fx = StateFixture::with_layers(layers, |c| c.window.opacity = 0.4);
+        assert_eq!(fx.read_memory().window.opacity, 0.8);
+
+        {
+            let mut cfg = fx.state.config.lock().unwrap();
+            cfg.home_url = "https://jellyfin.lan/".to_string();
+            save_config(&fx.state, &cfg);
+        }
+        shutdown(&fx.state);
+
+        let disk = fx.read_disk();
+        assert_eq!(disk.window.opacity, 0.4);
+        assert_eq!(disk.home_url, "https://jellyfin.lan/");
+        assert_eq!(fx.read_memory().window.opacity, 0.8);
+
+        let (_, user, _) = load_layered_config(&fx.temp.config_path(), &fx.state.layers);
+        let mut restored = fx.read_memory();
+        user.restore(&mut restored);
+        assert_eq!(restored.window.opacity, 0.4);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn scheme_handler_entry_is_written_once_per_executable() {
+        use crate::deep_link::{desktop_entry, write_desktop_entry, DESKTOP_FILE_NAME};
+        let temp = TempDir::new();
+        let dir = temp.path.join("applications");
+        let entry = dir.join(DESKTOP_FILE_NAME);
+
+        assert!(write_desktop_entry(&dir, "/opt/floatview/floatview").expect("write"));
+        assert_eq!(
+            fs::read_to_string(&entry).expect("read"),
+            desktop_entry("/opt/floatview/floatview")
+        );
+        assert!(!write_desktop_entry(&dir, "/opt/floatview/floatview").expect("rewrite"));
+        assert!(write_desktop_entry(&dir, "/home/u/FloatView.AppImage").expect("moved"));
+        assert!(fs::read_to_string(&entry)
+            .expect("read")
+            .contains("\"/home/u/FloatView.AppImage\" %u"));
+    }
+
+    #[test]
+    fn local_files_need_the_setting_and_grant_their_folder() {
+        use crate::local_media::{is_granted, local_file_path};
+        let fixture = StateFixture::new();
+        let media = fixture.temp.path.join("films");
+        fs::create_dir_all(&media).expect("create media dir");
+        let clip = media.join("clip.mp4");
+        fs::write(&clip, b"0123456789").expect("write clip");
+        let input = clip.to_string_lossy().into_owned();
+
+        assert_eq!(
+            crate::ops::open_local_file(&fixture.state, "https://example.com/a.mp4"),
+            Ok(None)
+        );
+        assert!(crate::ops::open_local_file(&fixture.state, &input).is_err());
+        assert!(fixture.state.local_folders.lock().unwrap().is_empty());
+
+        fixture.state.config.lock().unwrap().allow_local_files = true;
+        assert!(crate::ops::open_local_file(&fixture.state, &format!("{input}.missing")).is_err());
+        let url = crate::ops::open_local_file(&fixture.state, &input)
+            .expect("open")
+            .expect("local");
+        let real = clip.canonicalize().expect("canonicalize");
+        assert_eq!(local_file_path(&url).as_deref(), Some(real.as_path()));
+        let folders = fixture.state.local_folders.lock().unwrap();
+        assert!(is_granted(&folders, &real));
+        assert!(!is_granted(&folders, &fixture.temp.config_path()));
+    }
+
+    #[test]
+    fn local_file_responses_honor_ranges() {
+        use crate::local_media::file_response;
+        use tauri::http::{header, StatusCode};
+        let temp = TempDir::new();
+        let clip = temp.path.join("clip.webm");
+        fs::write(&clip, b"0123456789").expect("write clip");
+
+        let whole = file_response(&clip, None);
+        assert_eq!(whole.status(), StatusCode::OK);
+        assert_eq!(whole.headers()[header::CONTENT_TYPE], "video/webm");
+        assert_eq!(whole.body(), b"0123456789");
+
+        let part = file_response(&clip, Some("bytes=2-4"));
+        assert_eq!(part.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(part.headers()[header::CONTENT_RANGE], "bytes 2-4/10");
+        assert_eq!(part.body(), b"234");
+
+        let past = file_response(&clip, Some("bytes=10-"));
+        assert_eq!(past.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(past.headers()[header::CONTENT_RANGE], "bytes */10");
+
+        let missing = file_response(&temp.path.join("nope.mp4"), None);
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn validate_config_cli_exit_codes() {
+        use crate::cli::{run_cli, EXIT_INVALID, EXIT_USAGE, EXIT_VALID};
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let arg = |p: &std::path::Path| {
+            vec![
+                "--validate-config".to_string(),
+                p.to_string_lossy().into_owned(),
+            ]
+        };
+
+        do_save_config(
+            &path,
+            &crate::config_io::sanitize_config(AppConfig::default()),
+        );
+        assert_eq!(run_cli(&arg(&path)), Some(EXIT_VALID));
+
+        let mut config = AppConfig::default();
+        config.hotkeys.toggle_on_top = "nope<".to_string();
+        fs::write(&path, serde_json::to_string(&config).unwrap()).expect("write config");
+        assert_eq!(run_cli(&arg(&path)), Some(EXIT_INVALID));
+
+        assert_eq!(
+            run_cli(&arg(&temp.path.join("missing.json"))),
+            Some(EXIT_USAGE)
+        );
+        assert_eq!(
+            run_cli(&["--validate-config".to_string()]),
+            Some(EXIT_USAGE)
+        );
+        assert_eq!(run_cli(&[]), None);
+        // A deep link starts the app normally and is handled there.
+        assert_eq!(run_cli(&["floatview://open".to_string()]), None);
+    }
+
+    #[test]
+    fn v2_recent_urls_come_back_from_history() {
+        use crate::history::{
+            history_path, import_urls, load_history, recent_urls, write_history, MAX_RECENT_URLS,
+        };
+        let temp = TempDir::new();
+        let path = temp.config_path();
+        let mut v2 = serde_json::to_value(AppConfig::default()).unwrap();
+        v2["config_version"] = serde_json::Value::from(2);
+        v2["recent_urls"] =
+            serde_json::json!(["https://b.test/", "https://a.test/x", "javascript:x"]);
+        fs::write(&path, v2.to_string()).expect("write fixture");
+
+        // What setup does with the load report.
+        let (_, report) = load_config(&path);
+        let history_file = history_path(&path);
+        let mut history = load_history(&history_file);
+        assert!(import_urls(&mut history, &report.legacy_recent_urls, 100));
+        write_history(&history_file, &history);
+
+        assert_eq!(
+            recent_urls(&load_history(&history_file), MAX_RECENT_URLS),
+            ["https://b.test/", "https://a.test/x"]
+        );
+        // A second start before the config is saved adds nothing twice.
+        let (_, report) = load_config(&path);
+        let mut history = load_history(&history_file);
+        assert!(!import_urls(&mut history, &report.legacy_recent_urls, 200));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn history_appends_visits_and_compacts_on_load() {
+        use crate::history::{load_history, note_title, note_visit};
+        let fx = StateFixture::new();
+        note_visit(&fx.state, "https://a.test/").unwrap();
+        note_visit(&fx.state, "https://a.test/").unwrap();
+        note_title(&fx.state, "https://a.test/", "Alpha").unwrap();
+        note_visit(&fx.state, "https://b.test/").unwrap();
+        note_visit(&fx.state, "https://a.test/").unwrap();
+
+        let path = &fx.state.history_path;
+        let lines = |p: &std::path::Path| fs::read_to_string(p).unwrap().lines().count();
+        // Polling the same page adds nothing; the title and each visit do.
+        assert_eq!(lines(path), 4);
+
+        let loaded = load_history(path);
+        assert_eq!(loaded.len(), 2);
+        let a = loaded.iter().find(|e| e.url == "https://a.test/").unwrap();
+        assert_eq!((a.title.as_str(), a.visit_count), ("Alpha", 2));
+        assert_eq!(lines(path), 2, "superseded lines are compacted away");
+        assert_eq!(load_history(path), loaded);
+    }
+    #[test]
+    fn locked_kiosk_refuses_settings_edits_until_unlocked() {
+        let fx = StateFixture::with(|c| c.kiosk.enabled = true);
+        let config = fx.read_memory();
+        assert!(crate::ops::ensure_settings_unlocked(&fx.state, &config).is_err());
+
+        fx.state.kiosk.lock().unwrap().unlocked = true;
+        assert!(crate::ops::ensure_settings_unlocked(&fx.state, &config).is_ok());
+    }
 }
@@ -0,0 +1,443 @@
+//! Local files and bare media URLs.
+//!
+//! With `allow_local_files` on, a path or `file://` URL typed into the URL
+//! bar opens through the `floatview-file` protocol instead of raw
+//! `file://`, which the webview would either refuse or open with access
+//! to the whole disk. [`serve`] answers only for files under a folder the
+//! user opened something from this run ([`grant_folder`]), so a page
+//! can't read arbitrary paths by guessing URLs; a video's subtitles and
+//! an HLS playlist's segments next to it still load.
+//!
+//! A URL that points straight at a media file (`.mp4`, `.webm`, `.m3u8`,
+//! …) opens in the built-in player page (`src/player.html`) instead of
+//! the webview's bare media document, so the control strip's media
+//! hotkeys and zoom-to-video find a normal `<video>` element. The player
+//! page counts as the media URL it plays: the kiosk allowlist, history
+//! and bookmarks all see that URL.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime};
+use url::Url;
+
+use crate::state::AppState;
+
+/// Custom protocol local files are served through.
+pub const SCHEME: &str = "floatview-file";
+
+/// Where [`SCHEME`] URLs point. WebView2 only routes custom protocols
+/// through `http://<scheme>.localhost`.
+#[cfg(windows)]
+const LOCAL_ORIGIN: &str = "http://floatview-file.localhost";
+#[cfg(not(windows))]
+const LOCAL_ORIGIN: &str = "floatview-file://localhost";
+
+/// Where the app's own pages are served from.
+#[cfg(windows)]
+const APP_ORIGIN: &str = "http://tauri.localhost";
+#[cfg(not(windows))]
+const APP_ORIGIN: &str = "tauri://localhost";
+
+/// Path of the built-in player page under the app origin.
+pub const PLAYER_PATH: &str = "/player.html";
+
+/// Extensions opened in the player page rather than as a bare document.
+pub const MEDIA_EXTENSIONS: &[&str] = &["mp4", "m4v", "webm", "ogv", "mov", "m3u8"];
+
+/// Most bytes one range response carries. Players ask for the next range
+/// as they go, so a long video never sits in memory whole.
+pub const MAX_RANGE_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// Largest file sent whole to a request without a `Range` header; larger
+/// ones get their first [`MAX_RANGE_CHUNK`] as a range response.
+pub const MAX_WHOLE_FILE: u64 = 32 * 1024 * 1024;
+
+/// Most folders granted in one run; the oldest grant is dropped first.
+pub const MAX_GRANTED_FOLDERS: usize = 50;
+
+/// Whether `url` is served by [`SCHEME`].
+pub fn is_local_file_url(url: &Url) -> bool {
+    url.scheme() == SCHEME || url.host_str() == Some("floatview-file.localhost")
+}
+
+/// The [`SCHEME`] URL for an absolute path.
+pub fn local_file_url(path: &Path) -> Result<String, String> {
+    let file = Url::from_file_path(path).map_err(|_| "Not an absolute path".to_string())?;
+    Ok(format!("{LOCAL_ORIGIN}{}", file.path()))
+}
+
+/// The path a [`SCHEME`] URL names, if it is one.
+pub fn local_file_path(url: &Url) -> Option<PathBuf> {
+    if !is_local_file_url(url) {
+        return None;
+    }
+    Url::parse(&format!("file://{}", url.path()))
+        .ok()?
+        .to_file_path()
+        .ok()
+}
+
+/// The file URL bar input names, when it names one: an absolute path, a
+/// `file://` URL or a [`SCHEME`] URL. `None` for everything else, which
+/// is left to the usual URL/search rules.
+pub fn local_input_path(input: &str) -> Option<PathBuf> {
+    let input = input.trim();
+    if let Ok(url) = Url::parse(input) {
+        if url.scheme() == "file" {
+            return url.to_file_path().ok();
+        }
+        if let Some(path) = local_file_path(&url) {
+            return Some(path);
+        }
+    }
+    let path = Path::new(input);
+    path.is_absolute().then(|| path.to_path_buf())
+}
+
+/// Resolve a path from [`local_input_path`] to the file on disk, with
+/// symlinks and `..` resolved so grants compare real locations.
+pub fn resolve_local_file(path: &Path) -> Result<PathBuf, String> {
+    let real = path
+        .canonicalize()
+        .map_err(|_| format!("No such file: {}", path.display()))?;
+    if !real.is_file() {
+        return Err(format!("Not a file: {}", real.display()));
+    }
+    Ok(real)
+}
+
+/// Let [`serve`] answer for `file`'s folder. `file` is a resolved path.
+pub fn grant_folder(granted: &mut Vec<PathBuf>, file: &Path) {
+    let Some(folder) = file.parent() else {
+        return;
+    };
+    if is_granted(granted, file) {
+        return;
+    }
+    granted.retain(|g| !g.starts_with(folder));
+    granted.push(folder.to_path_buf());
+    if granted.len() > MAX_GRANTED_FOLDERS {
+        granted.remove(0);
+    }
+}
+
+/// Whether `path` (resolved) is inside a granted folder.
+pub fn is_granted(granted: &[PathBuf], path: &Path) -> bool {
+    granted.iter().any(|folder| path.starts_with(folder))
+}
+
+/// Whether `url` names a media file by its extension: http(s) or
+/// [`SCHEME`], and not the player page itself.
+pub fn is_media_url(url: &Url) -> bool {
+    if !matches!(url.scheme(), "http" | "https") && !is_local_file_url(url) {
+        return false;
+    }
+    let name = url.path_segments().and_then(|mut s| s.next_back());
+    name.and_then(|name| name.rsplit_once('.'))
+        .is_some_and(|(_, ext)| {
+            MEDIA_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// The player page URL playing `media`.
+pub fn player_url(media: &str) -> String {
+    let src: String = url::form_urlencoded::byte_serialize(media.as_bytes()).collect();
+    format!("{APP_ORIGIN}{PLAYER_PATH}?src={src}")
+}
+
+/// The media URL the player page at `url` plays, if `url` is the player.
+pub fn player_source(url: &Url) -> Option<String> {
+    let is_app = url.scheme() == "tauri" || url.host_str() == Some("tauri.localhost");
+    if !is_app || url.path() != PLAYER_PATH {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "src")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// The page that shows `url`: the player for a media URL, else `url`.
+pub fn page_for(url: Url) -> Url {
+    if !is_media_url(&url) {
+        return url;
+    }
+    Url::parse(&player_url(url.as_str())).unwrap_or(url)
+}
+
+/// What the window is showing at `url`, for kiosk checks: the media on
+/// the player page, else `url`.
+pub fn shown_url(url: &Url) -> Url {
+    player_source(url)
+        .and_then(|src| Url::parse(&src).ok())
+        .unwrap_or_else(|| url.clone())
+}
+
+/// `Content-Type` for a file by extension.
+pub fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "ogv" => "video/ogg",
+        "mov" => "video/quicktime",
+        "m3u8" => "application/vnd.apple.mpegurl",
+        "ts" => "video/mp2t",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "vtt" => "text/vtt",
+        "srt" => "text/plain; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Parse a single-range `Range` header against a file of `len` bytes into
+/// inclusive `(start, end)`, with `end` cut to [`MAX_RANGE_CHUNK`] bytes
+/// past `start`. `None` when the range can't be satisfied or isn't one
+/// this serves (multiple ranges, other units).
+pub fn parse_range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = header.trim().strip_prefix("bytes=")?.trim();
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    if start > end || start >= len {
+        return None;
+    }
+    Some((start, end.min(start + MAX_RANGE_CHUNK - 1)))
+}
+
+/// Read `path` for a request with the given `Range` header: 206 with the
+/// range, 200 with the whole file, or 416 for a range past the end.
+pub fn file_response(path: &Path, range: Option<&str>) -> Response<Vec<u8>> {
+    let read = || -> std::io::Result<Response<Vec<u8>>> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let range = match range {
+            Some(header) => match parse_range(header, len) {
+                Some(range) => Some(range),
+                None => {
+                    return Ok(Response::builder()
+                        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                        .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                        .body(Vec::new())
+                        .unwrap_or_default())
+                }
+            },
+            None if len > MAX_WHOLE_FILE => Some((0, MAX_RANGE_CHUNK - 1)),
+            None => None,
+        };
+        let builder = Response::builder()
+            .header(header::CONTENT_TYPE, content_type(path))
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(header::CACHE_CONTROL, "no-store");
+        Ok(match range {
+            Some((start, end)) => {
+                let mut body = vec![0; (end - start + 1) as usize];
+                file.seek(SeekFrom::Start(start))?;
+                file.read_exact(&mut body)?;
+                builder
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+                    .body(body)
+            }
+            None => {
+                let mut body = Vec::with_capacity(len as usize);
+                file.read_to_end(&mut body)?;
+                builder.status(StatusCode::OK).body(body)
+            }
+        }
+        .unwrap_or_default())
+    };
+    read().unwrap_or_else(|_| status_response(StatusCode::NOT_FOUND))
+}
+
+fn status_response(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .body(Vec::new())
+        .unwrap_or_default()
+}
+
+/// The [`SCHEME`] protocol handler. Refuses everything while
+/// `allow_local_files` is off, and any file outside the granted folders.
+/// Blocks on disk reads, so it runs on a worker thread rather than the
+/// main one.
+pub fn serve<R: Runtime>(app: &AppHandle<R>, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let state = app.state::<AppState>();
+    let allowed = state.config.lock().is_ok_and(|c| c.allow_local_files);
+    if !allowed {
+        return status_response(StatusCode::FORBIDDEN);
+    }
+    let Some(path) = Url::parse(&request.uri().to_string())
+        .ok()
+        .and_then(|url| local_file_path(&url))
+    else {
+        return status_response(StatusCode::BAD_REQUEST);
+    };
+    let Ok(real) = path.canonicalize() else {
+        return status_response(StatusCode::NOT_FOUND);
+    };
+    let granted = state
+        .local_folders
+        .lock()
+        .is_ok_and(|granted| is_granted(&granted, &real));
+    if !granted {
+        tracing::warn!(path = %real.display(), "Refused a local file outside the opened folders");
+        return status_response(StatusCode::FORBIDDEN);
+    }
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    file_response(&real, range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_urls_are_recognized_by_extension() {
+        let media = |u: &str| is_media_url(&Url::parse(u).unwrap());
+        assert!(media("https://cdn.test/clip.mp4"));
+        assert!(media("https://cdn.test/live/index.M3U8?token=1"));
+        assert!(media("http://nas.lan/films/a.webm"));
+        assert!(media(&local_file_url(Path::new("/videos/a.mp4")).unwrap()));
+        assert!(!media("https://cdn.test/clip.mp4/page"));
+        assert!(!media("https://cdn.test/watch?v=clip.mp4"));
+        assert!(!media("https://cdn.test/"));
+        assert!(!media("ftp://cdn.test/clip.mp4"));
+        assert!(!media(&player_url("https://cdn.test/clip.mp4")));
+    }
+
+    #[test]
+    fn player_url_round_trips_the_source() {
+        let src = "https://cdn.test/a b.mp4?x=1&y=2#t=10";
+        let player = Url::parse(&player_url(src)).unwrap();
+        assert_eq!(player_source(&player).as_deref(), Some(src));
+        assert_eq!(
+            shown_url(&player).as_str(),
+            Url::parse(src).unwrap().as_str()
+        );
+        let media = Url::parse(src).unwrap();
+        assert_eq!(
+            player_source(&page_for(media.clone())).as_deref(),
+            Some(media.as_str())
+        );
+        let site = Url::parse("https://cdn.test/watch").unwrap();
+        assert_eq!(page_for(site.clone()), site);
+        let page = Url::parse("https://cdn.test/player.html?src=x").unwrap();
+        assert_eq!(player_source(&page), None);
+        assert_eq!(shown_url(&page), page);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_urls_round_trip_paths() {
+        let path = Path::new("/home/me/Films/My Clip #1.mp4");
+        let url = local_file_url(path).unwrap();
+        assert_eq!(
+            url,
+            "floatview-file://localhost/home/me/Films/My%20Clip%20%231.mp4"
+        );
+        let parsed = Url::parse(&url).unwrap();
+        assert!(is_local_file_url(&parsed));
+        assert_eq!(local_file_path(&parsed).as_deref(), Some(path));
+        assert!(local_file_url(Path::new("relative.mp4")).is_err());
+
+        assert_eq!(
+            local_input_path(" /tmp/a.mp4 "),
+            Some(PathBuf::from("/tmp/a.mp4"))
+        );
+        assert_eq!(
+            local_input_path("file:///tmp/a%20b.mp4"),
+            Some(PathBuf::from("/tmp/a b.mp4"))
+        );
+        assert_eq!(local_input_path(&url), Some(path.to_path_buf()));
+        assert_eq!(local_input_path("example.com/a.mp4"), None);
+        assert_eq!(local_input_path("https://example.com/a.mp4"), None);
+    }
+
+    #[test]
+    fn grants_cover_subfolders_and_are_capped() {
+        let mut granted = Vec::new();
+        grant_folder(&mut granted, Path::new("/m/films/action/a.mp4"));
+        grant_folder(&mut granted, Path::new("/m/films/b.mp4"));
+        assert_eq!(granted, vec![PathBuf::from("/m/films")]);
+        assert!(is_granted(&granted, Path::new("/m/films/action/a.vtt")));
+        assert!(!is_granted(&granted, Path::new("/m/filmsx/a.mp4")));
+        assert!(!is_granted(&granted, Path::new("/m/a.mp4")));
+
+        for i in 0..=MAX_GRANTED_FOLDERS {
+            grant_folder(&mut granted, &PathBuf::from(format!("/d{i}/f.mp4")));
+        }
+        assert_eq!(granted.len(), MAX_GRANTED_FOLDERS);
+        assert!(!is_granted(&granted, Path::new("/d0/f.mp4")));
+    }
+
+    #[test]
+    fn parse_range_handles_each_form() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=990-2000", 1000), Some((990, 999)));
+        assert_eq!(
+            parse_range("bytes=0-", 10 * MAX_RANGE_CHUNK),
+            Some((0, MAX_RANGE_CHUNK - 1))
+        );
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=50-10", 1000), None);
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+    }
+
+    #[test]
+    fn content_type_follows_extension() {
+        assert_eq!(content_type(Path::new("/a/B.MP4")), "video/mp4");
+        assert_eq!(
+            content_type(Path::new("/a/live.m3u8")),
+            "application/vnd.apple.mpegurl"
+        );
+        assert_eq!(
+            content_type(Path::new("/a/noext")),
+            "application/octet-stream"
+        );
+    }
+}
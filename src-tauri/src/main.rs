@@ -3,5 +3,9 @@
 //! FloatView binary shim. All behavior lives in `lib.rs`.
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = floatview::cli::run_cli(&args) {
+        std::process::exit(code);
+    }
     floatview::run()
 }
@@ -0,0 +1,226 @@
+//! URL bar input: decide whether the text is an address or a search, and
+//! build the search URL from the configured engines.
+//!
+//! The rules, in order:
+//!
+//! 1. `<keyword> <query>`, where the keyword names an engine in
+//!    `search_engines`, searches that engine (`yt cats`).
+//! 2. Text without spaces that has a scheme, a dot or a colon
+//!    (`example.com`, `192.168.1.20:8096`, `localhost:3000`), or is
+//!    `localhost`, opens as a URL if [`normalize_url`] accepts it.
+//! 3. Anything else searches `default_search_engine`.
+//!
+//! Search URLs are an engine's `url` with `%s` replaced by the query,
+//! percent-encoded the way `encodeURIComponent` does it.
+
+use crate::config::SearchEngine;
+use crate::urls::normalize_url;
+
+/// Placeholder for the encoded query in a [`SearchEngine::url`].
+pub const QUERY_PLACEHOLDER: &str = "%s";
+
+pub const MAX_SEARCH_ENGINES: usize = 20;
+pub const MAX_ENGINE_NAME_LEN: usize = 40;
+pub const MAX_ENGINE_KEYWORD_LEN: usize = 16;
+
+/// Keyword of the default engine in [`default_search_engines`].
+pub const DEFAULT_SEARCH_ENGINE: &str = "ddg";
+
+/// Engines a new config starts with. DuckDuckGo comes first and is the
+/// default, as it was before engines were configurable.
+pub fn default_search_engines() -> Vec<SearchEngine> {
+    [
+        ("DuckDuckGo", "ddg", "https://duckduckgo.com/?q=%s"),
+        ("Google", "g", "https://www.google.com/search?q=%s"),
+        (
+            "YouTube",
+            "yt",
+            "https://www.youtube.com/results?search_query=%s",
+        ),
+        (
+            "Wikipedia",
+            "w",
+            "https://en.wikipedia.org/w/index.php?search=%s",
+        ),
+    ]
+    .into_iter()
+    .map(|(name, keyword, url)| SearchEngine {
+        name: name.to_string(),
+        keyword: keyword.to_string(),
+        url: url.to_string(),
+    })
+    .collect()
+}
+
+/// Resolve URL bar input to the URL to open, per the module rules.
+/// `default` is a keyword; when it names no engine the first engine is
+/// used, and with no engines at all, DuckDuckGo.
+pub fn resolve_omnibox_input(
+    input: &str,
+    engines: &[SearchEngine],
+    default: &str,
+) -> Result<String, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Nothing to open".to_string());
+    }
+
+    if let Some((keyword, query)) = input.split_once(char::is_whitespace) {
+        let query = query.trim();
+        if let Some(engine) = find_engine(engines, keyword).filter(|_| !query.is_empty()) {
+            return search_url(engine, query);
+        }
+    }
+
+    if looks_like_url(input) {
+        if let Ok(url) = normalize_url(input) {
+            return Ok(url);
+        }
+    }
+
+    match find_engine(engines, default).or(engines.first()) {
+        Some(engine) => search_url(engine, input),
+        None => search_url(&default_search_engines()[0], input),
+    }
+}
+
+/// The engine whose keyword is `keyword`, ignoring case.
+pub fn find_engine<'a>(engines: &'a [SearchEngine], keyword: &str) -> Option<&'a SearchEngine> {
+    engines
+        .iter()
+        .find(|e| !e.keyword.is_empty() && e.keyword.eq_ignore_ascii_case(keyword))
+}
+
+/// `engine.url` with the query substituted, as a normalized http(s) URL.
+pub fn search_url(engine: &SearchEngine, query: &str) -> Result<String, String> {
+    if !engine.url.contains(QUERY_PLACEHOLDER) {
+        return Err(format!("Search URL for {} has no %s", engine.name));
+    }
+    normalize_url(&engine.url.replace(QUERY_PLACEHOLDER, &encode_query(query)))
+}
+
+/// Trim and validate one engine for the sanitizer: a non-empty name (cut
+/// to [`MAX_ENGINE_NAME_LEN`]), a lowercase keyword of one word, and a
+/// `url` that yields an http(s) URL. The error is the correction reason.
+pub fn clean_search_engine(engine: &SearchEngine) -> Result<SearchEngine, &'static str> {
+    let name: String = engine
+        .name
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .chars()
+        .take(MAX_ENGINE_NAME_LEN)
+        .collect();
+    if name.is_empty() {
+        return Err("search engine needs a name");
+    }
+    let keyword = engine.keyword.trim().to_lowercase();
+    if keyword.is_empty()
+        || keyword.chars().count() > MAX_ENGINE_KEYWORD_LEN
+        || keyword.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err("search keyword must be one short word");
+    }
+    let clean = SearchEngine {
+        name,
+        keyword,
+        url: engine.url.trim().to_string(),
+    };
+    search_url(&clean, "test").map_err(|_| "search URL must be http(s) and contain %s")?;
+    Ok(clean)
+}
+
+fn looks_like_url(input: &str) -> bool {
+    if input.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let lower = input.to_ascii_lowercase();
+    lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || lower == "localhost"
+        || input.contains('.')
+        || input.contains(':')
+}
+
+/// Percent-encode everything but `encodeURIComponent`'s unreserved set.
+fn encode_query(query: &str) -> String {
+    let mut out = String::with_capacity(query.len());
+    for byte in query.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table-driven check against [`crate::url_fixtures::OMNIBOX_CASES`],
+    /// resolved with the default engines plus a keyword-only LAN engine.
+    #[test]
+    fn resolve_matches_shared_truth_table() {
+        let mut engines = default_search_engines();
+        engines.push(SearchEngine {
+            name: "Jellyfin".to_string(),
+            keyword: "jf".to_string(),
+            url: "http://192.168.1.20:8096/web/#/search.html?query=%s".to_string(),
+        });
+        for (i, (input, expected)) in crate::url_fixtures::OMNIBOX_CASES.iter().enumerate() {
+            let got = resolve_omnibox_input(input, &engines, DEFAULT_SEARCH_ENGINE);
+            assert_eq!(
+                got.as_deref(),
+                Ok(*expected),
+                "case #{i}: resolve_omnibox_input({input:?})"
+            );
+        }
+    }
+
+    #[test]
+    fn default_engine_falls_back_when_missing() {
+        let engines = default_search_engines();
+        assert_eq!(
+            resolve_omnibox_input("cats", &engines, "g").unwrap(),
+            "https://www.google.com/search?q=cats"
+        );
+        assert_eq!(
+            resolve_omnibox_input("cats", &engines[1..], "nope").unwrap(),
+            "https://www.google.com/search?q=cats",
+            "an unknown default uses the first engine"
+        );
+        assert_eq!(
+            resolve_omnibox_input("cats", &[], "ddg").unwrap(),
+            "https://duckduckgo.com/?q=cats"
+        );
+        assert!(resolve_omnibox_input("   ", &engines, "ddg").is_err());
+    }
+
+    #[test]
+    fn clean_search_engine_checks_each_field() {
+        let engine = |name: &str, keyword: &str, url: &str| SearchEngine {
+            name: name.to_string(),
+            keyword: keyword.to_string(),
+            url: url.to_string(),
+        };
+        assert_eq!(
+            clean_search_engine(&engine(" Docs ", " RS ", "https://docs.rs/?q=%s")),
+            Ok(engine("Docs", "rs", "https://docs.rs/?q=%s"))
+        );
+        assert!(clean_search_engine(&engine("", "rs", "https://docs.rs/?q=%s")).is_err());
+        assert!(clean_search_engine(&engine("Docs", "r s", "https://docs.rs/?q=%s")).is_err());
+        assert!(clean_search_engine(&engine("Docs", "rs", "https://docs.rs/")).is_err());
+    }
+
+    #[test]
+    fn search_url_requires_placeholder_and_http() {
+        let mut engine = default_search_engines().remove(0);
+        engine.url = "https://duckduckgo.com/".to_string();
+        assert!(search_url(&engine, "x").is_err());
+        engine.url = "javascript:alert('%s')".to_string();
+        assert!(search_url(&engine, "x").is_err());
+    }
+}
@@ -15,15 +15,35 @@
 //! save, and emit. Callers can layer best-effort vs. strict semantics
 //! on top without re-implementing the pipeline.
 
+use std::time::Instant;
+
 use serde::Serialize;
-use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewWindow};
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow};
+use tracing::warn;
 use url::Url;
 
-use crate::config::clamp_opacity;
-use crate::config_io::save_config;
+use crate::config::{clamp_opacity, AppConfig, MonitorInfo, WindowPreset};
+use crate::config_io::{persist_last_url, persisted_config, save_config, ConfigCorrection};
+use crate::deep_link::DeepLink;
+use crate::kiosk::{self, KioskStatus};
+use crate::layout;
+use crate::local_media;
 use crate::opacity;
-use crate::state::{update_tray_always_on_top, update_tray_locked, AppState};
+use crate::profiles::{self, active_profile_name, profile_names};
+use crate::site_settings::{
+    capture_site_settings, clamp_zoom, normalize_site_key, site_key_for_url, MAX_SITE_SETTINGS,
+};
+use crate::state::{
+    update_tray_always_on_top, update_tray_locked, update_tray_presets, update_tray_profiles,
+    AppState,
+};
 use crate::urls::{normalize_url, DEFAULT_HOME_URL};
+use crate::window_presets::{self, preset_hotkeys, preset_names};
+use crate::window_state::{
+    apply_window_state, clamp_scale_factor, config_size, current_monitors, monitor_for,
+    next_monitor, normalize_startup_window_size, persist_window_geometry, relative_rect,
+    to_logical, to_physical, Rect, MIN_WINDOW_SIZE,
+};
 
 /// Resolve the main webview window, returning a descriptive error instead
 /// of `None` so callers can propagate the failure upward uniformly.
@@ -32,6 +52,27 @@ pub fn main_window<R: Runtime>(app: &AppHandle<R>) -> Result<WebviewWindow<R>, S
         .ok_or_else(|| "main window not found".to_string())
 }
 
+/// `config` as the webview may see it: without the kiosk `pin_hash`.
+/// Pages share the injected script's event bus, and short PINs are quick
+/// to brute-force from a hash, so everything handed to JS (`get_config`,
+/// [`emit_config`]) goes through here.
+pub fn page_config(config: &AppConfig) -> AppConfig {
+    let mut config = config.clone();
+    config.kiosk.pin_hash = None;
+    config
+}
+
+/// Emit `event` (`config-changed`, `config-reloaded`) with
+/// [`page_config`] of `config`.
+pub fn emit_config<R: Runtime>(
+    app: &AppHandle<R>,
+    event: &str,
+    config: &AppConfig,
+) -> Result<(), String> {
+    app.emit(event, page_config(config))
+        .map_err(|e| e.to_string())
+}
+
 /// Fire `window.__floatViewUpdate(key, value)` in the main webview. Used by
 /// direct actions to notify the injected control strip that a state change
 /// originated in Rust (a hotkey press or tray menu click), so the strip
@@ -99,15 +140,21 @@ pub fn toggle_locked<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
 }
 
 /// Navigate to the configured home URL. Clears `last_url` first so a
-/// subsequent app restart lands on home, not on the prior page.
+/// subsequent app restart lands on home, not on the prior page. In kiosk
+/// mode a home page off the allowlist is refused.
 pub fn navigate_home<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
     let window = main_window(app)?;
     let state = app.state::<AppState>();
     let home_url = {
         let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        let home_url =
+            normalize_url(&config.home_url).unwrap_or_else(|_| DEFAULT_HOME_URL.to_string());
+        if !kiosk::allows_str(&config.kiosk, &home_url) {
+            return Err("The home page isn't on the kiosk allowlist".to_string());
+        }
         config.last_url = None;
         save_config(&state, &config);
-        normalize_url(&config.home_url).unwrap_or_else(|_| DEFAULT_HOME_URL.to_string())
+        home_url
     };
 
     let parsed = Url::parse(&home_url).map_err(|e| e.to_string())?;
@@ -115,6 +162,104 @@ pub fn navigate_home<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
     Ok(())
 }
 
+/// The webview's navigation handler, so it covers links, redirects and
+/// scripts too. Refuses what kiosk mode doesn't allow, raw `file://`, and
+/// local files while `allow_local_files` is off; the player page is
+/// judged by the media it plays. A bare media URL is sent to the player
+/// page instead. A poisoned config lock refuses while the rules can't be
+/// read.
+pub fn check_navigation<R: Runtime>(app: &AppHandle<R>, url: &Url) -> bool {
+    let state = app.state::<AppState>();
+    let shown = local_media::shown_url(url);
+    let refusal = match state.config.lock() {
+        Ok(config) if !kiosk::allows(&config.kiosk, &shown) => {
+            Some("Kiosk mode blocked navigation")
+        }
+        Ok(_) if shown.scheme() == "file" => Some("Blocked a file:// navigation"),
+        Ok(config) if local_media::is_local_file_url(&shown) && !config.allow_local_files => {
+            Some("Local files are turned off; blocked navigation")
+        }
+        Ok(_) => None,
+        Err(_) => Some("Config unavailable; blocked navigation"),
+    };
+    if let Some(reason) = refusal {
+        warn!(url = %url, "{}", reason);
+        return false;
+    }
+    if local_media::is_media_url(url) {
+        if let Ok(window) = main_window(app) {
+            let player = local_media::page_for(url.clone());
+            // Navigating from inside the handler would re-enter it.
+            std::thread::spawn(move || {
+                if let Err(e) = window.navigate(player) {
+                    warn!("Failed to open the media player: {}", e);
+                }
+            });
+        }
+        return false;
+    }
+    true
+}
+
+/// Resolve URL bar input naming a local file to its `floatview-file` URL
+/// and grant the file's folder. `Ok(None)` when the input isn't a local
+/// path; an error when it is but local files are off, kiosk mode refuses
+/// it, or there's no such file.
+pub fn open_local_file(state: &AppState, input: &str) -> Result<Option<Url>, String> {
+    let Some(path) = local_media::local_input_path(input) else {
+        return Ok(None);
+    };
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    if !config.allow_local_files {
+        return Err("Opening local files is turned off in Settings".to_string());
+    }
+    let file = local_media::resolve_local_file(&path)?;
+    let url = Url::parse(&local_media::local_file_url(&file)?).map_err(|e| e.to_string())?;
+    if !kiosk::allows(&config.kiosk, &url) {
+        return Err("That page isn't on the kiosk allowlist".to_string());
+    }
+    let mut folders = state.local_folders.lock().map_err(|e| e.to_string())?;
+    local_media::grant_folder(&mut folders, &file);
+    Ok(Some(url))
+}
+
+/// The kiosk state the page hides or shows its controls by.
+pub fn kiosk_status<R: Runtime>(app: &AppHandle<R>) -> Result<KioskStatus, String> {
+    let state = app.state::<AppState>();
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let session = state.kiosk.lock().map_err(|e| e.to_string())?;
+    Ok(session.status(&config.kiosk, Instant::now()))
+}
+
+/// The `exit_kiosk` hotkey: start the [`kiosk::EXIT_ARM_WINDOW`] in which
+/// the PIN can end kiosk mode. Returns the new status for the page.
+pub fn arm_kiosk_exit<R: Runtime>(app: &AppHandle<R>) -> Result<KioskStatus, String> {
+    let state = app.state::<AppState>();
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let mut session = state.kiosk.lock().map_err(|e| e.to_string())?;
+    let now = Instant::now();
+    if config.kiosk.enabled {
+        session.arm_exit(now);
+    }
+    Ok(session.status(&config.kiosk, now))
+}
+
+/// Turn kiosk mode off after `exit_kiosk` checked the hotkey and PIN:
+/// save, give the normal hotkeys back, and tell the page.
+pub fn leave_kiosk(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.kiosk.enabled = false;
+        state.kiosk.lock().map_err(|e| e.to_string())?.lock();
+        save_config(&state, &config);
+        config.clone()
+    };
+    crate::hotkeys::re_register_hotkeys(app);
+    emit_config(app, "config-changed", &config)?;
+    Ok(())
+}
+
 /// Apply an absolute opacity value (clamped). Returns the effective value
 /// after clamping, which callers can forward to JS.
 pub fn set_opacity<R: Runtime>(app: &AppHandle<R>, opacity: f64) -> Result<f64, String> {
@@ -144,3 +289,854 @@ pub fn adjust_opacity<R: Runtime>(app: &AppHandle<R>, delta: f64) -> Result<f64,
     };
     set_opacity(app, current + delta)
 }
+
+/// Refuse a settings change while kiosk mode is locked. Every command that
+/// edits the config (or history) checks this, with the config lock held,
+/// before touching anything.
+pub fn ensure_settings_unlocked(state: &AppState, config: &AppConfig) -> Result<(), String> {
+    if state
+        .kiosk
+        .lock()
+        .map_err(|e| e.to_string())?
+        .is_locked(&config.kiosk)
+    {
+        return Err("Settings are locked in kiosk mode".to_string());
+    }
+    Ok(())
+}
+
+/// Run a profile-list edit (create / rename / delete) against the live
+/// config, then save, push the new list into the tray submenu, and emit
+/// `config-changed`.
+pub fn edit_profiles<R: Runtime>(
+    app: &AppHandle<R>,
+    edit: impl FnOnce(&mut AppConfig) -> Result<(), String>,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let snapshot = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        ensure_settings_unlocked(&state, &config)?;
+        {
+            // Profiles capture the live fields; give them the global
+            // values, not the current site's overrides.
+            let mut site = state.site.lock().map_err(|e| e.to_string())?;
+            site.strip(&mut config);
+            let edited = edit(&mut config);
+            site.rebase(&mut config);
+            edited?;
+        }
+        save_config(&state, &config);
+        config.clone()
+    };
+    update_tray_profiles(
+        app,
+        &profile_names(&snapshot),
+        active_profile_name(&snapshot),
+    );
+    emit_config(app, "config-changed", &snapshot)?;
+    Ok(())
+}
+
+/// Run a window-preset edit (save / delete) against the live config, then
+/// save, re-register hotkeys if a preset's binding changed, rebuild the
+/// tray's "Presets" submenu, and emit `config-changed`.
+pub fn edit_window_presets(
+    app: &AppHandle,
+    edit: impl FnOnce(&mut AppConfig) -> Result<(), String>,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let (snapshot, hotkeys_changed) = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        ensure_settings_unlocked(&state, &config)?;
+        let before = config.window_presets.clone();
+        edit(&mut config)?;
+        save_config(&state, &config);
+        let changed = preset_hotkeys(&before) != preset_hotkeys(&config.window_presets);
+        (config.clone(), changed)
+    };
+    if hotkeys_changed {
+        crate::hotkeys::re_register_hotkeys(app);
+    }
+    update_tray_presets(app, &preset_names(&snapshot));
+    emit_config(app, "config-changed", &snapshot)?;
+    Ok(())
+}
+
+/// Save the window as it is on screen, current site's overrides included,
+/// as preset `name`; see [`window_presets::save_preset`].
+pub fn save_window_preset(app: &AppHandle, name: &str, hotkey: &str) -> Result<(), String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    // The periodic geometry saver may be up to a tick behind.
+    if let Err(e) = persist_window_geometry(&window, &state) {
+        warn!(error = %e, "save_window_preset: failed to capture geometry");
+    }
+    edit_window_presets(app, |config| {
+        window_presets::save_preset(config, name, hotkey)
+    })
+}
+
+/// Recall preset `name`: size and position per
+/// [`window_presets::preset_rect`], then always-on-top, click-through
+/// and opacity with the same tray updates and events as the toggles. The
+/// crop reaches the page through `config-changed`. Returns the preset so
+/// [`crate::actions`] can mirror it into the control strip.
+pub fn apply_window_preset<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+) -> Result<WindowPreset, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let preset = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        ensure_settings_unlocked(&state, &config)?;
+        let index = window_presets::find_preset(&config.window_presets, name)
+            .ok_or_else(|| format!("No preset named \"{name}\""))?;
+        let preset = config.window_presets[index].clone();
+        window_presets::apply_preset(&mut config, &preset);
+        save_config(&state, &config);
+        preset
+    };
+
+    if window.is_maximized().unwrap_or(false) {
+        window.unmaximize().map_err(|e| e.to_string())?;
+    }
+    // Expect the resize like a snap's, so it keeps `pre_snap_size`.
+    let expect = |size: PhysicalSize<u32>| {
+        if let Ok(mut expected) = state.snap_expected_size.lock() {
+            *expected = Some((size.width, size.height));
+        }
+    };
+    match window_presets::preset_rect(&current_monitors(&window), &preset) {
+        Some((x, y, width, height)) => {
+            let size = PhysicalSize::new(width as u32, height as u32);
+            expect(size);
+            window.set_size(size).map_err(|e| e.to_string())?;
+            window
+                .set_position(PhysicalPosition::new(x, y))
+                .map_err(|e| e.to_string())?;
+        }
+        None => {
+            let size = config_size(preset.scale_factor, preset.width, preset.height)
+                .to_physical::<u32>(window.scale_factor().unwrap_or(1.0));
+            expect(size);
+            window.set_size(size).map_err(|e| e.to_string())?;
+            window.center().map_err(|e| e.to_string())?;
+        }
+    }
+    window
+        .set_always_on_top(preset.always_on_top)
+        .map_err(|e| e.to_string())?;
+    window
+        .set_ignore_cursor_events(preset.locked)
+        .map_err(|e| e.to_string())?;
+    opacity::set_window_opacity(&window, preset.opacity);
+    persist_window_geometry(&window, &state)?;
+
+    update_tray_always_on_top(app, preset.always_on_top);
+    update_tray_locked(app, preset.locked);
+    app.emit("always-on-top-changed", preset.always_on_top)
+        .map_err(|e| e.to_string())?;
+    app.emit("locked-changed", preset.locked)
+        .map_err(|e| e.to_string())?;
+    app.emit("opacity-changed", preset.opacity)
+        .map_err(|e| e.to_string())?;
+    let snapshot = state.config.lock().map_err(|e| e.to_string())?.clone();
+    emit_config(app, "config-changed", &snapshot)?;
+    Ok(preset)
+}
+
+/// Move the window to `position` on its current monitor, then save the
+/// geometry. Positions are resolved by [`layout::resolve`]: anchors
+/// (corners, center) keep the window's size, zones resize it.
+pub fn snap_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    state: &AppState,
+    position: &str,
+) -> Result<(), String> {
+    let snap = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        layout::resolve(&config.layouts, position)?
+    };
+    let monitor = window
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .or(window.primary_monitor().map_err(|e| e.to_string())?)
+        .ok_or("No monitor found")?;
+
+    // The target monitor's scale, not the window's: they differ while
+    // the window straddles two displays.
+    let scale = clamp_scale_factor(monitor.scale_factor());
+    let mon_pos = monitor.position();
+    let mon_size = monitor.size();
+    let win_size = window.outer_size().map_err(|e| e.to_string())?;
+    let current = (win_size.width as i32, win_size.height as i32);
+
+    // Zones resize and mark the start of a snap chain. Anchors don't take
+    // a size directly: they restore the pre-snap size if one was saved (so
+    // a corner snap after a half/third feels like "back to my normal size
+    // in this corner"), otherwise keep the current size. Peek (don't
+    // consume) the saved size: if the resize below fails we must leave it
+    // intact for a retry. It is cleared only after an anchor snap succeeds
+    // (further down).
+    let restored_size: Option<(i32, i32)> = if snap.zone.resizes() {
+        None
+    } else {
+        state
+            .pre_snap_size
+            .lock()
+            .ok()
+            .and_then(|g| *g)
+            .map(|(w, h)| (to_physical(w as i32, scale), to_physical(h as i32, scale)))
+    };
+
+    // The target rect is always pulled onto the monitor, even when a
+    // restored pre-snap size (captured on a larger display) exceeds it.
+    let (x, y, width, height) = layout::target_rect(
+        (
+            mon_pos.x,
+            mon_pos.y,
+            mon_size.width as i32,
+            mon_size.height as i32,
+        ),
+        restored_size.unwrap_or(current),
+        &snap.zone,
+        to_physical(snap.padding, scale),
+        to_physical(MIN_WINDOW_SIZE, scale),
+    );
+    let new_size = if snap.zone.resizes() {
+        Some((width, height))
+    } else {
+        restored_size
+    };
+
+    if snap.zone.resizes() {
+        if let Ok(mut pre) = state.pre_snap_size.lock() {
+            if pre.is_none() {
+                *pre = Some((
+                    to_logical(current.0, scale) as u32,
+                    to_logical(current.1, scale) as u32,
+                ));
+            }
+        }
+    }
+
+    // Record the size we're about to apply so the Resized handler can tell
+    // this programmatic resize from a manual drag (see snap_expected_size).
+    if let Ok(mut expected) = state.snap_expected_size.lock() {
+        *expected = new_size.map(|(w, h)| (w as u32, h as u32));
+    }
+
+    if window.is_maximized().unwrap_or(false) {
+        window.unmaximize().map_err(|e| e.to_string())?;
+    }
+    if let Some((w, h)) = new_size {
+        window
+            .set_size(tauri::Size::Physical(tauri::PhysicalSize {
+                width: w as u32,
+                height: h as u32,
+            }))
+            .map_err(|e| e.to_string())?;
+    }
+
+    window
+        .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
+        .map_err(|e| e.to_string())?;
+
+    // An anchor snap has now successfully consumed the snap chain. Clear
+    // pre_snap_size only here (post-success); we only peeked it above so a
+    // failed resize leaves it intact for a retry.
+    if !snap.zone.resizes() {
+        if let Ok(mut pre) = state.pre_snap_size.lock() {
+            *pre = None;
+        }
+    }
+
+    persist_window_geometry(window, state)?;
+    Ok(())
+}
+
+/// The window's outer rect and the monitor it's mostly on, physical.
+fn window_on_monitor<R: Runtime>(
+    window: &WebviewWindow<R>,
+) -> Result<(Rect, MonitorInfo, Vec<MonitorInfo>), String> {
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let rect = (
+        position.x,
+        position.y,
+        size.width as i32,
+        size.height as i32,
+    );
+    let monitors = current_monitors(window);
+    let monitor = monitor_for(&monitors, rect)
+        .cloned()
+        .ok_or("No monitor found")?;
+    Ok((rect, monitor, monitors))
+}
+
+fn monitor_rect(monitor: &MonitorInfo) -> Rect {
+    (
+        monitor.x,
+        monitor.y,
+        monitor.width as i32,
+        monitor.height as i32,
+    )
+}
+
+/// Move the window from `from` to `to` (physical) and save the geometry.
+/// The position goes first so that after a move to a monitor with
+/// another scale the size is set in that monitor's terms; the size is
+/// only set when it changes.
+fn place_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    state: &AppState,
+    from: Rect,
+    to: Rect,
+) -> Result<(), String> {
+    if window.is_maximized().unwrap_or(false) {
+        window.unmaximize().map_err(|e| e.to_string())?;
+    }
+    let (x, y, width, height) = to;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())?;
+    if (width, height) != (from.2, from.3) {
+        // Expected like a snap, so grow/shrink keeps `pre_snap_size`.
+        if let Ok(mut expected) = state.snap_expected_size.lock() {
+            *expected = Some((width as u32, height as u32));
+        }
+        window
+            .set_size(PhysicalSize::new(width as u32, height as u32))
+            .map_err(|e| e.to_string())?;
+    }
+    persist_window_geometry(window, state)
+}
+
+/// The `nudge_*` hotkeys: move the window `nudge_step` logical pixels
+/// along `direction` (each of -1, 0 or 1), stopping at the edges of the
+/// monitor it's on. `move_to_next_monitor` crosses to another one.
+pub fn nudge_window<R: Runtime>(app: &AppHandle<R>, direction: (i32, i32)) -> Result<(), String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let step = state.config.lock().map_err(|e| e.to_string())?.nudge_step;
+    let (rect, monitor, _) = window_on_monitor(&window)?;
+    let step = to_physical(
+        step.min(layout::MAX_NUDGE_STEP) as i32,
+        clamp_scale_factor(monitor.scale_factor),
+    );
+    let offset = (direction.0 * step, direction.1 * step);
+    let target = layout::nudge_rect(monitor_rect(&monitor), rect, offset);
+    place_window(&window, &state, rect, target)
+}
+
+/// The grow and shrink hotkeys: scale the window by `factor` about its
+/// center, keeping its aspect ratio, within the same limits as
+/// `set_aspect_ratio`.
+pub fn scale_window<R: Runtime>(app: &AppHandle<R>, factor: f64) -> Result<(), String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let (rect, monitor, _) = window_on_monitor(&window)?;
+    let scale = clamp_scale_factor(monitor.scale_factor);
+    let target = layout::scale_rect(
+        monitor_rect(&monitor),
+        rect,
+        factor,
+        to_physical(layout::SNAP_PADDING, scale),
+        to_physical(MIN_WINDOW_SIZE, scale),
+    );
+    place_window(&window, &state, rect, target)
+}
+
+/// The `cycle_corner` hotkey: snap to the corner after the one the window
+/// is in (see [`layout::next_corner`]).
+pub fn cycle_corner<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let (rect, monitor, _) = window_on_monitor(&window)?;
+    let padding = to_physical(
+        layout::SNAP_PADDING,
+        clamp_scale_factor(monitor.scale_factor),
+    );
+    let corner = layout::next_corner(monitor_rect(&monitor), rect, padding);
+    snap_window(&window, &state, layout::anchor_name(corner))
+}
+
+/// The `next_monitor` hotkey: move the window to the same relative spot
+/// on the next monitor, per [`relative_rect`]. Returns false when there
+/// is only one monitor.
+pub fn move_to_next_monitor<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let (rect, monitor, monitors) = window_on_monitor(&window)?;
+    let Some(next) = next_monitor(&monitors, rect) else {
+        return Ok(false);
+    };
+    place_window(&window, &state, rect, relative_rect(&monitor, next, rect))?;
+    Ok(true)
+}
+
+/// Apply a parsed `floatview://` link: bring the window forward, then
+/// switch profile, set opacity, snap, and open the URL, in that order so
+/// the link's own values win over the profile's. Refused whole while
+/// kiosk mode is locked; the URL must pass the kiosk allowlist.
+pub fn open_deep_link<R: Runtime>(app: &AppHandle<R>, link: &DeepLink) -> Result<(), String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let session = state.kiosk.lock().map_err(|e| e.to_string())?;
+        if session.is_locked(&config.kiosk) {
+            return Err("Links are ignored in kiosk mode".to_string());
+        }
+        if let Some(url) = &link.url {
+            if !kiosk::allows_str(&config.kiosk, url) {
+                return Err("That page isn't on the kiosk allowlist".to_string());
+            }
+        }
+    }
+
+    if window.is_minimized().unwrap_or(false) {
+        window.unminimize().map_err(|e| e.to_string())?;
+    }
+    window.show().map_err(|e| e.to_string())?;
+    if let Err(e) = window.set_focus() {
+        warn!(error = %e, "open_deep_link: failed to focus window");
+    }
+    if let Some(profile) = &link.profile {
+        switch_profile(app, profile)?;
+    }
+    if let Some(opacity) = link.opacity {
+        set_opacity(app, opacity)?;
+    }
+    if let Some(position) = &link.position {
+        snap_window(&window, &state, position)?;
+    }
+    if let Some(url) = &link.url {
+        let parsed = Url::parse(url).map_err(|e| e.to_string())?;
+        window.navigate(parsed).map_err(|e| e.to_string())?;
+        persist_last_url(&state, url)?;
+    }
+    Ok(())
+}
+
+/// Make `name` the active profile and bring the window in line with it:
+/// geometry and always-on-top via `apply_window_state`, then opacity,
+/// then navigate to the profile's last page (or its home URL). The crop
+/// needs no separate step: the injected script applies `config.crop` when
+/// that page loads. Returns `false` if `name` was already active.
+pub fn switch_profile<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<bool, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    // Record where the outgoing profile's window actually is; the periodic
+    // geometry saver may be up to a tick behind.
+    if let Err(e) = persist_window_geometry(&window, &state) {
+        warn!(error = %e, "switch_profile: failed to capture geometry");
+    }
+
+    let (switched, snapshot, zoom) = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        ensure_settings_unlocked(&state, &config)?;
+        let zoom = {
+            let mut site = state.site.lock().map_err(|e| e.to_string())?;
+            let leaving = profiles::find_profile(&config, name)
+                .is_some_and(|index| config.profiles[index].name != active_profile_name(&config));
+            if leaving {
+                // The outgoing profile keeps its global values; the
+                // incoming page's site settings apply once it reports
+                // its URL.
+                site.leave(&mut config);
+            }
+            site.zoom()
+        };
+        // Switch on the user's own values for pinned keys, so the
+        // outgoing profile doesn't keep the policy or environment ones.
+        let mut next = config.clone();
+        state
+            .user_values
+            .lock()
+            .map_err(|e| e.to_string())?
+            .restore(&mut next);
+        let switched = profiles::switch_profile(&mut next, name)?;
+        if switched {
+            *state.user_values.lock().map_err(|e| e.to_string())? = state.layers.user_values(&next);
+            // A profile may carry values the policy has since locked.
+            *config = state.layers.enforce(next);
+            save_config(&state, &config);
+        }
+        (switched, config.clone(), zoom)
+    };
+    if !switched {
+        // Re-clicking the active tray item unchecks it; put the mark back.
+        update_tray_profiles(
+            app,
+            &profile_names(&snapshot),
+            active_profile_name(&snapshot),
+        );
+        return Ok(false);
+    }
+
+    apply_window_state(&window, &snapshot);
+    opacity::set_window_opacity(&window, snapshot.window.opacity);
+    if let Err(e) = window.set_zoom(zoom) {
+        warn!(error = %e, "switch_profile: failed to reset page zoom");
+    }
+    update_tray_always_on_top(app, snapshot.window.always_on_top);
+    update_tray_profiles(
+        app,
+        &profile_names(&snapshot),
+        active_profile_name(&snapshot),
+    );
+    emit_config(app, "config-changed", &snapshot)?;
+
+    let target = snapshot
+        .last_url
+        .as_deref()
+        .and_then(|url| normalize_url(url).ok())
+        .or_else(|| normalize_url(&snapshot.home_url).ok())
+        .unwrap_or_else(|| DEFAULT_HOME_URL.to_string());
+    let parsed = Url::parse(&target).map_err(|e| e.to_string())?;
+    window.navigate(parsed).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Replace the whole config with one built from the current value, the
+/// shared tail of backup restore and settings import. `build` runs under
+/// the config lock and sees the config as it belongs on disk (see
+/// [`crate::config_io::persisted_config`]); the active site's overrides
+/// are laid back on top of its result. See [`store_config`] for the rest.
+pub fn replace_config(
+    app: &AppHandle,
+    build: impl FnOnce(&AppConfig) -> Result<AppConfig, String>,
+) -> Result<Vec<ConfigCorrection>, String> {
+    store_config(app, build, false)
+}
+
+/// [`replace_config`] for `update_config`, whose copy comes from the page
+/// and so holds the live values: `build` sees and returns the live
+/// config. An edit to a value the current site overrides lasts until the
+/// site is left, like a slider or hotkey change, instead of being lost.
+pub fn update_live_config(
+    app: &AppHandle,
+    build: impl FnOnce(&AppConfig) -> Result<AppConfig, String>,
+) -> Result<Vec<ConfigCorrection>, String> {
+    store_config(app, build, true)
+}
+
+/// Run `build` against the current config, put its result through
+/// `ConfigLayers::enforce_with_report` (locked keys, then
+/// `sanitize_config`), store and save it. Afterwards hotkeys are
+/// re-registered if they (or a preset's) changed, the tray's profile and
+/// preset lists are refreshed,
+/// and `config-changed` is emitted. Returns what sanitizing and the
+/// policy changed in `build`'s result.
+///
+/// Refused while kiosk mode is locked. `kiosk` itself is always kept
+/// from the current config: it changes only in `config.json`, policy,
+/// or through `exit_kiosk`.
+fn store_config(
+    app: &AppHandle,
+    build: impl FnOnce(&AppConfig) -> Result<AppConfig, String>,
+    live: bool,
+) -> Result<Vec<ConfigCorrection>, String> {
+    let state = app.state::<AppState>();
+    let (config, corrections, hotkeys_changed) = {
+        let mut current = state.config.lock().map_err(|e| e.to_string())?;
+        ensure_settings_unlocked(&state, &current)?;
+        let base = if live {
+            current.clone()
+        } else {
+            persisted_config(&state, &current)
+        };
+        let mut next = build(&base)?;
+        next.kiosk = current.kiosk.clone();
+        if !live {
+            // A restored file or import holds the user's own values.
+            *state.user_values.lock().map_err(|e| e.to_string())? = state.layers.user_values(&next);
+        }
+        let (mut config, corrections) = state.layers.enforce_with_report(next);
+        if !live {
+            state
+                .site
+                .lock()
+                .map_err(|e| e.to_string())?
+                .rebase(&mut config);
+        }
+        let changed = current.hotkeys != config.hotkeys
+            || preset_hotkeys(&current.window_presets) != preset_hotkeys(&config.window_presets);
+        *current = config.clone();
+        save_config(&state, &current);
+        (config, corrections, changed)
+    };
+
+    if hotkeys_changed {
+        crate::hotkeys::re_register_hotkeys(app);
+    }
+    update_tray_profiles(app, &profile_names(&config), active_profile_name(&config));
+    update_tray_presets(app, &preset_names(&config));
+    emit_config(app, "config-changed", &config)?;
+    Ok(corrections)
+}
+
+/// Adopt a config re-read from disk after an external edit (see
+/// [`crate::config_watch`]). Diffs it against the live config and applies
+/// only what changed: hotkeys (built-in and preset) are re-registered,
+/// window geometry and always-on-top re-applied, opacity re-set, and the
+/// tray refreshed.
+/// `config-changed` and `config-reloaded` are emitted so the injected
+/// script can pick up crop and auto-refresh changes too. Returns `false`
+/// when the file matches what's already in memory.
+///
+/// Nothing is saved: `config.json` already holds these values (give or
+/// take sanitization, which the next save writes back).
+pub fn apply_reloaded_config(app: &AppHandle, reloaded: AppConfig) -> Result<bool, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let (previous, config) = {
+        let mut current = state.config.lock().map_err(|e| e.to_string())?;
+        *state.user_values.lock().map_err(|e| e.to_string())? = state.layers.user_values(&reloaded);
+        let mut next = state.layers.enforce(reloaded);
+        // Click-through stays session state, as on a profile switch:
+        // turning it on from a text editor would leave an unclickable
+        // window with no hint why.
+        next.window.locked = current.window.locked;
+        // The file holds global values; keep the current site's overrides
+        // over them, and pick up an edit to `site_settings` itself
+        // (`last_url` is the page on screen).
+        let mut site = state.site.lock().map_err(|e| e.to_string())?;
+        let zoom = site.zoom();
+        site.rebase(&mut next);
+        if let Some(url) = next.last_url.clone() {
+            site.navigate(&mut next, &url);
+        }
+        if site.zoom() != zoom {
+            if let Err(e) = window.set_zoom(site.zoom()) {
+                warn!(error = %e, "apply_reloaded_config: failed to set page zoom");
+            }
+        }
+        if *current == next {
+            return Ok(false);
+        }
+        let previous = std::mem::replace(&mut *current, next.clone());
+        (previous, next)
+    };
+
+    let kiosk_changed = previous.kiosk.enabled != config.kiosk.enabled;
+    let presets_rebound =
+        preset_hotkeys(&previous.window_presets) != preset_hotkeys(&config.window_presets);
+    if previous.hotkeys != config.hotkeys || presets_rebound || kiosk_changed {
+        crate::hotkeys::re_register_hotkeys(app);
+    }
+    if kiosk_changed {
+        state.kiosk.lock().map_err(|e| e.to_string())?.lock();
+        eval_ui_update(app, "kiosk", kiosk_status(app)?);
+    }
+    let (old, new) = (&previous.window, &config.window);
+    if (old.x, old.y, old.width, old.height, old.always_on_top)
+        != (new.x, new.y, new.width, new.height, new.always_on_top)
+    {
+        apply_window_state(&window, &config);
+        update_tray_always_on_top(app, new.always_on_top);
+        app.emit("always-on-top-changed", new.always_on_top)
+            .map_err(|e| e.to_string())?;
+    }
+    if old.opacity != new.opacity {
+        opacity::set_window_opacity(&window, new.opacity);
+        app.emit("opacity-changed", new.opacity)
+            .map_err(|e| e.to_string())?;
+    }
+    update_tray_profiles(app, &profile_names(&config), active_profile_name(&config));
+    update_tray_presets(app, &preset_names(&config));
+    emit_config(app, "config-changed", &config)?;
+    emit_config(app, "config-reloaded", &config)?;
+    Ok(true)
+}
+
+/// Bring the window in line with a site-settings change from `old` to
+/// `new`: size, always-on-top, opacity, and page zoom. Crop and
+/// auto-refresh live in the page, which re-reads them from the config
+/// (`config-changed` is emitted for pages already listening).
+fn apply_site_change<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    (old, old_zoom): (&AppConfig, f64),
+    (new, new_zoom): (&AppConfig, f64),
+) -> Result<(), String> {
+    let (before, after) = (&old.window, &new.window);
+    if (before.width, before.height) != (after.width, after.height) {
+        // Size only: moving the window to a stored position would undo
+        // wherever the user has dragged it since.
+        let (width, height) = normalize_startup_window_size(after.width, after.height);
+        window
+            .set_size(config_size(after.scale_factor, width, height))
+            .map_err(|e| e.to_string())?;
+    }
+    if before.always_on_top != after.always_on_top {
+        window
+            .set_always_on_top(after.always_on_top)
+            .map_err(|e| e.to_string())?;
+        update_tray_always_on_top(app, after.always_on_top);
+        app.emit("always-on-top-changed", after.always_on_top)
+            .map_err(|e| e.to_string())?;
+    }
+    if before.opacity != after.opacity {
+        opacity::set_window_opacity(window, after.opacity);
+        app.emit("opacity-changed", after.opacity)
+            .map_err(|e| e.to_string())?;
+    }
+    if old_zoom != new_zoom {
+        window.set_zoom(new_zoom).map_err(|e| e.to_string())?;
+    }
+    emit_config(app, "config-changed", new)?;
+    Ok(())
+}
+
+/// Follow the page to `url`: revert the previous site's overrides if it
+/// left that site, and apply the matching `site_settings` entry, if any.
+/// Returns `false` when nothing changed. Nothing is saved; the overrides
+/// never reach disk.
+pub fn apply_site_settings<R: Runtime>(app: &AppHandle<R>, url: &str) -> Result<bool, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    let (previous, config, zooms) = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        let mut site = state.site.lock().map_err(|e| e.to_string())?;
+        let previous = config.clone();
+        let old_zoom = site.zoom();
+        if !site.navigate(&mut config, url) {
+            return Ok(false);
+        }
+        // A site can't override a key the policy locks.
+        *config = state.layers.enforce(config.clone());
+        (previous, config.clone(), (old_zoom, site.zoom()))
+    };
+    apply_site_change(app, &window, (&previous, zooms.0), (&config, zooms.1))?;
+    Ok(true)
+}
+
+/// Run a `site_settings` edit, then re-match the page on screen so the
+/// edit takes effect (or stops) right away. Fails without changing
+/// anything if the policy pins `site_settings`.
+fn edit_site_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    edit: impl FnOnce(&mut AppConfig, f64) -> Result<(), String>,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let url = window.url().map_err(|e| e.to_string())?;
+    let (previous, config, zooms) = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        let previous = config.clone();
+        let zooms = {
+            let mut site = state.site.lock().map_err(|e| e.to_string())?;
+            let old_zoom = site.zoom();
+            let mut next = config.clone();
+            edit(&mut next, old_zoom)?;
+            let (mut next, corrections) = state.layers.enforce_with_report(next);
+            if let Some(pinned) = corrections.iter().find(|c| c.field == "site_settings") {
+                return Err(format!("Site settings can't be changed: {}", pinned.reason));
+            }
+            site.navigate(&mut next, url.as_str());
+            *config = next;
+            (old_zoom, site.zoom())
+        };
+        save_config(&state, &config);
+        (previous, config.clone(), zooms)
+    };
+    apply_site_change(app, window, (&previous, zooms.0), (&config, zooms.1))
+}
+
+/// Store the current opacity, crop, auto-refresh, window size,
+/// always-on-top and page zoom as the default for `key`, or for the
+/// current page's origin when `key` is `None`. Returns the normalized key.
+pub fn save_site_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    key: Option<&str>,
+) -> Result<String, String> {
+    let window = main_window(app)?;
+    let state = app.state::<AppState>();
+    // The geometry saver may be up to a tick behind a manual resize.
+    if let Err(e) = persist_window_geometry(&window, &state) {
+        warn!(error = %e, "save_site_settings: failed to capture geometry");
+    }
+    let key = match key {
+        Some(key) => normalize_site_key(key)?,
+        None => {
+            let url = window.url().map_err(|e| e.to_string())?;
+            site_key_for_url(url.as_str()).ok_or("This page isn't on a website")?
+        }
+    };
+    edit_site_settings(app, &window, |config, zoom| {
+        if !config.site_settings.contains_key(&key)
+            && config.site_settings.len() >= MAX_SITE_SETTINGS
+        {
+            return Err(format!("Site limit reached (max {MAX_SITE_SETTINGS})"));
+        }
+        let site = capture_site_settings(config, zoom);
+        config.site_settings.insert(key.clone(), site);
+        Ok(())
+    })?;
+    Ok(key)
+}
+
+/// Delete the `site_settings` entry for `key`, reverting its overrides if
+/// it's the one in effect. Returns `false` if there was no such entry.
+pub fn forget_site_settings<R: Runtime>(app: &AppHandle<R>, key: &str) -> Result<bool, String> {
+    let window = main_window(app)?;
+    let key = normalize_site_key(key)?;
+    let mut removed = false;
+    edit_site_settings(app, &window, |config, _| {
+        removed = config.site_settings.remove(&key).is_some();
+        Ok(())
+    })?;
+    Ok(removed)
+}
+
+/// Set the page zoom. Not saved on its own: it lasts until the site is
+/// left (or the app restarts) unless stored with [`save_site_settings`].
+/// Returns the clamped factor.
+pub fn set_page_zoom<R: Runtime>(app: &AppHandle<R>, zoom: f64) -> Result<f64, String> {
+    let zoom = clamp_zoom(zoom);
+    let window = main_window(app)?;
+    window.set_zoom(zoom).map_err(|e| e.to_string())?;
+    let state = app.state::<AppState>();
+    state.site.lock().map_err(|e| e.to_string())?.set_zoom(zoom);
+    Ok(zoom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kiosk::pin_hash;
+
+    #[test]
+    fn page_config_never_carries_the_pin_hash() {
+        let mut config = AppConfig::default();
+        config.kiosk.enabled = true;
+        config.kiosk.pin_hash = Some(pin_hash("2468"));
+        let json = serde_json::to_string(&page_config(&config)).unwrap();
+        assert!(!json.contains(&pin_hash("2468")));
+        assert!(json.contains("\"pin_hash\":null"));
+        assert_eq!(config.kiosk.pin_hash, Some(pin_hash("2468")));
+    }
+
+    /// Configs reach the webview only through `emit_config`, which
+    /// strips the hash.
+    #[test]
+    fn config_events_go_through_emit_config() {
+        let direct = format!("emit(\"{}", "config-");
+        for (file, source) in [
+            ("actions.rs", include_str!("actions.rs")),
+            ("commands.rs", include_str!("commands.rs")),
+            ("config_watch.rs", include_str!("config_watch.rs")),
+            ("lib.rs", include_str!("lib.rs")),
+            ("ops.rs", include_str!("ops.rs")),
+        ] {
+            let emits: Vec<&str> = source
+                .lines()
+                .filter(|line| line.contains(&direct) && !line.contains("config-save-status"))
+                .collect();
+            assert!(emits.is_empty(), "{file} emits a raw config: {emits:?}");
+        }
+    }
+}
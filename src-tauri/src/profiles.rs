@@ -0,0 +1,323 @@
+//! Named profiles: pure edits over `AppConfig`.
+//!
+//! The active profile's settings are the top-level `AppConfig` fields, so
+//! everything outside this module keeps reading `config.home_url`,
+//! `config.window` and friends without knowing profiles exist. Switching
+//! copies those fields into the outgoing profile's entry and the incoming
+//! profile's entry into them. The window/webview side of a switch (moving
+//! the window, navigating) lives in [`crate::ops::switch_profile`].
+
+use crate::config::{AppConfig, ProfileConfig, DEFAULT_PROFILE_NAME};
+
+/// Upper bound on stored profiles. Each one is a tray menu item.
+pub const MAX_PROFILES: usize = 20;
+
+/// Longest profile name accepted, in characters. Names are shown in the
+/// tray menu and a settings `<select>`, neither of which wraps.
+pub const MAX_PROFILE_NAME_LEN: usize = 40;
+
+/// Trim and validate a user-supplied profile name.
+pub fn normalize_profile_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile name is too long (max {MAX_PROFILE_NAME_LEN} characters)"
+        ));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Profile name cannot contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Index of the profile called `name`. Names are compared
+/// case-insensitively, matching how [`create_profile`] rejects
+/// near-duplicates ("Grafana" vs "grafana" would be indistinguishable in
+/// the tray).
+pub fn find_profile(config: &AppConfig, name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    config
+        .profiles
+        .iter()
+        .position(|p| p.name.to_lowercase() == name)
+}
+
+/// Name of the active profile. Falls back to the default name for a
+/// config that hasn't been through `sanitize_config` yet.
+pub fn active_profile_name(config: &AppConfig) -> &str {
+    config
+        .active_profile
+        .as_deref()
+        .unwrap_or(DEFAULT_PROFILE_NAME)
+}
+
+/// Profile names in menu order, for the tray submenu.
+pub fn profile_names(config: &AppConfig) -> Vec<String> {
+    config.profiles.iter().map(|p| p.name.clone()).collect()
+}
+
+/// Snapshot the live top-level fields as a profile called `name`.
+fn profile_from_live(config: &AppConfig, name: String) -> ProfileConfig {
+    ProfileConfig {
+        name,
+        window: config.window.clone(),
+        home_url: config.home_url.clone(),
+        last_url: config.last_url.clone(),
+        auto_refresh_minutes: config.auto_refresh_minutes,
+        bookmarks: config.bookmarks.clone(),
+        bookmark_folders: config.bookmark_folders.clone(),
+        crop: config.crop.clone(),
+    }
+}
+
+/// Load `profile` into the live top-level fields. Leaves
+/// `window.locked` alone; see [`ProfileConfig`].
+fn apply_profile(config: &mut AppConfig, profile: &ProfileConfig) {
+    let locked = config.window.locked;
+    config.window = profile.window.clone();
+    config.window.locked = locked;
+    config.home_url = profile.home_url.clone();
+    config.last_url = profile.last_url.clone();
+    config.auto_refresh_minutes = profile.auto_refresh_minutes;
+    config.bookmarks = profile.bookmarks.clone();
+    config.bookmark_folders = profile.bookmark_folders.clone();
+    config.crop = profile.crop.clone();
+}
+
+/// Make sure `active_profile` names an entry in `profiles`, adopting the
+/// live settings as that entry when it's missing. A config from before
+/// profiles existed comes out with a single "Default" profile.
+pub fn ensure_active_profile(config: &mut AppConfig) {
+    let active = match config.active_profile.as_deref().map(normalize_profile_name) {
+        Some(Ok(name)) => name,
+        _ => DEFAULT_PROFILE_NAME.to_string(),
+    };
+    match find_profile(config, &active) {
+        Some(index) => config.active_profile = Some(config.profiles[index].name.clone()),
+        None => {
+            if config.profiles.len() >= MAX_PROFILES {
+                config.profiles.truncate(MAX_PROFILES - 1);
+            }
+            let profile = profile_from_live(config, active.clone());
+            config.profiles.insert(0, profile);
+            config.active_profile = Some(active);
+        }
+    }
+}
+
+/// Refresh the active profile's entry from the live fields, so the list
+/// reflects what the user has actually been doing since the last switch.
+pub fn capture_active_profile(config: &mut AppConfig) {
+    ensure_active_profile(config);
+    let name = active_profile_name(config).to_string();
+    if let Some(index) = find_profile(config, &name) {
+        config.profiles[index] = profile_from_live(config, name);
+    }
+}
+
+/// Add a profile called `name`. It starts from the current window
+/// geometry and home page (so it opens somewhere sensible) with no
+/// bookmarks, crop, or history of its own. Does not switch to it.
+pub fn create_profile(config: &mut AppConfig, name: &str) -> Result<(), String> {
+    let name = normalize_profile_name(name)?;
+    capture_active_profile(config);
+    if find_profile(config, &name).is_some() {
+        return Err(format!("A profile named \"{name}\" already exists"));
+    }
+    if config.profiles.len() >= MAX_PROFILES {
+        return Err(format!("Profile limit reached (max {MAX_PROFILES})"));
+    }
+    let mut profile = profile_from_live(config, name);
+    profile.last_url = None;
+    profile.auto_refresh_minutes = 0;
+    profile.bookmarks = Vec::new();
+    profile.bookmark_folders = Vec::new();
+    profile.crop = None;
+    config.profiles.push(profile);
+    Ok(())
+}
+
+/// Rename profile `from` to `to`, following it with `active_profile` if
+/// it's the active one. Changing only the case of a name is allowed.
+pub fn rename_profile(config: &mut AppConfig, from: &str, to: &str) -> Result<(), String> {
+    let to = normalize_profile_name(to)?;
+    capture_active_profile(config);
+    let index = find_profile(config, from).ok_or_else(|| format!("No profile named \"{from}\""))?;
+    if find_profile(config, &to).is_some_and(|other| other != index) {
+        return Err(format!("A profile named \"{to}\" already exists"));
+    }
+    let was_active = active_profile_name(config) == config.profiles[index].name;
+    config.profiles[index].name = to.clone();
+    if was_active {
+        config.active_profile = Some(to);
+    }
+    Ok(())
+}
+
+/// Remove profile `name`. The active profile can't be deleted (switch
+/// away first), which also guarantees at least one profile remains.
+pub fn delete_profile(config: &mut AppConfig, name: &str) -> Result<(), String> {
+    capture_active_profile(config);
+    let index = find_profile(config, name).ok_or_else(|| format!("No profile named \"{name}\""))?;
+    if config.profiles[index].name == active_profile_name(config) {
+        return Err("Can't delete the active profile; switch to another one first".to_string());
+    }
+    config.profiles.remove(index);
+    Ok(())
+}
+
+/// Make `name` the active profile: save the live settings into the
+/// outgoing profile, then load the incoming one. Returns `false` if
+/// `name` is already active.
+pub fn switch_profile(config: &mut AppConfig, name: &str) -> Result<bool, String> {
+    capture_active_profile(config);
+    let index = find_profile(config, name).ok_or_else(|| format!("No profile named \"{name}\""))?;
+    let target = config.profiles[index].clone();
+    if target.name == active_profile_name(config) {
+        return Ok(false);
+    }
+    apply_profile(config, &target);
+    config.active_profile = Some(target.name);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Bookmark, CropConfig};
+
+    fn config_with_home(home: &str) -> AppConfig {
+        let mut config = AppConfig {
+            home_url: home.to_string(),
+            ..AppConfig::default()
+        };
+        ensure_active_profile(&mut config);
+        config
+    }
+
+    #[test]
+    fn ensure_active_profile_adopts_live_settings_as_default() {
+        let config = config_with_home("https://jellyfin.test/");
+        assert_eq!(config.active_profile.as_deref(), Some(DEFAULT_PROFILE_NAME));
+        assert_eq!(config.profiles.len(), 1);
+        assert_eq!(config.profiles[0].home_url, "https://jellyfin.test/");
+    }
+
+    #[test]
+    fn ensure_active_profile_repairs_dangling_pointer() {
+        let mut config = config_with_home("https://a.test/");
+        config.active_profile = Some("Gone".to_string());
+        ensure_active_profile(&mut config);
+        assert_eq!(config.active_profile.as_deref(), Some("Gone"));
+        assert!(find_profile(&config, "Gone").is_some());
+        assert_eq!(config.profiles.len(), 2);
+    }
+
+    #[test]
+    fn normalize_profile_name_rejects_bad_input() {
+        assert_eq!(
+            normalize_profile_name("  Grafana "),
+            Ok("Grafana".to_string())
+        );
+        assert!(normalize_profile_name("   ").is_err());
+        assert!(normalize_profile_name("tab\there").is_err());
+        assert!(normalize_profile_name(&"x".repeat(MAX_PROFILE_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn create_profile_starts_clean_and_rejects_duplicates() {
+        let mut config = config_with_home("https://a.test/");
+        config.bookmarks = vec![Bookmark::new("https://a.test/x".to_string(), String::new())];
+        config.bookmark_folders = vec!["Work".to_string()];
+        config.window.opacity = 0.5;
+        create_profile(&mut config, "Twitch").expect("create");
+
+        let twitch = &config.profiles[find_profile(&config, "twitch").unwrap()];
+        assert_eq!(twitch.window.opacity, 0.5, "geometry carries over");
+        assert!(twitch.bookmarks.is_empty());
+        assert!(twitch.bookmark_folders.is_empty());
+        assert_eq!(config.active_profile.as_deref(), Some(DEFAULT_PROFILE_NAME));
+
+        assert!(create_profile(&mut config, "TWITCH").is_err());
+    }
+
+    #[test]
+    fn create_profile_enforces_limit() {
+        let mut config = config_with_home("https://a.test/");
+        for i in 1..MAX_PROFILES {
+            create_profile(&mut config, &format!("P{i}")).expect("create");
+        }
+        assert!(create_profile(&mut config, "One too many").is_err());
+    }
+
+    #[test]
+    fn switch_profile_swaps_settings_both_ways() {
+        let mut config = config_with_home("https://jellyfin.test/");
+        config.window.opacity = 0.8;
+        config.crop = Some(CropConfig {
+            x: 0.1,
+            y: 0.1,
+            width: 0.5,
+            height: 0.5,
+        });
+        create_profile(&mut config, "Grafana").expect("create");
+
+        assert_eq!(switch_profile(&mut config, "Grafana"), Ok(true));
+        assert_eq!(config.active_profile.as_deref(), Some("Grafana"));
+        assert!(config.crop.is_none());
+        config.home_url = "https://grafana.test/".to_string();
+        config.window.opacity = 0.4;
+
+        assert_eq!(switch_profile(&mut config, DEFAULT_PROFILE_NAME), Ok(true));
+        assert_eq!(config.home_url, "https://jellyfin.test/");
+        assert_eq!(config.window.opacity, 0.8);
+        assert!(config.crop.is_some());
+
+        // Grafana's edits were captured on the way out.
+        let grafana = &config.profiles[find_profile(&config, "Grafana").unwrap()];
+        assert_eq!(grafana.home_url, "https://grafana.test/");
+        assert_eq!(grafana.window.opacity, 0.4);
+    }
+
+    #[test]
+    fn switch_profile_keeps_click_through_state() {
+        let mut config = config_with_home("https://a.test/");
+        create_profile(&mut config, "Other").expect("create");
+        let index = find_profile(&config, "Other").unwrap();
+        config.profiles[index].window.locked = true;
+
+        switch_profile(&mut config, "Other").expect("switch");
+        assert!(!config.window.locked);
+    }
+
+    #[test]
+    fn switch_profile_to_active_is_a_noop() {
+        let mut config = config_with_home("https://a.test/");
+        assert_eq!(switch_profile(&mut config, DEFAULT_PROFILE_NAME), Ok(false));
+        assert!(switch_profile(&mut config, "Missing").is_err());
+    }
+
+    #[test]
+    fn rename_profile_follows_active_pointer() {
+        let mut config = config_with_home("https://a.test/");
+        create_profile(&mut config, "Grafana").expect("create");
+        rename_profile(&mut config, DEFAULT_PROFILE_NAME, "Jellyfin").expect("rename");
+        assert_eq!(config.active_profile.as_deref(), Some("Jellyfin"));
+
+        assert!(rename_profile(&mut config, "Jellyfin", "grafana").is_err());
+        rename_profile(&mut config, "Grafana", "GRAFANA").expect("case-only rename");
+        assert_eq!(profile_names(&config), vec!["Jellyfin", "GRAFANA"]);
+    }
+
+    #[test]
+    fn delete_profile_refuses_active() {
+        let mut config = config_with_home("https://a.test/");
+        create_profile(&mut config, "Other").expect("create");
+        assert!(delete_profile(&mut config, DEFAULT_PROFILE_NAME).is_err());
+        delete_profile(&mut config, "other").expect("delete");
+        assert_eq!(profile_names(&config), vec![DEFAULT_PROFILE_NAME]);
+    }
+}
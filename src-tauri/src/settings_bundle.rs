@@ -0,0 +1,360 @@
+//! Portable settings bundle: the subset of `AppConfig` worth handing to
+//! another machine (hotkeys, bookmarks, home URL, auto-refresh, per-site
+//! settings, and optionally window geometry), wrapped in a small
+//! versioned envelope.
+//!
+//! Everything here is pure. `export_settings` / `import_settings` in
+//! [`crate::commands`] handle the file and push the result through
+//! [`crate::ops::replace_config`], which runs `sanitize_config` — so a
+//! hand-edited or hostile bundle gets exactly the same hotkey and URL
+//! checks as `config.json` itself.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bookmarks::find_folder;
+use crate::config::{new_bookmark_id, AppConfig, Bookmark, HotkeyConfig, SiteSettings};
+use crate::config_io::MAX_BOOKMARKS;
+use crate::site_settings::normalize_site_key;
+use crate::urls::{normalize_url, urls_match};
+
+/// Value of the bundle's `format` field. Lets import reject some other
+/// JSON file (say, `config.json` itself) with a clear message instead of
+/// a confusing partial import.
+pub const BUNDLE_FORMAT: &str = "floatview-settings";
+
+/// Current bundle layout. Bump when a section is renamed or retyped;
+/// new optional sections don't need it.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Window position and size. Opacity, always-on-top and click-through
+/// are left out on purpose: they're per-desk preferences, not part of a
+/// "standard setup". Units as in [`crate::config::WindowConfig`]: logical
+/// at `scale_factor`, physical when a bundle from an older version has
+/// none.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f64>,
+}
+
+/// The exported document. Every section is optional so a team can ship
+/// a bundle that only sets, say, the home URL and bookmarks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub format: String,
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkeys: Option<HotkeyConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Bare URL strings (all a v1 bundle held) are accepted here too;
+    /// see [`Bookmark`].
+    pub bookmarks: Option<Vec<Bookmark>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bookmark_folders: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_refresh_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_settings: Option<BTreeMap<String, SiteSettings>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<BundleGeometry>,
+}
+
+/// How an imported bundle combines with the current settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Bookmarks are added to the current list (skipping ones that
+    /// `urls_match` an existing entry) and site settings to the current
+    /// map (replacing entries for the same site); every other section in
+    /// the bundle overwrites the current value.
+    Merge,
+    /// Every section in the bundle overwrites the current value,
+    /// bookmarks and site settings included. Sections the bundle omits are left alone.
+    Replace,
+}
+
+/// Build a bundle from the live config.
+pub fn export_bundle(config: &AppConfig, include_geometry: bool) -> SettingsBundle {
+    SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        hotkeys: Some(config.hotkeys.clone()),
+        bookmarks: Some(config.bookmarks.clone()),
+        bookmark_folders: Some(config.bookmark_folders.clone()),
+        home_url: Some(config.home_url.clone()),
+        auto_refresh_minutes: Some(config.auto_refresh_minutes),
+        site_settings: Some(config.site_settings.clone()),
+        geometry: include_geometry.then_some(BundleGeometry {
+            x: config.window.x,
+            y: config.window.y,
+            width: config.window.width,
+            height: config.window.height,
+            scale_factor: config.window.scale_factor,
+        }),
+    }
+}
+
+/// Parse and check the envelope of a bundle file.
+pub fn parse_bundle(text: &str) -> Result<SettingsBundle, String> {
+    let bundle: SettingsBundle =
+        serde_json::from_str(text).map_err(|e| format!("Not a valid settings file: {e}"))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("Not a FloatView settings export".to_string());
+    }
+    if bundle.version == 0 || bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Settings file version {} is not supported by this version of FloatView",
+            bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Combine `bundle` with `current` according to `mode`. The result is not
+/// sanitized; callers must pass it through `sanitize_config`.
+pub fn apply_bundle(current: &AppConfig, bundle: &SettingsBundle, mode: ImportMode) -> AppConfig {
+    let mut config = current.clone();
+    if let Some(hotkeys) = &bundle.hotkeys {
+        config.hotkeys = hotkeys.clone();
+    }
+    if let Some(home_url) = &bundle.home_url {
+        config.home_url = home_url.clone();
+    }
+    if let Some(minutes) = bundle.auto_refresh_minutes {
+        config.auto_refresh_minutes = minutes;
+    }
+    if let Some(geometry) = &bundle.geometry {
+        config.window.x = geometry.x;
+        config.window.y = geometry.y;
+        config.window.width = geometry.width;
+        config.window.height = geometry.height;
+        config.window.scale_factor = geometry.scale_factor;
+    }
+    if let Some(bookmarks) = &bundle.bookmarks {
+        let folders = bundle.bookmark_folders.as_deref().unwrap_or_default();
+        match mode {
+            ImportMode::Replace => {
+                config.bookmarks = bookmarks.clone();
+                config.bookmark_folders = folders.to_vec();
+            }
+            ImportMode::Merge => {
+                config.bookmarks = merge_bookmarks(&current.bookmarks, bookmarks);
+                for folder in folders {
+                    if find_folder(&config.bookmark_folders, folder).is_none() {
+                        config.bookmark_folders.push(folder.clone());
+                    }
+                }
+            }
+        }
+    }
+    if let Some(sites) = &bundle.site_settings {
+        match mode {
+            ImportMode::Replace => config.site_settings = sites.clone(),
+            ImportMode::Merge => {
+                for (key, site) in sites {
+                    // Normalize first so an incoming `Chat.Test` replaces
+                    // an existing `chat.test` instead of sitting beside it.
+                    let key = normalize_site_key(key).unwrap_or_else(|_| key.clone());
+                    config.site_settings.insert(key, site.clone());
+                }
+            }
+        }
+    }
+    config
+}
+
+/// Existing bookmarks first, then each incoming one that normalizes and
+/// doesn't `urls_match` anything already in the list, up to the cap. An
+/// incoming entry keeps its title, folder and tags; its id is replaced if
+/// it collides with one already here.
+fn merge_bookmarks(existing: &[Bookmark], incoming: &[Bookmark]) -> Vec<Bookmark> {
+    let mut merged = existing.to_vec();
+    for bookmark in incoming {
+        if merged.len() >= MAX_BOOKMARKS {
+            break;
+        }
+        let Ok(url) = normalize_url(&bookmark.url) else {
+            continue;
+        };
+        if merged.iter().any(|b| urls_match(&b.url, &url)) {
+            continue;
+        }
+        let mut bookmark = bookmark.clone();
+        bookmark.url = url;
+        if merged.iter().any(|b| b.id == bookmark.id) {
+            bookmark.id = new_bookmark_id();
+        }
+        merged.push(bookmark);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_io::sanitize_config;
+
+    fn bookmark(url: &str) -> Bookmark {
+        Bookmark::new(url.to_string(), String::new())
+    }
+
+    fn urls(config: &AppConfig) -> Vec<&str> {
+        config.bookmarks.iter().map(|b| b.url.as_str()).collect()
+    }
+
+    fn bundle_with_bookmarks(bookmarks: &[&str]) -> SettingsBundle {
+        SettingsBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            hotkeys: None,
+            bookmarks: Some(bookmarks.iter().map(|s| bookmark(s)).collect()),
+            bookmark_folders: None,
+            home_url: None,
+            auto_refresh_minutes: None,
+            site_settings: None,
+            geometry: None,
+        }
+    }
+
+    #[test]
+    fn export_round_trips_through_parse() {
+        let mut config = AppConfig {
+            home_url: "https://grafana.test/".to_string(),
+            auto_refresh_minutes: 5,
+            bookmarks: vec![bookmark("https://a.test/")],
+            ..AppConfig::default()
+        };
+        config.window.x = 42;
+        let text = serde_json::to_string(&export_bundle(&config, true)).unwrap();
+        let bundle = parse_bundle(&text).expect("parse");
+        assert_eq!(bundle.home_url.as_deref(), Some("https://grafana.test/"));
+        assert_eq!(bundle.geometry.map(|g| g.x), Some(42));
+
+        let without = export_bundle(&config, false);
+        assert!(without.geometry.is_none());
+    }
+
+    #[test]
+    fn parse_bundle_rejects_foreign_and_future_files() {
+        let config_json = serde_json::to_string(&AppConfig::default()).unwrap();
+        assert!(parse_bundle(&config_json).is_err());
+        assert!(parse_bundle(r#"{"format":"something-else","version":1}"#).is_err());
+        assert!(parse_bundle(r#"{"format":"floatview-settings","version":99}"#).is_err());
+        assert!(parse_bundle(r#"{"format":"floatview-settings","version":1}"#).is_ok());
+    }
+
+    #[test]
+    fn merge_dedups_bookmarks_with_urls_match() {
+        let current = AppConfig {
+            bookmarks: vec![bookmark("https://a.test/path")],
+            ..AppConfig::default()
+        };
+        let bundle = bundle_with_bookmarks(&[
+            "https://a.test/path/",
+            "https://a.test/path#frag",
+            "b.test",
+            "javascript:alert(1)",
+        ]);
+        let merged = apply_bundle(&current, &bundle, ImportMode::Merge);
+        assert_eq!(
+            urls(&merged),
+            vec!["https://a.test/path", "https://b.test/"]
+        );
+    }
+
+    #[test]
+    fn replace_overwrites_bookmarks_and_leaves_missing_sections() {
+        let current = AppConfig {
+            home_url: "https://keep.test/".to_string(),
+            bookmarks: vec![bookmark("https://a.test/")],
+            ..AppConfig::default()
+        };
+        let bundle = bundle_with_bookmarks(&["https://b.test/"]);
+        let replaced = apply_bundle(&current, &bundle, ImportMode::Replace);
+        assert_eq!(urls(&replaced), vec!["https://b.test/"]);
+        assert_eq!(replaced.home_url, "https://keep.test/");
+    }
+
+    #[test]
+    fn merge_replaces_site_settings_for_the_same_site() {
+        let site = |opacity: f64| SiteSettings {
+            opacity: Some(opacity),
+            ..SiteSettings::default()
+        };
+        let current = AppConfig {
+            site_settings: [
+                ("chat.test".to_string(), site(0.4)),
+                ("video.test".to_string(), site(1.0)),
+            ]
+            .into_iter()
+            .collect(),
+            ..AppConfig::default()
+        };
+        let mut bundle = bundle_with_bookmarks(&[]);
+        bundle.site_settings = Some([("Chat.Test".to_string(), site(0.7))].into_iter().collect());
+
+        let merged = apply_bundle(&current, &bundle, ImportMode::Merge);
+        assert_eq!(merged.site_settings.len(), 2);
+        assert_eq!(merged.site_settings["chat.test"].opacity, Some(0.7));
+        let replaced = apply_bundle(&current, &bundle, ImportMode::Replace);
+        assert_eq!(replaced.site_settings.len(), 1);
+    }
+
+    #[test]
+    fn imported_values_are_sanitized() {
+        let mut bundle = bundle_with_bookmarks(&["ftp://x.test/", "c.test"]);
+        bundle.hotkeys = Some(HotkeyConfig {
+            toggle_on_top: "<img src=x>".to_string(),
+            ..HotkeyConfig::default()
+        });
+        bundle.home_url = Some("javascript:alert(1)".to_string());
+        bundle.auto_refresh_minutes = Some(u32::MAX);
+
+        let imported = sanitize_config(apply_bundle(
+            &AppConfig::default(),
+            &bundle,
+            ImportMode::Replace,
+        ));
+        assert_eq!(imported.hotkeys.toggle_on_top, "Alt+Shift+T");
+        assert_eq!(imported.home_url, "https://www.google.com/");
+        assert_eq!(urls(&imported), vec!["https://c.test/"]);
+        assert!(imported.auto_refresh_minutes <= crate::config_io::MAX_AUTO_REFRESH_MINUTES);
+    }
+
+    #[test]
+    fn v1_bundle_with_bare_urls_merges_and_folders_carry_over() {
+        let v1 = r#"{"format":"floatview-settings","version":1,"bookmarks":["b.test"]}"#;
+        let bundle = parse_bundle(v1).expect("v1 bundle parses");
+        let mut current = AppConfig {
+            bookmarks: vec![bookmark("https://a.test/")],
+            bookmark_folders: vec!["Work".to_string()],
+            ..AppConfig::default()
+        };
+        let merged = sanitize_config(apply_bundle(&current, &bundle, ImportMode::Merge));
+        assert_eq!(urls(&merged), vec!["https://a.test/", "https://b.test/"]);
+        assert!(!merged.bookmarks[1].id.is_empty());
+
+        current.bookmarks[0].folder = Some("Work".to_string());
+        let exported = export_bundle(&current, false);
+        let mut target = AppConfig {
+            bookmark_folders: vec!["work".to_string(), "Home".to_string()],
+            ..AppConfig::default()
+        };
+        target.bookmarks = vec![Bookmark {
+            id: current.bookmarks[0].id.clone(),
+            ..bookmark("https://c.test/")
+        }];
+        let merged = sanitize_config(apply_bundle(&target, &exported, ImportMode::Merge));
+        assert_eq!(merged.bookmark_folders, vec!["work", "Home"]);
+        assert_eq!(merged.bookmarks[1].folder.as_deref(), Some("work"));
+        assert_ne!(merged.bookmarks[0].id, merged.bookmarks[1].id);
+    }
+}
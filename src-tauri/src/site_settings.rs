@@ -0,0 +1,460 @@
+//! Per-site overrides (`AppConfig::site_settings`): opacity, crop,
+//! auto-refresh, window size, always-on-top and page zoom that take
+//! effect while the page is on a matching site and revert when it leaves.
+//!
+//! Keys are an origin (`https://jellyfin.local:8096`), a host
+//! (`example.com`, any scheme or port), or a wildcard host
+//! (`*.example.com`, the domain and all of its subdomains). When several
+//! match, the origin wins, then the exact host, then the longest wildcard.
+//!
+//! The overrides are written into the live `AppConfig`, so every existing
+//! path (the crop script, the opacity hotkeys, the geometry saver) sees
+//! the site's values without knowing sites exist. [`SiteSession`]
+//! remembers the global values they displaced: [`SiteSession::strip`]
+//! puts those back into anything headed for disk, and leaving the site
+//! restores them. Changing an overridden value while on the site
+//! therefore lasts until you leave, unless it's saved as the site's
+//! default.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use url::{Host, Url};
+
+use crate::config::{AppConfig, CropConfig, SiteSettings};
+use crate::urls::host_matches;
+
+/// Cap on `site_settings` entries.
+pub const MAX_SITE_SETTINGS: usize = 100;
+
+/// Page zoom bounds. WebView2 accepts 0.25–5.0; WebKitGTK and WKWebView
+/// take anything positive, so the narrower range is used everywhere.
+pub const MIN_PAGE_ZOOM: f64 = 0.25;
+pub const MAX_PAGE_ZOOM: f64 = 5.0;
+
+/// Clamp a page zoom factor into range. Non-finite inputs reset to 100%.
+pub fn clamp_zoom(zoom: f64) -> f64 {
+    if zoom.is_finite() {
+        zoom.clamp(MIN_PAGE_ZOOM, MAX_PAGE_ZOOM)
+    } else {
+        1.0
+    }
+}
+
+/// Canonicalize a `site_settings` key: an http(s) URL becomes its origin
+/// (any path is dropped), a host or `*.`-prefixed domain is lowercased
+/// and IDNA-encoded. Ports, paths, and wildcards anywhere but the front
+/// are rejected for host patterns.
+pub fn normalize_site_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+    let invalid = || format!("\"{key}\" is not an origin or host pattern");
+    if key.contains("://") {
+        let url = Url::parse(key).map_err(|_| invalid())?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(invalid());
+        }
+        return Ok(url.origin().ascii_serialization());
+    }
+    let (wildcard, host) = match key.strip_prefix("*.") {
+        Some(domain) => (true, domain),
+        None => (false, key),
+    };
+    match Host::parse(host) {
+        Ok(Host::Domain(domain)) if !domain.contains('*') => Ok(if wildcard {
+            format!("*.{domain}")
+        } else {
+            domain
+        }),
+        Ok(ip) if !wildcard && !matches!(ip, Host::Domain(_)) => Ok(ip.to_string()),
+        _ => Err(invalid()),
+    }
+}
+
+/// The key "save as site default" uses for `url`: its origin. `None` for
+/// anything that isn't http(s).
+pub fn site_key_for_url(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.host_str()?;
+    Some(url.origin().ascii_serialization())
+}
+
+/// Find the entry that applies to `url`, by the precedence in the module
+/// docs. Keys are assumed normalized (`sanitize_config` does that).
+pub fn match_site<'a>(
+    sites: &'a BTreeMap<String, SiteSettings>,
+    url: &str,
+) -> Option<(&'a String, &'a SiteSettings)> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?;
+    sites
+        .get_key_value(&url.origin().ascii_serialization())
+        .or_else(|| sites.get_key_value(host))
+        .or_else(|| {
+            sites
+                .iter()
+                .filter(|(key, _)| key.starts_with("*.") && host_matches(key, host))
+                .max_by_key(|(key, _)| key.len())
+        })
+}
+
+/// What the settings UI shows about site settings for the page on screen.
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteStatus {
+    /// Key of the entry in effect, if any.
+    pub active: Option<String>,
+    /// The key "save as site default" uses for this page (its origin),
+    /// or `None` for a non-http(s) page.
+    pub current: Option<String>,
+    /// Page zoom factor in effect.
+    pub zoom: f64,
+}
+
+/// Snapshot the current state as an override for every field: what
+/// "save as site default" stores.
+pub fn capture_site_settings(config: &AppConfig, zoom: f64) -> SiteSettings {
+    SiteSettings {
+        opacity: Some(config.window.opacity),
+        crop: config.crop.clone(),
+        auto_refresh_minutes: Some(config.auto_refresh_minutes),
+        width: Some(config.window.width),
+        height: Some(config.window.height),
+        always_on_top: Some(config.window.always_on_top),
+        zoom: Some(zoom),
+    }
+}
+
+/// The global values an active site's overrides displaced.
+#[derive(Debug, Clone, PartialEq)]
+struct Baseline {
+    opacity: f64,
+    crop: Option<CropConfig>,
+    auto_refresh_minutes: u32,
+    width: i32,
+    height: i32,
+    always_on_top: bool,
+    zoom: f64,
+}
+
+impl Baseline {
+    fn capture(config: &AppConfig, zoom: f64) -> Self {
+        Self {
+            opacity: config.window.opacity,
+            crop: config.crop.clone(),
+            auto_refresh_minutes: config.auto_refresh_minutes,
+            width: config.window.width,
+            height: config.window.height,
+            always_on_top: config.window.always_on_top,
+            zoom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ActiveSite {
+    key: String,
+    applied: SiteSettings,
+    baseline: Baseline,
+}
+
+impl ActiveSite {
+    /// Put the baseline back into every field the overrides touched.
+    fn restore(&self, config: &mut AppConfig) {
+        let (applied, baseline) = (&self.applied, &self.baseline);
+        if applied.opacity.is_some() {
+            config.window.opacity = baseline.opacity;
+        }
+        if applied.crop.is_some() {
+            config.crop = baseline.crop.clone();
+        }
+        if applied.auto_refresh_minutes.is_some() {
+            config.auto_refresh_minutes = baseline.auto_refresh_minutes;
+        }
+        if applied.width.is_some() {
+            config.window.width = baseline.width;
+        }
+        if applied.height.is_some() {
+            config.window.height = baseline.height;
+        }
+        if applied.always_on_top.is_some() {
+            config.window.always_on_top = baseline.always_on_top;
+        }
+    }
+}
+
+/// Write `site`'s overrides into `config`. Zoom isn't a config field;
+/// [`SiteSession`] tracks it.
+fn overlay(config: &mut AppConfig, site: &SiteSettings) {
+    if let Some(opacity) = site.opacity {
+        config.window.opacity = opacity;
+    }
+    if let Some(crop) = &site.crop {
+        config.crop = Some(crop.clone());
+    }
+    if let Some(minutes) = site.auto_refresh_minutes {
+        config.auto_refresh_minutes = minutes;
+    }
+    if let Some(width) = site.width {
+        config.window.width = width;
+    }
+    if let Some(height) = site.height {
+        config.window.height = height;
+    }
+    if let Some(on_top) = site.always_on_top {
+        config.window.always_on_top = on_top;
+    }
+}
+
+/// Which site's overrides are in the live config, and the page zoom
+/// currently applied to the webview. Lives in `AppState::site`; lock it
+/// after `AppState::config` when both are needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteSession {
+    active: Option<ActiveSite>,
+    zoom: f64,
+}
+
+impl Default for SiteSession {
+    fn default() -> Self {
+        Self {
+            active: None,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl SiteSession {
+    /// Key of the entry in effect, if any.
+    pub fn active_key(&self) -> Option<&str> {
+        self.active.as_ref().map(|active| active.key.as_str())
+    }
+
+    /// Page zoom the webview should be showing.
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Record a zoom change made from the UI. Like any other change to an
+    /// overridden value, it lasts until the site is left.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = clamp_zoom(zoom);
+    }
+
+    /// Follow a navigation to `url`: leave the current site unless `url`
+    /// still matches the same, unchanged entry, then enter whichever
+    /// entry matches. Returns whether `config` or the zoom changed.
+    pub fn navigate(&mut self, config: &mut AppConfig, url: &str) -> bool {
+        let target = match_site(&config.site_settings, url);
+        let current = self
+            .active
+            .as_ref()
+            .map(|active| (&active.key, &active.applied));
+        if current == target {
+            return false;
+        }
+        let target = target.map(|(key, site)| (key.clone(), site.clone()));
+        self.leave(config);
+        if let Some((key, applied)) = target {
+            let baseline = Baseline::capture(config, self.zoom);
+            overlay(config, &applied);
+            if let Some(zoom) = applied.zoom {
+                self.zoom = zoom;
+            }
+            self.active = Some(ActiveSite {
+                key,
+                applied,
+                baseline,
+            });
+        }
+        true
+    }
+
+    /// Restore the global values and forget the active site. Returns
+    /// `false` if no site was active.
+    pub fn leave(&mut self, config: &mut AppConfig) -> bool {
+        let Some(active) = self.active.take() else {
+            return false;
+        };
+        active.restore(config);
+        if active.applied.zoom.is_some() {
+            self.zoom = active.baseline.zoom;
+        }
+        true
+    }
+
+    /// Put the displaced global values back into `config`: turns the live
+    /// config into what belongs on disk.
+    pub fn strip(&self, config: &mut AppConfig) {
+        if let Some(active) = &self.active {
+            active.restore(config);
+        }
+    }
+
+    /// Re-apply the active site's overrides on top of `config`, whose
+    /// overridable fields hold global values (a reload from disk, a
+    /// restored backup, a stripped copy that was edited). Those values
+    /// become the new baseline.
+    pub fn rebase(&mut self, config: &mut AppConfig) {
+        if let Some(active) = &mut self.active {
+            active.baseline = Baseline::capture(config, active.baseline.zoom);
+            overlay(config, &active.applied);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sites(entries: &[(&str, SiteSettings)]) -> BTreeMap<String, SiteSettings> {
+        entries
+            .iter()
+            .map(|(key, site)| (key.to_string(), site.clone()))
+            .collect()
+    }
+
+    fn with_opacity(opacity: f64) -> SiteSettings {
+        SiteSettings {
+            opacity: Some(opacity),
+            ..SiteSettings::default()
+        }
+    }
+
+    #[test]
+    fn normalize_site_key_accepts_origins_and_host_patterns() {
+        assert_eq!(
+            normalize_site_key("HTTPS://Example.com:443/watch?v=1").unwrap(),
+            "https://example.com"
+        );
+        assert_eq!(
+            normalize_site_key("http://jellyfin.local:8096").unwrap(),
+            "http://jellyfin.local:8096"
+        );
+        assert_eq!(normalize_site_key(" Example.COM ").unwrap(), "example.com");
+        assert_eq!(
+            normalize_site_key("*.Example.com").unwrap(),
+            "*.example.com"
+        );
+        assert_eq!(normalize_site_key("127.0.0.1").unwrap(), "127.0.0.1");
+        for bad in [
+            "",
+            "*",
+            "*.",
+            "example.com:8080",
+            "a.*.example.com",
+            "*.127.0.0.1",
+            "ftp://example.com",
+            "example.com/path",
+        ] {
+            assert!(
+                normalize_site_key(bad).is_err(),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn match_site_prefers_origin_then_host_then_longest_wildcard() {
+        let map = sites(&[
+            ("*.example.com", with_opacity(0.2)),
+            ("*.video.example.com", with_opacity(0.3)),
+            ("video.example.com", with_opacity(0.4)),
+            ("https://video.example.com", with_opacity(0.5)),
+        ]);
+        let key = |url: &str| match_site(&map, url).map(|(key, _)| key.as_str());
+        assert_eq!(
+            key("https://video.example.com/x"),
+            Some("https://video.example.com")
+        );
+        assert_eq!(key("http://video.example.com/x"), Some("video.example.com"));
+        assert_eq!(
+            key("https://a.video.example.com/"),
+            Some("*.video.example.com")
+        );
+        assert_eq!(key("https://example.com/"), Some("*.example.com"));
+        assert_eq!(key("https://notexample.com/"), None);
+        assert_eq!(key("file:///tmp/x.html"), None);
+    }
+
+    #[test]
+    fn navigate_applies_and_reverts_overrides() {
+        let mut config = AppConfig {
+            site_settings: sites(&[(
+                "chat.test",
+                SiteSettings {
+                    opacity: Some(0.4),
+                    width: Some(400),
+                    zoom: Some(1.25),
+                    ..SiteSettings::default()
+                },
+            )]),
+            ..AppConfig::default()
+        };
+        let global = config.clone();
+        let mut session = SiteSession::default();
+
+        assert!(session.navigate(&mut config, "https://chat.test/room"));
+        assert_eq!(session.active_key(), Some("chat.test"));
+        assert_eq!(config.window.opacity, 0.4);
+        assert_eq!(config.window.width, 400);
+        assert_eq!(config.window.height, global.window.height);
+        assert_eq!(session.zoom(), 1.25);
+
+        // Same site, same entry: nothing to do.
+        assert!(!session.navigate(&mut config, "https://chat.test/other"));
+
+        // What goes to disk holds the global values.
+        let mut on_disk = config.clone();
+        session.strip(&mut on_disk);
+        assert_eq!(on_disk, global);
+
+        assert!(session.navigate(&mut config, "https://elsewhere.test/"));
+        assert_eq!(session.active_key(), None);
+        assert_eq!(config, global);
+        assert_eq!(session.zoom(), 1.0);
+    }
+
+    #[test]
+    fn navigate_reapplies_an_edited_entry() {
+        let mut config = AppConfig {
+            site_settings: sites(&[("chat.test", with_opacity(0.4))]),
+            ..AppConfig::default()
+        };
+        let mut session = SiteSession::default();
+        session.navigate(&mut config, "https://chat.test/");
+        config.site_settings = sites(&[("chat.test", with_opacity(0.6))]);
+
+        assert!(session.navigate(&mut config, "https://chat.test/"));
+        assert_eq!(config.window.opacity, 0.6);
+        session.leave(&mut config);
+        assert_eq!(config.window.opacity, 1.0);
+    }
+
+    #[test]
+    fn rebase_keeps_overrides_over_new_globals() {
+        let mut config = AppConfig {
+            site_settings: sites(&[("chat.test", with_opacity(0.4))]),
+            ..AppConfig::default()
+        };
+        let mut session = SiteSession::default();
+        session.navigate(&mut config, "https://chat.test/");
+
+        // A reload from disk: global values only, with a new global opacity
+        // and auto-refresh.
+        let mut reloaded = config.clone();
+        session.strip(&mut reloaded);
+        reloaded.window.opacity = 0.8;
+        reloaded.auto_refresh_minutes = 5;
+        session.rebase(&mut reloaded);
+        assert_eq!(reloaded.window.opacity, 0.4);
+        assert_eq!(reloaded.auto_refresh_minutes, 5);
+
+        session.leave(&mut reloaded);
+        assert_eq!(reloaded.window.opacity, 0.8);
+    }
+}
@@ -7,17 +7,23 @@
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::Sender;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use tauri::{AppHandle, Manager, Runtime};
 use tracing::{error, warn};
 
 use crate::config::AppConfig;
+use crate::config_io::{ConfigLoadReport, SaveRequest, WrittenDigests};
+use crate::config_layers::{ConfigLayers, UserValues};
+use crate::history::HistoryEntry;
+use crate::kiosk::KioskSession;
+use crate::site_settings::SiteSession;
 
 /// Window dimensions remembered across a snap chain so corner/center
 /// snaps can restore the user's pre-snap size after a halves/thirds/
 /// aspect resize. Cleared on a manual resize and on each corner snap
-/// that consumed it.
+/// that consumed it. Logical pixels, so it survives a move to a monitor
+/// with different scaling.
 pub type PreSnapSize = (u32, u32);
 
 /// Callback that flips a boolean tray state (a check mark, typically).
@@ -28,6 +34,14 @@ pub type TrayBoolSetter = Box<dyn Fn(bool) + Send + Sync>;
 /// and resets.
 pub type TrayUpdateSetter = Box<dyn Fn(Option<&str>) + Send + Sync>;
 
+/// Callback that rebuilds the tray's "Profiles" submenu from the profile
+/// names (in menu order) and the active profile's name.
+pub type TrayProfilesSetter = Box<dyn Fn(&[String], &str) + Send + Sync>;
+
+/// Callback that rebuilds the tray's "Presets" submenu from the window
+/// preset names, in menu order.
+pub type TrayPresetsSetter = Box<dyn Fn(&[String]) + Send + Sync>;
+
 /// Bundle of callbacks the tray exposes so the rest of the app can
 /// reflect state changes into the tray menu without knowing anything
 /// about Wry/muda types. Stored as closures so `AppState` stays free of
@@ -40,6 +54,10 @@ pub struct TraySetters {
     pub set_locked: TrayBoolSetter,
     /// Update the "Install Update" item's label + enabled state.
     pub set_update_available: TrayUpdateSetter,
+    /// Rebuild the "Profiles" submenu.
+    pub set_profiles: TrayProfilesSetter,
+    /// Rebuild the "Presets" submenu.
+    pub set_presets: TrayPresetsSetter,
 }
 
 /// Shared state held by Tauri for the life of the app.
@@ -53,7 +71,7 @@ pub struct AppState {
     pub command_token: String,
     /// `Some` during normal operation; `None` after `shutdown()`. Dropping
     /// the sender is how the saver thread learns it should exit.
-    pub save_tx: Mutex<Option<Sender<AppConfig>>>,
+    pub save_tx: Mutex<Option<Sender<SaveRequest>>>,
     /// JoinHandle for the saver thread; taken by `shutdown()` so we can
     /// wait for pending writes to drain before the process exits.
     pub save_thread: Mutex<Option<JoinHandle<()>>>,
@@ -76,6 +94,51 @@ pub struct AppState {
     /// the asynchronous `Resized` event is actually delivered on the main
     /// event loop. `None` means "any resize is manual."
     pub snap_expected_size: Mutex<Option<(u32, u32)>>,
+    /// Error from the most recent config write, `None` once one succeeds.
+    /// Shared with the saver thread, which fills it in; read by the UI so
+    /// a full disk or read-only config dir doesn't fail silently.
+    pub save_error: Arc<Mutex<Option<String>>>,
+    /// How the config was obtained at startup (file, recovered backup, or
+    /// defaults). Read by the settings UI to explain a recovery or reset.
+    pub load_report: Mutex<ConfigLoadReport>,
+    /// Digests of the `config.json` contents the saver recently wrote.
+    /// Filled in by the saver thread; the config watcher skips a change
+    /// whose content is listed, so the app never "reloads" its own saves.
+    pub written_digests: Arc<WrittenDigests>,
+    /// Set when `config.json` came from a newer FloatView: the saver,
+    /// shutdown and startup recovery then leave the file alone for the
+    /// run. Shared with the saver thread.
+    pub keep_newer_config: Arc<AtomicBool>,
+    /// Machine policy and `FLOATVIEW_*` overrides, resolved at startup.
+    /// Every config replacement goes through `layers.enforce` so locked
+    /// keys can't be changed from Settings or a hand edit.
+    pub layers: ConfigLayers,
+    /// The user's own values for the keys `layers` pins, which saves put
+    /// back; see [`crate::config_io::persisted_config`]. Replaced whenever
+    /// the config is re-read from the user (a reload, a restored backup,
+    /// a profile switch). Lock after `config`.
+    pub user_values: Mutex<UserValues>,
+    /// The `site_settings` entry currently overlaid on `config`, the
+    /// global values it displaced, and the page zoom. Config writes go
+    /// through [`crate::config_io::persisted_config`] so the overrides
+    /// never reach disk. Lock after `config`.
+    pub site: Mutex<SiteSession>,
+    /// Browsing history, newest first; see [`crate::history`]. Lock after
+    /// `config`.
+    pub history: Mutex<Vec<HistoryEntry>>,
+    /// `history.jsonl` beside `config_path`.
+    pub history_path: PathBuf,
+    /// Whether the kiosk PIN was entered this run, and when the exit
+    /// hotkey was pressed; see [`crate::kiosk`]. Lock after `config`.
+    pub kiosk: Mutex<KioskSession>,
+    /// Folders the user opened a local file from this run; the
+    /// `floatview-file` protocol serves nothing outside them. See
+    /// [`crate::local_media`]. Lock after `config`.
+    pub local_folders: Mutex<Vec<PathBuf>>,
+    /// The `floatview://` link FloatView was started with, held until the
+    /// first site page finishes loading so the startup navigation can't
+    /// overtake it.
+    pub pending_deep_link: Mutex<Option<String>>,
 }
 
 /// Constant-time token check would be nice, but this is a local IPC token
@@ -124,6 +187,18 @@ pub fn update_tray_locked<R: Runtime>(app: &AppHandle<R>, locked: bool) {
     with_tray_setters(app, |t| (t.set_locked)(locked));
 }
 
+/// Rebuild the tray's "Profiles" submenu after profiles were added,
+/// renamed, removed, or switched.
+pub fn update_tray_profiles<R: Runtime>(app: &AppHandle<R>, names: &[String], active: &str) {
+    with_tray_setters(app, |t| (t.set_profiles)(names, active));
+}
+
+/// Rebuild the tray's "Presets" submenu after window presets were saved,
+/// deleted, or reloaded from disk.
+pub fn update_tray_presets<R: Runtime>(app: &AppHandle<R>, names: &[String]) {
+    with_tray_setters(app, |t| (t.set_presets)(names));
+}
+
 /// Toggle the "Install Update" tray item. `Some(version)` enables and
 /// labels; `None` disables.
 pub fn update_tray_update_available<R: Runtime>(app: &AppHandle<R>, version: Option<&str>) {
@@ -0,0 +1,418 @@
+//! URL bar suggestions: bookmarks, recent URLs and history ranked by
+//! frecency (visit count weighted by how recently the page was visited)
+//! and by how well each page matches what's been typed.
+//!
+//! Every word of the query has to match the page's host, path or title,
+//! either as a substring or, for words of three or more characters, as a
+//! subsequence (`gfn` finds `grafana`). Where it matches sets a weight:
+//! the start of a host label beats the middle of a host, which beats the
+//! title and then the path. A page's score is its frecency times the
+//! weakest of its word weights, so one sloppy word can't ride on a strong
+//! one.
+//!
+//! The same page often appears in more than one source, and sometimes
+//! under a slightly different URL (`/` vs. no trailing slash, a
+//! `#fragment`). Entries are deduplicated with [`urls_match`]: a lower
+//! ranked copy folds into the suggestion it matches, adding its score and
+//! lending its source and title when they're more specific (a bookmark
+//! over a recent URL over plain history).
+//!
+//! Everything here is pure; [`crate::commands::suggest_urls`] supplies the
+//! config and history.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::config::Bookmark;
+use crate::history::HistoryEntry;
+use crate::urls::urls_match;
+
+/// Suggestions returned when the caller doesn't ask for a count, and the
+/// most it may ask for.
+pub const DEFAULT_SUGGESTIONS: usize = 8;
+pub const MAX_SUGGESTIONS: usize = 20;
+
+/// Frecency credited to a bookmark on top of its visits, so a bookmarked
+/// page outranks one visited once or twice.
+const BOOKMARK_BONUS: f64 = 140.0;
+
+/// Shortest query word tried as a subsequence; shorter ones match almost
+/// anything that way.
+const MIN_FUZZY_LEN: usize = 3;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Where a suggestion came from, most specific first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    Bookmark,
+    Recent,
+    History,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub url: String,
+    pub title: String,
+    pub source: MatchSource,
+    pub score: f64,
+}
+
+/// Visits weighted by age: full weight for the last four days, tapering to
+/// a tenth past three months.
+pub fn frecency(visit_count: u32, last_visit: u64, now: u64) -> f64 {
+    let weight = match now.saturating_sub(last_visit) / SECS_PER_DAY {
+        0..=3 => 100.0,
+        4..=14 => 70.0,
+        15..=31 => 50.0,
+        32..=90 => 30.0,
+        _ => 10.0,
+    };
+    f64::from(visit_count.max(1)) * weight
+}
+
+/// How well `query` matches a page, from 0 (no match) to 1. An empty query
+/// matches everything fully.
+pub fn match_quality(query: &str, url: &str, title: &str) -> f64 {
+    let (host, path) = match Url::parse(url) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or_default().to_lowercase();
+            let mut path = parsed.path().to_lowercase();
+            if let Some(q) = parsed.query() {
+                path.push('?');
+                path.push_str(&q.to_lowercase());
+            }
+            (host, path)
+        }
+        Err(_) => (String::new(), url.to_lowercase()),
+    };
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let title = title.to_lowercase();
+
+    query
+        .split_whitespace()
+        .map(|word| word_quality(&query_word(word), host, &path, &title))
+        .fold(1.0, f64::min)
+}
+
+/// Lowercase a query word and drop a typed scheme or `www.`, so
+/// `https://www.gra` still matches by host.
+fn query_word(word: &str) -> String {
+    let word = word.to_lowercase();
+    let word = word
+        .strip_prefix("https://")
+        .or_else(|| word.strip_prefix("http://"))
+        .unwrap_or(&word);
+    word.strip_prefix("www.").unwrap_or(word).to_string()
+}
+
+fn word_quality(word: &str, host: &str, path: &str, title: &str) -> f64 {
+    if word.is_empty() {
+        return 1.0;
+    }
+    if host.starts_with(word) || host.split('.').any(|label| label.starts_with(word)) {
+        return 1.0;
+    }
+    if host.contains(word) {
+        return 0.8;
+    }
+    // A typed `host/path` prefix.
+    if word.contains('/') && format!("{host}{path}").starts_with(word) {
+        return 0.9;
+    }
+    if title
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|w| w.starts_with(word))
+    {
+        return 0.7;
+    }
+    if title.contains(word) || path.contains(word) {
+        return 0.5;
+    }
+    if word.chars().count() >= MIN_FUZZY_LEN
+        && (is_subsequence(word, host) || is_subsequence(word, title))
+    {
+        return 0.25;
+    }
+    0.0
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
+
+struct Candidate<'a> {
+    url: &'a str,
+    title: &'a str,
+    source: MatchSource,
+    frecency: f64,
+}
+
+/// Rank every page in `bookmarks`, `recent` and `history` against `query`
+/// and return the best `limit` (at most [`MAX_SUGGESTIONS`]), best first.
+pub fn suggest(
+    bookmarks: &[Bookmark],
+    recent: &[String],
+    history: &[HistoryEntry],
+    query: &str,
+    limit: usize,
+    now: u64,
+) -> Vec<Suggestion> {
+    let limit = limit.min(MAX_SUGGESTIONS);
+    if limit == 0 {
+        return Vec::new();
+    }
+
+    // Merge identical URLs across sources first; near-identical ones are
+    // folded by `urls_match` below.
+    let mut by_url: HashMap<&str, Candidate> = HashMap::new();
+    for entry in history {
+        by_url.insert(
+            &entry.url,
+            Candidate {
+                url: &entry.url,
+                title: &entry.title,
+                source: MatchSource::History,
+                frecency: frecency(entry.visit_count, entry.last_visit, now),
+            },
+        );
+    }
+    for url in recent {
+        by_url
+            .entry(url)
+            .and_modify(|c| c.source = MatchSource::Recent)
+            .or_insert(Candidate {
+                url,
+                title: "",
+                source: MatchSource::Recent,
+                frecency: frecency(1, now, now),
+            });
+    }
+    for bookmark in bookmarks {
+        let candidate = by_url.entry(&bookmark.url).or_insert(Candidate {
+            url: &bookmark.url,
+            title: "",
+            source: MatchSource::Bookmark,
+            frecency: 0.0,
+        });
+        candidate.source = MatchSource::Bookmark;
+        candidate.frecency += BOOKMARK_BONUS;
+        if !bookmark.title.is_empty() {
+            candidate.title = &bookmark.title;
+        }
+    }
+
+    let mut scored: Vec<(f64, Candidate)> = by_url
+        .into_values()
+        .filter_map(|c| {
+            let quality = match_quality(query, c.url, c.title);
+            (quality > 0.0).then_some((c.frecency * quality, c))
+        })
+        .collect();
+    scored.sort_by(|(a, ca), (b, cb)| b.total_cmp(a).then_with(|| ca.url.cmp(cb.url)));
+
+    // Best first, so the first copy of a page names the group.
+    let mut out: Vec<Suggestion> = Vec::new();
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (score, candidate) in scored {
+        let slot = by_key.entry(page_key(candidate.url)).or_default();
+        if let Some(&i) = slot
+            .iter()
+            .find(|&&i| urls_match(&out[i].url, candidate.url))
+        {
+            let kept = &mut out[i];
+            kept.score += score;
+            let more_specific = candidate.source < kept.source;
+            if !candidate.title.is_empty() && (more_specific || kept.title.is_empty()) {
+                kept.title = candidate.title.to_string();
+            }
+            kept.source = kept.source.min(candidate.source);
+            continue;
+        }
+        slot.push(out.len());
+        out.push(Suggestion {
+            url: candidate.url.to_string(),
+            title: candidate.title.to_string(),
+            source: candidate.source,
+            score,
+        });
+    }
+    out.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.url.cmp(&b.url)));
+    out.truncate(limit);
+    out
+}
+
+/// Cheap bucket for [`urls_match`]: the URL without its fragment or the
+/// trailing slashes of its path. Stored URLs are normalized, so any two
+/// that `urls_match` share a key, and only same-key pairs get parsed and
+/// compared.
+fn page_key(url: &str) -> String {
+    let url = url.split('#').next().unwrap_or(url);
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (url, None),
+    };
+    let mut key = base.trim_end_matches('/').to_string();
+    if let Some(query) = query {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_760_000_000;
+    const DAY: u64 = SECS_PER_DAY;
+
+    fn visit(url: &str, title: &str, visit_count: u32, days_ago: u64) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visit_count,
+            last_visit: NOW - days_ago * DAY,
+        }
+    }
+
+    fn bookmark(url: &str, title: &str) -> Bookmark {
+        let mut b = Bookmark::new(url.to_string(), title.to_string());
+        b.created = NOW - 400 * DAY;
+        b
+    }
+
+    fn fixture() -> (Vec<Bookmark>, Vec<String>, Vec<HistoryEntry>) {
+        let history = vec![
+            visit("https://grafana.lan/d/node", "Node Exporter Full", 40, 1),
+            visit("https://github.com/floatview/floatview", "FloatView", 3, 2),
+            visit("https://jellyfin.lan/web/", "Jellyfin", 2, 1),
+            visit(
+                "https://news.example.com/grafana-11",
+                "Grafana 11 released",
+                1,
+                1,
+            ),
+            visit("https://old.example.com/", "Old thing", 50, 200),
+            visit("https://docs.rs/url/latest/url/", "url - Rust", 5, 10),
+        ];
+        let recent = vec![
+            "https://jellyfin.lan/web/".to_string(),
+            "https://github.com/floatview/floatview".to_string(),
+        ];
+        let bookmarks = vec![
+            bookmark("https://jellyfin.lan/web", "Media"),
+            bookmark("https://status.example.com/", "Status page"),
+        ];
+        (bookmarks, recent, history)
+    }
+
+    /// `(query, expected suggestions as (url, source), best first)`.
+    const SUGGEST_CASES: &[(&str, &[(&str, MatchSource)])] = &[
+        // Host prefix, ranked by frecency; the title-only match trails.
+        (
+            "gra",
+            &[
+                ("https://grafana.lan/d/node", MatchSource::History),
+                ("https://news.example.com/grafana-11", MatchSource::History),
+            ],
+        ),
+        // A typed scheme and www. are ignored.
+        (
+            "https://www.github",
+            &[(
+                "https://github.com/floatview/floatview",
+                MatchSource::Recent,
+            )],
+        ),
+        // The bookmark and the history/recent entry are one page (trailing
+        // slash); it keeps the best URL and reports the bookmark.
+        (
+            "jelly",
+            &[("https://jellyfin.lan/web/", MatchSource::Bookmark)],
+        ),
+        // Bookmark titles are searchable.
+        (
+            "media",
+            &[("https://jellyfin.lan/web", MatchSource::Bookmark)],
+        ),
+        // Every word has to match somewhere.
+        (
+            "node exporter",
+            &[("https://grafana.lan/d/node", MatchSource::History)],
+        ),
+        ("node jellyfin", &[]),
+        // Subsequence matching for longer words only.
+        (
+            "gfn",
+            &[
+                ("https://grafana.lan/d/node", MatchSource::History),
+                ("https://news.example.com/grafana-11", MatchSource::History),
+            ],
+        ),
+        ("gf", &[]),
+        // Path matches.
+        (
+            "latest",
+            &[("https://docs.rs/url/latest/url/", MatchSource::History)],
+        ),
+        // A bookmark that was never visited is still offered.
+        (
+            "status",
+            &[("https://status.example.com/", MatchSource::Bookmark)],
+        ),
+    ];
+
+    #[test]
+    fn suggest_matches_shared_table() {
+        let (bookmarks, recent, history) = fixture();
+        for (i, (query, expected)) in SUGGEST_CASES.iter().enumerate() {
+            let got: Vec<(String, MatchSource)> =
+                suggest(&bookmarks, &recent, &history, query, 10, NOW)
+                    .into_iter()
+                    .map(|s| (s.url, s.source))
+                    .collect();
+            let want: Vec<(String, MatchSource)> = expected
+                .iter()
+                .map(|(url, source)| (url.to_string(), *source))
+                .collect();
+            assert_eq!(got, want, "case #{i}: query {query:?}");
+        }
+    }
+
+    #[test]
+    fn empty_query_ranks_everything_by_frecency() {
+        let (bookmarks, recent, history) = fixture();
+        let got = suggest(&bookmarks, &recent, &history, "", 4, NOW);
+        let urls: Vec<&str> = got.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://grafana.lan/d/node",
+                "https://old.example.com/",
+                "https://docs.rs/url/latest/url/",
+                "https://jellyfin.lan/web/",
+            ]
+        );
+        // Visits (200) plus the bookmark (140) for the same page.
+        assert_eq!(got[3].score, 340.0);
+        assert_eq!(got[3].title, "Media", "bookmark titles win");
+        assert!(suggest(&bookmarks, &recent, &history, "", 0, NOW).is_empty());
+        assert_eq!(
+            suggest(&bookmarks, &recent, &history, "", usize::MAX, NOW).len(),
+            7
+        );
+    }
+
+    #[test]
+    fn frecency_decays_with_age() {
+        assert_eq!(frecency(0, NOW, NOW), 100.0);
+        assert_eq!(frecency(3, NOW - 10 * DAY, NOW), 210.0);
+        assert_eq!(frecency(1, NOW - 365 * DAY, NOW), 10.0);
+        assert_eq!(frecency(1, NOW + DAY, NOW), 100.0, "clock skew is fresh");
+    }
+}
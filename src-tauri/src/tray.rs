@@ -7,7 +7,7 @@
 //! via `state::update_tray_*` helpers that call closures stashed on
 //! `AppState::tray` during setup.
 //!
-//! Layout (seven items, no redundancy):
+//! Layout:
 //!
 //! ```text
 //! Show/Hide Window
@@ -15,35 +15,60 @@
 //! ☑ Always on Top        Alt+Shift+T
 //! ☐ Click-Through Mode   Alt+Shift+D
 //! ─────────────────
+//! Profiles            ▸  ☑ Jellyfin / ☐ Grafana / …
+//! Presets             ▸  Corner mini / Side panel / …
 //! Settings…
 //! Go Home
 //! ─────────────────
+//! Reload Page
+//! Show Control Strip
+//! ─────────────────
 //! Install Update v1.3.0      ← disabled when none available
 //! Quit
 //! ```
 
-use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Emitter, Manager};
 use tracing::{error, warn};
 
 use crate::actions::{
-    do_install_update, do_navigate_home, do_toggle_always_on_top, do_toggle_locked,
+    do_apply_window_preset, do_install_update, do_navigate_home, do_switch_profile,
+    do_toggle_always_on_top, do_toggle_locked,
+};
+use crate::profiles::{active_profile_name, profile_names};
+use crate::state::{
+    AppState, TrayBoolSetter, TrayPresetsSetter, TrayProfilesSetter, TraySetters, TrayUpdateSetter,
 };
-use crate::state::{AppState, TrayBoolSetter, TraySetters, TrayUpdateSetter};
+use crate::window_presets::preset_names;
 use crate::window_state::persist_window_geometry;
 
 const INSTALL_UPDATE_IDLE_LABEL: &str = "No Updates Available";
 
+/// Menu-id prefix for the per-profile items; the rest of the id is the
+/// profile name.
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+/// Menu-id prefix for the per-preset items; the rest of the id is the
+/// preset name.
+const PRESET_ITEM_PREFIX: &str = "preset:";
+
 pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let show = MenuItem::with_id(app, "show", "Show/Hide Window", true, None::<&str>)?;
 
     // Initial state loaded from config so the tray check marks match
     // reality as soon as the menu is first opened.
-    let (initial_ontop, initial_locked) = match app.state::<AppState>().config.lock() {
-        Ok(c) => (c.window.always_on_top, c.window.locked),
-        Err(_) => (true, false),
-    };
+    let (initial_ontop, initial_locked, profiles, active_profile, presets) =
+        match app.state::<AppState>().config.lock() {
+            Ok(c) => (
+                c.window.always_on_top,
+                c.window.locked,
+                profile_names(&c),
+                active_profile_name(&c).to_string(),
+                preset_names(&c),
+            ),
+            Err(_) => (true, false, Vec::new(), String::new(), Vec::new()),
+        };
 
     let toggle_top = CheckMenuItem::with_id(
         app,
@@ -62,6 +87,11 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         Some("Alt+Shift+D"),
     )?;
 
+    let profiles_menu = Submenu::with_id(app, "profiles", "Profiles", true)?;
+    fill_profiles_menu(app, &profiles_menu, &profiles, &active_profile);
+    let presets_menu = Submenu::with_id(app, "presets", "Presets", true)?;
+    fill_presets_menu(app, &presets_menu, &presets);
+
     let settings = MenuItem::with_id(app, "settings", "Settings…", true, None::<&str>)?;
     let go_home = MenuItem::with_id(app, "go_home", "Go Home", true, None::<&str>)?;
     // Rescue affordances: "Reload Page" hard-reloads the webview when
@@ -94,6 +124,8 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
             &toggle_top,
             &toggle_lock,
             &PredefinedMenuItem::separator(app)?,
+            &profiles_menu,
+            &presets_menu,
             &settings,
             &go_home,
             &PredefinedMenuItem::separator(app)?,
@@ -113,6 +145,8 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         toggle_top.clone(),
         toggle_lock.clone(),
         install_update.clone(),
+        profiles_menu.clone(),
+        presets_menu.clone(),
     );
 
     // Fallback icon: a 1x1 transparent pixel so a missing asset can't
@@ -144,6 +178,12 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
             "reload_page" => reload_page_hard(app),
             "show_strip" => force_show_strip(app),
             "install_update" => do_install_update(app),
+            id if id.starts_with(PROFILE_ITEM_PREFIX) => {
+                do_switch_profile(app, &id[PROFILE_ITEM_PREFIX.len()..]);
+            }
+            id if id.starts_with(PRESET_ITEM_PREFIX) => {
+                do_apply_window_preset(app, &id[PRESET_ITEM_PREFIX.len()..]);
+            }
             "quit" => {
                 if let Some(window) = app.get_webview_window("main") {
                     let state = app.state::<AppState>();
@@ -215,6 +255,66 @@ fn force_show_strip(app: &AppHandle) {
     }
 }
 
+/// Replace the contents of the "Profiles" submenu with one check item per
+/// profile, the active one checked. Rebuilt rather than patched so the
+/// marks never drift from config: muda flips a check item's mark on every
+/// click, including a click on the already-active profile.
+fn fill_profiles_menu(app: &AppHandle, menu: &Submenu<tauri::Wry>, names: &[String], active: &str) {
+    fill_named_menu(app, menu, PROFILE_ITEM_PREFIX, names, Some(active));
+}
+
+/// Replace the contents of the "Presets" submenu with one item per window
+/// preset, or a disabled placeholder when there are none.
+fn fill_presets_menu(app: &AppHandle, menu: &Submenu<tauri::Wry>, names: &[String]) {
+    fill_named_menu(app, menu, PRESET_ITEM_PREFIX, names, None);
+    if names.is_empty() {
+        match MenuItem::with_id(app, "presets_none", "No Presets Saved", false, None::<&str>) {
+            Ok(item) => {
+                if let Err(e) = menu.append(&item) {
+                    warn!("Failed to add tray presets placeholder: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to build tray presets placeholder: {}", e),
+        }
+    }
+}
+
+/// Clear `menu` and add one item per name, with id `prefix` + name. With
+/// `checked`, the items are check items and the one named `checked` is
+/// marked; without it they are plain items.
+fn fill_named_menu(
+    app: &AppHandle,
+    menu: &Submenu<tauri::Wry>,
+    prefix: &str,
+    names: &[String],
+    checked: Option<&str>,
+) {
+    loop {
+        match menu.remove_at(0) {
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => {
+                warn!(prefix, "Failed to clear tray submenu: {}", e);
+                break;
+            }
+        }
+    }
+    for name in names {
+        let id = format!("{prefix}{name}");
+        let added = match checked {
+            Some(checked) => {
+                CheckMenuItem::with_id(app, id, name, true, name == checked, None::<&str>)
+                    .and_then(|item| menu.append(&item))
+            }
+            None => MenuItem::with_id(app, id, name, true, None::<&str>)
+                .and_then(|item| menu.append(&item)),
+        };
+        if let Err(e) = added {
+            warn!(prefix, item = %name, "Failed to add tray menu item: {}", e);
+        }
+    }
+}
+
 /// Build the [`TraySetters`] closures that each capture their respective
 /// menu item and install them on `AppState`. The rest of the app talks
 /// to the tray exclusively through these closures, so `tray.rs` stays
@@ -224,6 +324,8 @@ fn install_setters(
     toggle_top: CheckMenuItem<tauri::Wry>,
     toggle_lock: CheckMenuItem<tauri::Wry>,
     install_update: MenuItem<tauri::Wry>,
+    profiles_menu: Submenu<tauri::Wry>,
+    presets_menu: Submenu<tauri::Wry>,
 ) {
     let top_item = toggle_top;
     let set_always_on_top: TrayBoolSetter = Box::new(move |on| {
@@ -261,10 +363,22 @@ fn install_setters(
             }
         });
 
+    let menu_app = app.clone();
+    let set_profiles: TrayProfilesSetter = Box::new(move |names: &[String], active: &str| {
+        fill_profiles_menu(&menu_app, &profiles_menu, names, active);
+    });
+
+    let menu_app = app.clone();
+    let set_presets: TrayPresetsSetter = Box::new(move |names: &[String]| {
+        fill_presets_menu(&menu_app, &presets_menu, names);
+    });
+
     let setters = TraySetters {
         set_always_on_top,
         set_locked,
         set_update_available,
+        set_profiles,
+        set_presets,
     };
 
     match app.state::<AppState>().tray.lock() {
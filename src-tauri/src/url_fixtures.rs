@@ -70,3 +70,144 @@ pub const URL_MATCH_CASES: &[(&str, &str, bool)] = &[
     ("not a url", "not a url", true),
     ("not a url", "https://example.com/", false),
 ];
+
+/// `(url, expected)` pairs for [`crate::urls::strip_tracking_params`]
+/// with the default `tracking_params` rules. Mirrored in
+/// `js/urltest.test.mjs` like [`URL_MATCH_CASES`].
+pub const STRIP_CASES: &[(&str, &str)] = &[
+    // Nothing to strip: returned as given.
+    ("https://example.com/", "https://example.com/"),
+    ("https://example.com/?q=1", "https://example.com/?q=1"),
+    ("not a url", "not a url"),
+    // Global names, with `utm_*` as a prefix; the query goes when empty.
+    (
+        "https://example.com/a?utm_source=x&utm_medium=y",
+        "https://example.com/a",
+    ),
+    (
+        "https://example.com/a?id=7&fbclid=abc&page=2",
+        "https://example.com/a?id=7&page=2",
+    ),
+    // Names compare case-insensitively and after decoding.
+    (
+        "https://example.com/?UTM_Source=x&q=1",
+        "https://example.com/?q=1",
+    ),
+    (
+        "https://example.com/?q=1&%67clid=abc",
+        "https://example.com/?q=1",
+    ),
+    // Other parameters keep their order and encoding; the fragment stays.
+    (
+        "https://example.com/?b=%20&gclid=1&a=x+y#top",
+        "https://example.com/?b=%20&a=x+y#top",
+    ),
+    // A prefix rule only covers the prefix; `utm` alone isn't a tag.
+    ("https://example.com/?utm=1", "https://example.com/?utm=1"),
+    // Host rules apply on their host, and `*.` covers subdomains.
+    (
+        "https://www.youtube.com/watch?v=abc&si=xyz&pp=1",
+        "https://www.youtube.com/watch?v=abc",
+    ),
+    (
+        "https://youtube.com/watch?v=abc&si=xyz",
+        "https://youtube.com/watch?v=abc",
+    ),
+    ("https://youtu.be/abc?si=xyz", "https://youtu.be/abc"),
+    (
+        "https://open.spotify.com/track/1?si=abc",
+        "https://open.spotify.com/track/1",
+    ),
+    // ...and nowhere else.
+    ("https://example.com/?si=1", "https://example.com/?si=1"),
+    (
+        "https://notyoutube.com/watch?v=abc&si=xyz",
+        "https://notyoutube.com/watch?v=abc&si=xyz",
+    ),
+];
+
+/// `(a, b, expected)` triples for [`crate::urls::urls_match_with`] with
+/// the default rules and `ignore_when_matching` on: [`URL_MATCH_CASES`]
+/// still hold, and tracking parameters no longer count. Mirrored in
+/// `js/urltest.test.mjs`.
+pub const URL_MATCH_IGNORING_CASES: &[(&str, &str, bool)] = &[
+    (
+        "https://example.com/a?utm_source=x",
+        "https://example.com/a",
+        true,
+    ),
+    (
+        "https://www.youtube.com/watch?v=abc&si=1",
+        "https://www.youtube.com/watch?v=abc&si=2",
+        true,
+    ),
+    (
+        "https://www.youtube.com/watch?v=abc&si=1",
+        "https://www.youtube.com/watch?v=def",
+        false,
+    ),
+    // Only the listed names are ignored.
+    ("https://example.com/?si=1", "https://example.com/", false),
+    (
+        "https://example.com/?q=1&fbclid=a",
+        "https://example.com/?q=2",
+        false,
+    ),
+    ("https://example.com/", "http://example.com/?gclid=1", false),
+];
+
+/// `(input, expected URL)` pairs for
+/// [`crate::omnibox::resolve_omnibox_input`] with the default engines,
+/// DuckDuckGo as the default, and a `jf` engine searching a Jellyfin
+/// server. Driven by [`crate::omnibox::tests`].
+///
+/// Unlike [`URL_MATCH_CASES`], this table has no JS copy: the strip asks
+/// Rust, and its own heuristic is only a fallback for pages where the
+/// command channel is down.
+pub const OMNIBOX_CASES: &[(&str, &str)] = &[
+    // Hosts and addresses open directly; a scheme is added when missing.
+    ("example.com", "https://example.com/"),
+    ("  example.com/path?q=1  ", "https://example.com/path?q=1"),
+    ("http://192.168.1.20:8096", "http://192.168.1.20:8096/"),
+    ("192.168.1.20:8096", "https://192.168.1.20:8096/"),
+    ("localhost", "https://localhost/"),
+    ("localhost:3000", "https://localhost:3000/"),
+    ("HTTPS://Example.COM", "https://example.com/"),
+    // Words, spaces, and things normalize_url refuses are searches.
+    ("cats", "https://duckduckgo.com/?q=cats"),
+    (
+        "rust web framework",
+        "https://duckduckgo.com/?q=rust%20web%20framework",
+    ),
+    (
+        "what is 2.5 + 2",
+        "https://duckduckgo.com/?q=what%20is%202.5%20%2B%202",
+    ),
+    ("c++", "https://duckduckgo.com/?q=c%2B%2B"),
+    (
+        "ftp://example.com",
+        "https://duckduckgo.com/?q=ftp%3A%2F%2Fexample.com",
+    ),
+    ("news:today", "https://duckduckgo.com/?q=news%3Atoday"),
+    // Keywords pick an engine, case-insensitively; a keyword alone is an
+    // ordinary query.
+    (
+        "yt cats",
+        "https://www.youtube.com/results?search_query=cats",
+    ),
+    (
+        "YT  lo-fi beats",
+        "https://www.youtube.com/results?search_query=lo-fi%20beats",
+    ),
+    (
+        "w example.com",
+        "https://en.wikipedia.org/w/index.php?search=example.com",
+    ),
+    ("yt", "https://duckduckgo.com/?q=yt"),
+    (
+        "jf movie name",
+        "http://192.168.1.20:8096/web/#/search.html?query=movie%20name",
+    ),
+    // Unicode is UTF-8 percent-encoded.
+    ("g café", "https://www.google.com/search?q=caf%C3%A9"),
+];
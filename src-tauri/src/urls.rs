@@ -1,12 +1,38 @@
 //! URL parsing, normalization, and comparison helpers.
 //!
-//! Used by command handlers, config sanitization, and bookmark dedup.
+//! Used by command handlers, config sanitization, and bookmark dedup,
+//! along with the tracking-parameter rules (`AppConfig::tracking_params`)
+//! applied before a URL is stored.
 
+use url::form_urlencoded;
 use url::Url;
 
+use crate::config::TrackingParams;
+
 pub const MAX_URL_LEN: usize = 2048;
 pub const DEFAULT_HOME_URL: &str = "https://www.google.com";
 
+/// Caps on `tracking_params`: global names, host rules, and the names in
+/// one host rule each count against [`MAX_TRACKING_PARAMS`].
+pub const MAX_TRACKING_PARAMS: usize = 100;
+pub const MAX_TRACKING_HOSTS: usize = 100;
+pub const MAX_TRACKING_PARAM_LEN: usize = 64;
+
+/// Parameters `tracking_params.params` starts with: campaign tags and
+/// ad-click IDs that never change what the page shows.
+pub const DEFAULT_TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "mc_cid", "mc_eid",
+    "igshid", "yclid", "_hsenc", "_hsmi", "mkt_tok",
+];
+
+/// Host rules `tracking_params.hosts` starts with: share and session
+/// tags on streaming sites, where the same names elsewhere may matter.
+pub const DEFAULT_HOST_TRACKING_PARAMS: &[(&str, &[&str])] = &[
+    ("*.youtube.com", &["si", "pp", "feature"]),
+    ("youtu.be", &["si", "feature"]),
+    ("open.spotify.com", &["si"]),
+];
+
 /// Normalize a user-supplied URL string.
 ///
 /// - Trims whitespace.
@@ -73,6 +99,117 @@ pub fn urls_match(a: &str, b: &str) -> bool {
         && ua.query() == ub.query()
 }
 
+/// Compare two URLs like [`urls_match`], but first strip the tracking
+/// parameters from both when `rules.ignore_when_matching` is on. A
+/// bookmark stored without `?si=` then matches the page opened with it.
+pub fn urls_match_with(a: &str, b: &str, rules: &TrackingParams) -> bool {
+    if !rules.ignore_when_matching {
+        return urls_match(a, b);
+    }
+    urls_match(
+        &strip_tracking_params(a, rules),
+        &strip_tracking_params(b, rules),
+    )
+}
+
+/// The form of `url` to store as a bookmark, recent URL or history entry:
+/// [`strip_tracking_params`] when `rules.strip` is on, else unchanged.
+pub fn url_to_store(url: &str, rules: &TrackingParams) -> String {
+    if rules.strip {
+        strip_tracking_params(url, rules)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Remove every query parameter named in `rules.params`, or in a
+/// `rules.hosts` entry matching the URL's host. Names compare
+/// case-insensitively after form-decoding, and a trailing `*` matches a
+/// prefix. Other parameters keep their order and encoding; a query left
+/// empty is dropped. Unparseable URLs, and URLs with nothing to strip,
+/// come back unchanged.
+pub fn strip_tracking_params(url: &str, rules: &TrackingParams) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(query) = parsed.query() else {
+        return url.to_string();
+    };
+    let host = parsed.host_str().unwrap_or_default();
+    let host_params: Vec<&String> = rules
+        .hosts
+        .iter()
+        .filter(|(pattern, _)| host_matches(pattern, host))
+        .flat_map(|(_, params)| params)
+        .collect();
+    let is_tracking = |segment: &str| {
+        let name = form_urlencoded::parse(segment.as_bytes())
+            .next()
+            .map(|(name, _)| name.to_lowercase())
+            .unwrap_or_default();
+        !name.is_empty()
+            && rules
+                .params
+                .iter()
+                .chain(host_params.iter().copied())
+                .any(|pattern| param_matches(pattern, &name))
+    };
+    let segments: Vec<&str> = query.split('&').collect();
+    let kept: Vec<&str> = segments
+        .iter()
+        .copied()
+        .filter(|segment| !is_tracking(segment))
+        .collect();
+    if kept.len() == segments.len() {
+        return url.to_string();
+    }
+    let kept = kept.join("&");
+    parsed.set_query((!kept.is_empty()).then_some(kept.as_str()));
+    parsed.to_string()
+}
+
+/// Whether a host pattern covers `host`: an exact host (`youtu.be`), or a
+/// domain and all of its subdomains (`*.youtube.com`). Both sides are
+/// assumed lowercase.
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => {
+            host == domain
+                || host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        }
+        None => host == pattern,
+    }
+}
+
+/// Whether a lowercase parameter `name` matches `pattern`, which is
+/// compared case-insensitively and may end in `*` for a prefix match.
+fn param_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+/// Trim and lowercase one `tracking_params` name for the sanitizer. A `*`
+/// is allowed only at the end, after at least one character. The error
+/// is the correction reason.
+pub fn clean_tracking_param(param: &str) -> Result<String, &'static str> {
+    let param = param.trim().to_lowercase();
+    let name = param.strip_suffix('*').unwrap_or(&param);
+    if name.is_empty()
+        || param.chars().count() > MAX_TRACKING_PARAM_LEN
+        || name
+            .chars()
+            .any(|c| c == '*' || c == '&' || c == '=' || c.is_whitespace() || c.is_control())
+    {
+        return Err("tracking parameter must be a name, optionally ending in *");
+    }
+    Ok(param)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,4 +293,84 @@ mod tests {
             );
         }
     }
+
+    /// [`crate::url_fixtures::STRIP_CASES`] against the default rules.
+    #[test]
+    fn strip_tracking_params_matches_shared_truth_table() {
+        let rules = TrackingParams::default();
+        for (i, (url, expected)) in crate::url_fixtures::STRIP_CASES.iter().enumerate() {
+            assert_eq!(
+                strip_tracking_params(url, &rules),
+                *expected,
+                "case #{i}: strip_tracking_params({url:?})"
+            );
+        }
+    }
+
+    /// [`crate::url_fixtures::URL_MATCH_IGNORING_CASES`] with the default
+    /// rules and `ignore_when_matching` on.
+    #[test]
+    fn urls_match_with_ignores_tracking_params_when_asked() {
+        let mut rules = TrackingParams {
+            ignore_when_matching: true,
+            ..TrackingParams::default()
+        };
+        for (i, (a, b, expected)) in crate::url_fixtures::URL_MATCH_IGNORING_CASES
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(
+                urls_match_with(a, b, &rules),
+                *expected,
+                "case #{i}: urls_match_with({a:?}, {b:?})"
+            );
+        }
+        for (i, (a, b, expected)) in crate::url_fixtures::URL_MATCH_CASES.iter().enumerate() {
+            assert_eq!(
+                urls_match_with(a, b, &rules),
+                *expected,
+                "URL_MATCH_CASES #{i} with rules"
+            );
+        }
+        rules.ignore_when_matching = false;
+        assert!(!urls_match_with(
+            "https://example.com/?utm_source=x",
+            "https://example.com/",
+            &rules
+        ));
+    }
+
+    #[test]
+    fn url_to_store_honors_strip_switch() {
+        let mut rules = TrackingParams::default();
+        let url = "https://example.com/a?id=1&fbclid=abc";
+        assert_eq!(url_to_store(url, &rules), "https://example.com/a?id=1");
+        rules.strip = false;
+        assert_eq!(url_to_store(url, &rules), url);
+    }
+
+    #[test]
+    fn clean_tracking_param_checks_shape() {
+        assert_eq!(clean_tracking_param(" UTM_* "), Ok("utm_*".to_string()));
+        assert_eq!(clean_tracking_param("si"), Ok("si".to_string()));
+        for bad in [
+            "",
+            "*",
+            "a*b",
+            "a b",
+            "a=b",
+            &"x".repeat(MAX_TRACKING_PARAM_LEN + 1),
+        ] {
+            assert!(clean_tracking_param(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn host_matches_exact_and_wildcard() {
+        assert!(host_matches("youtu.be", "youtu.be"));
+        assert!(!host_matches("youtu.be", "www.youtu.be"));
+        assert!(host_matches("*.youtube.com", "youtube.com"));
+        assert!(host_matches("*.youtube.com", "m.youtube.com"));
+        assert!(!host_matches("*.youtube.com", "notyoutube.com"));
+    }
 }
@@ -0,0 +1,337 @@
+//! Window presets: named snapshots of the window's geometry, opacity,
+//! always-on-top, click-through and crop ("corner mini", "side panel"),
+//! recalled from the snap popup, the tray's "Presets" submenu, or a
+//! preset's own global hotkey.
+//!
+//! Pure edits over `AppConfig` live here. Moving the window and keeping
+//! the tray and control strip in step is
+//! [`crate::ops::apply_window_preset`]. Presets sit outside profiles, so
+//! one saved while on "Grafana" is there on "Jellyfin" too.
+
+use crate::config::{clamp_opacity, AppConfig, MonitorInfo, WindowPreset};
+use crate::config_io::{MAX_HOTKEY_LEN, MAX_WINDOW_POS};
+use crate::hotkeys::parse_hotkey;
+use crate::window_state::{
+    is_rect_visible, restore_rect, Rect, MAX_SCALE_FACTOR, MAX_WINDOW_SIZE, MIN_SCALE_FACTOR,
+    MIN_WINDOW_SIZE,
+};
+
+/// Upper bound on stored presets. Each one is a tray menu item.
+pub const MAX_WINDOW_PRESETS: usize = 20;
+
+/// Longest preset name accepted, in characters.
+pub const MAX_PRESET_NAME_LEN: usize = 40;
+
+/// Trim and validate a user-supplied preset name.
+pub fn normalize_preset_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_PRESET_NAME_LEN {
+        return Err(format!(
+            "Preset name is too long (max {MAX_PRESET_NAME_LEN} characters)"
+        ));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Preset name cannot contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Trim and check a preset hotkey. Blank means no hotkey.
+pub fn normalize_preset_hotkey(hotkey: &str) -> Result<Option<String>, &'static str> {
+    let hotkey = hotkey.trim();
+    if hotkey.is_empty() {
+        return Ok(None);
+    }
+    if hotkey.len() > MAX_HOTKEY_LEN || parse_hotkey(hotkey).is_none() {
+        return Err("hotkey did not parse");
+    }
+    Ok(Some(hotkey.to_string()))
+}
+
+/// Index of the preset called `name`, compared case-insensitively.
+pub fn find_preset(presets: &[WindowPreset], name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    presets.iter().position(|p| p.name.to_lowercase() == name)
+}
+
+/// Index of the preset other than `except` bound to the same keys as
+/// `hotkey` (`Ctrl+Alt+1` and `alt+ctrl+1` are the same binding).
+pub fn find_hotkey_owner(
+    presets: &[WindowPreset],
+    hotkey: &str,
+    except: Option<usize>,
+) -> Option<usize> {
+    let keys = parse_hotkey(hotkey)?;
+    presets.iter().enumerate().position(|(i, p)| {
+        Some(i) != except && p.hotkey.as_deref().and_then(parse_hotkey) == Some(keys)
+    })
+}
+
+/// Preset names in menu order, for the tray submenu.
+pub fn preset_names(config: &AppConfig) -> Vec<String> {
+    config
+        .window_presets
+        .iter()
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+/// `(name, hotkey)` for each preset that has a hotkey. Compared before
+/// and after a config change to tell whether to re-register hotkeys.
+pub fn preset_hotkeys(presets: &[WindowPreset]) -> Vec<(&str, &str)> {
+    presets
+        .iter()
+        .filter_map(|p| Some((p.name.as_str(), p.hotkey.as_deref()?)))
+        .collect()
+}
+
+/// Snapshot the live window settings and crop as a preset.
+fn preset_from_live(config: &AppConfig, name: String, hotkey: Option<String>) -> WindowPreset {
+    let window = &config.window;
+    WindowPreset {
+        name,
+        hotkey,
+        x: window.x,
+        y: window.y,
+        width: window.width,
+        height: window.height,
+        scale_factor: window.scale_factor,
+        opacity: window.opacity,
+        always_on_top: window.always_on_top,
+        locked: window.locked,
+        crop: config.crop.clone(),
+    }
+}
+
+/// Save the live settings as preset `name`, replacing a preset of that
+/// name in place. `hotkey` is blank for none, and can't be another
+/// preset's.
+pub fn save_preset(config: &mut AppConfig, name: &str, hotkey: &str) -> Result<(), String> {
+    let name = normalize_preset_name(name)?;
+    let hotkey = normalize_preset_hotkey(hotkey)
+        .map_err(|_| format!("Hotkey \"{}\" is not recognized", hotkey.trim()))?;
+    let existing = find_preset(&config.window_presets, &name);
+    if let Some(hotkey) = &hotkey {
+        if let Some(owner) = find_hotkey_owner(&config.window_presets, hotkey, existing) {
+            return Err(format!(
+                "{hotkey} already applies \"{}\"",
+                config.window_presets[owner].name
+            ));
+        }
+    }
+    let preset = preset_from_live(config, name, hotkey);
+    match existing {
+        Some(index) => config.window_presets[index] = preset,
+        None if config.window_presets.len() >= MAX_WINDOW_PRESETS => {
+            return Err(format!("Preset limit reached (max {MAX_WINDOW_PRESETS})"));
+        }
+        None => config.window_presets.push(preset),
+    }
+    Ok(())
+}
+
+/// Remove preset `name`.
+pub fn delete_preset(config: &mut AppConfig, name: &str) -> Result<(), String> {
+    let index = find_preset(&config.window_presets, name)
+        .ok_or_else(|| format!("No preset named \"{name}\""))?;
+    config.window_presets.remove(index);
+    Ok(())
+}
+
+/// Load `preset`'s opacity, always-on-top, click-through and crop into
+/// the live fields. The geometry is applied to the window and saved from
+/// there, so the recorded monitor matches where it landed.
+pub fn apply_preset(config: &mut AppConfig, preset: &WindowPreset) {
+    config.window.opacity = preset.opacity;
+    config.window.always_on_top = preset.always_on_top;
+    config.window.locked = preset.locked;
+    config.crop = preset.crop.clone();
+}
+
+/// Where `preset` puts the window, in physical pixels (see
+/// [`restore_rect`]), or `None` when that's off every connected monitor
+/// and the window should be centered at the preset's size instead.
+pub fn preset_rect(monitors: &[MonitorInfo], preset: &WindowPreset) -> Option<Rect> {
+    let logical = (preset.x, preset.y, preset.width, preset.height);
+    let rect = restore_rect(monitors, logical, preset.scale_factor);
+    (monitors.is_empty() || is_rect_visible(monitors, rect)).then_some(rect)
+}
+
+/// Check a preset read from `config.json` for the sanitizer: its name,
+/// geometry and scale. Opacity is clamped; the hotkey and crop are left
+/// to the caller, which drops just those when they're bad. The error is
+/// the correction reason.
+pub fn clean_preset(preset: &WindowPreset) -> Result<WindowPreset, &'static str> {
+    let name = normalize_preset_name(&preset.name).map_err(|_| "invalid preset name")?;
+    let in_range = (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&preset.x)
+        && (-MAX_WINDOW_POS..=MAX_WINDOW_POS).contains(&preset.y)
+        && (MIN_WINDOW_SIZE..=MAX_WINDOW_SIZE).contains(&preset.width)
+        && (MIN_WINDOW_SIZE..=MAX_WINDOW_SIZE).contains(&preset.height);
+    if !in_range {
+        return Err("preset geometry out of range");
+    }
+    if preset
+        .scale_factor
+        .is_some_and(|s| !(MIN_SCALE_FACTOR..=MAX_SCALE_FACTOR).contains(&s))
+    {
+        return Err("invalid scale factor");
+    }
+    Ok(WindowPreset {
+        name,
+        opacity: clamp_opacity(preset.opacity),
+        ..preset.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CropConfig, WindowConfig};
+
+    fn monitor(x: i32, width: u32, scale_factor: f64) -> MonitorInfo {
+        MonitorInfo {
+            name: String::new(),
+            x,
+            y: 0,
+            width,
+            height: 1440,
+            scale_factor,
+        }
+    }
+
+    fn live() -> AppConfig {
+        AppConfig {
+            window: WindowConfig {
+                x: 40,
+                y: 60,
+                width: 480,
+                height: 270,
+                scale_factor: Some(1.0),
+                opacity: 0.7,
+                ..WindowConfig::default()
+            },
+            crop: Some(CropConfig {
+                x: 0.0,
+                y: 0.0,
+                width: 0.5,
+                height: 0.5,
+            }),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn save_preset_captures_live_settings_and_replaces_by_name() {
+        let mut config = live();
+        save_preset(&mut config, " Corner mini ", "Alt+Shift+1").expect("save");
+        let preset = &config.window_presets[0];
+        assert_eq!(preset.name, "Corner mini");
+        assert_eq!(preset.hotkey.as_deref(), Some("Alt+Shift+1"));
+        assert_eq!((preset.x, preset.width, preset.opacity), (40, 480, 0.7));
+        assert!(preset.crop.is_some());
+
+        config.window.opacity = 1.0;
+        save_preset(&mut config, "corner MINI", "").expect("overwrite");
+        assert_eq!(config.window_presets.len(), 1);
+        assert_eq!(config.window_presets[0].name, "corner MINI");
+        assert_eq!(config.window_presets[0].opacity, 1.0);
+        assert_eq!(config.window_presets[0].hotkey, None);
+    }
+
+    #[test]
+    fn save_preset_rejects_bad_names_hotkeys_and_overflow() {
+        let mut config = live();
+        assert!(save_preset(&mut config, "  ", "").is_err());
+        assert!(save_preset(&mut config, "Big", "Alt+nope").is_err());
+
+        save_preset(&mut config, "Big", "Ctrl+Alt+B").expect("save");
+        assert!(
+            save_preset(&mut config, "Side", "alt+ctrl+b").is_err(),
+            "another preset's binding"
+        );
+        save_preset(&mut config, "big", "Alt+Ctrl+B").expect("rebinding itself is fine");
+
+        for i in 1..MAX_WINDOW_PRESETS {
+            save_preset(&mut config, &format!("P{i}"), "").expect("save");
+        }
+        assert!(save_preset(&mut config, "One too many", "").is_err());
+        assert!(save_preset(&mut config, "P1", "").is_ok(), "replacing is");
+
+        delete_preset(&mut config, "BIG").expect("delete");
+        assert!(find_preset(&config.window_presets, "Big").is_none());
+        assert!(delete_preset(&mut config, "Big").is_err());
+    }
+
+    #[test]
+    fn apply_preset_sets_everything_but_geometry() {
+        let mut config = live();
+        save_preset(&mut config, "Review", "").expect("save");
+        let mut other = AppConfig::default();
+        apply_preset(&mut other, &config.window_presets[0]);
+        assert_eq!(other.window.opacity, 0.7);
+        assert_eq!(other.crop, config.crop);
+        assert_eq!(other.window.x, AppConfig::default().window.x);
+    }
+
+    #[test]
+    fn preset_rect_lands_on_a_monitor_or_centers() {
+        let mut config = live();
+        save_preset(&mut config, "Side", "").expect("save");
+        let mut preset = config.window_presets[0].clone();
+        preset.scale_factor = Some(2.0);
+        let monitors = [monitor(0, 3840, 2.0), monitor(3840, 2560, 1.0)];
+        assert_eq!(preset_rect(&monitors, &preset), Some((80, 120, 960, 540)));
+
+        preset.x = 2000;
+        assert_eq!(
+            preset_rect(&monitors, &preset),
+            Some((4000, 120, 480, 270)),
+            "sized for the monitor it lands on"
+        );
+
+        preset.x = -5000;
+        assert_eq!(preset_rect(&monitors, &preset), None);
+        assert!(preset_rect(&[], &preset).is_some());
+    }
+
+    #[test]
+    fn clean_preset_checks_name_geometry_and_scale() {
+        let mut config = live();
+        save_preset(&mut config, "Side", "Alt+1").expect("save");
+        let preset = config.window_presets[0].clone();
+        assert_eq!(clean_preset(&preset), Ok(preset.clone()));
+        assert_eq!(
+            clean_preset(&WindowPreset {
+                opacity: 0.0,
+                ..preset.clone()
+            })
+            .map(|p| p.opacity),
+            Ok(crate::config::MIN_OPACITY)
+        );
+        for bad in [
+            WindowPreset {
+                name: "\t".to_string(),
+                ..preset.clone()
+            },
+            WindowPreset {
+                width: 10,
+                ..preset.clone()
+            },
+            WindowPreset {
+                y: i32::MAX,
+                ..preset.clone()
+            },
+            WindowPreset {
+                scale_factor: Some(f64::NAN),
+                ..preset.clone()
+            },
+        ] {
+            assert!(clean_preset(&bad).is_err(), "{bad:?}");
+        }
+        assert_eq!(normalize_preset_hotkey(" "), Ok(None));
+        assert!(normalize_preset_hotkey("Ctrl+🎹").is_err());
+    }
+}
@@ -6,11 +6,22 @@
 //! minimized/maximized windows (whose reported size wouldn't reflect
 //! user intent) and anything unreasonably small, to avoid overwriting
 //! good geometry with a transient bad measurement.
+//!
+//! Geometry is saved in logical pixels with the scale it was captured at
+//! (`WindowConfig::scale_factor`), so a window saved on a 200% display
+//! comes back the same apparent size on a 100% one; see [`restore_rect`].
+//!
+//! Each save also records the monitor the window is on and files the
+//! geometry under the current monitor setup (`WindowConfig::placements`),
+//! so docking and undocking a laptop puts the window back where it was
+//! on each desk. [`placement_for`] picks the geometry to restore; it runs
+//! at startup, on a profile switch, and from the geometry saver thread
+//! when the connected monitors change.
 
-use tauri::{Monitor, WebviewWindow};
-use tracing::{debug, warn};
+use tauri::{Monitor, Runtime, WebviewWindow};
+use tracing::{debug, info, warn};
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, MonitorInfo, WindowConfig, WindowPlacement};
 use crate::config_io::save_config;
 use crate::state::AppState;
 
@@ -29,6 +40,91 @@ pub const MAX_WINDOW_SIZE: i32 = 10_000;
 pub const MIN_INNER_WIDTH: f64 = 880.0;
 pub const MIN_INNER_HEIGHT: f64 = 400.0;
 
+/// Most monitor setups a window remembers a placement for.
+pub const MAX_WINDOW_PLACEMENTS: usize = 10;
+
+/// A window rectangle: `(x, y, width, height)`. Physical pixels unless
+/// said otherwise.
+pub type Rect = (i32, i32, i32, i32);
+
+/// Scale factors outside this range are taken as garbage.
+pub const MIN_SCALE_FACTOR: f64 = 0.25;
+pub const MAX_SCALE_FACTOR: f64 = 8.0;
+
+/// `scale` if it's a usable scale factor, else 1.0.
+pub fn clamp_scale_factor(scale: f64) -> f64 {
+    if scale.is_finite() {
+        scale.clamp(MIN_SCALE_FACTOR, MAX_SCALE_FACTOR)
+    } else {
+        1.0
+    }
+}
+
+/// A physical length in logical pixels at `scale`.
+pub fn to_logical(physical: i32, scale: f64) -> i32 {
+    (physical as f64 / clamp_scale_factor(scale)).round() as i32
+}
+
+/// A logical length in physical pixels at `scale`.
+pub fn to_physical(logical: i32, scale: f64) -> i32 {
+    (logical as f64 * clamp_scale_factor(scale)).round() as i32
+}
+
+/// A physical rectangle in logical pixels at `scale`.
+pub fn logical_rect(rect: Rect, scale: f64) -> Rect {
+    (
+        to_logical(rect.0, scale),
+        to_logical(rect.1, scale),
+        to_logical(rect.2, scale),
+        to_logical(rect.3, scale),
+    )
+}
+
+/// A logical rectangle in physical pixels at `scale`.
+pub fn physical_rect(rect: Rect, scale: f64) -> Rect {
+    (
+        to_physical(rect.0, scale),
+        to_physical(rect.1, scale),
+        to_physical(rect.2, scale),
+        to_physical(rect.3, scale),
+    )
+}
+
+/// A size from the config as a window size: logical when the config
+/// records the `scale` it was captured at, physical (legacy) otherwise.
+pub fn config_size(scale: Option<f64>, width: i32, height: i32) -> tauri::Size {
+    match scale {
+        Some(_) => tauri::Size::Logical(tauri::LogicalSize {
+            width: width as f64,
+            height: height as f64,
+        }),
+        None => tauri::Size::Physical(tauri::PhysicalSize {
+            width: width as u32,
+            height: height as u32,
+        }),
+    }
+}
+
+/// Saved geometry back in physical pixels. The position is scaled by the
+/// factor it was `captured` at, so the window lands on the same pixel it
+/// was saved at; the size by the factor of the monitor it lands on, so it
+/// keeps its apparent size across displays of different scaling. With no
+/// `captured` scale the rectangle is physical already.
+pub fn restore_rect(monitors: &[MonitorInfo], rect: Rect, captured: Option<f64>) -> Rect {
+    let Some(captured) = captured else {
+        return rect;
+    };
+    let at_captured = physical_rect(rect, captured);
+    let (x, y) = (at_captured.0, at_captured.1);
+    let target = monitor_for(monitors, at_captured).map_or(captured, |m| m.scale_factor);
+    (
+        x,
+        y,
+        to_physical(rect.2, target),
+        to_physical(rect.3, target),
+    )
+}
+
 /// Clamp a persisted size to sane bounds. If both dimensions match the
 /// minimum (typically meaning the window was saved while minimized) we
 /// reset to the default size rather than come back up as a 200x200 square.
@@ -57,12 +153,201 @@ pub fn update_window_geometry_config(
     config.window.height = height.clamp(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE);
 }
 
+/// What's recorded about a connected monitor.
+pub fn monitor_info(monitor: &Monitor) -> MonitorInfo {
+    MonitorInfo {
+        name: monitor.name().cloned().unwrap_or_default(),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+        scale_factor: monitor.scale_factor(),
+    }
+}
+
+/// The monitors connected to `window`'s display, as [`MonitorInfo`]s.
+pub fn current_monitors<R: Runtime>(window: &WebviewWindow<R>) -> Vec<MonitorInfo> {
+    window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(monitor_info)
+        .collect()
+}
+
+/// Identifies a monitor setup: each monitor's name and rectangle, in a
+/// fixed order. Scale factors are left out, so changing a display's
+/// scaling doesn't make the desk look new.
+pub fn setup_key(monitors: &[MonitorInfo]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|m| format!("{}@{},{}+{}x{}", m.name, m.x, m.y, m.width, m.height))
+        .collect();
+    parts.sort();
+    parts.join(";")
+}
+
+/// Whether `a` and `b` are the same monitor in the same place.
+fn same_monitor(a: &MonitorInfo, b: &MonitorInfo) -> bool {
+    (&a.name, a.x, a.y, a.width, a.height) == (&b.name, b.x, b.y, b.width, b.height)
+}
+
+fn center_distance(monitor: &MonitorInfo, (x, y, width, height): Rect) -> i64 {
+    let cx = x as i64 + width as i64 / 2;
+    let cy = y as i64 + height as i64 / 2;
+    let mx = monitor.x as i64 + monitor.width as i64 / 2;
+    let my = monitor.y as i64 + monitor.height as i64 / 2;
+    (cx - mx).pow(2) + (cy - my).pow(2)
+}
+
+/// The monitor `rect` overlaps most, or the nearest one if it overlaps
+/// none.
+pub fn monitor_for(monitors: &[MonitorInfo], rect: Rect) -> Option<&MonitorInfo> {
+    let (x, y, width, height) = (rect.0 as i64, rect.1 as i64, rect.2 as i64, rect.3 as i64);
+    let overlap = |m: &MonitorInfo| {
+        let w = (x + width).min(m.x as i64 + m.width as i64) - x.max(m.x as i64);
+        let h = (y + height).min(m.y as i64 + m.height as i64) - y.max(m.y as i64);
+        w.max(0) * h.max(0)
+    };
+    monitors
+        .iter()
+        .max_by_key(|m| (overlap(m), -center_distance(m, rect)))
+}
+
+/// The connected monitor standing in for `saved`: the one with its name,
+/// else the one nearest to where it was.
+fn closest_monitor<'a>(
+    monitors: &'a [MonitorInfo],
+    saved: &MonitorInfo,
+) -> Option<&'a MonitorInfo> {
+    let by_name = monitors
+        .iter()
+        .find(|m| !saved.name.is_empty() && m.name == saved.name);
+    let saved_rect = (saved.x, saved.y, saved.width as i32, saved.height as i32);
+    by_name.or_else(|| {
+        monitors
+            .iter()
+            .min_by_key(|m| center_distance(m, saved_rect))
+    })
+}
+
+/// `rect` moved from monitor `from` to the same relative spot on `to`:
+/// the offset of its top-left corner keeps its fraction of the monitor's
+/// width and height, the size follows the change in scale, and the
+/// window is shrunk and pulled in to fit.
+pub fn relative_rect(from: &MonitorInfo, to: &MonitorInfo, rect: Rect) -> Rect {
+    let (x, y, width, height) = rect;
+    let rescale = |len: i32| to_physical(to_logical(len, from.scale_factor), to.scale_factor);
+    let min = to_physical(MIN_WINDOW_SIZE, to.scale_factor);
+    let width = rescale(width).min(to.width as i32).max(min);
+    let height = rescale(height).min(to.height as i32).max(min);
+    let place = |offset: i32, from_len: u32, to_start: i32, to_len: u32, len: i32| {
+        let fraction = if from_len == 0 {
+            0.0
+        } else {
+            (offset as f64 / from_len as f64).clamp(0.0, 1.0)
+        };
+        let pos = to_start as i64 + (fraction * to_len as f64).round() as i64;
+        let max = (to_start as i64 + to_len as i64 - len as i64).max(to_start as i64);
+        pos.clamp(to_start as i64, max) as i32
+    };
+    (
+        place(x - from.x, from.width, to.x, to.width, width),
+        place(y - from.y, from.height, to.y, to.height, height),
+        width,
+        height,
+    )
+}
+
+/// The monitor after the one `rect` is on, going left to right (then top
+/// to bottom) and wrapping around. `None` with fewer than two monitors.
+pub fn next_monitor(monitors: &[MonitorInfo], rect: Rect) -> Option<&MonitorInfo> {
+    if monitors.len() < 2 {
+        return None;
+    }
+    let current = monitor_for(monitors, rect)?;
+    let mut ordered: Vec<&MonitorInfo> = monitors.iter().collect();
+    ordered.sort_by_key(|m| (m.x, m.y));
+    let at = ordered.iter().position(|m| std::ptr::eq(*m, current))?;
+    Some(ordered[(at + 1) % ordered.len()])
+}
+
+/// Record `window`'s geometry (already updated) against the monitor it's
+/// on and the current setup. A no-op when the monitors are unknown.
+pub fn remember_placement(window: &mut WindowConfig, monitors: &[MonitorInfo]) {
+    let rect = (window.x, window.y, window.width, window.height);
+    let scale = window.scale_factor.unwrap_or(1.0);
+    let Some(monitor) = monitor_for(monitors, physical_rect(rect, scale)).cloned() else {
+        return;
+    };
+    let setup = setup_key(monitors);
+    window.monitor = Some(monitor.clone());
+    window.placements.retain(|p| p.setup != setup);
+    window.placements.insert(
+        0,
+        WindowPlacement {
+            setup,
+            monitor,
+            x: rect.0,
+            y: rect.1,
+            width: rect.2,
+            height: rect.3,
+            scale_factor: scale,
+        },
+    );
+    window.placements.truncate(MAX_WINDOW_PLACEMENTS);
+}
+
+/// Where to put the window on `monitors`, in physical pixels, or `None`
+/// to center it:
+///
+/// 1. The saved geometry, if the monitor it was on is still connected
+///    unchanged and the window would be visible there.
+/// 2. The placement remembered for this monitor setup.
+/// 3. The saved geometry moved to the same relative spot on the monitor
+///    with the saved monitor's name, or the nearest one.
+/// 4. For a config without a recorded monitor, the saved geometry if
+///    it's visible anywhere.
+pub fn placement_for(window: &WindowConfig, monitors: &[MonitorInfo]) -> Option<Rect> {
+    let (width, height) = normalize_startup_window_size(window.width, window.height);
+    let logical = (window.x, window.y, width, height);
+    let saved = restore_rect(monitors, logical, window.scale_factor);
+    if monitors.is_empty() {
+        return Some(saved);
+    }
+    if let Some(monitor) = &window.monitor {
+        if monitors.iter().any(|m| same_monitor(m, monitor)) && is_rect_visible(monitors, saved) {
+            return Some(saved);
+        }
+    }
+    let setup = setup_key(monitors);
+    if let Some(placement) = window.placements.iter().find(|p| p.setup == setup) {
+        let (width, height) = normalize_startup_window_size(placement.width, placement.height);
+        let logical = (placement.x, placement.y, width, height);
+        let rect = restore_rect(monitors, logical, Some(placement.scale_factor));
+        if is_rect_visible(monitors, rect) {
+            return Some(rect);
+        }
+    }
+    if let Some(monitor) = &window.monitor {
+        let target = closest_monitor(monitors, monitor)?;
+        // Where the window was on its old monitor, at that monitor's scale.
+        let on_saved = restore_rect(std::slice::from_ref(monitor), logical, window.scale_factor);
+        return Some(relative_rect(monitor, target, on_saved));
+    }
+    is_rect_visible(monitors, saved).then_some(saved)
+}
+
 /// Snapshot the current window geometry and enqueue a config save.
 ///
 /// Skips minimized/maximized windows (preserving whatever was saved last
 /// time the window was a normal restorable size) and windows reporting
-/// dimensions below `MIN_WINDOW_SIZE` (treated as a transient bad read).
-pub fn persist_window_geometry(window: &WebviewWindow, state: &AppState) -> Result<(), String> {
+/// dimensions below `MIN_WINDOW_SIZE` logical pixels (treated as a
+/// transient bad read).
+pub fn persist_window_geometry<R: Runtime>(
+    window: &WebviewWindow<R>,
+    state: &AppState,
+) -> Result<(), String> {
     if window.is_minimized().map_err(|e| e.to_string())? {
         debug!("Skipping geometry persistence because window is minimized");
         return Ok(());
@@ -74,23 +359,31 @@ pub fn persist_window_geometry(window: &WebviewWindow, state: &AppState) -> Resu
 
     let position = window.outer_position().map_err(|e| e.to_string())?;
     let size = window.outer_size().map_err(|e| e.to_string())?;
-    if size.width < MIN_WINDOW_SIZE as u32 || size.height < MIN_WINDOW_SIZE as u32 {
+    let scale = clamp_scale_factor(window.scale_factor().map_err(|e| e.to_string())?);
+    let (x, y, width, height) = logical_rect(
+        (
+            position.x,
+            position.y,
+            size.width as i32,
+            size.height as i32,
+        ),
+        scale,
+    );
+    if width < MIN_WINDOW_SIZE || height < MIN_WINDOW_SIZE {
         warn!(
             width = size.width,
             height = size.height,
+            scale,
             "Skipping geometry persistence due to unexpectedly small window size"
         );
         return Ok(());
     }
 
+    let monitors = current_monitors(window);
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    update_window_geometry_config(
-        &mut config,
-        position.x,
-        position.y,
-        size.width as i32,
-        size.height as i32,
-    );
+    update_window_geometry_config(&mut config, x, y, width, height);
+    config.window.scale_factor = Some(scale);
+    remember_placement(&mut config.window, &monitors);
     save_config(state, &config);
     drop(config);
     Ok(())
@@ -99,19 +392,17 @@ pub fn persist_window_geometry(window: &WebviewWindow, state: &AppState) -> Resu
 /// Is the saved geometry within a reasonable overlap of *any* current
 /// monitor? Used to avoid restoring a window onto a now-disconnected
 /// display, where it would be invisible.
-pub fn is_position_visible(monitors: &[Monitor], x: i32, y: i32, width: i32, height: i32) -> bool {
+pub fn is_rect_visible(monitors: &[MonitorInfo], rect: Rect) -> bool {
     const MIN_OVERLAP: i64 = 50;
     // Compute overlap in i64 so a corrupt/tampered config with x/y near
     // i32::MAX/MIN can't overflow `x + width` (which would panic in debug
     // and wrap in release, defeating the off-screen safety check itself).
-    let (x, y, width, height) = (x as i64, y as i64, width as i64, height as i64);
+    let (x, y, width, height) = (rect.0 as i64, rect.1 as i64, rect.2 as i64, rect.3 as i64);
     for monitor in monitors {
-        let mp = monitor.position();
-        let ms = monitor.size();
-        let mx = mp.x as i64;
-        let my = mp.y as i64;
-        let mw = ms.width as i64;
-        let mh = ms.height as i64;
+        let mx = monitor.x as i64;
+        let my = monitor.y as i64;
+        let mw = monitor.width as i64;
+        let mh = monitor.height as i64;
         let overlap_x = (x + width).min(mx + mw) - x.max(mx);
         let overlap_y = (y + height).min(my + mh) - y.max(my);
         if overlap_x >= MIN_OVERLAP && overlap_y >= MIN_OVERLAP {
@@ -122,29 +413,77 @@ pub fn is_position_visible(monitors: &[Monitor], x: i32, y: i32, width: i32, hei
 }
 
 /// Apply persisted window state (always-on-top, click-through, size, and
-/// position) to a freshly-created window. If the saved position isn't on
-/// any currently connected monitor, center the window instead.
-pub fn apply_window_state(window: &WebviewWindow, config: &AppConfig) {
+/// position) to a freshly-created window, or to the live one on a profile
+/// switch. Geometry comes from [`placement_for`]; when nothing fits the
+/// connected monitors, the window is centered.
+pub fn apply_window_state<R: Runtime>(window: &WebviewWindow<R>, config: &AppConfig) {
     let _ = window.set_always_on_top(config.window.always_on_top);
     let _ = window.set_ignore_cursor_events(config.window.locked);
+    apply_placement(window, &config.window, &current_monitors(window));
+}
 
-    let (width, height) = normalize_startup_window_size(config.window.width, config.window.height);
+/// Size and position `window` per [`placement_for`].
+fn apply_placement<R: Runtime>(
+    window: &WebviewWindow<R>,
+    saved: &WindowConfig,
+    monitors: &[MonitorInfo],
+) {
+    let placement = placement_for(saved, monitors);
+    let size = match placement {
+        Some((_, _, width, height)) => config_size(None, width, height),
+        None => {
+            let (width, height) = normalize_startup_window_size(saved.width, saved.height);
+            config_size(saved.scale_factor, width, height)
+        }
+    };
+    let _ = window.set_size(size);
+    match placement {
+        Some((x, y, _, _)) => {
+            let _ =
+                window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }));
+        }
+        None => {
+            let _ = window.center();
+        }
+    }
+}
 
-    let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
-        width: width as u32,
-        height: height as u32,
-    }));
+/// Follows the connected monitors from the geometry saver thread. When
+/// the setup changes (a dock, a projector), the window goes back to its
+/// spot for the new setup before anything saves where the OS pushed it.
+#[derive(Debug, Default)]
+pub struct MonitorWatch {
+    setup: Option<String>,
+}
 
-    let monitors = window.available_monitors().unwrap_or_default();
-    if !monitors.is_empty()
-        && !is_position_visible(&monitors, config.window.x, config.window.y, width, height)
-    {
-        let _ = window.center();
-    } else {
-        let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
-            x: config.window.x,
-            y: config.window.y,
-        }));
+impl MonitorWatch {
+    /// Compare the connected monitors with the last check and restore the
+    /// window's placement if they changed. Returns whether they did. The
+    /// first check only records the setup.
+    pub fn check<R: Runtime>(&mut self, window: &WebviewWindow<R>, state: &AppState) -> bool {
+        let monitors = current_monitors(window);
+        if monitors.is_empty() {
+            return false;
+        }
+        let setup = setup_key(&monitors);
+        let changed = self.setup.as_ref().is_some_and(|last| *last != setup);
+        self.setup = Some(setup);
+        if !changed
+            || window.is_minimized().unwrap_or(true)
+            || window.is_maximized().unwrap_or(true)
+        {
+            return changed;
+        }
+        let saved = match state.config.lock() {
+            Ok(config) => config.window.clone(),
+            Err(_) => return true,
+        };
+        info!(
+            monitors = monitors.len(),
+            "Monitor setup changed; restoring window placement"
+        );
+        apply_placement(window, &saved, &monitors);
+        true
     }
 }
 
@@ -162,4 +501,230 @@ mod tests {
         assert_eq!(config.window.width, MIN_WINDOW_SIZE);
         assert_eq!(config.window.height, MAX_WINDOW_SIZE);
     }
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo {
+            name: name.to_string(),
+            x,
+            y,
+            width,
+            height,
+            scale_factor: 1.0,
+        }
+    }
+
+    fn laptop() -> MonitorInfo {
+        monitor("eDP-1", 0, 0, 1920, 1200)
+    }
+
+    /// The laptop with a 2560x1440 display docked to its right.
+    fn docked() -> Vec<MonitorInfo> {
+        vec![laptop(), monitor("DP-1", 1920, 0, 2560, 1440)]
+    }
+
+    fn saved_at(rect: Rect, monitors: &[MonitorInfo]) -> WindowConfig {
+        let mut window = WindowConfig::default();
+        (window.x, window.y, window.width, window.height) = rect;
+        remember_placement(&mut window, monitors);
+        window
+    }
+
+    #[test]
+    fn setup_key_ignores_order_and_scale() {
+        let mut reversed = docked();
+        reversed.reverse();
+        reversed[0].scale_factor = 2.0;
+        assert_eq!(setup_key(&docked()), setup_key(&reversed));
+        assert_ne!(setup_key(&docked()), setup_key(&[laptop()]));
+    }
+
+    #[test]
+    fn remember_placement_records_monitor_per_setup() {
+        let mut window = saved_at((3000, 100, 800, 450), &docked());
+        assert_eq!(
+            window.monitor.as_ref().map(|m| m.name.as_str()),
+            Some("DP-1")
+        );
+        assert_eq!(window.placements.len(), 1);
+
+        (window.x, window.y) = (100, 100);
+        remember_placement(&mut window, &[laptop()]);
+        (window.x, window.y) = (3100, 200);
+        remember_placement(&mut window, &docked());
+        assert_eq!(window.placements.len(), 2);
+        assert_eq!(window.placements[0].setup, setup_key(&docked()));
+        assert_eq!(
+            (window.placements[0].x, window.placements[0].y),
+            (3100, 200)
+        );
+        assert_eq!((window.placements[1].x, window.placements[1].y), (100, 100));
+
+        for i in 0..MAX_WINDOW_PLACEMENTS as i32 {
+            remember_placement(&mut window, &[monitor("X", i * 5000, 0, 1920, 1080)]);
+        }
+        assert_eq!(window.placements.len(), MAX_WINDOW_PLACEMENTS);
+
+        let mut unknown = WindowConfig::default();
+        remember_placement(&mut unknown, &[]);
+        assert_eq!((unknown.monitor, unknown.placements.len()), (None, 0));
+    }
+
+    #[test]
+    fn placement_follows_dock_and_undock() {
+        // Saved on the laptop alone, then on the external display.
+        let mut window = saved_at((200, 150, 800, 450), &[laptop()]);
+        (window.x, window.y) = (3000, 300);
+        remember_placement(&mut window, &docked());
+
+        assert_eq!(
+            placement_for(&window, &docked()),
+            Some((3000, 300, 800, 450)),
+            "the saved monitor is connected: saved geometry"
+        );
+        assert_eq!(
+            placement_for(&window, &[laptop()]),
+            Some((200, 150, 800, 450)),
+            "undocked: the laptop's own placement"
+        );
+    }
+
+    #[test]
+    fn placement_uses_relative_spot_on_a_new_setup() {
+        let window = saved_at((2560 + 1920 - 900, 100, 800, 450), &docked());
+        // The external display moved to the left and got smaller.
+        let moved = [laptop(), monitor("DP-1", -1920, 0, 1920, 1080)];
+        let (x, y, width, height) = placement_for(&window, &moved).unwrap();
+        assert_eq!((width, height), (800, 450));
+        assert_eq!(y, 75, "100/1440 of the height");
+        assert!(
+            (-1920..0).contains(&x) && x + width <= 0,
+            "kept on DP-1: {x}"
+        );
+
+        // Without a same-named monitor, the nearest one stands in.
+        let renamed = [monitor("HDMI-1", 1920, 0, 1280, 720)];
+        let (x, y, width, height) = placement_for(&window, &renamed).unwrap();
+        assert_eq!((width, height), (800, 450));
+        assert!(x >= 1920 && x + width <= 1920 + 1280 && y >= 0 && y + height <= 720);
+    }
+
+    #[test]
+    fn legacy_geometry_is_kept_only_when_visible() {
+        let mut window = WindowConfig::default();
+        (window.x, window.y) = (3000, 100);
+        assert_eq!(
+            placement_for(&window, &docked()),
+            Some((3000, 100, window.width, window.height))
+        );
+        assert_eq!(placement_for(&window, &[laptop()]), None);
+        assert!(placement_for(&window, &[]).is_some());
+    }
+
+    #[test]
+    fn relative_rect_shrinks_to_fit() {
+        let big = monitor("A", 0, 0, 3840, 2160);
+        let small = monitor("B", 100, 50, 1280, 720);
+        assert_eq!(
+            relative_rect(&big, &small, (3840 - 1600, 2160 - 1000, 1600, 1000)),
+            (100, 50, 1280, 720)
+        );
+        assert_eq!(
+            relative_rect(&big, &small, (1920, 1080, 640, 360)),
+            (100 + 640, 50 + 360, 640, 360)
+        );
+    }
+
+    /// The laptop at 200% with a 100% display docked to its right.
+    fn mixed() -> Vec<MonitorInfo> {
+        let mut laptop = monitor("eDP-1", 0, 0, 3840, 2400);
+        laptop.scale_factor = 2.0;
+        vec![laptop, monitor("DP-1", 3840, 0, 2560, 1440)]
+    }
+
+    #[test]
+    fn scale_conversions_round_and_clamp() {
+        assert_eq!(to_logical(3001, 2.0), 1501);
+        assert_eq!(to_physical(1500, 1.5), 2250);
+        assert_eq!(clamp_scale_factor(f64::NAN), 1.0);
+        assert_eq!(clamp_scale_factor(0.0), MIN_SCALE_FACTOR);
+        assert_eq!(
+            logical_rect((-200, 300, 1600, 900), 2.0),
+            (-100, 150, 800, 450)
+        );
+        assert_eq!(
+            physical_rect((-100, 150, 800, 450), 1.25),
+            (-125, 188, 1000, 563)
+        );
+    }
+
+    #[test]
+    fn restore_rect_keeps_apparent_size_across_scales() {
+        let rect = (100, 100, 800, 450);
+        assert_eq!(restore_rect(&mixed(), rect, None), rect, "legacy: physical");
+        assert_eq!(
+            restore_rect(&mixed(), rect, Some(2.0)),
+            (200, 200, 1600, 900)
+        );
+        // Saved at 200% at a spot that is now on the 100% display.
+        assert_eq!(
+            restore_rect(&mixed(), (2000, 50, 800, 450), Some(2.0)),
+            (4000, 100, 800, 450)
+        );
+    }
+
+    #[test]
+    fn placement_is_restored_at_its_own_scale() {
+        let mut window = WindowConfig::default();
+        (window.x, window.y, window.width, window.height) = (100, 100, 800, 450);
+        window.scale_factor = Some(2.0);
+        remember_placement(&mut window, &mixed());
+        assert_eq!(
+            window.monitor.as_ref().map(|m| m.name.as_str()),
+            Some("eDP-1")
+        );
+        assert_eq!(window.placements[0].scale_factor, 2.0);
+        assert_eq!(
+            placement_for(&window, &mixed()),
+            Some((200, 200, 1600, 900))
+        );
+
+        // The same size saved on the 100% display is half the physical
+        // size there.
+        window.scale_factor = Some(1.0);
+        (window.x, window.y) = (4000, 100);
+        window.placements.clear();
+        remember_placement(&mut window, &mixed());
+        assert_eq!(
+            placement_for(&window, &mixed()),
+            Some((4000, 100, 800, 450))
+        );
+    }
+
+    #[test]
+    fn relative_rect_follows_scale() {
+        let standard = monitor("A", 0, 0, 1920, 1080);
+        let mut hidpi = monitor("B", 0, 0, 3840, 2160);
+        hidpi.scale_factor = 2.0;
+        assert_eq!(
+            relative_rect(&standard, &hidpi, (480, 270, 800, 450)),
+            (960, 540, 1600, 900)
+        );
+        assert_eq!(
+            relative_rect(&hidpi, &standard, (960, 540, 1600, 900)),
+            (480, 270, 800, 450)
+        );
+    }
+
+    #[test]
+    fn next_monitor_goes_left_to_right_and_wraps() {
+        let left = monitor("L", -1920, 0, 1920, 1080);
+        let main = monitor("M", 0, 0, 2560, 1440);
+        let right = monitor("R", 2560, 200, 1920, 1080);
+        let monitors = [main.clone(), right, left];
+        let next = |rect| next_monitor(&monitors, rect).map(|m| m.name.as_str());
+        assert_eq!(next((100, 100, 800, 450)), Some("R"));
+        assert_eq!(next((3000, 300, 800, 450)), Some("L"));
+        assert_eq!(next((-1500, 100, 800, 450)), Some("M"));
+        assert_eq!(next_monitor(&[main], (100, 100, 800, 450)), None);
+    }
 }
@@ -0,0 +1,47 @@
+// Built-in player for bare media URLs (src-tauri/src/local_media.rs
+// sends them here as ?src=). The control strip is injected into this
+// page like any other, so the media hotkeys and zoom-to-video act on
+// the <video> below.
+
+const video = document.getElementById('player');
+const message = document.getElementById('player-message');
+const src = new URLSearchParams(window.location.search).get('src') || '';
+
+function parseSource(raw) {
+    try {
+        const url = new URL(raw);
+        const local = url.protocol === 'floatview-file:' || url.hostname === 'floatview-file.localhost';
+        return local || /^https?:$/.test(url.protocol) ? url : null;
+    } catch {
+        return null;
+    }
+}
+
+function showMessage(text) {
+    video.hidden = true;
+    message.textContent = text;
+    message.hidden = false;
+}
+
+const source = parseSource(src);
+if (!source) {
+    showMessage('Nothing to play.');
+} else {
+    const name = source.pathname.split('/').pop();
+    try {
+        document.title = decodeURIComponent(name) || source.host;
+    } catch {
+        document.title = name || source.host;
+    }
+    // HLS plays only where the webview supports it natively; elsewhere
+    // say so rather than show a black box.
+    const isHls = /\.m3u8$/i.test(source.pathname);
+    if (isHls && !video.canPlayType('application/vnd.apple.mpegurl')) {
+        showMessage("This system's webview can't play HLS streams (.m3u8): " + src);
+    } else {
+        video.addEventListener('error', () => {
+            showMessage("Couldn't play " + src);
+        });
+        video.src = source.href;
+    }
+}